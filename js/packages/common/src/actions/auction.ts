@@ -15,16 +15,67 @@ import { findProgramAddress } from '../utils';
 export const AUCTION_PREFIX = 'auction';
 export const METADATA = 'metadata';
 export const EXTENDED = 'extended';
+export const BIDS = 'bids';
+export const HISTORY = 'history';
+/// Token mint of an auction taking bids in native SOL. Its bidder pots hold the lamports bid
+/// themselves, so the pot key is passed in place of the pot token account, and the bidder's wallet
+/// in place of their token account.
+export const NATIVE_MINT = SystemProgram.programId;
 
 export enum AuctionState {
   Created = 0,
   Started,
   Ended,
+  Cancelled,
 }
 
 export enum BidStateType {
   EnglishAuction = 0,
   OpenEdition = 1,
+  DutchAuction = 2,
+  SealedBid = 3,
+  PagedEnglishAuction = 4,
+}
+
+export enum PriceCurve {
+  Linear = 0,
+  Stepped = 1,
+}
+
+export class DutchAuctionArgs {
+  /// Price at the moment the auction starts.
+  startPrice: BN;
+  /// Price the auction has fallen to by its end time.
+  endPrice: BN;
+  /// Shape of the fall from start price to end price.
+  curve: PriceCurve;
+  /// Seconds between price drops, only used by a stepped curve.
+  step: BN;
+  constructor(args: {
+    startPrice: BN;
+    endPrice: BN;
+    curve: PriceCurve;
+    step: BN;
+  }) {
+    this.startPrice = args.startPrice;
+    this.endPrice = args.endPrice;
+    this.curve = args.curve;
+    this.step = args.step;
+  }
+}
+
+export class ExtensionPolicy {
+  /// A bid placed within this many seconds of the end extends the auction.
+  window: BN;
+  /// Seconds the end is pushed back by for each such bid.
+  extension: BN;
+  /// Hard cap on the total seconds the end can be pushed back by, uncapped if null.
+  maxExtension: BN | null;
+  constructor(args: { window: BN; extension: BN; maxExtension: BN | null }) {
+    this.window = args.window;
+    this.extension = args.extension;
+    this.maxExtension = args.maxExtension;
+  }
 }
 
 export class Bid {
@@ -87,7 +138,41 @@ export const BidderPotParser: AccountParser = (
   info: decodeBidderPot(account.data),
 });
 
+export const BidPageParser: AccountParser = (
+  pubkey: PublicKey,
+  account: AccountInfo<Buffer>,
+) => ({
+  pubkey,
+  account,
+  info: decodeBidPage(account.data),
+});
+
+export const decodeBidPage = (buffer: Buffer) => {
+  return deserializeUnchecked(AUCTION_SCHEMA, BidPage, buffer) as BidPage;
+};
+
+export const BidHistoryPageParser: AccountParser = (
+  pubkey: PublicKey,
+  account: AccountInfo<Buffer>,
+) => ({
+  pubkey,
+  account,
+  info: decodeBidHistoryPage(account.data),
+});
+
+export const decodeBidHistoryPage = (buffer: Buffer) => {
+  return deserializeUnchecked(
+    AUCTION_SCHEMA,
+    BidHistoryPage,
+    buffer,
+  ) as BidHistoryPage;
+};
+
 export const decodeBidderPot = (buffer: Buffer) => {
+  // Pots created before the payer was recorded were paid for by the bidder.
+  if (buffer.length === LEGACY_BIDDER_POT_LEN) {
+    buffer = Buffer.concat([buffer, buffer.slice(32, 64)]);
+  }
   return deserializeUnchecked(AUCTION_SCHEMA, BidderPot, buffer) as BidderPot;
 };
 
@@ -101,6 +186,10 @@ export const BidderMetadataParser: AccountParser = (
 });
 
 export const decodeBidderMetadata = (buffer: Buffer) => {
+  // Metadata created before the payer was recorded was paid for by the bidder.
+  if (buffer.length === LEGACY_BIDDER_METADATA_LEN) {
+    buffer = Buffer.concat([buffer, buffer.slice(0, 32)]);
+  }
   return deserializeUnchecked(
     AUCTION_SCHEMA,
     BidderMetadata,
@@ -144,20 +233,79 @@ export class PriceFloor {
   }
 }
 
+export enum BidderGateType {
+  MerkleRoot = 0,
+  HolderMint = 1,
+}
+export class BidderGate {
+  type: BidderGateType;
+  // The Merkle root of the allowed bidder keys, or the holder mint as bytes.
+  key: Uint8Array;
+
+  constructor(args: { type: BidderGateType; key: Uint8Array }) {
+    this.type = args.type;
+    this.key = args.key;
+  }
+}
+
 export class AuctionDataExtended {
   /// Total uncancelled bids
   totalUncancelledBids: BN;
   tickSize: BN | null;
   gapTickSizePercentage: number | null;
+  /// Winners all pay the clearing price rather than their own bid
+  uniformPrice: boolean;
+  /// Late bids push the end of the auction back
+  extensionPolicy: ExtensionPolicy | null;
+  /// Total seconds the end has been pushed back by so far
+  totalExtension: BN;
+  /// A bid at or above this price wins outright and ends the auction
+  instantSalePrice: BN | null;
+  /// Bidder pots not closed yet, the auction can only be closed once none are left
+  openBidderPots: BN | null;
+  /// Bid history pages written so far, null for auctions that keep no history
+  bidHistoryPages: number | null;
+  /// If set, the auction starts on its own at this time
+  startAuctionAt: BN | null;
+  /// If set, only bidders let in by this gate can bid
+  bidderGate: BidderGate | null;
+  /// If set, the most places one wallet can win, counting the live bids of every bidder it funds
+  maxWinsPerWallet: number | null;
+  /// Whether the authority can cancel the auction once it has bids
+  cancellableWithBids: boolean;
+  /// Exchange rates of the other currencies bids can be placed in
+  exchangeRates: PublicKey[];
 
   constructor(args: {
     totalUncancelledBids: BN;
     tickSize: BN | null;
     gapTickSizePercentage: number | null;
+    uniformPrice: boolean;
+    extensionPolicy: ExtensionPolicy | null;
+    totalExtension: BN;
+    instantSalePrice: BN | null;
+    openBidderPots: BN | null;
+    bidHistoryPages: number | null;
+    startAuctionAt: BN | null;
+    bidderGate: BidderGate | null;
+    maxWinsPerWallet: number | null;
+    cancellableWithBids: boolean;
+    exchangeRates: PublicKey[];
   }) {
     this.totalUncancelledBids = args.totalUncancelledBids;
     this.tickSize = args.tickSize;
     this.gapTickSizePercentage = args.gapTickSizePercentage;
+    this.uniformPrice = args.uniformPrice;
+    this.extensionPolicy = args.extensionPolicy;
+    this.totalExtension = args.totalExtension;
+    this.instantSalePrice = args.instantSalePrice;
+    this.openBidderPots = args.openBidderPots;
+    this.bidHistoryPages = args.bidHistoryPages;
+    this.startAuctionAt = args.startAuctionAt;
+    this.bidderGate = args.bidderGate;
+    this.maxWinsPerWallet = args.maxWinsPerWallet;
+    this.cancellableWithBids = args.cancellableWithBids;
+    this.exchangeRates = args.exchangeRates;
   }
 }
 
@@ -192,9 +340,9 @@ export class AuctionData {
   /// Used for precalculation on the front end, not a backend key
   bidRedemptionKey?: PublicKey;
 
-  public timeToEnd(): CountdownState {
-    const now = moment().unix();
-    const ended = { days: 0, hours: 0, minutes: 0, seconds: 0 };
+  /// The real deadline for bids, taking the end gap after the last bid into account. Auctions
+  /// with an extension policy have late bids move endedAt itself.
+  public deadline(): number {
     let endAt = this.endedAt?.toNumber() || 0;
 
     if (this.auctionGap && this.lastBid) {
@@ -204,6 +352,14 @@ export class AuctionData {
       );
     }
 
+    return endAt;
+  }
+
+  public timeToEnd(): CountdownState {
+    const now = moment().unix();
+    const ended = { days: 0, hours: 0, minutes: 0, seconds: 0 };
+    const endAt = this.deadline();
+
     let delta = endAt - now;
 
     if (!endAt || delta <= 0) return ended;
@@ -224,6 +380,12 @@ export class AuctionData {
 
   public ended() {
     const now = moment().unix();
+    // Ended outright, by the authority or a buy-now bid, or cancelled.
+    if (
+      this.state === AuctionState.Ended ||
+      this.state === AuctionState.Cancelled
+    )
+      return true;
     if (!this.endedAt) return false;
 
     if (this.endedAt.toNumber() > now) return false;
@@ -261,7 +423,8 @@ export class AuctionData {
   }
 }
 
-export const BIDDER_METADATA_LEN = 32 + 32 + 8 + 8 + 1;
+export const BIDDER_METADATA_LEN = 32 + 32 + 8 + 8 + 1 + 32;
+export const LEGACY_BIDDER_METADATA_LEN = 32 + 32 + 8 + 8 + 1;
 export class BidderMetadata {
   // Relationship with the bidder who's metadata this covers.
   bidderPubkey: PublicKey;
@@ -274,38 +437,116 @@ export class BidderMetadata {
   // Whether the last bid the user made was cancelled. This should also be enough to know if the
   // user is a winner, as if cancelled it implies previous bids were also cancelled.
   cancelled: boolean;
+  // Gets the rent back when the metadata is closed.
+  payer: PublicKey;
   constructor(args: {
     bidderPubkey: PublicKey;
     auctionPubkey: PublicKey;
     lastBid: BN;
     lastBidTimestamp: BN;
     cancelled: boolean;
+    payer: PublicKey;
   }) {
     this.bidderPubkey = args.bidderPubkey;
     this.auctionPubkey = args.auctionPubkey;
     this.lastBid = args.lastBid;
     this.lastBidTimestamp = args.lastBidTimestamp;
     this.cancelled = args.cancelled;
+    this.payer = args.payer;
   }
 }
 
-export const BIDDER_POT_LEN = 32 + 32 + 32 + 1;
+export const BIDDER_POT_LEN = 32 + 32 + 32 + 1 + 32;
+export const LEGACY_BIDDER_POT_LEN = 32 + 32 + 32 + 1;
 export class BidderPot {
   /// Points at actual pot that is a token account
   bidderPot: PublicKey;
   bidderAct: PublicKey;
   auctionAct: PublicKey;
   emptied: boolean;
+  /// Gets the rent back when the pot is closed
+  payer: PublicKey;
   constructor(args: {
     bidderPot: PublicKey;
     bidderAct: PublicKey;
     auctionAct: PublicKey;
     emptied: boolean;
+    payer: PublicKey;
   }) {
     this.bidderPot = args.bidderPot;
     this.bidderAct = args.bidderAct;
     this.auctionAct = args.auctionAct;
     this.emptied = args.emptied;
+    this.payer = args.payer;
+  }
+}
+
+export const BIDS_PER_PAGE = 250;
+export const BID_PAGE_LEN = 32 + 4 + 32 + 4 + BIDS_PER_PAGE * (32 + 8);
+/// A page of the bids of a paged English auction, highest bid first.
+export class BidPage {
+  auction: PublicKey;
+  page: number;
+  /// Gets the rent back when the page is closed
+  payer: PublicKey;
+  bids: Bid[];
+  constructor(args: {
+    auction: PublicKey;
+    page: number;
+    payer: PublicKey;
+    bids: Bid[];
+  }) {
+    this.auction = args.auction;
+    this.page = args.page;
+    this.payer = args.payer;
+    this.bids = args.bids;
+  }
+}
+
+export enum BidHistoryAction {
+  Placed = 0,
+  Cancelled = 1,
+  Evicted = 2,
+  Refunded = 3,
+}
+
+export class BidHistoryEntry {
+  bidder: PublicKey;
+  amount: BN;
+  timestamp: BN;
+  action: BidHistoryAction;
+  constructor(args: {
+    bidder: PublicKey;
+    amount: BN;
+    timestamp: BN;
+    action: BidHistoryAction;
+  }) {
+    this.bidder = args.bidder;
+    this.amount = args.amount;
+    this.timestamp = args.timestamp;
+    this.action = args.action;
+  }
+}
+
+export const BID_HISTORY_ENTRIES_PER_PAGE = 200;
+export const BID_HISTORY_PAGE_LEN =
+  32 + 4 + 32 + 4 + BID_HISTORY_ENTRIES_PER_PAGE * (32 + 8 + 8 + 1);
+/// A page of the append-only bid history of an auction, oldest entry first.
+export class BidHistoryPage {
+  auction: PublicKey;
+  page: number;
+  payer: PublicKey;
+  entries: BidHistoryEntry[];
+  constructor(args: {
+    auction: PublicKey;
+    page: number;
+    payer: PublicKey;
+    entries: BidHistoryEntry[];
+  }) {
+    this.auction = args.auction;
+    this.page = args.page;
+    this.payer = args.payer;
+    this.entries = args.entries;
   }
 }
 
@@ -339,6 +580,37 @@ class CreateAuctionArgs {
   resource: PublicKey;
 
   priceFloor: PriceFloor;
+  /// Minimum raise over the lowest winning bid. See AuctionDataExtended.
+  tickSize: BN | null;
+  /// Minimum raise as a percentage inside the gap window. See AuctionDataExtended.
+  gapTickSizePercentage: number | null;
+  /// If set, run a Dutch auction with a falling price instead of an English auction. Each
+  /// winning place is sold to a bid meeting the price of the moment, and the auction ends once
+  /// every place is sold.
+  dutchAuction: DutchAuctionArgs | null;
+  /// If set, run a sealed bid auction with a reveal window of this many seconds after the end.
+  revealPeriod: BN | null;
+  /// If set, winners all pay the clearing price rather than their own bid.
+  uniformPrice: boolean;
+  /// If set, bids close to the end push the end back.
+  extensionPolicy: ExtensionPolicy | null;
+  /// If set, a bid at or above this price wins outright and ends the auction.
+  instantSalePrice: BN | null;
+  /// If set, bids are stored in bid pages rather than the auction account.
+  pagedBids: boolean;
+  /// If set, every bid placed and cancelled is recorded in bid history pages.
+  bidHistory: boolean;
+  /// If set, the auction starts on its own at this time.
+  startAuctionAt: BN | null;
+  /// If set, only bidders let in by this gate can bid.
+  bidderGate: BidderGate | null;
+  /// If set, the most places one wallet can win, counting the live bids of every bidder it funds.
+  /// Requires a bidder gate, which then admits wallets rather than bidders.
+  maxWinsPerWallet: number | null;
+  /// Whether the authority can cancel the auction once it has bids.
+  cancellableWithBids: boolean;
+  /// Exchange rates of other currencies to accept bids in.
+  exchangeRates: PublicKey[];
 
   constructor(args: {
     winners: WinnerLimit;
@@ -348,6 +620,20 @@ class CreateAuctionArgs {
     authority: PublicKey;
     resource: PublicKey;
     priceFloor: PriceFloor;
+    tickSize: BN | null;
+    gapTickSizePercentage: number | null;
+    dutchAuction: DutchAuctionArgs | null;
+    revealPeriod: BN | null;
+    uniformPrice: boolean;
+    extensionPolicy: ExtensionPolicy | null;
+    instantSalePrice: BN | null;
+    pagedBids: boolean;
+    bidHistory: boolean;
+    startAuctionAt: BN | null;
+    bidderGate: BidderGate | null;
+    maxWinsPerWallet: number | null;
+    cancellableWithBids: boolean;
+    exchangeRates: PublicKey[];
   }) {
     this.winners = args.winners;
     this.endAuctionAt = args.endAuctionAt;
@@ -356,6 +642,29 @@ class CreateAuctionArgs {
     this.authority = args.authority;
     this.resource = args.resource;
     this.priceFloor = args.priceFloor;
+    this.tickSize = args.tickSize;
+    this.gapTickSizePercentage = args.gapTickSizePercentage;
+    this.dutchAuction = args.dutchAuction;
+    this.revealPeriod = args.revealPeriod;
+    this.uniformPrice = args.uniformPrice;
+    this.extensionPolicy = args.extensionPolicy;
+    this.instantSalePrice = args.instantSalePrice;
+    this.pagedBids = args.pagedBids;
+    this.bidHistory = args.bidHistory;
+    this.startAuctionAt = args.startAuctionAt;
+    this.bidderGate = args.bidderGate;
+    this.maxWinsPerWallet = args.maxWinsPerWallet;
+    this.cancellableWithBids = args.cancellableWithBids;
+    this.exchangeRates = args.exchangeRates;
+  }
+}
+
+class CancelAuctionArgs {
+  instruction: number = 13;
+  resource: PublicKey;
+
+  constructor(args: { resource: PublicKey }) {
+    this.resource = args.resource;
   }
 }
 
@@ -372,10 +681,25 @@ class PlaceBidArgs {
   instruction: number = 6;
   resource: PublicKey;
   amount: BN;
+  /// Only used by sealed bid auctions, see RevealBid.
+  commitment: Uint8Array | null;
+  /// Raise the active bid to amount, paying in only the difference.
+  topUp: boolean;
+  /// Merkle proof of the bidder's key, for an auction gated by a Merkle root.
+  allowlistProof: Uint8Array[];
 
-  constructor(args: { resource: PublicKey; amount: BN }) {
+  constructor(args: {
+    resource: PublicKey;
+    amount: BN;
+    commitment?: Uint8Array | null;
+    topUp?: boolean;
+    allowlistProof?: Uint8Array[];
+  }) {
     this.resource = args.resource;
     this.amount = args.amount;
+    this.commitment = args.commitment || null;
+    this.topUp = !!args.topUp;
+    this.allowlistProof = args.allowlistProof || [];
   }
 }
 
@@ -388,6 +712,51 @@ class CancelBidArgs {
   }
 }
 
+class RefundEvictedBidArgs {
+  instruction: number = 8;
+  resource: PublicKey;
+
+  constructor(args: { resource: PublicKey }) {
+    this.resource = args.resource;
+  }
+}
+
+class RefundLosingBidsArgs {
+  instruction: number = 9;
+  resource: PublicKey;
+
+  constructor(args: { resource: PublicKey }) {
+    this.resource = args.resource;
+  }
+}
+
+class ReclaimAuctionRentArgs {
+  instruction: number = 10;
+  resource: PublicKey;
+
+  constructor(args: { resource: PublicKey }) {
+    this.resource = args.resource;
+  }
+}
+
+class CloseBidderMetadataArgs {
+  instruction: number = 11;
+  resource: PublicKey;
+
+  constructor(args: { resource: PublicKey }) {
+    this.resource = args.resource;
+  }
+}
+
+class CloseWalletBidsArgs {
+  instruction: number = 12;
+  resource: PublicKey;
+
+  constructor(args: { resource: PublicKey }) {
+    this.resource = args.resource;
+  }
+}
+
 export const AUCTION_SCHEMA = new Map<any, any>([
   [
     CreateAuctionArgs,
@@ -402,6 +771,53 @@ export const AUCTION_SCHEMA = new Map<any, any>([
         ['authority', 'pubkey'],
         ['resource', 'pubkey'],
         ['priceFloor', PriceFloor],
+        ['tickSize', { kind: 'option', type: 'u64' }],
+        ['gapTickSizePercentage', { kind: 'option', type: 'u8' }],
+        ['dutchAuction', { kind: 'option', type: DutchAuctionArgs }],
+        ['revealPeriod', { kind: 'option', type: 'u64' }],
+        ['uniformPrice', 'u8'],
+        ['extensionPolicy', { kind: 'option', type: ExtensionPolicy }],
+        ['instantSalePrice', { kind: 'option', type: 'u64' }],
+        ['pagedBids', 'u8'],
+        ['bidHistory', 'u8'],
+        ['startAuctionAt', { kind: 'option', type: 'u64' }],
+        ['bidderGate', { kind: 'option', type: BidderGate }],
+        ['maxWinsPerWallet', { kind: 'option', type: 'u8' }],
+        ['cancellableWithBids', 'u8'],
+        ['exchangeRates', ['pubkey']],
+      ],
+    },
+  ],
+  [
+    ExtensionPolicy,
+    {
+      kind: 'struct',
+      fields: [
+        ['window', 'u64'],
+        ['extension', 'u64'],
+        ['maxExtension', { kind: 'option', type: 'u64' }],
+      ],
+    },
+  ],
+  [
+    BidderGate,
+    {
+      kind: 'struct',
+      fields: [
+        ['type', 'u8'],
+        ['key', [32]],
+      ],
+    },
+  ],
+  [
+    DutchAuctionArgs,
+    {
+      kind: 'struct',
+      fields: [
+        ['startPrice', 'u64'],
+        ['endPrice', 'u64'],
+        ['curve', 'u8'],
+        ['step', 'u64'],
       ],
     },
   ],
@@ -415,6 +831,16 @@ export const AUCTION_SCHEMA = new Map<any, any>([
       ],
     },
   ],
+  [
+    CancelAuctionArgs,
+    {
+      kind: 'struct',
+      fields: [
+        ['instruction', 'u8'],
+        ['resource', 'pubkey'],
+      ],
+    },
+  ],
   [
     StartAuctionArgs,
     {
@@ -433,6 +859,9 @@ export const AUCTION_SCHEMA = new Map<any, any>([
         ['instruction', 'u8'],
         ['amount', 'u64'],
         ['resource', 'pubkey'],
+        ['commitment', { kind: 'option', type: [32] }],
+        ['topUp', 'u8'],
+        ['allowlistProof', [[32]]],
       ],
     },
   ],
@@ -446,6 +875,56 @@ export const AUCTION_SCHEMA = new Map<any, any>([
       ],
     },
   ],
+  [
+    RefundEvictedBidArgs,
+    {
+      kind: 'struct',
+      fields: [
+        ['instruction', 'u8'],
+        ['resource', 'pubkey'],
+      ],
+    },
+  ],
+  [
+    RefundLosingBidsArgs,
+    {
+      kind: 'struct',
+      fields: [
+        ['instruction', 'u8'],
+        ['resource', 'pubkey'],
+      ],
+    },
+  ],
+  [
+    ReclaimAuctionRentArgs,
+    {
+      kind: 'struct',
+      fields: [
+        ['instruction', 'u8'],
+        ['resource', 'pubkey'],
+      ],
+    },
+  ],
+  [
+    CloseBidderMetadataArgs,
+    {
+      kind: 'struct',
+      fields: [
+        ['instruction', 'u8'],
+        ['resource', 'pubkey'],
+      ],
+    },
+  ],
+  [
+    CloseWalletBidsArgs,
+    {
+      kind: 'struct',
+      fields: [
+        ['instruction', 'u8'],
+        ['resource', 'pubkey'],
+      ],
+    },
+  ],
   [
     AuctionData,
     {
@@ -471,6 +950,17 @@ export const AUCTION_SCHEMA = new Map<any, any>([
         ['totalUncancelledBids', 'u64'],
         ['tickSize', { kind: 'option', type: 'u64' }],
         ['gapTickSizePercentage', { kind: 'option', type: 'u8' }],
+        ['uniformPrice', 'u8'],
+        ['extensionPolicy', { kind: 'option', type: ExtensionPolicy }],
+        ['totalExtension', 'u64'],
+        ['instantSalePrice', { kind: 'option', type: 'u64' }],
+        ['openBidderPots', { kind: 'option', type: 'u64' }],
+        ['bidHistoryPages', { kind: 'option', type: 'u32' }],
+        ['startAuctionAt', { kind: 'option', type: 'u64' }],
+        ['bidderGate', { kind: 'option', type: BidderGate }],
+        ['maxWinsPerWallet', { kind: 'option', type: 'u8' }],
+        ['cancellableWithBids', 'u8'],
+        ['exchangeRates', ['pubkey']],
       ],
     },
   ],
@@ -515,6 +1005,7 @@ export const AUCTION_SCHEMA = new Map<any, any>([
         ['lastBid', 'u64'],
         ['lastBidTimestamp', 'u64'],
         ['cancelled', 'u8'],
+        ['payer', 'pubkey'],
       ],
     },
   ],
@@ -527,6 +1018,43 @@ export const AUCTION_SCHEMA = new Map<any, any>([
         ['bidderAct', 'pubkey'],
         ['auctionAct', 'pubkey'],
         ['emptied', 'u8'],
+        ['payer', 'pubkey'],
+      ],
+    },
+  ],
+  [
+    BidHistoryEntry,
+    {
+      kind: 'struct',
+      fields: [
+        ['bidder', 'pubkey'],
+        ['amount', 'u64'],
+        ['timestamp', 'u64'],
+        ['action', 'u8'],
+      ],
+    },
+  ],
+  [
+    BidHistoryPage,
+    {
+      kind: 'struct',
+      fields: [
+        ['auction', 'pubkey'],
+        ['page', 'u32'],
+        ['payer', 'pubkey'],
+        ['entries', [BidHistoryEntry]],
+      ],
+    },
+  ],
+  [
+    BidPage,
+    {
+      kind: 'struct',
+      fields: [
+        ['auction', 'pubkey'],
+        ['page', 'u32'],
+        ['payer', 'pubkey'],
+        ['bids', [Bid]],
       ],
     },
   ],
@@ -550,6 +1078,20 @@ export async function createAuction(
   authority: PublicKey,
   creator: PublicKey,
   instructions: TransactionInstruction[],
+  tickSize: BN | null = null,
+  gapTickSizePercentage: number | null = null,
+  dutchAuction: DutchAuctionArgs | null = null,
+  revealPeriod: BN | null = null,
+  uniformPrice: boolean = false,
+  extensionPolicy: ExtensionPolicy | null = null,
+  instantSalePrice: BN | null = null,
+  pagedBids: boolean = false,
+  bidHistory: boolean = false,
+  startAuctionAt: BN | null = null,
+  bidderGate: BidderGate | null = null,
+  maxWinsPerWallet: number | null = null,
+  cancellableWithBids: boolean = false,
+  exchangeRates: PublicKey[] = [],
 ) {
   const auctionProgramId = programIds().auction;
 
@@ -564,6 +1106,20 @@ export async function createAuction(
         tokenMint,
         authority,
         priceFloor,
+        tickSize,
+        gapTickSizePercentage,
+        dutchAuction,
+        revealPeriod,
+        uniformPrice,
+        extensionPolicy,
+        instantSalePrice,
+        pagedBids,
+        bidHistory,
+        startAuctionAt,
+        bidderGate,
+        maxWinsPerWallet,
+        cancellableWithBids,
+        exchangeRates,
       }),
     ),
   );
@@ -605,6 +1161,11 @@ export async function createAuction(
       isSigner: false,
       isWritable: false,
     },
+    {
+      pubkey: tokenMint,
+      isSigner: false,
+      isWritable: false,
+    },
   ];
   instructions.push(
     new TransactionInstruction({
@@ -615,20 +1176,15 @@ export async function createAuction(
   );
 }
 
-export async function startAuction(
+export async function cancelAuction(
   resource: PublicKey,
-  creator: PublicKey,
+  authority: PublicKey,
   instructions: TransactionInstruction[],
 ) {
   const auctionProgramId = programIds().auction;
 
   const data = Buffer.from(
-    serialize(
-      AUCTION_SCHEMA,
-      new StartAuctionArgs({
-        resource,
-      }),
-    ),
+    serialize(AUCTION_SCHEMA, new CancelAuctionArgs({ resource })),
   );
 
   const auctionKey: PublicKey = (
@@ -644,15 +1200,20 @@ export async function startAuction(
 
   const keys = [
     {
-      pubkey: creator,
-      isSigner: false,
-      isWritable: true,
+      pubkey: authority,
+      isSigner: true,
+      isWritable: false,
     },
     {
       pubkey: auctionKey,
       isSigner: false,
       isWritable: true,
     },
+    {
+      pubkey: await getAuctionExtended({ auctionProgramId, resource }),
+      isSigner: false,
+      isWritable: false,
+    },
     {
       pubkey: SYSVAR_CLOCK_PUBKEY,
       isSigner: false,
@@ -668,25 +1229,93 @@ export async function startAuction(
   );
 }
 
-export async function placeBid(
-  bidderPubkey: PublicKey,
-  bidderTokenPubkey: PublicKey,
-  bidderPotTokenPubkey: PublicKey,
-  tokenMintPubkey: PublicKey,
-  transferAuthority: PublicKey,
-  payer: PublicKey,
+export async function startAuction(
   resource: PublicKey,
-  amount: BN,
+  creator: PublicKey,
   instructions: TransactionInstruction[],
 ) {
   const auctionProgramId = programIds().auction;
 
+  const data = Buffer.from(
+    serialize(
+      AUCTION_SCHEMA,
+      new StartAuctionArgs({
+        resource,
+      }),
+    ),
+  );
+
+  const auctionKey: PublicKey = (
+    await findProgramAddress(
+      [
+        Buffer.from(AUCTION_PREFIX),
+        auctionProgramId.toBuffer(),
+        resource.toBuffer(),
+      ],
+      auctionProgramId,
+    )
+  )[0];
+
+  const keys = [
+    {
+      pubkey: creator,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: auctionKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: SYSVAR_CLOCK_PUBKEY,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: await getAuctionExtended({ auctionProgramId, resource }),
+      isSigner: false,
+      isWritable: false,
+    },
+  ];
+  instructions.push(
+    new TransactionInstruction({
+      keys,
+      programId: auctionProgramId,
+      data: data,
+    }),
+  );
+}
+
+export async function placeBid(
+  bidderPubkey: PublicKey,
+  bidderTokenPubkey: PublicKey,
+  bidderPotTokenPubkey: PublicKey,
+  tokenMintPubkey: PublicKey,
+  transferAuthority: PublicKey,
+  payer: PublicKey,
+  resource: PublicKey,
+  amount: BN,
+  instructions: TransactionInstruction[],
+  bidPageKeys: PublicKey[] = [],
+  bidHistoryPageKeys: PublicKey[] = [],
+  topUp: boolean = false,
+  allowlistProof: Uint8Array[] = [],
+  holderTokenKey: PublicKey | null = null,
+  walletBidsKey: PublicKey | null = null,
+  bidCurrencyKey: PublicKey | null = null,
+  exchangeRateKey: PublicKey | null = null,
+) {
+  const auctionProgramId = programIds().auction;
+
   const data = Buffer.from(
     serialize(
       AUCTION_SCHEMA,
       new PlaceBidArgs({
         resource,
         amount,
+        topUp,
+        allowlistProof,
       }),
     ),
   );
@@ -725,7 +1354,8 @@ export async function placeBid(
     {
       pubkey: bidderPubkey,
       isSigner: true,
-      isWritable: false,
+      // Pays the bid itself in a native SOL auction.
+      isWritable: true,
     },
     {
       pubkey: bidderTokenPubkey,
@@ -793,6 +1423,57 @@ export async function placeBid(
       isWritable: false,
     },
   ];
+
+  // Pages follow the commitment, which goes unused outside of sealed bid auctions, with the bid
+  // history pages ahead of the bid pages. The currency record of an auction accepting other
+  // currencies comes before them all, with the exchange rate of a bid in another currency, then
+  // the holder account of a holder gated auction, then the wallet bids account of an auction
+  // capping wins per wallet.
+  const pageKeys = [...bidHistoryPageKeys, ...bidPageKeys];
+  if (
+    bidCurrencyKey ||
+    holderTokenKey ||
+    walletBidsKey ||
+    pageKeys.length > 0
+  ) {
+    const bidderCommitmentKey: PublicKey = (
+      await findProgramAddress(
+        [
+          Buffer.from(AUCTION_PREFIX),
+          auctionProgramId.toBuffer(),
+          auctionKey.toBuffer(),
+          bidderPubkey.toBuffer(),
+          Buffer.from('commitment'),
+        ],
+        auctionProgramId,
+      )
+    )[0];
+    keys.push({
+      pubkey: bidderCommitmentKey,
+      isSigner: false,
+      isWritable: true,
+    });
+    if (bidCurrencyKey) {
+      keys.push({ pubkey: bidCurrencyKey, isSigner: false, isWritable: true });
+    }
+    if (exchangeRateKey) {
+      keys.push({
+        pubkey: exchangeRateKey,
+        isSigner: false,
+        isWritable: false,
+      });
+    }
+    if (holderTokenKey) {
+      keys.push({ pubkey: holderTokenKey, isSigner: false, isWritable: false });
+    }
+    if (walletBidsKey) {
+      keys.push({ pubkey: walletBidsKey, isSigner: false, isWritable: true });
+    }
+    pageKeys.forEach(pubkey =>
+      keys.push({ pubkey, isSigner: false, isWritable: true }),
+    );
+  }
+
   instructions.push(
     new TransactionInstruction({
       keys,
@@ -828,6 +1509,108 @@ export async function getBidderPotKey({
   )[0];
 }
 
+/// Pages of a paged English auction are numbered from 0. Pass every existing page to instructions
+/// that touch the bids, and the next page too when placing a bid in case the last one is full.
+export async function getBidPageKey({
+  auctionProgramId,
+  auctionKey,
+  page,
+}: {
+  auctionProgramId: PublicKey;
+  auctionKey: PublicKey;
+  page: number;
+}): Promise<PublicKey> {
+  const pageBytes = Buffer.alloc(4);
+  pageBytes.writeUInt32LE(page);
+  return (
+    await findProgramAddress(
+      [
+        Buffer.from(AUCTION_PREFIX),
+        auctionProgramId.toBuffer(),
+        auctionKey.toBuffer(),
+        Buffer.from(BIDS),
+        pageBytes,
+      ],
+      auctionProgramId,
+    )
+  )[0];
+}
+
+/// Instructions that record bid history take the last page written, or page 0 before any have
+/// been, followed by the page after it.
+export async function getBidHistoryPageKey({
+  auctionProgramId,
+  auctionKey,
+  page,
+}: {
+  auctionProgramId: PublicKey;
+  auctionKey: PublicKey;
+  page: number;
+}): Promise<PublicKey> {
+  const pageBytes = Buffer.alloc(4);
+  pageBytes.writeUInt32LE(page);
+  return (
+    await findProgramAddress(
+      [
+        Buffer.from(AUCTION_PREFIX),
+        auctionProgramId.toBuffer(),
+        auctionKey.toBuffer(),
+        Buffer.from(HISTORY),
+        pageBytes,
+      ],
+      auctionProgramId,
+    )
+  )[0];
+}
+
+/// The wallet is the owner of the token account bids are paid from, or the bidder in a native SOL
+/// auction.
+export async function getWalletBidsKey({
+  auctionProgramId,
+  auctionKey,
+  wallet,
+}: {
+  auctionProgramId: PublicKey;
+  auctionKey: PublicKey;
+  wallet: PublicKey;
+}): Promise<PublicKey> {
+  return (
+    await findProgramAddress(
+      [
+        Buffer.from(AUCTION_PREFIX),
+        auctionProgramId.toBuffer(),
+        auctionKey.toBuffer(),
+        wallet.toBuffer(),
+        Buffer.from('wallet'),
+      ],
+      auctionProgramId,
+    )
+  )[0];
+}
+
+export async function getBidCurrencyKey({
+  auctionProgramId,
+  auctionKey,
+  bidderPubkey,
+}: {
+  auctionProgramId: PublicKey;
+  auctionKey: PublicKey;
+  bidderPubkey: PublicKey;
+}): Promise<PublicKey> {
+  return (
+    await findProgramAddress(
+      [
+        Buffer.from(AUCTION_PREFIX),
+        auctionProgramId.toBuffer(),
+        auctionKey.toBuffer(),
+        bidderPubkey.toBuffer(),
+        Buffer.from('currency'),
+      ],
+      auctionProgramId,
+    )
+  )[0];
+}
+
 export async function getAuctionExtended({
   auctionProgramId,
   resource,
@@ -854,7 +1637,11 @@ export async function cancelBid(
   bidderPotTokenPubkey: PublicKey,
   tokenMintPubkey: PublicKey,
   resource: PublicKey,
+  payer: PublicKey,
   instructions: TransactionInstruction[],
+  bidPageKeys: PublicKey[] = [],
+  bidHistoryPageKeys: PublicKey[] = [],
+  walletBidsKey: PublicKey | null = null,
 ) {
   const auctionProgramId = programIds().auction;
 
@@ -901,7 +1688,8 @@ export async function cancelBid(
     {
       pubkey: bidderPubkey,
       isSigner: true,
-      isWritable: false,
+      // Pays for a new bid history page, when one is needed.
+      isWritable: true,
     },
     {
       pubkey: bidderTokenPubkey,
@@ -958,6 +1746,21 @@ export async function cancelBid(
       isSigner: false,
       isWritable: false,
     },
+    {
+      pubkey: payer,
+      isSigner: false,
+      isWritable: true,
+    },
+    // The wallet bids account of an auction capping wins per wallet comes ahead of the pages.
+    ...[
+      ...(walletBidsKey ? [walletBidsKey] : []),
+      ...bidHistoryPageKeys,
+      ...bidPageKeys,
+    ].map(pubkey => ({
+      pubkey,
+      isSigner: false,
+      isWritable: true,
+    })),
   ];
   instructions.push(
     new TransactionInstruction({
@@ -967,3 +1770,569 @@ export async function cancelBid(
     }),
   );
 }
+
+/// Refunds a bid that higher bids pushed out of the bid state. Anyone can send this, the refund
+/// only ever goes to a token account owned by the bidder.
+export async function refundEvictedBid(
+  bidderPubkey: PublicKey,
+  bidderTokenPubkey: PublicKey,
+  bidderPotTokenPubkey: PublicKey,
+  tokenMintPubkey: PublicKey,
+  resource: PublicKey,
+  payer: PublicKey,
+  historyPayer: PublicKey,
+  instructions: TransactionInstruction[],
+  bidPageKeys: PublicKey[] = [],
+  bidHistoryPageKeys: PublicKey[] = [],
+  walletBidsKey: PublicKey | null = null,
+) {
+  const auctionProgramId = programIds().auction;
+
+  const data = Buffer.from(
+    serialize(
+      AUCTION_SCHEMA,
+      new RefundEvictedBidArgs({
+        resource,
+      }),
+    ),
+  );
+
+  const auctionKey: PublicKey = (
+    await findProgramAddress(
+      [
+        Buffer.from(AUCTION_PREFIX),
+        auctionProgramId.toBuffer(),
+        resource.toBuffer(),
+      ],
+      auctionProgramId,
+    )
+  )[0];
+
+  const bidderPotKey = await getBidderPotKey({
+    auctionProgramId,
+    auctionKey,
+    bidderPubkey,
+  });
+
+  const bidderMetaKey: PublicKey = (
+    await findProgramAddress(
+      [
+        Buffer.from(AUCTION_PREFIX),
+        auctionProgramId.toBuffer(),
+        auctionKey.toBuffer(),
+        bidderPubkey.toBuffer(),
+        Buffer.from('metadata'),
+      ],
+      auctionProgramId,
+    )
+  )[0];
+
+  const bidderCommitmentKey: PublicKey = (
+    await findProgramAddress(
+      [
+        Buffer.from(AUCTION_PREFIX),
+        auctionProgramId.toBuffer(),
+        auctionKey.toBuffer(),
+        bidderPubkey.toBuffer(),
+        Buffer.from('commitment'),
+      ],
+      auctionProgramId,
+    )
+  )[0];
+
+  const keys = [
+    {
+      pubkey: bidderPubkey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: bidderTokenPubkey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: bidderPotKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: bidderPotTokenPubkey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: bidderMetaKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: auctionKey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: await getAuctionExtended({ auctionProgramId, resource }),
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: tokenMintPubkey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: programIds().token,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: payer,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: bidderCommitmentKey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: SYSVAR_CLOCK_PUBKEY,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: SYSVAR_RENT_PUBKEY,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: SystemProgram.programId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: historyPayer,
+      isSigner: true,
+      isWritable: true,
+    },
+    // The wallet bids account of an auction capping wins per wallet comes ahead of the pages.
+    ...[
+      ...(walletBidsKey ? [walletBidsKey] : []),
+      ...bidHistoryPageKeys,
+    ].map(pubkey => ({
+      pubkey,
+      isSigner: false,
+      isWritable: true,
+    })),
+    ...bidPageKeys.map(pubkey => ({
+      pubkey,
+      isSigner: false,
+      isWritable: false,
+    })),
+  ];
+  instructions.push(
+    new TransactionInstruction({
+      keys,
+      programId: auctionProgramId,
+      data: data,
+    }),
+  );
+}
+
+/// Refunds a batch of losing bids once the auction has ended, each into the associated token
+/// account of its bidder. Anyone can send this, so it can be run as a cleanup crank.
+export async function refundLosingBids(
+  bids: {
+    bidderPubkey: PublicKey;
+    bidderPotTokenPubkey: PublicKey;
+    destinationPubkey: PublicKey;
+    payerPubkey: PublicKey;
+    walletBidsKey?: PublicKey | null;
+  }[],
+  resource: PublicKey,
+  payer: PublicKey,
+  instructions: TransactionInstruction[],
+  bidPageKeys: PublicKey[] = [],
+  bidHistoryPageKeys: PublicKey[] = [],
+) {
+  const auctionProgramId = programIds().auction;
+
+  const data = Buffer.from(
+    serialize(
+      AUCTION_SCHEMA,
+      new RefundLosingBidsArgs({
+        resource,
+      }),
+    ),
+  );
+
+  const auctionKey: PublicKey = (
+    await findProgramAddress(
+      [
+        Buffer.from(AUCTION_PREFIX),
+        auctionProgramId.toBuffer(),
+        resource.toBuffer(),
+      ],
+      auctionProgramId,
+    )
+  )[0];
+
+  const keys = [
+    {
+      pubkey: auctionKey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: await getAuctionExtended({ auctionProgramId, resource }),
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: SYSVAR_CLOCK_PUBKEY,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: programIds().token,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: payer,
+      isSigner: true,
+      isWritable: true,
+    },
+    {
+      pubkey: SYSVAR_RENT_PUBKEY,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: SystemProgram.programId,
+      isSigner: false,
+      isWritable: false,
+    },
+    ...bidHistoryPageKeys.map(pubkey => ({
+      pubkey,
+      isSigner: false,
+      isWritable: true,
+    })),
+    ...bidPageKeys.map(pubkey => ({
+      pubkey,
+      isSigner: false,
+      isWritable: false,
+    })),
+  ];
+
+  for (const bid of bids) {
+    const bidderMetaKey: PublicKey = (
+      await findProgramAddress(
+        [
+          Buffer.from(AUCTION_PREFIX),
+          auctionProgramId.toBuffer(),
+          auctionKey.toBuffer(),
+          bid.bidderPubkey.toBuffer(),
+          Buffer.from('metadata'),
+        ],
+        auctionProgramId,
+      )
+    )[0];
+
+    keys.push(
+      {
+        pubkey: bidderMetaKey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: await getBidderPotKey({
+          auctionProgramId,
+          auctionKey,
+          bidderPubkey: bid.bidderPubkey,
+        }),
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: bid.bidderPotTokenPubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: bid.destinationPubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: bid.payerPubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+    );
+    if (bid.walletBidsKey) {
+      keys.push({ pubkey: bid.walletBidsKey, isSigner: false, isWritable: true });
+    }
+  }
+
+  instructions.push(
+    new TransactionInstruction({
+      keys,
+      programId: auctionProgramId,
+      data: data,
+    }),
+  );
+}
+
+/// Closes the accounts of an ended auction once every bidder pot has been closed, returning their
+/// rent to the destination. Only the auction authority can do this. Every bid page and bid history
+/// page goes with it, each given with the payer that gets its rent back. Auctions created before
+/// bidder pots were counted can't be closed, as there is no telling whether any pot still holds
+/// funds.
+export async function reclaimAuctionRent(
+  authorityPubkey: PublicKey,
+  destinationPubkey: PublicKey,
+  resource: PublicKey,
+  instructions: TransactionInstruction[],
+  bidPages: { pageKey: PublicKey; payerPubkey: PublicKey }[] = [],
+  bidHistoryPages: { pageKey: PublicKey; payerPubkey: PublicKey }[] = [],
+) {
+  const auctionProgramId = programIds().auction;
+
+  const data = Buffer.from(
+    serialize(
+      AUCTION_SCHEMA,
+      new ReclaimAuctionRentArgs({
+        resource,
+      }),
+    ),
+  );
+
+  const auctionKey: PublicKey = (
+    await findProgramAddress(
+      [
+        Buffer.from(AUCTION_PREFIX),
+        auctionProgramId.toBuffer(),
+        resource.toBuffer(),
+      ],
+      auctionProgramId,
+    )
+  )[0];
+
+  const keys = [
+    {
+      pubkey: authorityPubkey,
+      isSigner: true,
+      isWritable: false,
+    },
+    {
+      pubkey: auctionKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: await getAuctionExtended({ auctionProgramId, resource }),
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: destinationPubkey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: SYSVAR_CLOCK_PUBKEY,
+      isSigner: false,
+      isWritable: false,
+    },
+  ];
+  for (const page of [...bidPages, ...bidHistoryPages]) {
+    keys.push(
+      {
+        pubkey: page.pageKey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: page.payerPubkey,
+        isSigner: false,
+        isWritable: true,
+      },
+    );
+  }
+  instructions.push(
+    new TransactionInstruction({
+      keys,
+      programId: auctionProgramId,
+      data: data,
+    }),
+  );
+}
+
+/// Closes the metadata of a bidder, and their commitment in a sealed bid auction or currency record
+/// in an auction accepting other currencies, once the auction itself has been closed. Claiming or
+/// cancelling the bid isn't enough, as redeeming won items relies on the metadata until then.
+/// Anyone can send this, the rent only ever goes back to the payer recorded on the metadata.
+export async function closeBidderMetadata(
+  bidderPubkey: PublicKey,
+  payer: PublicKey,
+  resource: PublicKey,
+  sealed: boolean,
+  instructions: TransactionInstruction[],
+  multiCurrency: boolean = false,
+) {
+  const auctionProgramId = programIds().auction;
+
+  const data = Buffer.from(
+    serialize(
+      AUCTION_SCHEMA,
+      new CloseBidderMetadataArgs({
+        resource,
+      }),
+    ),
+  );
+
+  const auctionKey: PublicKey = (
+    await findProgramAddress(
+      [
+        Buffer.from(AUCTION_PREFIX),
+        auctionProgramId.toBuffer(),
+        resource.toBuffer(),
+      ],
+      auctionProgramId,
+    )
+  )[0];
+
+  const bidderMetaKey: PublicKey = (
+    await findProgramAddress(
+      [
+        Buffer.from(AUCTION_PREFIX),
+        auctionProgramId.toBuffer(),
+        auctionKey.toBuffer(),
+        bidderPubkey.toBuffer(),
+        Buffer.from('metadata'),
+      ],
+      auctionProgramId,
+    )
+  )[0];
+
+  const keys = [
+    {
+      pubkey: bidderPubkey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: bidderMetaKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: auctionKey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: payer,
+      isSigner: false,
+      isWritable: true,
+    },
+  ];
+
+  if (sealed) {
+    const bidderCommitmentKey: PublicKey = (
+      await findProgramAddress(
+        [
+          Buffer.from(AUCTION_PREFIX),
+          auctionProgramId.toBuffer(),
+          auctionKey.toBuffer(),
+          bidderPubkey.toBuffer(),
+          Buffer.from('commitment'),
+        ],
+        auctionProgramId,
+      )
+    )[0];
+    keys.push({
+      pubkey: bidderCommitmentKey,
+      isSigner: false,
+      isWritable: true,
+    });
+  } else if (multiCurrency) {
+    keys.push({
+      pubkey: await getBidCurrencyKey({
+        auctionProgramId,
+        auctionKey,
+        bidderPubkey,
+      }),
+      isSigner: false,
+      isWritable: true,
+    });
+  }
+
+  instructions.push(
+    new TransactionInstruction({
+      keys,
+      programId: auctionProgramId,
+      data: data,
+    }),
+  );
+}
+
+/// Closes the record of the bids a wallet funded in an auction capping wins per wallet, once the
+/// auction itself has been closed. Anyone can send this, the rent only ever goes back to the payer
+/// recorded on the account.
+export async function closeWalletBids(
+  wallet: PublicKey,
+  payer: PublicKey,
+  resource: PublicKey,
+  instructions: TransactionInstruction[],
+) {
+  const auctionProgramId = programIds().auction;
+
+  const data = Buffer.from(
+    serialize(
+      AUCTION_SCHEMA,
+      new CloseWalletBidsArgs({
+        resource,
+      }),
+    ),
+  );
+
+  const auctionKey: PublicKey = (
+    await findProgramAddress(
+      [
+        Buffer.from(AUCTION_PREFIX),
+        auctionProgramId.toBuffer(),
+        resource.toBuffer(),
+      ],
+      auctionProgramId,
+    )
+  )[0];
+
+  const keys = [
+    {
+      pubkey: await getWalletBidsKey({ auctionProgramId, auctionKey, wallet }),
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: auctionKey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: payer,
+      isSigner: false,
+      isWritable: true,
+    },
+  ];
+
+  instructions.push(
+    new TransactionInstruction({
+      keys,
+      programId: auctionProgramId,
+      data: data,
+    }),
+  );
+}
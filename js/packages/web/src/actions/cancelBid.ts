@@ -107,6 +107,7 @@ export async function setupCancelBid(
       auctionView.myBidderPot.info.bidderPot,
       auctionView.auction.info.tokenMint,
       auctionView.vault.pubkey,
+      auctionView.myBidderPot.info.payer,
       cancelInstructions,
     );
     signers.push(cancelSigners);
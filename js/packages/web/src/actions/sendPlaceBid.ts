@@ -87,18 +87,12 @@ export async function setupPlaceBid(
   let lamports = toLamports(amount, mint.info) + accountRentExempt;
 
   let bidderPotTokenAccount: PublicKey;
-  if (!auctionView.myBidderPot) {
-    bidderPotTokenAccount = createTokenAccount(
-      instructions,
-      wallet.publicKey,
-      accountRentExempt,
-      auctionView.auction.info.tokenMint,
-      auctionView.auction.pubkey,
-      signers,
-    );
+  if (auctionView.myBidderPot && auctionView.auction.info.ended()) {
+    bidderPotTokenAccount = auctionView.myBidderPot.info.bidderPot;
   } else {
-    bidderPotTokenAccount = auctionView.myBidderPot?.info.bidderPot;
-    if (!auctionView.auction.info.ended()) {
+    // Cancelling closes the old pot and its token account, so a new one is
+    // needed to bid again.
+    if (auctionView.myBidderPot) {
       let cancelSigners: Keypair[][] = [];
       let cancelInstr: TransactionInstruction[][] = [];
       await setupCancelBid(
@@ -112,6 +106,14 @@ export async function setupPlaceBid(
       signers = [...signers, ...cancelSigners[0]];
       instructions = [...cancelInstr[0], ...instructions];
     }
+    bidderPotTokenAccount = createTokenAccount(
+      instructions,
+      wallet.publicKey,
+      accountRentExempt,
+      auctionView.auction.info.tokenMint,
+      auctionView.auction.pubkey,
+      signers,
+    );
   }
 
   const payingSolAccount = ensureWrappedAccount(
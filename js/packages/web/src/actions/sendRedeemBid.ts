@@ -176,6 +176,7 @@ export async function sendRedeemBid(
         auctionView.auctionManager.info.acceptPayment,
         auctionView.myBidderMetadata.info.bidderPubkey,
         auctionView.myBidderPot?.info.bidderPot,
+        auctionView.myBidderPot?.info.payer,
         auctionView.vault.pubkey,
         auctionView.auction.info.tokenMint,
         claimInstructions,
@@ -102,8 +102,14 @@ async function emptyPaymentAccountForAllTokens(
             auctionView.auctionManager.info.authority.toBase58(),
         );
 
+      // Payout split recipients take their cut of the auctioneer's share, so
+      // they sit between the creators and the auctioneer.
+      const splitRecipients =
+        auctionView.auctionManager.info.settings.payoutSplit?.recipients || [];
+      const numCreators = creators ? creators.length : 0;
       const addresses = [
         ...(creators ? creators.map(c => c.address) : []),
+        ...splitRecipients.map(r => r.address),
         ...[auctionView.auctionManager.info.authority],
       ];
 
@@ -132,11 +138,14 @@ async function emptyPaymentAccountForAllTokens(
 
         ataLookup[ata.toBase58()] = true;
 
-        const creatorIndex = creators
-          ? creators
-              .map(c => c.address.toBase58())
-              .indexOf(addresses[k].toBase58())
-          : null;
+        const payoutSplitIndex =
+          k >= numCreators && k < addresses.length - 1 ? k - numCreators : null;
+        const creatorIndex =
+          creators && payoutSplitIndex === null
+            ? creators
+                .map(c => c.address.toBase58())
+                .indexOf(addresses[k].toBase58())
+            : null;
 
         await emptyPaymentAccount(
           auctionView.auctionManager.info.acceptPayment,
@@ -156,6 +165,7 @@ async function emptyPaymentAccountForAllTokens(
             (edgeCaseWhereCreatorIsAuctioneer && k === addresses.length - 1)
             ? null
             : creatorIndex,
+          payoutSplitIndex,
           settleInstructions,
         );
 
@@ -240,6 +250,7 @@ async function claimAllBids(
       auctionView.auctionManager.info.acceptPayment,
       bid.info.bidderAct,
       bid.info.bidderPot,
+      bid.info.payer,
       auctionView.vault.pubkey,
       auctionView.auction.info.tokenMint,
       claimBidInstructions,
@@ -22,7 +22,9 @@ import {
   BidderPot,
   BidderPotParser,
   BIDDER_METADATA_LEN,
+  LEGACY_BIDDER_METADATA_LEN,
   BIDDER_POT_LEN,
+  LEGACY_BIDDER_POT_LEN,
   decodeVault,
   Vault,
   setProgramIds,
@@ -521,7 +523,10 @@ const processAuctions = (
     // add type as first byte for easier deserialization
   }
   try {
-    if (a.account.data.length === BIDDER_METADATA_LEN) {
+    if (
+      a.account.data.length === BIDDER_METADATA_LEN ||
+      a.account.data.length === LEGACY_BIDDER_METADATA_LEN
+    ) {
       const account = cache.add(
         a.pubkey,
         a.account,
@@ -540,7 +545,10 @@ const processAuctions = (
     // add type as first byte for easier deserialization
   }
   try {
-    if (a.account.data.length === BIDDER_POT_LEN) {
+    if (
+      a.account.data.length === BIDDER_POT_LEN ||
+      a.account.data.length === LEGACY_BIDDER_POT_LEN
+    ) {
       const account = cache.add(
         a.pubkey,
         a.account,
@@ -1,4 +1,9 @@
-import { getBidderPotKey, programIds } from '@oyster/common';
+import {
+  findProgramAddress,
+  getAuctionExtended,
+  getBidderPotKey,
+  programIds,
+} from '@oyster/common';
 import {
   PublicKey,
   SYSVAR_CLOCK_PUBKEY,
@@ -12,6 +17,7 @@ export async function claimBid(
   acceptPayment: PublicKey,
   bidder: PublicKey,
   bidderPotToken: PublicKey,
+  bidderPotPayer: PublicKey,
   vault: PublicKey,
   tokenMint: PublicKey,
   instructions: TransactionInstruction[],
@@ -30,6 +36,18 @@ export async function claimBid(
     bidderPubkey: bidder,
   });
 
+  // Anything bid over the clearing price of a uniform price auction is refunded here.
+  const bidderToken = (
+    await findProgramAddress(
+      [
+        bidder.toBuffer(),
+        PROGRAM_IDS.token.toBuffer(),
+        tokenMint.toBuffer(),
+      ],
+      PROGRAM_IDS.associatedToken,
+    )
+  )[0];
+
   const value = new ClaimBidArgs();
   const data = Buffer.from(serialize(SCHEMA, value));
 
@@ -95,6 +113,24 @@ export async function claimBid(
       isSigner: false,
       isWritable: false,
     },
+    {
+      pubkey: await getAuctionExtended({
+        auctionProgramId: PROGRAM_IDS.auction,
+        resource: vault,
+      }),
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: bidderToken,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: bidderPotPayer,
+      isSigner: false,
+      isWritable: true,
+    },
   ];
 
   instructions.push(
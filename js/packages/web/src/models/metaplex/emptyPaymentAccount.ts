@@ -1,4 +1,4 @@
-import { programIds } from '@oyster/common';
+import { getAuctionExtended, programIds } from '@oyster/common';
 import {
   PublicKey,
   SystemProgram,
@@ -23,6 +23,7 @@ export async function emptyPaymentAccount(
   winningConfigIndex: number | null,
   winningConfigItemIndex: number | null,
   creatorIndex: number | null,
+  payoutSplitIndex: number | null,
   instructions: TransactionInstruction[],
 ) {
   const PROGRAM_IDS = programIds();
@@ -35,6 +36,7 @@ export async function emptyPaymentAccount(
     winningConfigIndex,
     winningConfigItemIndex,
     creatorIndex,
+    payoutSplitIndex,
   });
   const data = Buffer.from(serialize(SCHEMA, value));
 
@@ -62,6 +64,7 @@ export async function emptyPaymentAccount(
         creatorIndex,
         safetyDepositBox,
         recipient,
+        payoutSplitIndex,
       ),
       isSigner: false,
       isWritable: true,
@@ -116,6 +119,14 @@ export async function emptyPaymentAccount(
       isSigner: false,
       isWritable: false,
     },
+    {
+      pubkey: await getAuctionExtended({
+        auctionProgramId: PROGRAM_IDS.auction,
+        resource: vault,
+      }),
+      isSigner: false,
+      isWritable: false,
+    },
   ];
 
   instructions.push(
@@ -0,0 +1,164 @@
+import { getAuctionExtended, programIds } from '@oyster/common';
+import {
+  PublicKey,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+  TransactionInstruction,
+} from '@solana/web3.js';
+import { serialize } from 'borsh';
+
+import {
+  EmptyPaymentAccountsArgs,
+  EmptyPaymentAccountsPayout,
+  getPayoutTicket,
+  SCHEMA,
+} from '.';
+
+export interface EmptyPaymentAccountsPayoutParams {
+  destination: PublicKey;
+  recipient: PublicKey;
+  metadata: PublicKey;
+  masterEdition: PublicKey | undefined;
+  safetyDepositBox: PublicKey;
+  bidCurrency: PublicKey | undefined;
+  winningConfigIndex: number | null;
+  winningConfigItemIndex: number | null;
+  creatorIndex: number | null;
+  payoutSplitIndex: number | null;
+}
+
+export async function emptyPaymentAccounts(
+  acceptPayment: PublicKey,
+  auctionManager: PublicKey,
+  vault: PublicKey,
+  auction: PublicKey,
+  payer: PublicKey,
+  payouts: EmptyPaymentAccountsPayoutParams[],
+  instructions: TransactionInstruction[],
+) {
+  const PROGRAM_IDS = programIds();
+  const store = PROGRAM_IDS.store;
+  if (!store) {
+    throw new Error('Store not initialized');
+  }
+
+  const value = new EmptyPaymentAccountsArgs({
+    payouts: payouts.map(
+      p =>
+        new EmptyPaymentAccountsPayout({
+          winningConfigIndex: p.winningConfigIndex,
+          winningConfigItemIndex: p.winningConfigItemIndex,
+          creatorIndex: p.creatorIndex,
+          payoutSplitIndex: p.payoutSplitIndex,
+        }),
+    ),
+  });
+  const data = Buffer.from(serialize(SCHEMA, value));
+
+  const keys = [
+    {
+      pubkey: acceptPayment,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: auctionManager,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: payer,
+      isSigner: true,
+      isWritable: false,
+    },
+    {
+      pubkey: store,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: vault,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: auction,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: PROGRAM_IDS.token,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: SystemProgram.programId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: SYSVAR_RENT_PUBKEY,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: await getAuctionExtended({
+        auctionProgramId: PROGRAM_IDS.auction,
+        resource: vault,
+      }),
+      isSigner: false,
+      isWritable: false,
+    },
+  ];
+
+  for (const p of payouts) {
+    keys.push(
+      {
+        pubkey: p.destination,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: await getPayoutTicket(
+          auctionManager,
+          p.winningConfigIndex,
+          p.winningConfigItemIndex,
+          p.creatorIndex,
+          p.safetyDepositBox,
+          p.recipient,
+          p.payoutSplitIndex,
+        ),
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: p.metadata,
+        isSigner: false,
+        isWritable: false,
+      },
+      {
+        pubkey: p.masterEdition || SystemProgram.programId,
+        isSigner: false,
+        isWritable: false,
+      },
+      {
+        pubkey: p.safetyDepositBox,
+        isSigner: false,
+        isWritable: false,
+      },
+      {
+        pubkey: p.bidCurrency || SystemProgram.programId,
+        isSigner: false,
+        isWritable: false,
+      },
+    );
+  }
+
+  instructions.push(
+    new TransactionInstruction({
+      keys,
+      programId: PROGRAM_IDS.metaplex,
+      data,
+    }),
+  );
+}
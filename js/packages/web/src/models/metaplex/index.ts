@@ -80,6 +80,15 @@ export class InitAuctionManagerArgs {
   }
 }
 
+export class UpdateAuctionManagerSettingsArgs {
+  instruction = 20;
+  settings: AuctionManagerSettings;
+
+  constructor(args: { settings: AuctionManagerSettings }) {
+    this.settings = args.settings;
+  }
+}
+
 export class ValidateSafetyDepositBoxArgs {
   instruction = 1;
 }
@@ -129,14 +138,17 @@ export class EmptyPaymentAccountArgs {
   winningConfigIndex: number | null;
   winningConfigItemIndex: number | null;
   creatorIndex: number | null;
+  payoutSplitIndex: number | null;
   constructor(args: {
     winningConfigIndex: number | null;
     winningConfigItemIndex: number | null;
     creatorIndex: number | null;
+    payoutSplitIndex: number | null;
   }) {
     this.winningConfigIndex = args.winningConfigIndex;
     this.winningConfigItemIndex = args.winningConfigItemIndex;
     this.creatorIndex = args.creatorIndex;
+    this.payoutSplitIndex = args.payoutSplitIndex;
   }
 }
 
@@ -164,6 +176,36 @@ export class DecommissionAuctionManagerArgs {
   instruction = 13;
 }
 
+export class CancelAuctionArgs {
+  instruction = 14;
+}
+
+export class EmptyPaymentAccountsPayout {
+  winningConfigIndex: number | null;
+  winningConfigItemIndex: number | null;
+  creatorIndex: number | null;
+  payoutSplitIndex: number | null;
+  constructor(args: {
+    winningConfigIndex: number | null;
+    winningConfigItemIndex: number | null;
+    creatorIndex: number | null;
+    payoutSplitIndex: number | null;
+  }) {
+    this.winningConfigIndex = args.winningConfigIndex;
+    this.winningConfigItemIndex = args.winningConfigItemIndex;
+    this.creatorIndex = args.creatorIndex;
+    this.payoutSplitIndex = args.payoutSplitIndex;
+  }
+}
+
+export class EmptyPaymentAccountsArgs {
+  instruction = 15;
+  payouts: EmptyPaymentAccountsPayout[];
+  constructor(args: { payouts: EmptyPaymentAccountsPayout[] }) {
+    this.payouts = args.payouts;
+  }
+}
+
 export enum WinningConstraint {
   NoParticipationPrize = 0,
   ParticipationPrizeGiven = 1,
@@ -178,12 +220,32 @@ export enum NonWinningConstraint {
 export class AuctionManagerSettings {
   winningConfigs: WinningConfig[] = [];
   participationConfig: ParticipationConfig | null = null;
+  payoutSplit: PayoutSplit | null = null;
+  fixedPrice: BN | null = null;
 
   constructor(args?: AuctionManagerSettings) {
     Object.assign(this, args);
   }
 }
 
+export class PayoutSplit {
+  recipients: PayoutSplitRecipient[] = [];
+
+  constructor(args?: PayoutSplit) {
+    Object.assign(this, args);
+  }
+}
+
+export class PayoutSplitRecipient {
+  address: PublicKey;
+  basisPoints: number;
+
+  constructor(args: { address: PublicKey; basisPoints: number }) {
+    this.address = args.address;
+    this.basisPoints = args.basisPoints;
+  }
+}
+
 export enum WinningConfigType {
   /// You may be selling your one-of-a-kind NFT for the first time, but not it's accompanying Metadata,
   /// of which you would like to retain ownership. You get 100% of the payment the first sale, then
@@ -204,6 +266,9 @@ export enum WinningConfigType {
   FullRightsTransfer,
   /// Means you are using authorization tokens to print off editions during the auction
   Printing,
+  /// Means you are selling authorization tokens on a fixed price listing, each of which is redeemed
+  /// for a limited edition as soon as it is bought, so the buyer walks away with the edition itself.
+  LimitedEdition,
 }
 export class ParticipationState {
   collectedToAcceptPayment: BN = new BN(0);
@@ -409,6 +474,25 @@ export const SCHEMA = new Map<any, any>([
       fields: [
         ['winningConfigs', [WinningConfig]],
         ['participationConfig', { kind: 'option', type: ParticipationConfig }],
+        ['payoutSplit', { kind: 'option', type: PayoutSplit }],
+        ['fixedPrice', { kind: 'option', type: 'u64' }],
+      ],
+    },
+  ],
+  [
+    PayoutSplit,
+    {
+      kind: 'struct',
+      fields: [['recipients', [PayoutSplitRecipient]]],
+    },
+  ],
+  [
+    PayoutSplitRecipient,
+    {
+      kind: 'struct',
+      fields: [
+        ['address', 'pubkey'],
+        ['basisPoints', 'u16'],
       ],
     },
   ],
@@ -549,6 +633,35 @@ export const SCHEMA = new Map<any, any>([
       fields: [['instruction', 'u8']],
     },
   ],
+  [
+    CancelAuctionArgs,
+    {
+      kind: 'struct',
+      fields: [['instruction', 'u8']],
+    },
+  ],
+  [
+    EmptyPaymentAccountsPayout,
+    {
+      kind: 'struct',
+      fields: [
+        ['winningConfigIndex', { kind: 'option', type: 'u8' }],
+        ['winningConfigItemIndex', { kind: 'option', type: 'u8' }],
+        ['creatorIndex', { kind: 'option', type: 'u8' }],
+        ['payoutSplitIndex', { kind: 'option', type: 'u8' }],
+      ],
+    },
+  ],
+  [
+    EmptyPaymentAccountsArgs,
+    {
+      kind: 'struct',
+      fields: [
+        ['instruction', 'u8'],
+        ['payouts', [EmptyPaymentAccountsPayout]],
+      ],
+    },
+  ],
   [
     InitAuctionManagerArgs,
     {
@@ -559,6 +672,16 @@ export const SCHEMA = new Map<any, any>([
       ],
     },
   ],
+  [
+    UpdateAuctionManagerSettingsArgs,
+    {
+      kind: 'struct',
+      fields: [
+        ['instruction', 'u8'],
+        ['settings', AuctionManagerSettings],
+      ],
+    },
+  ],
   [
     ValidateSafetyDepositBoxArgs,
     {
@@ -610,6 +733,7 @@ export const SCHEMA = new Map<any, any>([
         ['winningConfigIndex', { kind: 'option', type: 'u8' }],
         ['winningConfigItemIndex', { kind: 'option', type: 'u8' }],
         ['creatorIndex', { kind: 'option', type: 'u8' }],
+        ['payoutSplitIndex', { kind: 'option', type: 'u8' }],
       ],
     },
   ],
@@ -773,6 +897,7 @@ export async function getPayoutTicket(
   creatorIndex: number | null | undefined,
   safetyDepositBox: PublicKey,
   recipient: PublicKey,
+  payoutSplitIndex?: number | null,
 ) {
   const PROGRAM_IDS = programIds();
 
@@ -794,6 +919,8 @@ export async function getPayoutTicket(
         Buffer.from(
           creatorIndex !== null && creatorIndex !== undefined
             ? creatorIndex.toString()
+            : payoutSplitIndex !== null && payoutSplitIndex !== undefined
+            ? `split-${payoutSplitIndex}`
             : 'auctioneer',
         ),
         safetyDepositBox.toBuffer(),
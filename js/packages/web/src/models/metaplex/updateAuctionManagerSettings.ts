@@ -0,0 +1,85 @@
+import { programIds } from '@oyster/common';
+import { PublicKey, TransactionInstruction } from '@solana/web3.js';
+import { serialize } from 'borsh';
+
+import {
+  AuctionManagerSettings,
+  getAuctionKeys,
+  getSafetyDepositBoxValidationTicket,
+  SCHEMA,
+  UpdateAuctionManagerSettingsArgs,
+} from '.';
+
+// Changed safety deposit boxes are those used differently by the new
+// settings than by the old, which must not have been validated yet.
+export async function updateAuctionManagerSettings(
+  vault: PublicKey,
+  auctionManagerAuthority: PublicKey,
+  store: PublicKey,
+  changedSafetyDepositBoxes: PublicKey[],
+  settings: AuctionManagerSettings,
+  instructions: TransactionInstruction[],
+) {
+  const PROGRAM_IDS = programIds();
+  const { auctionKey, auctionManagerKey } = await getAuctionKeys(vault);
+
+  const value = new UpdateAuctionManagerSettingsArgs({
+    settings,
+  });
+
+  const data = Buffer.from(serialize(SCHEMA, value));
+
+  const keys = [
+    {
+      pubkey: auctionManagerKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: auctionManagerAuthority,
+      isSigner: true,
+      isWritable: false,
+    },
+    {
+      pubkey: vault,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: auctionKey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: store,
+      isSigner: false,
+      isWritable: false,
+    },
+  ];
+
+  for (const safetyDepositBox of changedSafetyDepositBoxes) {
+    keys.push(
+      {
+        pubkey: safetyDepositBox,
+        isSigner: false,
+        isWritable: false,
+      },
+      {
+        pubkey: await getSafetyDepositBoxValidationTicket(
+          auctionManagerKey,
+          safetyDepositBox,
+        ),
+        isSigner: false,
+        isWritable: false,
+      },
+    );
+  }
+
+  instructions.push(
+    new TransactionInstruction({
+      keys,
+      programId: PROGRAM_IDS.metaplex,
+      data,
+    }),
+  );
+}
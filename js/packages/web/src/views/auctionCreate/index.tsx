@@ -180,6 +180,8 @@ export const AuctionCreateView = () => {
             toLamports(attributes.participationFixedPrice, mint) || 0,
           ),
         }),
+        payoutSplit: null,
+        fixedPrice: null,
       });
 
       winnerLimit = new WinnerLimit({
@@ -238,6 +240,8 @@ export const AuctionCreateView = () => {
               ),
             })
           : null,
+        payoutSplit: null,
+        fixedPrice: null,
       });
       winnerLimit = new WinnerLimit({
         type: WinnerLimitType.Capped,
@@ -301,6 +305,8 @@ export const AuctionCreateView = () => {
               ),
             })
           : null,
+        payoutSplit: null,
+        fixedPrice: null,
       });
 
       console.log('Tiered settings', settings);
@@ -18,7 +18,10 @@ use {
         instruction::{initialize_account, initialize_mint, mint_to},
         state::{Account, Mint},
     },
-    std::str::FromStr,
+    std::{
+        str::FromStr,
+        time::{SystemTime, UNIX_EPOCH},
+    },
 };
 
 const PROGRAM_PUBKEY: &str = "HLGetPpEUaagthEtF4px9S24hwJrwz3qvgRZxkWTw4ei";
@@ -116,6 +119,20 @@ fn create_auction(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) {
                 token_mint: mint.pubkey(),
                 winners: WinnerLimit::Capped(5),
                 price_floor: floor.unwrap_or(PriceFloor::None([0; 32])),
+                tick_size: None,
+                gap_tick_size_percentage: None,
+                dutch_auction: None,
+                reveal_period: None,
+                uniform_price: false,
+                extension_policy: None,
+                instant_sale_price: None,
+                paged_bids: false,
+                bid_history: false,
+                start_auction_at: None,
+                bidder_gate: None,
+                max_wins_per_wallet: None,
+                cancellable_with_bids: false,
+                exchange_rates: vec![],
             },
         ),
     ];
@@ -130,7 +147,10 @@ fn create_auction(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) {
 }
 
 fn inspect_auction(app_matches: &ArgMatches, _payer: Keypair, client: RpcClient) {
-    use spl_auction::processor::{BidState, PriceFloor};
+    use spl_auction::{
+        processor::{BidState, PriceFloor},
+        EXTENDED, PREFIX,
+    };
 
     // Fixed Addresses.
     let auction_pubkey = pubkey_of(app_matches, "auction").unwrap();
@@ -140,6 +160,22 @@ fn inspect_auction(app_matches: &ArgMatches, _payer: Keypair, client: RpcClient)
     let auction: spl_auction::processor::AuctionData =
         try_from_slice_unchecked(&auction_data.data).unwrap();
 
+    // Extended data can only be found through the resource, so is only shown when given one.
+    let auction_extended = pubkey_of(app_matches, "resource").map(|resource| {
+        let program_key = Pubkey::from_str(PROGRAM_PUBKEY).unwrap();
+        let seeds = &[
+            PREFIX.as_bytes(),
+            program_key.as_ref(),
+            resource.as_ref(),
+            EXTENDED.as_bytes(),
+        ];
+        let (auction_extended_pubkey, _) = Pubkey::find_program_address(seeds, &program_key);
+        let auction_extended_data = client.get_account(&auction_extended_pubkey).unwrap();
+        let auction_extended: spl_auction::processor::AuctionDataExtended =
+            try_from_slice_unchecked(&auction_extended_data.data).unwrap();
+        auction_extended
+    });
+
     println!(
         "\n\
         Inspecting Auction:\n\
@@ -151,6 +187,7 @@ fn inspect_auction(app_matches: &ArgMatches, _payer: Keypair, client: RpcClient)
         - Will End At: {}\n\
         - Gap Time: {}\n\
         - Ended At: {}\n\
+        - Deadline: {}\n\
         - Price Floor: {}
     ",
         auction.state,
@@ -161,6 +198,7 @@ fn inspect_auction(app_matches: &ArgMatches, _payer: Keypair, client: RpcClient)
         auction.end_auction_at.unwrap_or(0),
         auction.end_auction_gap.unwrap_or(0),
         auction.ended_at.unwrap_or(0),
+        auction.deadline().unwrap_or(0),
         match auction.price_floor {
             PriceFloor::None(_) => "No Floor".to_string(),
             PriceFloor::MinimumPrice(min) => format!("Minimum Bid: {}", min[0]),
@@ -168,10 +206,64 @@ fn inspect_auction(app_matches: &ArgMatches, _payer: Keypair, client: RpcClient)
         },
     );
 
-    if let BidState::EnglishAuction { ref bids, max } = auction.bid_state {
-        println!("Winning Bids (Max {}):", max);
-        for bid in bids {
-            println!("- {:?}", bid);
+    match auction.bid_state {
+        BidState::EnglishAuction { ref bids, max } | BidState::SealedBid { ref bids, max, .. } => {
+            println!("Winning Bids (Max {}):", max);
+            for bid in bids {
+                println!("- {:?}", bid);
+            }
+        }
+        BidState::DutchAuction {
+            ref bids,
+            max,
+            start_price,
+            end_price,
+            ..
+        } => {
+            println!(
+                "Dutch Auction Price: {} falling to {}",
+                start_price, end_price
+            );
+            println!("Sold Places (Max {}):", max);
+            for bid in bids {
+                println!("- {:?}", bid);
+            }
+        }
+        BidState::PagedEnglishAuction { max, pages, .. } => {
+            println!("Winning Bids (Max {}) Stored In {} Pages", max, pages);
+        }
+        BidState::OpenEdition { .. } => {}
+    }
+
+    if let Some(extended) = auction_extended {
+        // Paged auctions keep their bids elsewhere, so the minimum can't be worked out from here.
+        if !matches!(auction.bid_state, BidState::PagedEnglishAuction { .. }) {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs() as i64;
+            println!(
+                "Minimum Next Bid: {}",
+                auction.minimum_next_bid(&extended, now).unwrap()
+            );
+        }
+        if let Some(price) = extended.instant_sale_price {
+            println!("Buy Now Price: {}", price);
+        }
+        if let Some(policy) = extended.extension_policy {
+            println!(
+                "Extension Policy:\n\
+                - Bids Within: {}\n\
+                - Extend By: {}\n\
+                - Max Extension: {}\n\
+                - Extended So Far: {}",
+                policy.window,
+                policy.extension,
+                policy
+                    .max_extension
+                    .map_or("Uncapped".to_string(), |max| max.to_string()),
+                extended.total_extension,
+            );
         }
     }
 }
@@ -307,7 +399,13 @@ fn place_bid(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) {
             mint.pubkey(),   // Token Mint
             transfer_authority.pubkey(), // Account Approved to Move Tokens
             payer.pubkey(),  // Pays for Transactions
-            PlaceBidArgs { amount, resource },
+            PlaceBidArgs {
+                amount,
+                resource,
+                commitment: None,
+                top_up: false,
+                allowlist_proof: vec![],
+            },
         ),
     ];
 
@@ -405,6 +503,8 @@ fn claim_bid(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) {
         bidder.pubkey(),
         bidpot.bidder_pot,
         mint.pubkey(),
+        bidder.pubkey(), // SPL Token Account (Refund)
+        bidpot.payer,    // Pot Payer (Rent Refund)
         ClaimBidArgs { resource },
     )];
 
@@ -453,6 +553,7 @@ fn cancel_bid(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) {
         bidder.pubkey(), // SPL Token Account (Source)
         bidpot.bidder_pot, // SPL Token Account (Destination)
         mint.pubkey(),   // Token Mint
+        bidpot.payer,    // Pot Payer (Rent Refund)
         CancelBidArgs { resource },
     )];
 
@@ -615,6 +716,15 @@ fn main() {
                         .takes_value(true)
                         .help("Pubkey of an auction account."),
                 )
+                .arg(
+                    Arg::with_name("resource")
+                        .long("resource")
+                        .value_name("PUBKEY")
+                        .required(false)
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .help("Pubkey of the resource being auctioned, to show extended data."),
+                )
         )
         .subcommand(
             SubCommand::with_name("bid")
@@ -122,6 +122,134 @@ pub enum AuctionError {
     /// Data type mismatch
     #[error("Data type mismatch")]
     DataTypeMismatch,
+
+    /// Gap tick size percentage must be between 0 and 100
+    #[error("Gap tick size percentage must be between 0 and 100")]
+    InvalidGapTickSizePercentage,
+
+    /// Bid does not beat the lowest winning bid by the required tick size
+    #[error("Bid does not beat the lowest winning bid by the required tick size")]
+    BidIncrementTooSmall,
+
+    /// Dutch auction settings are invalid
+    #[error("Dutch auction settings are invalid")]
+    InvalidDutchAuctionSettings,
+
+    /// A winning bid in a Dutch auction cannot be cancelled
+    #[error("A winning bid in a Dutch auction cannot be cancelled")]
+    DutchAuctionBidFinal,
+
+    /// Sealed bid settings are invalid
+    #[error("Sealed bid settings are invalid")]
+    InvalidSealedBidSettings,
+
+    /// Bids in a sealed bid auction must be placed with a commitment
+    #[error("Bids in a sealed bid auction must be placed with a commitment")]
+    BidCommitmentMissing,
+
+    /// This bid has already been revealed
+    #[error("This bid has already been revealed")]
+    BidAlreadyRevealed,
+
+    /// Uniform pricing is only available to auctions that rank bids
+    #[error("Uniform pricing is only available to auctions that rank bids")]
+    InvalidUniformPriceSettings,
+
+    /// Refund account does not belong to the bidder
+    #[error("Refund account does not belong to the bidder")]
+    RefundAccountOwnerMismatch,
+
+    /// Extension policy settings are invalid
+    #[error("Extension policy settings are invalid")]
+    InvalidExtensionPolicy,
+
+    /// Instant sale price settings are invalid
+    #[error("Instant sale price settings are invalid")]
+    InvalidInstantSalePrice,
+
+    /// This bid has not been evicted
+    #[error("This bid has not been evicted")]
+    BidNotEvicted,
+
+    /// A winning bid cannot be refunded
+    #[error("A winning bid cannot be refunded")]
+    WinningBidCannotBeRefunded,
+
+    /// Failed to close the token account
+    #[error("Failed to close the token account")]
+    TokenCloseFailed,
+
+    /// Rent payer does not match the one recorded on the account being closed
+    #[error("Rent payer does not match the one recorded on the account being closed")]
+    PayerMismatch,
+
+    /// Every bidder pot must be closed before the auction can be closed
+    #[error("Every bidder pot must be closed before the auction can be closed")]
+    BidderPotsStillOpen,
+
+    /// The auction must be closed first
+    #[error("The auction must be closed first")]
+    AuctionNotClosed,
+
+    /// Paged bids are only available to English auctions with at most 150 capped winners
+    #[error("Paged bids are only available to English auctions with at most 150 capped winners")]
+    InvalidPagedBidSettings,
+
+    /// A page of bids for this auction is missing
+    #[error("A page of bids for this auction is missing")]
+    BidPageMissing,
+
+    /// Bid history is not available to Dutch auctions
+    #[error("Bid history is not available to Dutch auctions")]
+    InvalidBidHistorySettings,
+
+    /// A page of the bid history of this auction is missing
+    #[error("A page of the bid history of this auction is missing")]
+    BidHistoryPageMissing,
+
+    /// There is no active bid to top up
+    #[error("There is no active bid to top up")]
+    BidNotActive,
+
+    /// Bidder is not allowed to bid on this auction
+    #[error("Bidder is not allowed to bid on this auction")]
+    BidderNotAllowed,
+
+    /// The maximum wins per wallet must be at least one, and set along with a bidder gate
+    #[error("The maximum wins per wallet must be at least one, and set along with a bidder gate")]
+    InvalidMaxWinsPerWallet,
+
+    /// This wallet already funds as many live bids as the auction lets it win
+    #[error("This wallet already funds as many live bids as the auction lets it win")]
+    WalletWinLimitReached,
+
+    /// The wallet bids account given does not belong to this auction or does not list this bidder
+    #[error("The wallet bids account given does not belong to this auction or does not list this bidder")]
+    WalletBidsMismatch,
+
+    /// The auction has bids and was not created to allow cancelling with bids
+    #[error("The auction has bids and was not created to allow cancelling with bids")]
+    AuctionHasBids,
+
+    /// Price floor settings are invalid
+    #[error("Price floor settings are invalid")]
+    InvalidPriceFloor,
+
+    /// Tick size must be greater than zero
+    #[error("Tick size must be greater than zero")]
+    InvalidTickSize,
+
+    /// Other currencies can only be accepted by English auctions in an SPL token
+    #[error("Other currencies can only be accepted by English auctions in an SPL token")]
+    InvalidExchangeRateSettings,
+
+    /// Exchange rate is invalid
+    #[error("Exchange rate is invalid")]
+    InvalidExchangeRate,
+
+    /// This auction does not accept bids in this currency
+    #[error("This auction does not accept bids in this currency")]
+    CurrencyNotAccepted,
 }
 
 impl PrintProgramError for AuctionError {
@@ -0,0 +1,123 @@
+//! Events logged by the auction program as it moves an auction through its life, so indexers can
+//! follow auctions from the transaction logs instead of polling and diffing accounts.
+//!
+//! Each event is written as a single log line: EVENT_PREFIX followed by the base64 encoding of a
+//! borsh serialized EventEnvelope. The envelope carries a version, so the layout of the events can
+//! change later without older decoders misreading newer logs. See the decoder module for reading
+//! them back.
+
+pub mod decoder;
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{clock::UnixTimestamp, msg, pubkey::Pubkey},
+};
+
+/// Marks a log line as carrying an auction event.
+pub const EVENT_PREFIX: &str = "AUCTION_EVENT:";
+
+/// Version of the event layout written by this program. Bumped whenever AuctionEvent changes in a
+/// way older decoders can't read.
+pub const EVENT_VERSION: u8 = 1;
+
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct EventEnvelope {
+    pub version: u8,
+    pub event: AuctionEvent,
+}
+
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub enum AuctionEvent {
+    /// An auction was created, and is waiting to be started.
+    AuctionCreated {
+        auction: Pubkey,
+        authority: Pubkey,
+        resource: Pubkey,
+        token_mint: Pubkey,
+    },
+    /// An auction was started, either by its authority or on reaching its scheduled start time.
+    AuctionStarted {
+        auction: Pubkey,
+        started_at: UnixTimestamp,
+    },
+    /// A bid was placed, or topped up. The amount is the bid as ranked in the auction, which for a
+    /// bid in another currency is its value in the auction's own mint.
+    BidPlaced {
+        auction: Pubkey,
+        bidder: Pubkey,
+        amount: u64,
+        timestamp: UnixTimestamp,
+    },
+    /// A bid was cancelled and its funds returned to the bidder.
+    BidCancelled {
+        auction: Pubkey,
+        bidder: Pubkey,
+        amount: u64,
+        timestamp: UnixTimestamp,
+    },
+    /// A bid was pushed out of the bid state by a higher one, and can be refunded with
+    /// RefundEvictedBid.
+    BidEvicted {
+        auction: Pubkey,
+        bidder: Pubkey,
+        amount: u64,
+    },
+    /// An auction ended, whether by its authority, on selling out or when a bid found it over.
+    AuctionEnded {
+        auction: Pubkey,
+        ended_at: UnixTimestamp,
+    },
+    /// A winning bid was paid out to the auction's destination.
+    BidClaimed {
+        auction: Pubkey,
+        bidder: Pubkey,
+        amount: u64,
+    },
+    /// An auction was cancelled by its authority, leaving it without winners.
+    AuctionCancelled { auction: Pubkey },
+    /// A bid was refunded without its bidder, after being evicted or losing the auction. The
+    /// amount is the bid as ranked in the auction, as for BidPlaced.
+    BidRefunded {
+        auction: Pubkey,
+        bidder: Pubkey,
+        amount: u64,
+        timestamp: UnixTimestamp,
+    },
+    /// A finished auction was closed and its rent returned to the destination.
+    AuctionClosed {
+        auction: Pubkey,
+        destination: Pubkey,
+    },
+    /// The metadata of a bidder was closed after the auction was, and its rent returned to its
+    /// payer.
+    BidderMetadataClosed { auction: Pubkey, bidder: Pubkey },
+    /// The price of a bid currency was set, in billionths of a price_mint unit per unit of mint.
+    ExchangeRateUpdated {
+        exchange_rate: Pubkey,
+        mint: Pubkey,
+        price_mint: Pubkey,
+        price_per_token: u64,
+    },
+    /// The authority of an auction was handed over to another account.
+    AuthorityChanged { auction: Pubkey, authority: Pubkey },
+}
+
+/// The log line carrying an event, wrapped in an envelope of the current version. Serializing into
+/// a Vec can't fail, but should it, there's no line to log.
+pub fn encode(event: AuctionEvent) -> Option<String> {
+    let envelope = EventEnvelope {
+        version: EVENT_VERSION,
+        event,
+    };
+    let data = envelope.try_to_vec().ok()?;
+    Some(format!("{}{}", EVENT_PREFIX, base64::encode(data)))
+}
+
+/// Logs an event. An event is never worth failing the instruction over.
+pub fn emit(event: AuctionEvent) {
+    if let Some(line) = encode(event) {
+        msg!("{}", line);
+    }
+}
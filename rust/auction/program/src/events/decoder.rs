@@ -0,0 +1,124 @@
+//! Reads auction events back out of transaction logs, for clients and indexers built on this crate.
+//! Any program in a transaction can log a line that looks like an event, so only lines logged while
+//! the auction program itself is running are read. Lines that aren't events, or events of a version
+//! this decoder doesn't know, are skipped rather than treated as errors.
+
+use {
+    super::{AuctionEvent, EventEnvelope, EVENT_PREFIX, EVENT_VERSION},
+    borsh::BorshDeserialize,
+    solana_program::pubkey::Pubkey,
+};
+
+/// Prefix the runtime adds to every line logged through msg!.
+pub const PROGRAM_LOG_PREFIX: &str = "Program log: ";
+
+/// Prefix of the lines the runtime logs as programs are invoked and return.
+const PROGRAM_PREFIX: &str = "Program ";
+
+/// Decodes a single log line, as logged by the program or as returned in a transaction's log
+/// messages. This can't tell which program logged the line, see decode_events for that.
+pub fn decode_event(line: &str) -> Option<AuctionEvent> {
+    let line = line.strip_prefix(PROGRAM_LOG_PREFIX).unwrap_or(line);
+    let data = base64::decode(line.strip_prefix(EVENT_PREFIX)?).ok()?;
+    let envelope = EventEnvelope::try_from_slice(&data).ok()?;
+    if envelope.version != EVENT_VERSION {
+        return None;
+    }
+    Some(envelope.event)
+}
+
+/// Decodes every event the auction program at `program_id` logged in a transaction's log messages,
+/// in the order they were logged. The runtime's invoke lines, and the success or failure lines that
+/// close them, are followed to know which program is running, through any cross program calls.
+pub fn decode_events<S: AsRef<str>>(program_id: &Pubkey, logs: &[S]) -> Vec<AuctionEvent> {
+    let program_id = program_id.to_string();
+    let mut running: Vec<&str> = vec![];
+    let mut events = vec![];
+    for line in logs.iter().map(|line| line.as_ref()) {
+        if line.starts_with(PROGRAM_LOG_PREFIX) {
+            if running.last() == Some(&program_id.as_str()) {
+                events.extend(decode_event(line));
+            }
+            continue;
+        }
+
+        let mut words = match line.strip_prefix(PROGRAM_PREFIX) {
+            Some(rest) => rest.split_whitespace(),
+            None => continue,
+        };
+        match (words.next(), words.next()) {
+            (Some(program), Some("invoke")) => running.push(program),
+            (Some(_), Some("success")) | (Some(_), Some("failed:")) => {
+                running.pop();
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::events::{encode, AuctionEvent},
+    };
+
+    fn event() -> AuctionEvent {
+        AuctionEvent::BidPlaced {
+            auction: Pubkey::new_unique(),
+            bidder: Pubkey::new_unique(),
+            amount: 1_000,
+            timestamp: 42,
+        }
+    }
+
+    #[test]
+    fn test_decode_event_round_trip() {
+        let event = event();
+        let line = encode(event.clone()).unwrap();
+        assert_eq!(decode_event(&line), Some(event.clone()));
+        assert_eq!(
+            decode_event(&format!("{}{}", PROGRAM_LOG_PREFIX, line)),
+            Some(event)
+        );
+        assert_eq!(decode_event("Program log: something else"), None);
+    }
+
+    #[test]
+    fn test_decode_events_only_reads_the_auction_program() {
+        let auction_program = Pubkey::new_unique();
+        let other_program = Pubkey::new_unique();
+        let (ours, theirs, after_call) = (event(), event(), event());
+        let log = |event: &AuctionEvent| {
+            format!("{}{}", PROGRAM_LOG_PREFIX, encode(event.clone()).unwrap())
+        };
+        let logs = vec![
+            log(&theirs),
+            format!("Program {} invoke [1]", other_program),
+            log(&theirs),
+            format!("Program {} invoke [2]", auction_program),
+            log(&ours),
+            format!(
+                "Program {} consumed 1000 of 200000 compute units",
+                auction_program
+            ),
+            format!("Program {} success", auction_program),
+            log(&theirs),
+            format!("Program {} success", other_program),
+            format!("Program {} invoke [1]", auction_program),
+            format!("Program {} invoke [2]", other_program),
+            log(&theirs),
+            format!(
+                "Program {} failed: custom program error: 0x1",
+                other_program
+            ),
+            log(&after_call),
+            format!("Program {} success", auction_program),
+        ];
+        assert_eq!(
+            decode_events(&auction_program, &logs),
+            vec![ours, after_call]
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{EXTENDED, PREFIX};
+use crate::{BIDS, CURRENCY, EXTENDED, HISTORY, PREFIX};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     instruction::{AccountMeta, Instruction},
@@ -7,10 +7,17 @@ use solana_program::{
 };
 
 pub use crate::processor::{
-    cancel_bid::CancelBidArgs, claim_bid::ClaimBidArgs, create_auction::CreateAuctionArgs,
-    end_auction::EndAuctionArgs, place_bid::PlaceBidArgs, start_auction::StartAuctionArgs,
+    cancel_auction::CancelAuctionArgs, cancel_bid::CancelBidArgs, claim_bid::ClaimBidArgs,
+    close_bidder_metadata::CloseBidderMetadataArgs, close_wallet_bids::CloseWalletBidsArgs,
+    create_auction::CreateAuctionArgs, end_auction::EndAuctionArgs, place_bid::PlaceBidArgs,
+    reclaim_auction_rent::ReclaimAuctionRentArgs, refund_evicted_bid::RefundEvictedBidArgs,
+    refund_losing_bids::RefundLosingBidsArgs, reveal_bid::RevealBidArgs,
+    start_auction::StartAuctionArgs, ExchangeRate,
 };
 
+/// Bids on a native SOL auction, one created with the system program as its token mint, are paid in
+/// lamports. For these the bidder's wallet is passed in place of their token account, the pot in
+/// place of its SPL account, and the system program in place of the token mint.
 #[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
 pub enum AuctionInstruction {
     /// Cancel a bid on a running auction.
@@ -20,59 +27,262 @@ pub enum AuctionInstruction {
     ///   3. `[writable]` The pot SPL account, where the tokens will be deposited.
     ///   4. `[writable]` The metadata account, storing information about the bidders actions.
     ///   5. `[writable]` Auction account, containing data about the auction and item being bid on.
-    ///   6. `[writable]` Token mint, for transfer instructions and verification.
-    ///   7. `[]` Clock sysvar
-    ///   8. `[]` Rent sysvar
-    ///   9. `[]` System program
-    ///   10. `[]` SPL Token Program
+    ///   6. `[writable]` Auction extended account, storing further data about the auction.
+    ///   7. `[writable]` Token mint, for transfer instructions and verification.
+    ///   8. `[]` Clock sysvar
+    ///   9. `[]` Rent sysvar
+    ///   10. `[]` System program
+    ///   11. `[]` SPL Token Program
+    ///   12. `[writable]` The payer of the pot, refunded the rent of the closed pot accounts.
+    ///   13..n. `[writable]` The wallet bids account the bid was counted against, for an auction
+    ///          capping wins per wallet. Then the last bid history page written and the one after
+    ///          it, for an auction keeping a bid history, then the bid pages of a paged auction, in
+    ///          order. See wallet_bids_pubkey, bid_history_page_pubkey and bid_page_pubkey.
     CancelBid(CancelBidArgs),
 
     /// Create a new auction account bound to a resource, initially in a pending state.
     ///   0. `[signer]` The account creating the auction, which is authorised to make changes.
     ///   1. `[writable]` Uninitialized auction account.
-    ///   2. `[]` Rent sysvar
-    ///   3. `[]` System account
+    ///   2. `[writable]` Uninitialized auction extended account.
+    ///   3. `[]` Rent sysvar
+    ///   4. `[]` System account
+    ///   5. `[]` Token mint of the auction, the system program for native SOL.
     CreateAuction(CreateAuctionArgs),
 
     /// Move SPL tokens from winning bid to the destination account.
     ///   0. `[writable]` The destination account
     ///   1. `[writable]` The bidder pot token account
-    ///   2. `[]` The bidder pot pda account [seed of ['auction', program_id, auction key, bidder key]]
+    ///   2. `[writable]` The bidder pot pda account [seed of ['auction', program_id, auction key, bidder key]]
     ///   3. `[signer]` The authority on the auction
     ///   4. `[]` The auction
     ///   5. `[]` The bidder wallet
-    ///   6. `[]` Token mint of the auction
+    ///   6. `[]` Token mint the bid was paid in
     ///   7. `[]` Clock sysvar
     ///   8. `[]` Token program
+    ///   9. `[writable]` The auction extended data pda account [seed of ['auction', program_id, resource id, 'extended']]
+    ///   10. `[writable]` The bidder's token account, refunded any amount bid over the clearing
+    ///          price
+    ///   11. `[writable]` The payer of the pot, refunded the rent of the closed pot accounts
+    ///   12..n. `[]` The bid pages of a paged auction, in order. See bid_page_pubkey.
     ClaimBid(ClaimBidArgs),
 
-    /// Ends an auction, regardless of end timing conditions
+    /// Ends an auction, regardless of end timing conditions. A started sealed bid auction can only
+    /// be ended once its reveal window has closed.
     EndAuction(EndAuctionArgs),
 
-    /// Start an inactive auction.
+    /// Start an inactive auction. Once the scheduled start time of an auction has passed, any
+    /// signer can start it, as long as the extended account is passed.
     ///   0. `[signer]` The creator/authorised account.
     ///   1. `[writable]` Initialized auction account.
     ///   2. `[]` Clock sysvar
+    ///   3. `[]` Auction extended account, holding the scheduled start time. Optional.
     StartAuction(StartAuctionArgs),
 
     /// Update the authority for an auction account.
     SetAuthority,
 
-    /// Place a bid on a running auction.
+    /// Place a bid on a running auction. With top_up set, raises the bidder's active bid instead,
+    /// paying into the existing pot only the difference over what it already holds. An auction
+    /// accepting other currencies takes bids in any of them, passed as the token mint along with a
+    /// pot SPL account of that mint, ranked at their value in the auction's token mint.
     ///   0. `[signer]` The bidders primary account, for PDA calculation/transit auth.
     ///   1. `[writable]` The bidders token account they'll pay with
     ///   2. `[writable]` The pot, containing a reference to the stored SPL token account.
     ///   3. `[writable]` The pot SPL account, where the tokens will be deposited.
     ///   4. `[writable]` The metadata account, storing information about the bidders actions.
     ///   5. `[writable]` Auction account, containing data about the auction and item being bid on.
-    ///   6. `[writable]` Token mint, for transfer instructions and verification.
-    ///   7. `[signer]` Transfer authority, for moving tokens into the bid pot.
-    ///   8. `[signer]` Payer
-    ///   9. `[]` Clock sysvar
-    ///   10. `[]` Rent sysvar
-    ///   11. `[]` System program
-    ///   12. `[]` SPL Token Program
+    ///   6. `[writable]` Auction extended account, storing further data about the auction.
+    ///   7. `[writable]` Token mint, for transfer instructions and verification.
+    ///   8. `[signer]` Transfer authority, for moving tokens into the bid pot.
+    ///   9. `[signer]` Payer
+    ///   10. `[]` Clock sysvar
+    ///   11. `[]` Rent sysvar
+    ///   12. `[]` System program
+    ///   13. `[]` SPL Token Program
+    ///   14. `[writable]` The commitment account, storing the sealed bid. Only used by sealed bid
+    ///       auctions.
+    ///   15..n. `[writable]` The bidder's currency record, for an auction accepting other
+    ///          currencies, followed by `[]` the exchange rate of the currency for a bid in anything
+    ///          but the token mint. Then `[]` the bidder's token account holding the gate mint, for
+    ///          an auction gated by a holder mint, or the paying wallet's if the auction caps wins
+    ///          per wallet. Then `[writable]` the wallet bids account of the wallet paying, for an
+    ///          auction capping wins per wallet. Then `[writable]` the last bid history page
+    ///          written and the one after it, for an auction keeping a bid history, then the bid
+    ///          pages of a paged auction, in order, followed by the next page in case the last one
+    ///          is full. See bid_currency_pubkey, wallet_bids_pubkey, bid_history_page_pubkey and
+    ///          bid_page_pubkey.
     PlaceBid(PlaceBidArgs),
+
+    /// Reveal a sealed bid after bidding has closed, ranking it into the winners.
+    ///   0. `[signer]` The bidders primary account, for PDA calculation/transit auth.
+    ///   1. `[writable]` The bidders token account, refunded with any escrow above the bid.
+    ///   2. `[]` The pot, containing a reference to the stored SPL token account.
+    ///   3. `[writable]` The pot SPL account, holding the escrowed tokens.
+    ///   4. `[writable]` The metadata account, storing information about the bidders actions.
+    ///   5. `[writable]` The commitment account, storing the sealed bid.
+    ///   6. `[writable]` Auction account, containing data about the auction and item being bid on.
+    ///   7. `[]` Clock sysvar
+    ///   8. `[]` SPL Token Program
+    ///   9. `[writable]` The auction extended data pda account [seed of ['auction', program_id, resource id, 'extended']]
+    ///   10. `[writable, signer]` Pays for a new bid history page, for an auction keeping a bid
+    ///       history.
+    ///   11. `[]` Rent sysvar
+    ///   12. `[]` System program
+    ///   13..n. `[writable]` The last bid history page written and the one after it, for an
+    ///          auction keeping a bid history, where a bid the reveal pushes out of the winners is
+    ///          recorded. See bid_history_page_pubkey.
+    RevealBid(RevealBidArgs),
+
+    /// Refund a bid that was evicted from the bid state by higher bids. Permissionless, the funds
+    /// can only go to a token account owned by the bidder.
+    ///   0. `[]` The bidders primary account, for PDA calculation.
+    ///   1. `[writable]` The bidders token account they'll receive the refund with.
+    ///   2. `[writable]` The pot, containing a reference to the stored SPL token account.
+    ///   3. `[writable]` The pot SPL account, where the bid tokens are held.
+    ///   4. `[writable]` The metadata account, storing information about the bidders actions.
+    ///   5. `[]` Auction account, containing data about the auction and item being bid on.
+    ///   6. `[writable]` The auction extended data pda account [seed of ['auction', program_id, resource id, 'extended']]
+    ///   7. `[]` Token mint, for transfer instructions and verification.
+    ///   8. `[]` SPL Token Program
+    ///   9. `[writable]` The payer of the pot, refunded the rent of the closed pot accounts.
+    ///   10. `[]` The commitment account, storing the sealed bid. Only used by sealed bid auctions.
+    ///   11. `[]` Clock sysvar
+    ///   12. `[]` Rent sysvar
+    ///   13. `[]` System program
+    ///   14. `[writable, signer]` Pays for a new bid history page, for an auction keeping a bid
+    ///       history.
+    ///   15..n. `[writable]` The wallet bids account the bid was counted against, for an auction
+    ///          capping wins per wallet. Then the last bid history page written and the one after
+    ///          it, for an auction keeping a bid history, then `[]` the bid pages of a paged
+    ///          auction, in order. See wallet_bids_pubkey, bid_history_page_pubkey and
+    ///          bid_page_pubkey.
+    RefundEvictedBid(RefundEvictedBidArgs),
+
+    /// Refund a batch of losing bids once the auction has ended. Permissionless, each refund can
+    /// only go to the associated token account of its bidder. Already refunded bids are skipped.
+    ///   0. `[]` Auction account, containing data about the auction and item being bid on.
+    ///   1. `[writable]` The auction extended data pda account [seed of ['auction', program_id, resource id, 'extended']]
+    ///   2. `[]` Clock sysvar
+    ///   3. `[]` SPL Token Program
+    ///   4. `[writable, signer]` Pays for a new bid history page, for an auction keeping a bid
+    ///      history.
+    ///   5. `[]` Rent sysvar
+    ///   6. `[]` System program
+    ///   Then `[writable]` the last bid history page written and the one after it, for an auction
+    ///   keeping a bid history, and `[]` the bid pages of a paged auction, in order. See
+    ///   bid_history_page_pubkey and bid_page_pubkey. Then for each bid being refunded:
+    ///   0. `[writable]` The metadata account, storing information about the bidders actions.
+    ///   1. `[writable]` The pot, containing a reference to the stored SPL token account.
+    ///   2. `[writable]` The pot SPL account, where the bid tokens are held.
+    ///   3. `[writable]` The bidders associated token account they'll receive the refund with.
+    ///   4. `[writable]` The payer of the pot, refunded the rent of the closed pot accounts.
+    ///   5. `[writable]` The wallet bids account the bid was counted against, for an auction
+    ///      capping wins per wallet only. See wallet_bids_pubkey.
+    RefundLosingBids(RefundLosingBidsArgs),
+
+    /// Close the accounts of an ended auction once every bidder pot has been closed, returning
+    /// their rent to the destination. Auctions created before bidder pots were counted can't be
+    /// closed, as there is no telling whether any pot still holds funds.
+    ///   0. `[signer]` The authority on the auction
+    ///   1. `[writable]` Auction account, containing data about the auction and item being bid on.
+    ///   2. `[writable]` The auction extended data pda account [seed of ['auction', program_id, resource id, 'extended']]
+    ///   3. `[writable]` The destination account, receiving the rent.
+    ///   4. `[]` Clock sysvar
+    ///   Then for each bid page of a paged auction, in order, followed by each bid history page of
+    ///   an auction keeping a history, in order:
+    ///   0. `[writable]` The page. See bid_page_pubkey and bid_history_page_pubkey.
+    ///   1. `[writable]` The payer of the page, refunded its rent.
+    ReclaimAuctionRent(ReclaimAuctionRentArgs),
+
+    /// Close the metadata of a bidder once the auction has been closed, returning the rent to the
+    /// account that paid for it. Permissionless. Claiming or cancelling the bid isn't enough, as
+    /// redeeming won items on the auction relies on the metadata until then.
+    ///   0. `[]` The bidders primary account, for PDA calculation.
+    ///   1. `[writable]` The metadata account, storing information about the bidders actions.
+    ///   2. `[]` The closed auction account.
+    ///   3. `[writable]` The payer of the metadata, receiving the rent. The bidder for metadata
+    ///          created before the payer was recorded.
+    ///   4. `[writable]` The commitment account, storing the sealed bid, for sealed bid auctions.
+    ///          Or the currency record of the bid, for auctions accepting other currencies.
+    CloseBidderMetadata(CloseBidderMetadataArgs),
+
+    /// Close the record of the bids a wallet funded in an auction capping wins per wallet, once the
+    /// auction has been closed, returning the rent to the account that paid for it. Permissionless.
+    ///   0. `[writable]` The wallet bids account. See wallet_bids_pubkey.
+    ///   1. `[]` The closed auction account.
+    ///   2. `[writable]` The payer of the wallet bids account, receiving the rent.
+    CloseWalletBids(CloseWalletBidsArgs),
+
+    /// Cancel an auction that has not ended, leaving it without winners so every bid can be
+    /// refunded. Only possible once bids have landed if the auction was created to allow it.
+    ///   0. `[signer]` The authority on the auction
+    ///   1. `[writable]` Auction account, containing data about the auction and item being bid on.
+    ///   2. `[]` The auction extended data pda account [seed of ['auction', program_id, resource id, 'extended']]
+    ///   3. `[]` Clock sysvar
+    CancelAuction(CancelAuctionArgs),
+
+    /// Create or update an exchange rate, pricing a currency that auctions can list as accepted
+    /// for bids. The account must already be allocated with EXCHANGE_RATE_LEN bytes and owned by
+    /// this program, and its mints can't change once set.
+    ///   0. `[writable, signer]` Exchange rate account
+    UpdateExchangeRate(ExchangeRate),
+}
+
+/// Derives the key of a page of bids of a paged English auction, to be appended to the accounts
+/// of the instructions that read or write the bids.
+pub fn bid_page_pubkey(program_id: Pubkey, auction_pubkey: Pubkey, page: u32) -> Pubkey {
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        auction_pubkey.as_ref(),
+        BIDS.as_bytes(),
+        &page.to_le_bytes(),
+    ];
+    let (bid_page_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+    bid_page_pubkey
+}
+
+/// Derives the key of a page of the bid history of an auction. Instructions recording history take
+/// the last page written, or page 0 before any have been, and the page after it.
+pub fn bid_history_page_pubkey(program_id: Pubkey, auction_pubkey: Pubkey, page: u32) -> Pubkey {
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        auction_pubkey.as_ref(),
+        HISTORY.as_bytes(),
+        &page.to_le_bytes(),
+    ];
+    let (bid_history_page_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+    bid_history_page_pubkey
+}
+
+/// Derives the key of the account listing the bidders funded from a wallet, for auctions capping
+/// wins per wallet. The wallet is the owner of the token account bids are paid from, or the bidder
+/// in a native SOL auction.
+pub fn wallet_bids_pubkey(program_id: Pubkey, auction_pubkey: Pubkey, wallet: Pubkey) -> Pubkey {
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        auction_pubkey.as_ref(),
+        wallet.as_ref(),
+        "wallet".as_bytes(),
+    ];
+    let (wallet_bids_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+    wallet_bids_pubkey
+}
+
+/// Derives the key of the record of the currency a bidder paid in, for auctions accepting other
+/// currencies.
+pub fn bid_currency_pubkey(program_id: Pubkey, auction_pubkey: Pubkey, bidder: Pubkey) -> Pubkey {
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        auction_pubkey.as_ref(),
+        bidder.as_ref(),
+        CURRENCY.as_bytes(),
+    ];
+    let (bid_currency_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+    bid_currency_pubkey
 }
 
 /// Creates an CreateAuction instruction.
@@ -104,6 +314,7 @@ pub fn create_auction_instruction(
             AccountMeta::new(auction_extended_pubkey, false),
             AccountMeta::new_readonly(sysvar::rent::id(), false),
             AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(args.token_mint, false),
         ],
         data: AuctionInstruction::CreateAuction(args)
             .try_to_vec()
@@ -202,6 +413,16 @@ pub fn place_bid_instruction(
     ];
     let (bidder_meta_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
 
+    // Derive Bidder Commitment
+    let seeds = &[
+        PREFIX.as_bytes(),
+        &program_id.as_ref(),
+        auction_pubkey.as_ref(),
+        bidder_pubkey.as_ref(),
+        "commitment".as_bytes(),
+    ];
+    let (bidder_commitment_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
     Instruction {
         program_id,
         accounts: vec![
@@ -219,6 +440,7 @@ pub fn place_bid_instruction(
             AccountMeta::new_readonly(sysvar::rent::id(), false),
             AccountMeta::new_readonly(solana_program::system_program::id(), false),
             AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(bidder_commitment_pubkey, false),
         ],
         data: AuctionInstruction::PlaceBid(args).try_to_vec().unwrap(),
     }
@@ -231,6 +453,7 @@ pub fn cancel_bid_instruction(
     bidder_token_pubkey: Pubkey,
     bidder_pot_token_pubkey: Pubkey,
     token_mint_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
     args: CancelBidArgs,
 ) -> Instruction {
     // Derive Auction Key
@@ -283,6 +506,7 @@ pub fn cancel_bid_instruction(
             AccountMeta::new_readonly(sysvar::rent::id(), false),
             AccountMeta::new_readonly(solana_program::system_program::id(), false),
             AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(payer_pubkey, false),
         ],
         data: AuctionInstruction::CancelBid(args).try_to_vec().unwrap(),
     }
@@ -319,6 +543,8 @@ pub fn claim_bid_instruction(
     bidder_pubkey: Pubkey,
     bidder_pot_token_pubkey: Pubkey,
     token_mint_pubkey: Pubkey,
+    bidder_token_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
     args: ClaimBidArgs,
 ) -> Instruction {
     // Derive Auction Key
@@ -338,6 +564,15 @@ pub fn claim_bid_instruction(
     ];
     let (bidder_pot_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
 
+    // Derive Auction Extended Key
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        args.resource.as_ref(),
+        EXTENDED.as_bytes(),
+    ];
+    let (auction_extended_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
     Instruction {
         program_id,
         accounts: vec![
@@ -350,7 +585,443 @@ pub fn claim_bid_instruction(
             AccountMeta::new_readonly(token_mint_pubkey, false),
             AccountMeta::new_readonly(sysvar::clock::id(), false),
             AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(auction_extended_pubkey, false),
+            AccountMeta::new(bidder_token_pubkey, false),
+            AccountMeta::new(payer_pubkey, false),
         ],
         data: AuctionInstruction::ClaimBid(args).try_to_vec().unwrap(),
     }
 }
+
+/// Creates an RevealBid instruction.
+pub fn reveal_bid_instruction(
+    program_id: Pubkey,
+    bidder_pubkey: Pubkey,
+    bidder_token_pubkey: Pubkey,
+    bidder_pot_token_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+    args: RevealBidArgs,
+) -> Instruction {
+    // Derive Auction Key
+    let seeds = &[
+        PREFIX.as_bytes(),
+        &program_id.as_ref(),
+        args.resource.as_ref(),
+    ];
+    let (auction_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    // Derive Auction Extended Key
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        args.resource.as_ref(),
+        EXTENDED.as_bytes(),
+    ];
+    let (auction_extended_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    // Derive Bidder Pot
+    let seeds = &[
+        PREFIX.as_bytes(),
+        &program_id.as_ref(),
+        auction_pubkey.as_ref(),
+        bidder_pubkey.as_ref(),
+    ];
+    let (bidder_pot_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    // Derive Bidder Meta
+    let seeds = &[
+        PREFIX.as_bytes(),
+        &program_id.as_ref(),
+        auction_pubkey.as_ref(),
+        bidder_pubkey.as_ref(),
+        "metadata".as_bytes(),
+    ];
+    let (bidder_meta_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    // Derive Bidder Commitment
+    let seeds = &[
+        PREFIX.as_bytes(),
+        &program_id.as_ref(),
+        auction_pubkey.as_ref(),
+        bidder_pubkey.as_ref(),
+        "commitment".as_bytes(),
+    ];
+    let (bidder_commitment_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(bidder_pubkey, true),
+            AccountMeta::new(bidder_token_pubkey, false),
+            AccountMeta::new_readonly(bidder_pot_pubkey, false),
+            AccountMeta::new(bidder_pot_token_pubkey, false),
+            AccountMeta::new(bidder_meta_pubkey, false),
+            AccountMeta::new(bidder_commitment_pubkey, false),
+            AccountMeta::new(auction_pubkey, false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(auction_extended_pubkey, false),
+            AccountMeta::new(payer_pubkey, true),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        ],
+        data: AuctionInstruction::RevealBid(args).try_to_vec().unwrap(),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn refund_evicted_bid_instruction(
+    program_id: Pubkey,
+    bidder_pubkey: Pubkey,
+    bidder_token_pubkey: Pubkey,
+    bidder_pot_token_pubkey: Pubkey,
+    token_mint_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+    history_payer_pubkey: Pubkey,
+    args: RefundEvictedBidArgs,
+) -> Instruction {
+    // Derive Auction Key
+    let seeds = &[
+        PREFIX.as_bytes(),
+        &program_id.as_ref(),
+        args.resource.as_ref(),
+    ];
+    let (auction_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    // Derive Auction Extended Key
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        args.resource.as_ref(),
+        EXTENDED.as_bytes(),
+    ];
+    let (auction_extended_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    // Derive Bidder Pot
+    let seeds = &[
+        PREFIX.as_bytes(),
+        &program_id.as_ref(),
+        auction_pubkey.as_ref(),
+        bidder_pubkey.as_ref(),
+    ];
+    let (bidder_pot_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    // Derive Bidder Meta
+    let seeds = &[
+        PREFIX.as_bytes(),
+        &program_id.as_ref(),
+        auction_pubkey.as_ref(),
+        bidder_pubkey.as_ref(),
+        "metadata".as_bytes(),
+    ];
+    let (bidder_meta_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    // Derive Bidder Commitment
+    let seeds = &[
+        PREFIX.as_bytes(),
+        &program_id.as_ref(),
+        auction_pubkey.as_ref(),
+        bidder_pubkey.as_ref(),
+        "commitment".as_bytes(),
+    ];
+    let (bidder_commitment_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(bidder_pubkey, false),
+            AccountMeta::new(bidder_token_pubkey, false),
+            AccountMeta::new(bidder_pot_pubkey, false),
+            AccountMeta::new(bidder_pot_token_pubkey, false),
+            AccountMeta::new(bidder_meta_pubkey, false),
+            AccountMeta::new_readonly(auction_pubkey, false),
+            AccountMeta::new(auction_extended_pubkey, false),
+            AccountMeta::new_readonly(token_mint_pubkey, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(payer_pubkey, false),
+            AccountMeta::new_readonly(bidder_commitment_pubkey, false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new(history_payer_pubkey, true),
+        ],
+        data: AuctionInstruction::RefundEvictedBid(args)
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Builds a refund for each (bidder, bidder pot token account, destination token account, pot
+/// payer, wallet bids account) given, the last only for an auction capping wins per wallet. The
+/// payer pays for a new bid history page.
+pub fn refund_losing_bids_instruction(
+    program_id: Pubkey,
+    payer_pubkey: Pubkey,
+    history_page_pubkeys: &[Pubkey],
+    bid_page_pubkeys: &[Pubkey],
+    bids: &[(Pubkey, Pubkey, Pubkey, Pubkey, Option<Pubkey>)],
+    args: RefundLosingBidsArgs,
+) -> Instruction {
+    // Derive Auction Key
+    let seeds = &[
+        PREFIX.as_bytes(),
+        &program_id.as_ref(),
+        args.resource.as_ref(),
+    ];
+    let (auction_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    // Derive Auction Extended Key
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        args.resource.as_ref(),
+        EXTENDED.as_bytes(),
+    ];
+    let (auction_extended_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(auction_pubkey, false),
+        AccountMeta::new(auction_extended_pubkey, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(payer_pubkey, true),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(solana_program::system_program::id(), false),
+    ];
+    for page_pubkey in history_page_pubkeys {
+        accounts.push(AccountMeta::new(*page_pubkey, false));
+    }
+    for page_pubkey in bid_page_pubkeys {
+        accounts.push(AccountMeta::new_readonly(*page_pubkey, false));
+    }
+
+    for (bidder_pubkey, bidder_pot_token_pubkey, destination_pubkey, payer_pubkey, wallet_bids) in
+        bids
+    {
+        // Derive Bidder Meta
+        let seeds = &[
+            PREFIX.as_bytes(),
+            &program_id.as_ref(),
+            auction_pubkey.as_ref(),
+            bidder_pubkey.as_ref(),
+            "metadata".as_bytes(),
+        ];
+        let (bidder_meta_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+        // Derive Bidder Pot
+        let seeds = &[
+            PREFIX.as_bytes(),
+            &program_id.as_ref(),
+            auction_pubkey.as_ref(),
+            bidder_pubkey.as_ref(),
+        ];
+        let (bidder_pot_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+        accounts.push(AccountMeta::new(bidder_meta_pubkey, false));
+        accounts.push(AccountMeta::new(bidder_pot_pubkey, false));
+        accounts.push(AccountMeta::new(*bidder_pot_token_pubkey, false));
+        accounts.push(AccountMeta::new(*destination_pubkey, false));
+        accounts.push(AccountMeta::new(*payer_pubkey, false));
+        if let Some(wallet_bids_pubkey) = wallet_bids {
+            accounts.push(AccountMeta::new(*wallet_bids_pubkey, false));
+        }
+    }
+
+    Instruction {
+        program_id,
+        accounts,
+        data: AuctionInstruction::RefundLosingBids(args)
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+pub fn reclaim_auction_rent_instruction(
+    program_id: Pubkey,
+    authority_pubkey: Pubkey,
+    destination_pubkey: Pubkey,
+    bid_pages: &[(Pubkey, Pubkey)],
+    history_pages: &[(Pubkey, Pubkey)],
+    args: ReclaimAuctionRentArgs,
+) -> Instruction {
+    // Derive Auction Key
+    let seeds = &[
+        PREFIX.as_bytes(),
+        &program_id.as_ref(),
+        args.resource.as_ref(),
+    ];
+    let (auction_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    // Derive Auction Extended Key
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        args.resource.as_ref(),
+        EXTENDED.as_bytes(),
+    ];
+    let (auction_extended_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(authority_pubkey, true),
+        AccountMeta::new(auction_pubkey, false),
+        AccountMeta::new(auction_extended_pubkey, false),
+        AccountMeta::new(destination_pubkey, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    for (page_pubkey, payer_pubkey) in bid_pages.iter().chain(history_pages) {
+        accounts.push(AccountMeta::new(*page_pubkey, false));
+        accounts.push(AccountMeta::new(*payer_pubkey, false));
+    }
+
+    Instruction {
+        program_id,
+        accounts,
+        data: AuctionInstruction::ReclaimAuctionRent(args)
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Creates a CloseBidderMetadata instruction, which also closes the bidder's commitment when
+/// `sealed` is set, or their currency record when `multi_currency` is.
+pub fn close_bidder_metadata_instruction(
+    program_id: Pubkey,
+    bidder_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+    sealed: bool,
+    multi_currency: bool,
+    args: CloseBidderMetadataArgs,
+) -> Instruction {
+    // Derive Auction Key
+    let seeds = &[
+        PREFIX.as_bytes(),
+        &program_id.as_ref(),
+        args.resource.as_ref(),
+    ];
+    let (auction_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    // Derive Bidder Meta
+    let seeds = &[
+        PREFIX.as_bytes(),
+        &program_id.as_ref(),
+        auction_pubkey.as_ref(),
+        bidder_pubkey.as_ref(),
+        "metadata".as_bytes(),
+    ];
+    let (bidder_meta_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(bidder_pubkey, false),
+        AccountMeta::new(bidder_meta_pubkey, false),
+        AccountMeta::new_readonly(auction_pubkey, false),
+        AccountMeta::new(payer_pubkey, false),
+    ];
+
+    if sealed {
+        // Derive Bidder Commitment
+        let seeds = &[
+            PREFIX.as_bytes(),
+            &program_id.as_ref(),
+            auction_pubkey.as_ref(),
+            bidder_pubkey.as_ref(),
+            "commitment".as_bytes(),
+        ];
+        let (bidder_commitment_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+        accounts.push(AccountMeta::new(bidder_commitment_pubkey, false));
+    } else if multi_currency {
+        accounts.push(AccountMeta::new(
+            bid_currency_pubkey(program_id, auction_pubkey, bidder_pubkey),
+            false,
+        ));
+    }
+
+    Instruction {
+        program_id,
+        accounts,
+        data: AuctionInstruction::CloseBidderMetadata(args)
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Creates a CloseWalletBids instruction.
+pub fn close_wallet_bids_instruction(
+    program_id: Pubkey,
+    wallet: Pubkey,
+    payer_pubkey: Pubkey,
+    args: CloseWalletBidsArgs,
+) -> Instruction {
+    // Derive Auction Key
+    let seeds = &[
+        PREFIX.as_bytes(),
+        &program_id.as_ref(),
+        args.resource.as_ref(),
+    ];
+    let (auction_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(
+                wallet_bids_pubkey(program_id, auction_pubkey, wallet),
+                false,
+            ),
+            AccountMeta::new_readonly(auction_pubkey, false),
+            AccountMeta::new(payer_pubkey, false),
+        ],
+        data: AuctionInstruction::CloseWalletBids(args)
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Creates a CancelAuction instruction.
+pub fn cancel_auction_instruction(
+    program_id: Pubkey,
+    authority_pubkey: Pubkey,
+    args: CancelAuctionArgs,
+) -> Instruction {
+    // Derive Auction Key
+    let seeds = &[
+        PREFIX.as_bytes(),
+        &program_id.as_ref(),
+        args.resource.as_ref(),
+    ];
+    let (auction_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        args.resource.as_ref(),
+        EXTENDED.as_bytes(),
+    ];
+    let (auction_extended_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(authority_pubkey, true),
+            AccountMeta::new(auction_pubkey, false),
+            AccountMeta::new_readonly(auction_extended_pubkey, false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: AuctionInstruction::CancelAuction(args)
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Creates an UpdateExchangeRate instruction.
+pub fn update_exchange_rate_instruction(
+    program_id: Pubkey,
+    exchange_rate_pubkey: Pubkey,
+    args: ExchangeRate,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![AccountMeta::new(exchange_rate_pubkey, true)],
+        data: AuctionInstruction::UpdateExchangeRate(args)
+            .try_to_vec()
+            .unwrap(),
+    }
+}
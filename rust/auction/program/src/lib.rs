@@ -1,9 +1,10 @@
 #![allow(warnings)]
 
-mod errors;
+pub mod errors;
 mod utils;
 
 pub mod entrypoint;
+pub mod events;
 pub mod instruction;
 pub mod processor;
 
@@ -11,4 +12,13 @@ pub mod processor;
 pub const PREFIX: &str = "auction";
 
 pub const EXTENDED: &str = "extended";
+
+/// Used as a seed for the pages of bids of a paged English auction. See BidPage.
+pub const BIDS: &str = "bids";
+
+/// Used as a seed for the pages of the bid history of an auction. See BidHistoryPage.
+pub const HISTORY: &str = "history";
+
+/// Used as a seed for the record of the currency a bid was paid in. See BidCurrency.
+pub const CURRENCY: &str = "currency";
 solana_program::declare_id!("auctxRXPeJoc4817jDhf4HbjnhEcr1cCXenosMhK5R8");
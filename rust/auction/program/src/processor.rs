@@ -1,28 +1,57 @@
-use crate::errors::AuctionError;
+use crate::{
+    errors::AuctionError,
+    utils::{
+        assert_derivation, assert_initialized, assert_owned_by, create_or_allocate_account_raw,
+    },
+    BIDS, HISTORY, PREFIX,
+};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
-    account_info::AccountInfo, borsh::try_from_slice_unchecked, clock::UnixTimestamp,
-    entrypoint::ProgramResult, hash::Hash, msg, program_error::ProgramError, pubkey::Pubkey,
+    account_info::AccountInfo,
+    borsh::try_from_slice_unchecked,
+    clock::UnixTimestamp,
+    entrypoint::ProgramResult,
+    hash::{hashv, Hash},
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
 };
+use spl_token::state::Account;
 use std::{cmp, mem};
 
 // Declare submodules, each contains a single handler for each instruction variant in the program.
+pub mod cancel_auction;
 pub mod cancel_bid;
 pub mod claim_bid;
+pub mod close_bidder_metadata;
+pub mod close_wallet_bids;
 pub mod create_auction;
 pub mod end_auction;
 pub mod place_bid;
+pub mod reclaim_auction_rent;
+pub mod refund_evicted_bid;
+pub mod refund_losing_bids;
+pub mod reveal_bid;
 pub mod set_authority;
 pub mod start_auction;
+pub mod update_exchange_rate;
 
 // Re-export submodules handlers + associated types for other programs to consume.
+pub use cancel_auction::*;
 pub use cancel_bid::*;
 pub use claim_bid::*;
+pub use close_bidder_metadata::*;
+pub use close_wallet_bids::*;
 pub use create_auction::*;
 pub use end_auction::*;
 pub use place_bid::*;
+pub use reclaim_auction_rent::*;
+pub use refund_evicted_bid::*;
+pub use refund_losing_bids::*;
+pub use reveal_bid::*;
 pub use set_authority::*;
 pub use start_auction::*;
+pub use update_exchange_rate::*;
 
 pub fn process_instruction(
     program_id: &Pubkey,
@@ -38,6 +67,24 @@ pub fn process_instruction(
         AuctionInstruction::PlaceBid(args) => place_bid(program_id, accounts, args),
         AuctionInstruction::SetAuthority => set_authority(program_id, accounts),
         AuctionInstruction::StartAuction(args) => start_auction(program_id, accounts, args),
+        AuctionInstruction::RevealBid(args) => reveal_bid(program_id, accounts, args),
+        AuctionInstruction::RefundEvictedBid(args) => {
+            refund_evicted_bid(program_id, accounts, args)
+        }
+        AuctionInstruction::RefundLosingBids(args) => {
+            refund_losing_bids(program_id, accounts, args)
+        }
+        AuctionInstruction::ReclaimAuctionRent(args) => {
+            reclaim_auction_rent(program_id, accounts, args)
+        }
+        AuctionInstruction::CloseBidderMetadata(args) => {
+            close_bidder_metadata(program_id, accounts, args)
+        }
+        AuctionInstruction::CloseWalletBids(args) => close_wallet_bids(program_id, accounts, args),
+        AuctionInstruction::CancelAuction(args) => cancel_auction(program_id, accounts, args),
+        AuctionInstruction::UpdateExchangeRate(args) => {
+            update_exchange_rate(program_id, accounts, args)
+        }
     }
 }
 
@@ -58,6 +105,15 @@ pub enum PriceFloor {
 // The two extra 8's are present, one 8 is for the Vec's amount of elements and one is for the max
 // usize in bid state.
 pub const BASE_AUCTION_DATA_SIZE: usize = 32 + 32 + 9 + 9 + 9 + 9 + 1 + 32 + 1 + 8 + 8 + 8;
+// Start price, end price, curve and step of a Dutch auction take 8 + 8 + 1 + 8 bytes, padded up to
+// the size of a Bid so the account still passes the length check in from_account_info.
+pub const DUTCH_AUCTION_DATA_SIZE: usize = 40;
+// The reveal period of a sealed bid auction takes 8 bytes, padded up to the size of a Bid for the
+// same reason.
+pub const SEALED_BID_DATA_SIZE: usize = 40;
+// The page count of a paged English auction takes 4 bytes, padded up to the size of a Bid for the
+// same reason.
+pub const PAGED_BID_DATA_SIZE: usize = 40;
 #[repr(C)]
 #[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
 pub struct AuctionData {
@@ -95,11 +151,106 @@ pub const MAX_AUCTION_DATA_EXTENDED_SIZE: usize = 8 + 9 + 2 + 200;
 pub struct AuctionDataExtended {
     /// Total uncancelled bids
     pub total_uncancelled_bids: u64,
-    // Unimplemented fields
-    /// Tick size
+    /// Minimum amount a new bid must beat the lowest winning bid by once all winning places are
+    /// taken.
     pub tick_size: Option<u64>,
-    /// gap_tick_size_percentage - two decimal points
+    /// Minimum raise over the lowest winning bid, as a whole percentage, that applies instead of
+    /// the tick size once the auction is inside its end_auction_gap window.
     pub gap_tick_size_percentage: Option<u8>,
+    /// If set, every winner pays the same clearing price instead of their own bid, which is the
+    /// second price when there is a single winner. See AuctionData::settlement_amount.
+    pub uniform_price: bool,
+    /// If set, late bids push the end of the auction back. See ExtensionPolicy.
+    pub extension_policy: Option<ExtensionPolicy>,
+    /// Total seconds the end has been pushed back by the extension policy so far.
+    pub total_extension: UnixTimestamp,
+    /// If set, a bid at or above this price wins a single winner English auction outright and
+    /// ends it on the spot.
+    pub instant_sale_price: Option<u64>,
+    /// Number of bidder pots that have not been closed yet. The auction can only have its rent
+    /// reclaimed once every pot is closed, None for auctions created before pots were counted.
+    pub open_bidder_pots: Option<u64>,
+    /// Number of bid history pages written so far, for auctions that keep a bid history. None for
+    /// auctions that don't. See BidHistoryPage.
+    pub bid_history_pages: Option<u32>,
+    /// If set, the auction starts on its own at this time. The first bid placed after it starts
+    /// the auction, as can anybody calling StartAuction. See AuctionData::start_if_scheduled.
+    pub start_auction_at: Option<UnixTimestamp>,
+    /// If set, only the bidders let in by this gate can place bids. See BidderGate.
+    pub bidder_gate: Option<BidderGate>,
+    /// If set, the most live bids that can be funded from any one wallet, and so the most places
+    /// that wallet can win. Only set along with a bidder gate, which admits the wallet rather than
+    /// the bidder. See WalletBids.
+    pub max_wins_per_wallet: Option<u8>,
+    /// Whether the authority can still cancel the auction once it has taken bids. It can always be
+    /// cancelled before then. See CancelAuction.
+    pub cancellable_with_bids: bool,
+    /// Exchange rate accounts of the other currencies bids can be placed in, empty for auctions
+    /// that only take their token mint. Bids in another currency are ranked at their value in the
+    /// token mint. See ExchangeRate and BidCurrency.
+    pub exchange_rates: Vec<Pubkey>,
+}
+
+/// Anti-sniping policy that moves the stored end of an auction back when bids land close to it,
+/// so the end time shown to bidders is always the real deadline.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct ExtensionPolicy {
+    /// A bid placed within this many seconds of the end extends the auction.
+    pub window: UnixTimestamp,
+    /// Seconds the end is pushed back by for each such bid.
+    pub extension: UnixTimestamp,
+    /// Hard cap on the total seconds the end can be pushed back by, uncapped if None.
+    pub max_extension: Option<UnixTimestamp>,
+}
+
+/// Restricts who can bid on an auction, so membership-only sales hold up against anyone calling
+/// the program directly.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub enum BidderGate {
+    /// Root of a Merkle tree whose leaves are the hashes of the allowed bidder keys. Each pair of
+    /// nodes is hashed in sorted order, so a proof is just the list of sibling hashes.
+    MerkleRoot([u8; 32]),
+    /// Bidders must hold a token account of their own with a balance of this mint.
+    HolderMint(Pubkey),
+}
+
+impl BidderGate {
+    /// Checks the gate lets the bidder in, either with a Merkle proof of their key or with a token
+    /// account of theirs holding the required mint.
+    pub fn assert_allowed(
+        &self,
+        bidder: &Pubkey,
+        proof: &[[u8; 32]],
+        holder: Option<&AccountInfo>,
+    ) -> ProgramResult {
+        let allowed = match self {
+            BidderGate::MerkleRoot(root) => {
+                let leaf = hashv(&[bidder.as_ref()]).to_bytes();
+                let computed = proof.iter().fold(leaf, |node, sibling| {
+                    if node <= *sibling {
+                        hashv(&[&node, sibling]).to_bytes()
+                    } else {
+                        hashv(&[sibling, &node]).to_bytes()
+                    }
+                });
+                computed == *root
+            }
+            BidderGate::HolderMint(mint) => match holder {
+                Some(holder) => {
+                    assert_owned_by(holder, &spl_token::id())?;
+                    let account: Account = assert_initialized(holder)?;
+                    account.owner == *bidder && account.mint == *mint && account.amount > 0
+                }
+                None => false,
+            },
+        };
+        if !allowed {
+            return Err(AuctionError::BidderNotAllowed.into());
+        }
+        Ok(())
+    }
 }
 
 impl AuctionDataExtended {
@@ -112,6 +263,93 @@ impl AuctionDataExtended {
 
         Ok(auction_extended)
     }
+
+    /// Appends entries to the bid history of an auction that keeps one, doing nothing otherwise.
+    /// Takes the last page written followed by the page after it, which is created once the last
+    /// page fills up. Page 0 takes the place of the last page before any have been written.
+    pub fn record_bid_history<'b>(
+        &mut self,
+        program_id: &Pubkey,
+        auction_key: &Pubkey,
+        accounts: &[AccountInfo<'b>],
+        creation: BidPageCreation<'_, 'b>,
+        entries: Vec<BidHistoryEntry>,
+    ) -> ProgramResult {
+        let written = match self.bid_history_pages {
+            Some(written) => written,
+            None => return Ok(()),
+        };
+        if accounts.len() < 2 {
+            return Err(AuctionError::BidHistoryPageMissing.into());
+        }
+
+        let last = written.saturating_sub(1);
+        let mut pages: Vec<(&AccountInfo<'b>, Option<BidHistoryPage>)> = vec![];
+        for (offset, page_info) in accounts[..2].iter().enumerate() {
+            let page = last + offset as u32;
+            assert_bid_history_page_derivation(program_id, page_info, auction_key, page)?;
+            let loaded = if page < written {
+                assert_owned_by(page_info, program_id)?;
+                Some(BidHistoryPage::from_account_info(page_info)?)
+            } else {
+                None
+            };
+            pages.push((page_info, loaded));
+        }
+
+        for entry in entries {
+            let (offset, page_info) = match &pages[0].1 {
+                Some(page) if page.entries.len() >= BID_HISTORY_ENTRIES_PER_PAGE => (1, pages[1].0),
+                _ => (0, pages[0].0),
+            };
+            if pages[offset].1.is_none() {
+                let page = last + offset as u32;
+                let bump =
+                    assert_bid_history_page_derivation(program_id, page_info, auction_key, page)?;
+                create_or_allocate_account_raw(
+                    *program_id,
+                    page_info,
+                    creation.rent,
+                    creation.system,
+                    creation.payer,
+                    BID_HISTORY_PAGE_LEN,
+                    &[
+                        PREFIX.as_bytes(),
+                        program_id.as_ref(),
+                        auction_key.as_ref(),
+                        HISTORY.as_bytes(),
+                        &page.to_le_bytes(),
+                        &[bump],
+                    ],
+                )?;
+                pages[offset].1 = Some(BidHistoryPage {
+                    auction: *auction_key,
+                    page,
+                    payer: *creation.payer.key,
+                    entries: vec![],
+                });
+                self.bid_history_pages = Some(page + 1);
+            }
+            if let Some(page) = &mut pages[offset].1 {
+                page.entries.push(entry);
+            }
+        }
+
+        for (page_info, page) in pages {
+            if let Some(page) = page {
+                page.serialize(&mut *page_info.data.borrow_mut())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Counts a bidder pot as closed, for auctions that keep count of their open pots.
+    pub fn count_closed_pot(&mut self) {
+        if let Some(open) = self.open_bidder_pots {
+            self.open_bidder_pots = Some(open.saturating_sub(1));
+        }
+    }
 }
 
 impl AuctionData {
@@ -126,6 +364,32 @@ impl AuctionData {
     }
 
     pub fn ended(&self, now: UnixTimestamp) -> Result<bool, ProgramError> {
+        // An auction that has been ended outright, by its authority or a buy-now bid, is over, as
+        // is one that was cancelled.
+        if self.state == AuctionState::Ended || self.state == AuctionState::Cancelled {
+            return Ok(true);
+        }
+
+        // An auction whose every place has been bought outright is over regardless of timing.
+        if self.bid_state.sold_out() {
+            return Ok(true);
+        }
+
+        // A sealed bid auction stops taking bids at its end time, but only ends once the reveal
+        // window after it has closed.
+        if let BidState::SealedBid { reveal_period, .. } = self.bid_state {
+            return match self.ended_at {
+                Some(end) => {
+                    let reveal_end = match end.checked_add(reveal_period) {
+                        Some(val) => val,
+                        None => return Err(AuctionError::NumericalOverflowError.into()),
+                    };
+                    Ok(now > reveal_end)
+                }
+                None => Ok(false),
+            };
+        }
+
         // If there is an end time specified, handle conditions.
         return match (self.ended_at, self.end_auction_gap) {
             // NOTE if changing this, change in auction.ts on front end as well where logic duplicates.
@@ -144,7 +408,8 @@ impl AuctionData {
                 }
             }
 
-            // Simply whether now has passed the end.
+            // Simply whether now has passed the end. Auctions with an extension policy always
+            // land here, as late bids move the stored end itself.
             (Some(end), None) => Ok(now > end),
 
             // No other end conditions.
@@ -152,7 +417,290 @@ impl AuctionData {
         };
     }
 
+    /// The real deadline for bids, taking the end gap after the last bid into account. Auctions
+    /// with an extension policy store their deadline directly in ended_at. Sealed bid auctions
+    /// still have their reveal window to run after this.
+    pub fn deadline(&self) -> Option<UnixTimestamp> {
+        match (self.ended_at, self.end_auction_gap, self.last_bid) {
+            (Some(end), Some(gap), Some(last)) => Some(cmp::max(end, last.saturating_add(gap))),
+            (end, _, _) => end,
+        }
+    }
+
+    /// Pushes the end back by the extension of the policy when a bid at `now` lands inside its
+    /// window, stopping at the hard cap. Does nothing for auctions without a policy.
+    pub fn extend_for_bid(
+        &mut self,
+        auction_extended: &mut AuctionDataExtended,
+        now: UnixTimestamp,
+    ) -> ProgramResult {
+        let (policy, end) = match (&auction_extended.extension_policy, self.ended_at) {
+            (Some(policy), Some(end)) => (policy, end),
+            _ => return Ok(()),
+        };
+
+        let window_start = end
+            .checked_sub(policy.window)
+            .ok_or(AuctionError::NumericalOverflowError)?;
+        if now < window_start {
+            return Ok(());
+        }
+
+        let extension = match policy.max_extension {
+            Some(max) => cmp::min(
+                policy.extension,
+                cmp::max(max - auction_extended.total_extension, 0),
+            ),
+            None => policy.extension,
+        };
+        if extension == 0 {
+            return Ok(());
+        }
+
+        self.ended_at = Some(
+            end.checked_add(extension)
+                .ok_or(AuctionError::NumericalOverflowError)?,
+        );
+        auction_extended.total_extension = auction_extended
+            .total_extension
+            .checked_add(extension)
+            .ok_or(AuctionError::NumericalOverflowError)?;
+
+        Ok(())
+    }
+
+    /// Moves a created auction into the started state, with any end time counted from `start`.
+    pub fn start_from(&mut self, start: UnixTimestamp) -> ProgramResult {
+        self.ended_at = match self.end_auction_at {
+            Some(end_auction_at) => Some(
+                start
+                    .checked_add(end_auction_at)
+                    .ok_or(AuctionError::NumericalOverflowError)?,
+            ),
+            None => None,
+        };
+        self.state = self.state.clone().start()?;
+        Ok(())
+    }
+
+    /// Starts a created auction whose scheduled start time has passed, returning whether it did.
+    /// The end time is counted from the scheduled start rather than from whenever this is called,
+    /// so the auction runs to the same schedule however late the first bid arrives.
+    pub fn start_if_scheduled(
+        &mut self,
+        auction_extended: &AuctionDataExtended,
+        now: UnixTimestamp,
+    ) -> Result<bool, ProgramError> {
+        match (&self.state, auction_extended.start_auction_at) {
+            (AuctionState::Created, Some(start)) if now >= start => {
+                self.start_from(start)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Whether new bids are no longer accepted. This is the same as the auction having ended,
+    /// except for sealed bid auctions which stop taking bids before their reveal window.
+    pub fn bidding_closed(&self, now: UnixTimestamp) -> Result<bool, ProgramError> {
+        match (&self.bid_state, self.ended_at) {
+            (BidState::SealedBid { .. }, Some(end)) => Ok(now > end),
+            _ => self.ended(now),
+        }
+    }
+
+    /// Whether the auction has entered the closing window of `end_auction_gap` seconds before its
+    /// end time, during which the gap tick size is used instead of the flat tick size.
+    pub fn in_gap_window(&self, now: UnixTimestamp) -> Result<bool, ProgramError> {
+        match (self.ended_at, self.end_auction_gap) {
+            (Some(end), Some(gap)) => {
+                let window_start = match end.checked_sub(gap) {
+                    Some(val) => val,
+                    None => return Err(AuctionError::NumericalOverflowError.into()),
+                };
+                Ok(now >= window_start)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// How much a new bid has to beat the lowest winning bid by: the tick size, or the gap tick
+    /// size percentage of the lowest winning bid when inside the gap window.
+    fn required_raise(
+        &self,
+        lowest: u64,
+        auction_extended: &AuctionDataExtended,
+        now: UnixTimestamp,
+    ) -> Result<u64, ProgramError> {
+        Ok(
+            match (
+                auction_extended.gap_tick_size_percentage,
+                self.in_gap_window(now)?,
+            ) {
+                (Some(percentage), true) => {
+                    // Widen to avoid overflowing before the division brings the value back down.
+                    let raise = (lowest as u128)
+                        .checked_mul(percentage as u128)
+                        .ok_or(AuctionError::NumericalOverflowError)?
+                        / 100;
+                    raise as u64
+                }
+                _ => auction_extended.tick_size.unwrap_or(0),
+            },
+        )
+    }
+
+    /// What a new bid has to raise over, and by how much. Once every winning place is taken this
+    /// is the lowest winning bid, or the price floor if that is higher, by the required raise.
+    /// While a place is open it is the price floor, by the tick size if there is one. Dutch bids
+    /// only have to meet the price, so have nothing to raise over.
+    fn raise_over(
+        &self,
+        auction_extended: &AuctionDataExtended,
+        now: UnixTimestamp,
+    ) -> Result<Option<(u64, u64)>, ProgramError> {
+        if let BidState::DutchAuction { .. } = self.bid_state {
+            return Ok(None);
+        }
+        Ok(Some(match self.bid_state.lowest_winning_bid() {
+            Some(lowest) => {
+                let lowest = cmp::max(lowest, self.floor());
+                (lowest, self.required_raise(lowest, auction_extended, now)?)
+            }
+            None => (self.floor(), auction_extended.tick_size.unwrap_or(0)),
+        }))
+    }
+
+    /// Checks that a new bid of `amount` raises over the current lowest winning bid, or the price
+    /// floor if that is higher, by at least the tick size, or by the gap tick size percentage when
+    /// inside the gap window. While a winning place is open the bid only has to raise over the
+    /// price floor, by the tick size.
+    pub fn assert_valid_raise(
+        &self,
+        amount: u64,
+        auction_extended: &AuctionDataExtended,
+        now: UnixTimestamp,
+    ) -> ProgramResult {
+        let (base, raise) = match self.raise_over(auction_extended, now)? {
+            Some(val) => val,
+            None => return Ok(()),
+        };
+
+        let minimum = base
+            .checked_add(raise)
+            .ok_or(AuctionError::NumericalOverflowError)?;
+        // A raise of zero still requires strictly beating the lowest winner, which the bid state
+        // already enforces by not displacing equal bids.
+        if raise > 0 && amount < minimum {
+            msg!(
+                "Bid of {:?} does not raise over {:?} by the required {:?}",
+                amount,
+                base,
+                raise
+            );
+            return Err(AuctionError::BidIncrementTooSmall.into());
+        }
+
+        Ok(())
+    }
+
+    /// The smallest bid the auction accepts right now. This is the current price of a Dutch
+    /// auction, or the price floor by the tick size while a winning place is open, after which the
+    /// lowest winning bid has to be beaten by the required raise. Clients should ask this rather
+    /// than repeat the rules, which also cover the gap window and the price floor. The pages of a
+    /// paged auction must be loaded first.
+    pub fn minimum_next_bid(
+        &self,
+        auction_extended: &AuctionDataExtended,
+        now: UnixTimestamp,
+    ) -> Result<u64, ProgramError> {
+        if let Some(price) = self.dutch_price(now)? {
+            return Ok(price);
+        }
+
+        let (base, raise) = match self.raise_over(auction_extended, now)? {
+            Some(val) => val,
+            None => return Ok(self.floor()),
+        };
+        // Without a raise a bid still has to strictly beat the lowest winner.
+        let raise = match self.bid_state.lowest_winning_bid() {
+            Some(_) => cmp::max(raise, 1),
+            None => raise,
+        };
+        Ok(base
+            .checked_add(raise)
+            .ok_or(AuctionError::NumericalOverflowError)?)
+    }
+
+    /// The price floor bids must meet, zero when there is none or it is still blinded.
+    pub fn floor(&self) -> u64 {
+        match self.price_floor {
+            PriceFloor::MinimumPrice(min) => min[0],
+            _ => 0,
+        }
+    }
+
+    /// The price a bid must meet right now in a Dutch auction, falling from the start price at the
+    /// start of the auction to the end price at its end time. None for any other kind of auction.
+    pub fn dutch_price(&self, now: UnixTimestamp) -> Result<Option<u64>, ProgramError> {
+        let (start_price, end_price, curve, step) = match &self.bid_state {
+            BidState::DutchAuction {
+                start_price,
+                end_price,
+                curve,
+                step,
+                ..
+            } => (*start_price, *end_price, curve, *step),
+            _ => return Ok(None),
+        };
+
+        // The auction only knows its absolute end and its duration, so work the start back out.
+        let (end, duration) = match (self.ended_at, self.end_auction_at) {
+            (Some(end), Some(duration)) if duration > 0 => (end, duration),
+            _ => return Ok(Some(start_price)),
+        };
+        let start = end
+            .checked_sub(duration)
+            .ok_or(AuctionError::NumericalOverflowError)?;
+
+        // A stepped curve still reaches the end price at the end time, whatever the step.
+        let mut elapsed = cmp::min(cmp::max(now - start, 0), duration);
+        if *curve == PriceCurve::Stepped && step > 0 && elapsed < duration {
+            elapsed -= elapsed % step;
+        }
+
+        // Widen to avoid overflowing before the division brings the value back down.
+        let drop = ((start_price - end_price) as u128)
+            .checked_mul(elapsed as u128)
+            .ok_or(AuctionError::NumericalOverflowError)?
+            / duration as u128;
+
+        Ok(Some(start_price - drop as u64))
+    }
+
+    /// Amount the winner at `index` actually pays for their place. In a uniform price auction this
+    /// is the clearing price, otherwise it is their own bid.
+    pub fn settlement_amount(&self, index: usize, auction_extended: &AuctionDataExtended) -> u64 {
+        let bid = self.bid_state.amount(index);
+        if !auction_extended.uniform_price {
+            return bid;
+        }
+
+        let minimum = match self.price_floor {
+            PriceFloor::MinimumPrice(min) => min[0],
+            _ => 0,
+        };
+        match self.bid_state.clearing_price(minimum) {
+            Some(price) => cmp::min(bid, price),
+            None => bid,
+        }
+    }
+
     pub fn is_winner(&self, key: &Pubkey) -> Option<usize> {
+        // A cancelled auction has no winners, whatever bids it took.
+        if self.state == AuctionState::Cancelled {
+            return None;
+        }
         let minimum = match self.price_floor {
             PriceFloor::MinimumPrice(min) => min[0],
             _ => 0,
@@ -161,6 +709,9 @@ impl AuctionData {
     }
 
     pub fn num_winners(&self) -> u64 {
+        if self.state == AuctionState::Cancelled {
+            return 0;
+        }
         let minimum = match self.price_floor {
             PriceFloor::MinimumPrice(min) => min[0],
             _ => 0,
@@ -172,7 +723,114 @@ impl AuctionData {
         self.bid_state.num_possible_winners()
     }
 
+    /// Loads the bids of a paged English auction into the bid state, so that it can be worked with
+    /// as if the bids were stored inline. Takes the page accounts from the front of `accounts` in
+    /// page order, returning the accounts after them. Does nothing for any other kind of auction.
+    ///
+    /// The bids are read straight out of the pages into a single allocation, with room for one more
+    /// bid, as the program heap can't afford a copy of every page or the vector growing. This is
+    /// what caps the winners of a paged auction at MAX_PAGED_WINNERS.
+    pub fn load_bid_pages<'a, 'b>(
+        &mut self,
+        program_id: &Pubkey,
+        auction_key: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<&'a [AccountInfo<'b>], ProgramError> {
+        let (bids, pages) = match &mut self.bid_state {
+            BidState::PagedEnglishAuction { bids, pages, .. } => (bids, *pages as usize),
+            _ => return Ok(accounts),
+        };
+        if accounts.len() < pages {
+            return Err(AuctionError::BidPageMissing.into());
+        }
+
+        let mut total = 0;
+        for (page, page_info) in accounts[..pages].iter().enumerate() {
+            assert_owned_by(page_info, program_id)?;
+            assert_bid_page_derivation(program_id, page_info, auction_key, page as u32)?;
+            total += BidPage::bid_count(page_info)?;
+        }
+
+        let mut loaded = Vec::with_capacity(total + 1);
+        for page_info in accounts[..pages].iter() {
+            BidPage::read_bids(page_info, &mut loaded)?;
+        }
+
+        // Pages hold the highest bid first, while the bid state holds it last.
+        loaded.reverse();
+        *bids = loaded;
+
+        Ok(&accounts[pages..])
+    }
+
+    /// Writes the bid state of a paged English auction back out to the pages it was loaded from,
+    /// leaving the bids out of the auction account itself. When the pages are full, the next page
+    /// is created from the account following them, if `creation` allows for it. Does nothing for
+    /// any other kind of auction.
+    pub fn store_bid_pages<'b>(
+        &mut self,
+        program_id: &Pubkey,
+        auction_key: &Pubkey,
+        accounts: &[AccountInfo<'b>],
+        creation: Option<BidPageCreation<'_, 'b>>,
+    ) -> ProgramResult {
+        let (bids, pages) = match &mut self.bid_state {
+            BidState::PagedEnglishAuction { bids, pages, .. } => (bids, pages),
+            _ => return Ok(()),
+        };
+
+        let needed = (bids.len() + BIDS_PER_PAGE - 1) / BIDS_PER_PAGE;
+        if needed > *pages as usize {
+            let creation = creation.ok_or(AuctionError::BidPageMissing)?;
+            let page_info = accounts
+                .get(*pages as usize)
+                .ok_or(AuctionError::BidPageMissing)?;
+            let page_bytes = pages.to_le_bytes();
+            let bump = assert_bid_page_derivation(program_id, page_info, auction_key, *pages)?;
+            create_or_allocate_account_raw(
+                *program_id,
+                page_info,
+                creation.rent,
+                creation.system,
+                creation.payer,
+                BID_PAGE_LEN,
+                &[
+                    PREFIX.as_bytes(),
+                    program_id.as_ref(),
+                    auction_key.as_ref(),
+                    BIDS.as_bytes(),
+                    &page_bytes,
+                    &[bump],
+                ],
+            )?;
+            BidPage {
+                auction: *auction_key,
+                page: *pages,
+                payer: *creation.payer.key,
+                bids: vec![],
+            }
+            .serialize(&mut &mut page_info.data.borrow_mut()[..])?;
+            *pages += 1;
+        }
+        if needed > *pages as usize || accounts.len() < *pages as usize {
+            return Err(AuctionError::BidPageMissing.into());
+        }
+
+        // Pages hold the highest bid first, while the bid state holds it last.
+        for (page, page_info) in accounts[..*pages as usize].iter().enumerate() {
+            let start = cmp::min(page * BIDS_PER_PAGE, bids.len());
+            let end = cmp::min(start + BIDS_PER_PAGE, bids.len());
+            BidPage::write_bids(page_info, bids.iter().rev().skip(start).take(end - start))?;
+        }
+        bids.clear();
+
+        Ok(())
+    }
+
     pub fn winner_at(&self, idx: usize) -> Option<Pubkey> {
+        if self.state == AuctionState::Cancelled {
+            return None;
+        }
         let minimum = match self.price_floor {
             PriceFloor::MinimumPrice(min) => min[0],
             _ => 0,
@@ -188,6 +846,8 @@ pub enum AuctionState {
     Created,
     Started,
     Ended,
+    /// Called off by the authority before it ended, without winners. See CancelAuction.
+    Cancelled,
 }
 
 impl AuctionState {
@@ -211,6 +871,15 @@ impl AuctionState {
             _ => Err(AuctionError::AuctionTransitionInvalid.into()),
         }
     }
+
+    #[inline(always)]
+    pub fn cancel(self) -> Result<Self, ProgramError> {
+        match self {
+            AuctionState::Created => Ok(AuctionState::Cancelled),
+            AuctionState::Started => Ok(AuctionState::Cancelled),
+            _ => Err(AuctionError::AuctionTransitionInvalid.into()),
+        }
+    }
 }
 
 /// Bids associate a bidding key with an amount bid.
@@ -218,13 +887,47 @@ impl AuctionState {
 #[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
 pub struct Bid(pub Pubkey, pub u64);
 
+/// Shape of the fall in price of a Dutch auction between its start and end time.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub enum PriceCurve {
+    /// Price falls continuously in a straight line.
+    Linear,
+    /// Price follows the straight line, but only drops once every step seconds.
+    Stepped,
+}
+
 /// BidState tracks the running state of an auction, each variant represents a different kind of
 /// auction being run.
 #[repr(C)]
 #[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
 pub enum BidState {
-    EnglishAuction { bids: Vec<Bid>, max: usize },
-    OpenEdition { bids: Vec<Bid>, max: usize },
+    EnglishAuction {
+        bids: Vec<Bid>,
+        max: usize,
+    },
+    OpenEdition {
+        bids: Vec<Bid>,
+        max: usize,
+    },
+    DutchAuction {
+        bids: Vec<Bid>,
+        max: usize,
+        start_price: u64,
+        end_price: u64,
+        curve: PriceCurve,
+        step: UnixTimestamp,
+    },
+    SealedBid {
+        bids: Vec<Bid>,
+        max: usize,
+        reveal_period: UnixTimestamp,
+    },
+    PagedEnglishAuction {
+        bids: Vec<Bid>,
+        max: usize,
+        pages: u32,
+    },
 }
 
 /// Bidding Implementations.
@@ -234,6 +937,18 @@ pub enum BidState {
 ///
 /// Open Edition: All bids are accepted, cancellations return money to the bidder and always
 /// succeed.
+///
+/// Dutch Auction: The first bids to meet the falling price each take a place outright, stored
+/// oldest last so that winner #1 sits at the same end as in an English auction. Once every place
+/// is taken the auction is over, and winning bids can no longer be cancelled.
+///
+/// Sealed Bid: Bids are kept as hash commitments until the end time, and only enter the state
+/// when revealed during the reveal window, where they are ranked as in an English auction.
+///
+/// Paged English Auction: An English auction whose bids are stored in BidPage accounts rather than
+/// in the auction account, so the number of winners isn't bounded by its size, only by the heap
+/// they are loaded into, see MAX_PAGED_WINNERS. The bids are only held in the state while the pages
+/// are loaded, see AuctionData::load_bid_pages.
 impl BidState {
     pub fn new_english(n: usize) -> Self {
         BidState::EnglishAuction {
@@ -249,6 +964,39 @@ impl BidState {
         }
     }
 
+    pub fn new_dutch(
+        n: usize,
+        start_price: u64,
+        end_price: u64,
+        curve: PriceCurve,
+        step: UnixTimestamp,
+    ) -> Self {
+        BidState::DutchAuction {
+            bids: vec![],
+            max: n,
+            start_price,
+            end_price,
+            curve,
+            step,
+        }
+    }
+
+    pub fn new_sealed_bid(n: usize, reveal_period: UnixTimestamp) -> Self {
+        BidState::SealedBid {
+            bids: vec![],
+            max: n,
+            reveal_period,
+        }
+    }
+
+    pub fn new_paged_english(n: usize) -> Self {
+        BidState::PagedEnglishAuction {
+            bids: vec![],
+            max: n,
+            pages: 0,
+        }
+    }
+
     pub fn max_array_size_for(n: usize) -> usize {
         let mut real_max = n;
         if real_max < 8 {
@@ -260,61 +1008,83 @@ impl BidState {
     }
 
     /// Push a new bid into the state, this succeeds only if the bid is larger than the current top
-    /// winner stored. Crappy list information to start with.
-    pub fn place_bid(&mut self, bid: Bid) -> Result<(), ProgramError> {
+    /// winner stored. Crappy list information to start with. Returns the lowest bid if it had to be
+    /// evicted to make room, whose bidder can then be refunded with RefundEvictedBid.
+    pub fn place_bid(&mut self, bid: Bid) -> Result<Option<Bid>, ProgramError> {
         match self {
             // In a capped auction, track the limited number of winners.
-            BidState::EnglishAuction { ref mut bids, max } => match bids.last() {
+            BidState::EnglishAuction { ref mut bids, max }
+            | BidState::PagedEnglishAuction {
+                ref mut bids, max, ..
+            }
+            | BidState::SealedBid {
+                ref mut bids, max, ..
+            } => match bids.last() {
                 Some(top) => {
-                    msg!("Looking to go over the loop");
                     for i in (0..bids.len()).rev() {
-                        msg!("Comparison of {:?} and {:?} for {:?}", bids[i].1, bid.1, i);
                         if bids[i].1 < bid.1 {
-                            msg!("Ok we can do an insert");
                             if i + 1 < bids.len() {
-                                msg!("Doing a normal insert");
                                 bids.insert(i + 1, bid);
                             } else {
-                                msg!("Doing an on the end insert");
                                 bids.push(bid)
                             }
                             break;
                         } else if bids[i].1 == bid.1 {
-                            msg!("Ok we can do an equivalent insert");
+                            // Equal bids rank below the ones placed before them.
                             if i == 0 {
-                                msg!("Doing a normal insert");
                                 bids.insert(0, bid);
                                 break;
-                            } else {
-                                if bids[i - 1].1 != bids[i].1 {
-                                    msg!("Doing an insert just before");
-                                    bids.insert(i, bid);
-                                    break;
-                                }
-                                msg!("More duplicates ahead...")
+                            } else if bids[i - 1].1 != bids[i].1 {
+                                bids.insert(i, bid);
+                                break;
                             }
                         } else if i == 0 {
-                            msg!("Inserting at 0");
                             bids.insert(0, bid);
                             break;
                         }
                     }
                     let max_size = BidState::max_array_size_for(*max);
 
+                    // Keeping the list bounded pushes out the lowest bid, which is handed back so
+                    // the eviction can be reported and the bidder refunded.
                     if bids.len() > max_size {
-                        bids.remove(0);
+                        return Ok(Some(bids.remove(0)));
                     }
-                    Ok(())
+                    Ok(None)
                 }
                 _ => {
-                    msg!("Pushing bid onto stack");
                     bids.push(bid);
-                    Ok(())
+                    Ok(None)
                 }
             },
 
             // In an open auction, bidding simply succeeds.
-            BidState::OpenEdition { bids, max } => Ok(()),
+            BidState::OpenEdition { bids, max } => Ok(None),
+
+            // In a Dutch auction, the price has already been checked, so the bid takes the next
+            // free place if there is one.
+            BidState::DutchAuction {
+                ref mut bids, max, ..
+            } => {
+                if bids.len() >= *max {
+                    return Err(AuctionError::InvalidState.into());
+                }
+                bids.insert(0, bid);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Raises the standing bid of a bidder, moving their existing entry to its new place rather
+    /// than adding another. A bidder that was evicted re-enters the ranking. Only the open bids of
+    /// an English auction can be raised.
+    pub fn raise_bid(&mut self, bid: Bid) -> Result<Option<Bid>, ProgramError> {
+        match self {
+            BidState::EnglishAuction { .. } | BidState::PagedEnglishAuction { .. } => {
+                self.cancel_bid(bid.0)?;
+                self.place_bid(bid)
+            }
+            _ => Err(AuctionError::InvalidState.into()),
         }
     }
 
@@ -322,7 +1092,13 @@ impl BidState {
     /// function simple no-ops.
     pub fn cancel_bid(&mut self, key: Pubkey) -> Result<(), ProgramError> {
         match self {
-            BidState::EnglishAuction { ref mut bids, max } => {
+            BidState::EnglishAuction { ref mut bids, max }
+            | BidState::PagedEnglishAuction {
+                ref mut bids, max, ..
+            }
+            | BidState::SealedBid {
+                ref mut bids, max, ..
+            } => {
                 bids.retain(|b| b.0 != key);
                 Ok(())
             }
@@ -330,12 +1106,79 @@ impl BidState {
             // In an open auction, cancelling simply succeeds. It's up to the manager of an auction
             // to decide what to do with open edition bids.
             BidState::OpenEdition { bids, max } => Ok(()),
+
+            // Every bid in a Dutch auction is a purchase, so a bid that holds a place is final.
+            BidState::DutchAuction { bids, .. } => {
+                if bids.iter().any(|b| b.0 == key) {
+                    return Err(AuctionError::DutchAuctionBidFinal.into());
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Whether a bid from this key is currently held in the bid state, winning or not.
+    pub fn has_bid(&self, key: &Pubkey) -> bool {
+        match self {
+            BidState::EnglishAuction { bids, .. }
+            | BidState::PagedEnglishAuction { bids, .. }
+            | BidState::OpenEdition { bids, .. }
+            | BidState::DutchAuction { bids, .. }
+            | BidState::SealedBid { bids, .. } => bids.iter().any(|bid| &bid.0 == key),
+        }
+    }
+
+    /// Whether every place has been taken in an auction that ends as soon as that happens.
+    pub fn sold_out(&self) -> bool {
+        match self {
+            BidState::DutchAuction { bids, max, .. } => bids.len() >= *max,
+            _ => false,
+        }
+    }
+
+    /// Amount of the lowest bid currently holding a winning place, if every winning place is taken.
+    pub fn lowest_winning_bid(&self) -> Option<u64> {
+        match self {
+            BidState::EnglishAuction { bids, max }
+            | BidState::PagedEnglishAuction { bids, max, .. }
+            | BidState::SealedBid { bids, max, .. } => {
+                if *max > 0 && bids.len() >= *max {
+                    Some(bids[bids.len() - max].1)
+                } else {
+                    None
+                }
+            }
+            BidState::OpenEdition { bids, max } => None,
+            // Dutch bids only have to meet the price, not beat each other.
+            BidState::DutchAuction { .. } => None,
+        }
+    }
+
+    /// Price every winner pays in a uniform price auction: the highest bid that did not win a
+    /// place, or the price floor if that is higher or there is no such bid. None for auctions that
+    /// do not rank bids against each other.
+    pub fn clearing_price(&self, min: u64) -> Option<u64> {
+        match self {
+            BidState::EnglishAuction { bids, max }
+            | BidState::PagedEnglishAuction { bids, max, .. }
+            | BidState::SealedBid { bids, max, .. } => {
+                let highest_losing_bid = if bids.len() > *max {
+                    bids[bids.len() - max - 1].1
+                } else {
+                    0
+                };
+                Some(cmp::max(highest_losing_bid, min))
+            }
+            BidState::OpenEdition { .. } | BidState::DutchAuction { .. } => None,
         }
     }
 
     pub fn amount(&self, index: usize) -> u64 {
         match self {
-            BidState::EnglishAuction { bids, max } => {
+            BidState::EnglishAuction { bids, max }
+            | BidState::PagedEnglishAuction { bids, max, .. }
+            | BidState::DutchAuction { bids, max, .. }
+            | BidState::SealedBid { bids, max, .. } => {
                 if index >= 0 as usize && index < bids.len() {
                     return bids[bids.len() - index - 1].1;
                 } else {
@@ -352,7 +1195,10 @@ impl BidState {
 
         match self {
             // Presense in the winner list is enough to check win state.
-            BidState::EnglishAuction { bids, max } => {
+            BidState::EnglishAuction { bids, max }
+            | BidState::PagedEnglishAuction { bids, max, .. }
+            | BidState::DutchAuction { bids, max, .. }
+            | BidState::SealedBid { bids, max, .. } => {
                 match bids.iter().position(|bid| &bid.0 == key && bid.1 >= min) {
                     Some(val) => {
                         let zero_based_index = bids.len() - val - 1;
@@ -373,7 +1219,10 @@ impl BidState {
 
     pub fn num_winners(&self, min: u64) -> u64 {
         match self {
-            BidState::EnglishAuction { bids, max } => cmp::min(
+            BidState::EnglishAuction { bids, max }
+            | BidState::PagedEnglishAuction { bids, max, .. }
+            | BidState::DutchAuction { bids, max, .. }
+            | BidState::SealedBid { bids, max, .. } => cmp::min(
                 bids.iter()
                     .filter(|b| b.1 >= min)
                     .collect::<Vec<&Bid>>()
@@ -386,7 +1235,10 @@ impl BidState {
 
     pub fn num_possible_winners(&self) -> u64 {
         match self {
-            BidState::EnglishAuction { bids, max } => *max as u64,
+            BidState::EnglishAuction { bids, max }
+            | BidState::PagedEnglishAuction { bids, max, .. }
+            | BidState::DutchAuction { bids, max, .. }
+            | BidState::SealedBid { bids, max, .. } => *max as u64,
             BidState::OpenEdition { bids, max } => 0,
         }
     }
@@ -394,7 +1246,10 @@ impl BidState {
     // Idea is to present winner as index 0 to outside world
     pub fn winner_at(&self, index: usize, min: u64) -> Option<Pubkey> {
         match self {
-            BidState::EnglishAuction { bids, max } => {
+            BidState::EnglishAuction { bids, max }
+            | BidState::PagedEnglishAuction { bids, max, .. }
+            | BidState::DutchAuction { bids, max, .. }
+            | BidState::SealedBid { bids, max, .. } => {
                 if index < *max && index < bids.len() {
                     let bid = &bids[bids.len() - index - 1];
                     if bid.1 >= min {
@@ -418,7 +1273,9 @@ pub enum WinnerLimit {
     Capped(usize),
 }
 
-pub const BIDDER_METADATA_LEN: usize = 32 + 32 + 8 + 8 + 1;
+pub const BIDDER_METADATA_LEN: usize = 32 + 32 + 8 + 8 + 1 + 32;
+/// Length of metadata created before the payer was recorded, these were paid for by the bidder.
+pub const LEGACY_BIDDER_METADATA_LEN: usize = 32 + 32 + 8 + 8 + 1;
 /// Models a set of metadata for a bidder, meant to be stored in a PDA. This allows looking up
 /// information about a bidder regardless of if they have won, lost or cancelled.
 #[repr(C)]
@@ -435,20 +1292,250 @@ pub struct BidderMetadata {
     // Whether the last bid the user made was cancelled. This should also be enough to know if the
     // user is a winner, as if cancelled it implies previous bids were also cancelled.
     pub cancelled: bool,
+    // Account that paid for the metadata, which gets the rent back when it is closed.
+    pub payer: Pubkey,
+}
+
+pub const BID_COMMITMENT_LEN: usize = 32 + 32 + 32 + 1;
+/// Holds a bidder's sealed bid in a sealed bid auction until it is revealed, meant to be stored in
+/// a PDA next to the bidder's metadata. The amount escrowed in the pot is only the most the bidder
+/// is willing to pay, any excess is returned when the bid is revealed.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct BidCommitment {
+    // Relationship with the bidder who's commitment this is.
+    pub bidder_pubkey: Pubkey,
+    // Relationship with the auction this bid was placed on.
+    pub auction_pubkey: Pubkey,
+    // Hash of the big endian bid amount followed by the big endian salt.
+    pub commitment: Hash,
+    // Whether the bid has been revealed and entered into the bid state.
+    pub revealed: bool,
+}
+
+impl BidCommitment {
+    pub fn from_account_info(a: &AccountInfo) -> Result<BidCommitment, ProgramError> {
+        if a.data_len() != BID_COMMITMENT_LEN {
+            return Err(AuctionError::DataTypeMismatch.into());
+        }
+
+        let commitment: BidCommitment = try_from_slice_unchecked(&a.data.borrow_mut())?;
+
+        Ok(commitment)
+    }
 }
 
 impl BidderMetadata {
     pub fn from_account_info(a: &AccountInfo) -> Result<BidderMetadata, ProgramError> {
-        if a.data_len() != BIDDER_METADATA_LEN {
+        let bidder_meta: BidderMetadata = match a.data_len() {
+            BIDDER_METADATA_LEN => try_from_slice_unchecked(&a.data.borrow_mut())?,
+            LEGACY_BIDDER_METADATA_LEN => {
+                let data = a.data.borrow();
+                let mut padded = data.to_vec();
+                padded.extend_from_slice(&data[0..32]);
+                try_from_slice_unchecked(&padded)?
+            }
+            _ => return Err(AuctionError::DataTypeMismatch.into()),
+        };
+
+        Ok(bidder_meta)
+    }
+
+    /// Writes the metadata back to its account. Legacy metadata has no room for the payer, which
+    /// is left out as it reads back as the bidder anyway.
+    pub fn save(&self, a: &AccountInfo) -> ProgramResult {
+        let data = self.try_to_vec()?;
+        let len = a.data_len();
+        if len != BIDDER_METADATA_LEN && len != LEGACY_BIDDER_METADATA_LEN {
             return Err(AuctionError::DataTypeMismatch.into());
         }
+        a.data.borrow_mut().copy_from_slice(&data[..len]);
+        Ok(())
+    }
+}
 
-        let bidder_meta: BidderMetadata = try_from_slice_unchecked(&a.data.borrow_mut())?;
+/// Length of the WalletBids account of an auction capping each wallet at `max` places.
+pub fn wallet_bids_len(max: u8) -> usize {
+    32 + 32 + 32 + 4 + 32 * max as usize
+}
 
-        Ok(bidder_meta)
+/// Lists the bidders with a live bid paid from a wallet, the owner of the token account bids are
+/// paid from, or the bidder themselves in a native SOL auction. Meant to be stored in a PDA keyed
+/// by the wallet, so that an auction capping wins per wallet can't be swept by one wallet funding
+/// many bidder accounts. Each bidder holds at most one place, so keeping the list within the cap
+/// keeps the places the wallet can win within it too. Bidders come off the list when their bid is
+/// cancelled or refunded, and the account is closed with CloseWalletBids once the auction is.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct WalletBids {
+    // Relationship with the auction the bids were placed on.
+    pub auction_pubkey: Pubkey,
+    // The wallet the bids were paid from.
+    pub wallet: Pubkey,
+    // Account that paid the rent, which is refunded to it once closed.
+    pub payer: Pubkey,
+    // Bidders funded by the wallet with a bid still live, in the order they bid.
+    pub bidders: Vec<Pubkey>,
+}
+
+impl WalletBids {
+    pub fn from_account_info(a: &AccountInfo) -> Result<WalletBids, ProgramError> {
+        if a.data_len() < wallet_bids_len(0) {
+            return Err(AuctionError::DataTypeMismatch.into());
+        }
+
+        let wallet_bids: WalletBids = try_from_slice_unchecked(&a.data.borrow_mut())?;
+
+        Ok(wallet_bids)
+    }
+
+    /// Loads the wallet bids account of an auction, checking it belongs to the auction and is the
+    /// PDA of its wallet.
+    pub fn from_auction_account(
+        program_id: &Pubkey,
+        auction_key: &Pubkey,
+        a: &AccountInfo,
+    ) -> Result<WalletBids, ProgramError> {
+        assert_owned_by(a, program_id)?;
+        let wallet_bids = WalletBids::from_account_info(a)?;
+        if wallet_bids.auction_pubkey != *auction_key {
+            return Err(AuctionError::WalletBidsMismatch.into());
+        }
+        assert_derivation(
+            program_id,
+            a,
+            &[
+                PREFIX.as_bytes(),
+                program_id.as_ref(),
+                auction_key.as_ref(),
+                wallet_bids.wallet.as_ref(),
+                "wallet".as_bytes(),
+            ],
+        )?;
+        Ok(wallet_bids)
+    }
+
+    /// Records a bid from the bidder, who takes up one of the `max` places of the wallet unless
+    /// they already hold one.
+    pub fn admit(&mut self, bidder: &Pubkey, max: u8) -> ProgramResult {
+        if self.bidders.contains(bidder) {
+            return Ok(());
+        }
+        if self.bidders.len() >= max as usize {
+            return Err(AuctionError::WalletWinLimitReached.into());
+        }
+        self.bidders.push(*bidder);
+        Ok(())
+    }
+
+    /// Frees the place held by the bidder, whose bid was cancelled or refunded.
+    pub fn release(&mut self, bidder: &Pubkey) -> ProgramResult {
+        match self.bidders.iter().position(|b| b == bidder) {
+            Some(index) => {
+                self.bidders.remove(index);
+                Ok(())
+            }
+            None => Err(AuctionError::WalletBidsMismatch.into()),
+        }
     }
 }
 
+/// Frees the place of a bidder whose bid was cancelled or refunded in an auction capping wins per
+/// wallet, in the wallet bids account the bid was counted against.
+pub fn release_wallet_bid(
+    program_id: &Pubkey,
+    auction_key: &Pubkey,
+    wallet_bids_info: &AccountInfo,
+    bidder: &Pubkey,
+) -> ProgramResult {
+    let mut wallet_bids =
+        WalletBids::from_auction_account(program_id, auction_key, wallet_bids_info)?;
+    wallet_bids.release(bidder)?;
+    wallet_bids.serialize(&mut &mut wallet_bids_info.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+/// Most other currencies an auction can accept bids in, as many as fit in the extended data.
+pub const MAX_EXCHANGE_RATES: usize = 2;
+
+/// Exchange rates are given in billionths of a token mint unit per unit of the bid currency.
+pub const EXCHANGE_RATE_SCALE: u128 = 1_000_000_000;
+
+pub const EXCHANGE_RATE_LEN: usize = 8 + 32 + 32;
+
+/// Price of one currency in another, shaped like token-vault's ExternalPriceAccount. The account
+/// signs for its own updates, so whoever holds its key runs the feed, and each auction lists the
+/// feeds it trusts when created. See UpdateExchangeRate.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct ExchangeRate {
+    /// Billionths of a price_mint unit that one unit of mint is worth. See EXCHANGE_RATE_SCALE.
+    pub price_per_token: u64,
+    /// Mint the price is given in, the token mint of the auctions using this rate.
+    pub price_mint: Pubkey,
+    /// Mint of the currency being priced.
+    pub mint: Pubkey,
+}
+
+impl ExchangeRate {
+    pub fn from_account_info(a: &AccountInfo) -> Result<ExchangeRate, ProgramError> {
+        if a.data_len() != EXCHANGE_RATE_LEN {
+            return Err(AuctionError::DataTypeMismatch.into());
+        }
+
+        let rate: ExchangeRate = try_from_slice_unchecked(&a.data.borrow_mut())?;
+
+        Ok(rate)
+    }
+
+    /// Value of an amount of mint in price_mint, rounded down.
+    pub fn normalize(&self, amount: u64) -> Result<u64, ProgramError> {
+        let value = (amount as u128)
+            .checked_mul(self.price_per_token as u128)
+            .ok_or(AuctionError::NumericalOverflowError)?
+            / EXCHANGE_RATE_SCALE;
+        if value > u64::MAX as u128 {
+            return Err(AuctionError::NumericalOverflowError.into());
+        }
+        Ok(value as u64)
+    }
+}
+
+pub const BID_CURRENCY_LEN: usize = 32 + 32 + 32 + 8;
+
+/// Records the currency a bidder's latest bid was paid in and the amount escrowed, for auctions
+/// taking more than one currency, where the bid state only holds the value of each bid in the
+/// token mint. Meant to be stored in a PDA next to the bidder's metadata, and kept after the pot
+/// is emptied so that winnings can be paid out in the currency they came in. Closed along with the
+/// metadata.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct BidCurrency {
+    // Relationship with the bidder who's bid this is.
+    pub bidder_pubkey: Pubkey,
+    // Relationship with the auction this bid was placed on.
+    pub auction_pubkey: Pubkey,
+    // Mint the bid was paid in.
+    pub mint: Pubkey,
+    // Amount of mint escrowed for the bid.
+    pub amount: u64,
+}
+
+impl BidCurrency {
+    pub fn from_account_info(a: &AccountInfo) -> Result<BidCurrency, ProgramError> {
+        if a.data_len() != BID_CURRENCY_LEN {
+            return Err(AuctionError::DataTypeMismatch.into());
+        }
+
+        let bid_currency: BidCurrency = try_from_slice_unchecked(&a.data.borrow_mut())?;
+
+        Ok(bid_currency)
+    }
+}
+
+pub const BIDDER_POT_LEN: usize = 32 + 32 + 32 + 1 + 32;
+/// Length of pots created before the payer was recorded, these were paid for by the bidder.
+pub const LEGACY_BIDDER_POT_LEN: usize = 32 + 32 + 32 + 1;
 #[repr(C)]
 #[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
 pub struct BidderPot {
@@ -460,16 +1547,213 @@ pub struct BidderPot {
     pub auction_act: Pubkey,
     /// emptied or not
     pub emptied: bool,
+    /// Account that paid for the pot, which gets the rent back when the pot is closed
+    pub payer: Pubkey,
 }
 
 impl BidderPot {
     pub fn from_account_info(a: &AccountInfo) -> Result<BidderPot, ProgramError> {
-        if a.data_len() != mem::size_of::<BidderPot>() {
+        let bidder_pot: BidderPot = match a.data_len() {
+            BIDDER_POT_LEN => try_from_slice_unchecked(&a.data.borrow_mut())?,
+            LEGACY_BIDDER_POT_LEN => {
+                let data = a.data.borrow();
+                let mut padded = data.to_vec();
+                padded.extend_from_slice(&data[32..64]);
+                try_from_slice_unchecked(&padded)?
+            }
+            _ => return Err(AuctionError::DataTypeMismatch.into()),
+        };
+
+        Ok(bidder_pot)
+    }
+}
+
+/// Accounts needed to create a new bid or bid history page when the existing pages of an auction
+/// are full.
+pub struct BidPageCreation<'a, 'b: 'a> {
+    pub payer: &'a AccountInfo<'b>,
+    pub rent: &'a AccountInfo<'b>,
+    pub system: &'a AccountInfo<'b>,
+}
+
+pub const BIDS_PER_PAGE: usize = 250;
+pub const BID_PAGE_LEN: usize = 32 + 4 + 32 + 4 + BIDS_PER_PAGE * mem::size_of::<Bid>();
+/// Offset of the bid count of a page, after the auction, page number and payer.
+const BID_PAGE_BIDS_OFFSET: usize = 32 + 4 + 32;
+/// Most winners a paged English auction can have. Up to twice as many bids are kept, and all of
+/// them are loaded into the 32KB program heap whenever the auction is worked with, see
+/// AuctionData::load_bid_pages.
+pub const MAX_PAGED_WINNERS: usize = 150;
+/// A page of the bids of a paged English auction, meant to be stored in a PDA of ['auction',
+/// program_id, auction key, 'bids', little endian page number]. Bids are held highest first, so
+/// winner #1 is the first bid of page 0, and every page but the last is full.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct BidPage {
+    // Relationship with the auction these bids were placed on.
+    pub auction: Pubkey,
+    // Position of this page in the auction's pages.
+    pub page: u32,
+    // Account that paid for the page, which gets the rent back when the page is closed.
+    pub payer: Pubkey,
+    // The bids on this page, highest first.
+    pub bids: Vec<Bid>,
+}
+
+impl BidPage {
+    pub fn from_account_info(a: &AccountInfo) -> Result<BidPage, ProgramError> {
+        if a.data_len() != BID_PAGE_LEN {
             return Err(AuctionError::DataTypeMismatch.into());
         }
 
-        let bidder_pot: BidderPot = try_from_slice_unchecked(&a.data.borrow_mut())?;
+        let page: BidPage = try_from_slice_unchecked(&a.data.borrow_mut())?;
 
-        Ok(bidder_pot)
+        Ok(page)
     }
+
+    /// Number of bids on a page account, read without loading them.
+    pub fn bid_count(a: &AccountInfo) -> Result<usize, ProgramError> {
+        if a.data_len() != BID_PAGE_LEN {
+            return Err(AuctionError::DataTypeMismatch.into());
+        }
+
+        let data = a.data.borrow();
+        let count =
+            u32::try_from_slice(&data[BID_PAGE_BIDS_OFFSET..BID_PAGE_BIDS_OFFSET + 4])? as usize;
+        if count > BIDS_PER_PAGE {
+            return Err(AuctionError::DataTypeMismatch.into());
+        }
+
+        Ok(count)
+    }
+
+    /// Appends the bids on a page account to `bids`, highest first.
+    pub fn read_bids(a: &AccountInfo, bids: &mut Vec<Bid>) -> ProgramResult {
+        let count = BidPage::bid_count(a)?;
+        let data = a.data.borrow();
+        let mut rest = &data[BID_PAGE_BIDS_OFFSET + 4..];
+        for _ in 0..count {
+            bids.push(Bid::deserialize(&mut rest)?);
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the bids on a page account, keeping its auction, page number and payer.
+    pub fn write_bids<'a>(
+        a: &AccountInfo,
+        bids: impl ExactSizeIterator<Item = &'a Bid>,
+    ) -> ProgramResult {
+        if a.data_len() != BID_PAGE_LEN || bids.len() > BIDS_PER_PAGE {
+            return Err(AuctionError::DataTypeMismatch.into());
+        }
+
+        let mut data = a.data.borrow_mut();
+        let mut rest = &mut data[BID_PAGE_BIDS_OFFSET..];
+        (bids.len() as u32).serialize(&mut rest)?;
+        for bid in bids {
+            bid.serialize(&mut rest)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks that a bid page account is the PDA for the given page of the auction, returning its bump.
+pub fn assert_bid_page_derivation(
+    program_id: &Pubkey,
+    page_info: &AccountInfo,
+    auction_key: &Pubkey,
+    page: u32,
+) -> Result<u8, ProgramError> {
+    assert_derivation(
+        program_id,
+        page_info,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            auction_key.as_ref(),
+            BIDS.as_bytes(),
+            &page.to_le_bytes(),
+        ],
+    )
+}
+
+pub const BID_HISTORY_ENTRIES_PER_PAGE: usize = 200;
+pub const BID_HISTORY_ENTRY_LEN: usize = 32 + 8 + 8 + 1;
+pub const BID_HISTORY_PAGE_LEN: usize =
+    32 + 4 + 32 + 4 + BID_HISTORY_ENTRIES_PER_PAGE * BID_HISTORY_ENTRY_LEN;
+
+/// What happened to a bid, as recorded in the bid history.
+#[repr(C)]
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub enum BidHistoryAction {
+    /// The bid was placed.
+    Placed,
+    /// The bidder cancelled the bid.
+    Cancelled,
+    /// A higher bid pushed the bid out of the winning bids.
+    Evicted,
+    /// The bid was refunded without the bidder, after being evicted or losing the auction.
+    Refunded,
+}
+
+/// A single event in the bid history of an auction.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct BidHistoryEntry {
+    pub bidder: Pubkey,
+    pub amount: u64,
+    pub timestamp: UnixTimestamp,
+    pub action: BidHistoryAction,
+}
+
+/// A page of the append-only bid history of an auction, meant to be stored in a PDA of ['auction',
+/// program_id, auction key, 'history', little endian page number]. Entries are held oldest first,
+/// and every page but the last is full. Pages are closed along with the auction, each returning its
+/// rent to the account that paid for it.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct BidHistoryPage {
+    // Relationship with the auction this history was recorded for.
+    pub auction: Pubkey,
+    // Position of this page in the auction's history.
+    pub page: u32,
+    // Account that paid for the page, which gets the rent back when the page is closed.
+    pub payer: Pubkey,
+    // The entries on this page, oldest first.
+    pub entries: Vec<BidHistoryEntry>,
+}
+
+impl BidHistoryPage {
+    pub fn from_account_info(a: &AccountInfo) -> Result<BidHistoryPage, ProgramError> {
+        if a.data_len() != BID_HISTORY_PAGE_LEN {
+            return Err(AuctionError::DataTypeMismatch.into());
+        }
+
+        let page: BidHistoryPage = try_from_slice_unchecked(&a.data.borrow_mut())?;
+
+        Ok(page)
+    }
+}
+
+/// Checks that a bid history page account is the PDA for the given page of the auction, returning
+/// its bump.
+pub fn assert_bid_history_page_derivation(
+    program_id: &Pubkey,
+    page_info: &AccountInfo,
+    auction_key: &Pubkey,
+    page: u32,
+) -> Result<u8, ProgramError> {
+    assert_derivation(
+        program_id,
+        page_info,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            auction_key.as_ref(),
+            HISTORY.as_bytes(),
+            &page.to_le_bytes(),
+        ],
+    )
 }
@@ -3,14 +3,22 @@
 //! 1) The auction is still going on, in which case it is possible to cancel a bid at any time.
 //! 2) The auction has finished, but the bid did not win. This allows users to claim back their
 //!    funds from bid accounts.
+//!
+//! Either way the emptied pot and its SPL account are closed, with their rent going back to the
+//! pot's payer. In an auction capping wins per wallet, the place the bid held is freed up for
+//! another bidder funded by the same wallet.
 
 use crate::{
     errors::AuctionError,
-    processor::{AuctionData, AuctionDataExtended, BidderMetadata, BidderPot},
+    events::{emit, AuctionEvent},
+    processor::{
+        release_wallet_bid, AuctionData, AuctionDataExtended, BidHistoryAction, BidHistoryEntry,
+        BidPageCreation, BidderMetadata, BidderPot,
+    },
     utils::{
-        assert_derivation, assert_initialized, assert_owned_by, assert_signer,
-        assert_token_program_matches_package, create_or_allocate_account_raw, spl_token_transfer,
-        TokenTransferParams,
+        assert_derivation, assert_owned_by, assert_pot_balance, assert_signer,
+        assert_token_program_matches_package, close_bidder_pot, create_or_allocate_account_raw,
+        is_native_mint, pot_mint, pot_transfer, BidderPotCloseParams, TokenTransferParams,
     },
     EXTENDED, PREFIX,
 };
@@ -31,6 +39,7 @@ use {
         sysvar::{clock::Clock, Sysvar},
     },
     spl_token::state::Account,
+    std::cmp,
 };
 
 #[repr(C)]
@@ -52,6 +61,8 @@ struct Accounts<'a, 'b: 'a> {
     rent: &'a AccountInfo<'b>,
     system: &'a AccountInfo<'b>,
     token_program: &'a AccountInfo<'b>,
+    payer: &'a AccountInfo<'b>,
+    page_accounts: &'a [AccountInfo<'b>],
 }
 
 fn parse_accounts<'a, 'b: 'a>(
@@ -72,14 +83,17 @@ fn parse_accounts<'a, 'b: 'a>(
         rent: next_account_info(account_iter)?,
         system: next_account_info(account_iter)?,
         token_program: next_account_info(account_iter)?,
+        payer: next_account_info(account_iter)?,
+        page_accounts: &accounts[13..],
     };
 
     assert_owned_by(accounts.auction, program_id)?;
     assert_owned_by(accounts.auction_extended, program_id)?;
     assert_owned_by(accounts.bidder_meta, program_id)?;
-    assert_owned_by(accounts.mint, &spl_token::id())?;
+    if !is_native_mint(accounts.mint.key) {
+        assert_owned_by(accounts.mint, &spl_token::id())?;
+    }
     assert_owned_by(accounts.bidder_pot, program_id)?;
-    assert_owned_by(accounts.bidder_pot_token, &spl_token::id())?;
     assert_signer(accounts.bidder)?;
     assert_token_program_matches_package(accounts.token_program)?;
 
@@ -99,10 +113,12 @@ pub fn cancel_bid(
     let accounts = parse_accounts(program_id, accounts)?;
 
     // The account within the pot must be owned by us.
-    let actual_account: Account = assert_initialized(accounts.bidder_pot_token)?;
-    if actual_account.owner != *accounts.auction.key {
-        return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
-    }
+    let balance = assert_pot_balance(
+        is_native_mint(accounts.mint.key),
+        accounts.auction,
+        accounts.bidder_pot,
+        accounts.bidder_pot_token,
+    )?;
 
     // Derive and load Auction.
     let auction_bump = assert_derivation(
@@ -124,11 +140,54 @@ pub fn cancel_bid(
 
     // Load the auction and verify this bid is valid.
     let mut auction = AuctionData::from_account_info(accounts.auction)?;
-    // The mint provided in this bid must match the one the auction was initialized with.
-    if auction.token_mint != *accounts.mint.key {
+    // The mint provided in this bid must match the one the bid was paid in.
+    let mint = pot_mint(
+        is_native_mint(&auction.token_mint),
+        &auction.token_mint,
+        accounts.bidder_pot_token,
+    )?;
+    if mint != *accounts.mint.key {
         return Err(AuctionError::IncorrectMint.into());
     }
 
+    assert_derivation(
+        program_id,
+        accounts.auction_extended,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+            EXTENDED.as_bytes(),
+        ],
+    )?;
+    let mut auction_extended = AuctionDataExtended::from_account_info(accounts.auction_extended)?;
+
+    // An auction capping the wins of each wallet takes the wallet bids account the bid was counted
+    // against ahead of the pages, and frees up the place the bid held.
+    let page_accounts = match auction_extended.max_wins_per_wallet {
+        Some(_) => {
+            let (wallet_bids_info, rest) = accounts
+                .page_accounts
+                .split_first()
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            release_wallet_bid(
+                program_id,
+                accounts.auction.key,
+                wallet_bids_info,
+                accounts.bidder.key,
+            )?;
+            rest
+        }
+        None => accounts.page_accounts,
+    };
+
+    // The bid history pages of an auction that keeps one come ahead of the bid pages.
+    let (history_pages, bid_pages) = match auction_extended.bid_history_pages {
+        Some(_) => page_accounts.split_at(cmp::min(2, page_accounts.len())),
+        None => (&page_accounts[..0], page_accounts),
+    };
+    auction.load_bid_pages(program_id, accounts.auction.key, bid_pages)?;
+
     // Load the clock, used for various auction timing.
     let clock = Clock::from_account_info(accounts.clock_sysvar)?;
 
@@ -175,7 +234,14 @@ pub fn cancel_bid(
     }
 
     // Refuse to cancel if the auction ended and this person is a winning account.
-    if auction.ended(clock.unix_timestamp)? && auction.is_winner(accounts.bidder.key).is_some() {
+    let ended = auction.ended(clock.unix_timestamp)?;
+    if ended && auction.is_winner(accounts.bidder.key).is_some() {
+        return Err(AuctionError::InvalidState.into());
+    }
+
+    // Sealed bids are binding through the reveal window, they can only be withdrawn while bidding
+    // is open, or refunded once the auction ends without them winning.
+    if auction.bidding_closed(clock.unix_timestamp)? && !ended {
         return Err(AuctionError::InvalidState.into());
     }
 
@@ -185,56 +251,87 @@ pub fn cancel_bid(
         return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
     }
 
-    // Transfer SPL bid balance back to the user.
-    let account: Account = Account::unpack_from_slice(&accounts.bidder_pot_token.data.borrow())?;
-    spl_token_transfer(TokenTransferParams {
-        source: accounts.bidder_pot_token.clone(),
-        destination: accounts.bidder_token.clone(),
-        authority: accounts.auction.clone(),
-        authority_signer_seeds: auction_seeds,
-        token_program: accounts.token_program.clone(),
-        amount: account.amount,
-    })?;
+    let metadata = BidderMetadata::from_account_info(accounts.bidder_meta)?;
+
+    // The bidder pays for a new history page, as the payer of the pot need not be around to sign.
+    // This comes ahead of moving any funds, as the runtime would hold a pot emptied by hand against
+    // the creation of the page.
+    auction_extended.record_bid_history(
+        program_id,
+        accounts.auction.key,
+        history_pages,
+        BidPageCreation {
+            payer: accounts.bidder,
+            rent: accounts.rent,
+            system: accounts.system,
+        },
+        vec![BidHistoryEntry {
+            bidder: *accounts.bidder.key,
+            amount: metadata.last_bid,
+            timestamp: clock.unix_timestamp,
+            action: BidHistoryAction::Cancelled,
+        }],
+    )?;
+
+    // Transfer the bid balance back to the user.
+    pot_transfer(
+        is_native_mint(&auction.token_mint),
+        TokenTransferParams {
+            source: accounts.bidder_pot_token.clone(),
+            destination: accounts.bidder_token.clone(),
+            authority: accounts.auction.clone(),
+            authority_signer_seeds: auction_seeds,
+            token_program: accounts.token_program.clone(),
+            amount: balance,
+        },
+    )?;
 
     // Update Metadata
-    let metadata = BidderMetadata::from_account_info(accounts.bidder_meta)?;
     let already_cancelled = metadata.cancelled;
     BidderMetadata {
         cancelled: true,
-        ..metadata
+        ..metadata.clone()
     }
-    .serialize(&mut *accounts.bidder_meta.data.borrow_mut())?;
+    .save(accounts.bidder_meta)?;
 
-    // Update Auction
+    // Return the rent of the emptied pot.
+    close_bidder_pot(BidderPotCloseParams {
+        pot: &bidder_pot,
+        bidder_pot: accounts.bidder_pot.clone(),
+        bidder_pot_token: accounts.bidder_pot_token.clone(),
+        payer: accounts.payer.clone(),
+        auction: accounts.auction.clone(),
+        auction_signer_seeds: auction_seeds,
+        token_program: accounts.token_program.clone(),
+    })?;
 
-    if auction.state != AuctionState::Ended {
+    // Update Auction
+    if auction.state != AuctionState::Ended && auction.state != AuctionState::Cancelled {
         // Once ended we want uncancelled bids to retain it's pre-ending count
-        assert_derivation(
-            program_id,
-            accounts.auction_extended,
-            &[
-                PREFIX.as_bytes(),
-                program_id.as_ref(),
-                args.resource.as_ref(),
-                EXTENDED.as_bytes(),
-            ],
-        )?;
-        let mut auction_extended =
-            AuctionDataExtended::from_account_info(accounts.auction_extended)?;
-
-        msg!("Already cancelled is {:?}", already_cancelled);
-
         if !already_cancelled && auction_extended.total_uncancelled_bids > 0 {
             auction_extended.total_uncancelled_bids = auction_extended
                 .total_uncancelled_bids
                 .checked_sub(1)
                 .ok_or(AuctionError::NumericalOverflowError)?;
         }
-        auction_extended.serialize(&mut *accounts.auction_extended.data.borrow_mut())?;
     }
+    auction_extended.count_closed_pot();
+    auction_extended.serialize(&mut *accounts.auction_extended.data.borrow_mut())?;
 
-    auction.bid_state.cancel_bid(*accounts.bidder.key);
+    // Once ended the bids are frozen, the losing bids still set the clearing price of a uniform
+    // price auction after their funds have been returned.
+    if !ended {
+        auction.bid_state.cancel_bid(*accounts.bidder.key)?;
+    }
+    auction.store_bid_pages(program_id, accounts.auction.key, bid_pages, None)?;
     auction.serialize(&mut *accounts.auction.data.borrow_mut())?;
 
+    emit(AuctionEvent::BidCancelled {
+        auction: *accounts.auction.key,
+        bidder: *accounts.bidder.key,
+        amount: metadata.last_bid,
+        timestamp: clock.unix_timestamp,
+    });
+
     Ok(())
 }
@@ -1,15 +1,22 @@
 //! Claim bid winnings into a target SPL account, only the authorised key can do this, though the
-//! target can be any SPL account.
+//! target can be any SPL account, or any account at all for native SOL winnings. In a uniform price
+//! auction only the clearing price is claimed, and whatever the winner bid over it is refunded to
+//! them. A bid in another currency than the auction's token mint is claimed whole, in that
+//! currency.
+//!
+//! The emptied pot and its SPL account are closed, with their rent going back to the pot's payer.
 
 use crate::{
     errors::AuctionError,
-    processor::{AuctionData, BidderMetadata, BidderPot},
+    events::{emit, AuctionEvent},
+    processor::{AuctionData, AuctionDataExtended, BidderMetadata, BidderPot},
     utils::{
-        assert_derivation, assert_initialized, assert_owned_by, assert_signer,
-        assert_token_program_matches_package, create_or_allocate_account_raw, spl_token_transfer,
-        TokenTransferParams,
+        assert_derivation, assert_owned_by, assert_pot_balance, assert_refund_account,
+        assert_signer, assert_token_program_matches_package, close_bidder_pot,
+        create_or_allocate_account_raw, is_native_mint, pot_mint, pot_transfer,
+        BidderPotCloseParams, TokenTransferParams,
     },
-    PREFIX,
+    EXTENDED, PREFIX,
 };
 
 use {
@@ -26,6 +33,7 @@ use {
         sysvar::{clock::Clock, Sysvar},
     },
     spl_token::state::Account,
+    std::cmp,
 };
 
 #[repr(C)]
@@ -44,6 +52,10 @@ struct Accounts<'a, 'b: 'a> {
     mint: &'a AccountInfo<'b>,
     clock_sysvar: &'a AccountInfo<'b>,
     token_program: &'a AccountInfo<'b>,
+    auction_extended: &'a AccountInfo<'b>,
+    bidder_token: &'a AccountInfo<'b>,
+    payer: &'a AccountInfo<'b>,
+    bid_pages: &'a [AccountInfo<'b>],
 }
 
 fn parse_accounts<'a, 'b: 'a>(
@@ -61,13 +73,20 @@ fn parse_accounts<'a, 'b: 'a>(
         mint: next_account_info(account_iter)?,
         clock_sysvar: next_account_info(account_iter)?,
         token_program: next_account_info(account_iter)?,
+        auction_extended: next_account_info(account_iter)?,
+        bidder_token: next_account_info(account_iter)?,
+        payer: next_account_info(account_iter)?,
+        bid_pages: &accounts[12..],
     };
 
     assert_owned_by(accounts.auction, program_id)?;
-    assert_owned_by(accounts.mint, &spl_token::id())?;
-    assert_owned_by(accounts.destination, &spl_token::id())?;
-    assert_owned_by(accounts.bidder_pot_token, &spl_token::id())?;
+    // Native SOL winnings are paid out in lamports, to any account.
+    if !is_native_mint(accounts.mint.key) {
+        assert_owned_by(accounts.mint, &spl_token::id())?;
+        assert_owned_by(accounts.destination, &spl_token::id())?;
+    }
     assert_owned_by(accounts.bidder_pot, program_id)?;
+    assert_owned_by(accounts.auction_extended, program_id)?;
     assert_signer(accounts.authority)?;
     assert_token_program_matches_package(accounts.token_program)?;
 
@@ -88,10 +107,13 @@ pub fn claim_bid(
     let clock = Clock::from_account_info(accounts.clock_sysvar)?;
 
     // The account within the pot must be owned by us.
-    let actual_account: Account = assert_initialized(accounts.bidder_pot_token)?;
-    if actual_account.owner != *accounts.auction.key {
-        return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
-    }
+    let native = is_native_mint(accounts.mint.key);
+    let balance = assert_pot_balance(
+        native,
+        accounts.auction,
+        accounts.bidder_pot,
+        accounts.bidder_pot_token,
+    )?;
 
     // Derive and load Auction.
     let auction_bump = assert_derivation(
@@ -112,7 +134,8 @@ pub fn claim_bid(
     ];
 
     // Load the auction and verify this bid is valid.
-    let auction = AuctionData::from_account_info(accounts.auction)?;
+    let mut auction = AuctionData::from_account_info(accounts.auction)?;
+    auction.load_bid_pages(program_id, accounts.auction.key, accounts.bid_pages)?;
 
     if auction.authority != *accounts.authority.key {
         return Err(AuctionError::InvalidAuthority.into());
@@ -120,18 +143,22 @@ pub fn claim_bid(
 
     // User must have won the auction in order to claim their funds. Check early as the rest of the
     // checks will be for nothing otherwise.
-    if auction.is_winner(accounts.bidder.key).is_none() {
-        msg!("User {:?} is not winner", accounts.bidder.key);
-        return Err(AuctionError::InvalidState.into());
-    }
+    let winner_index = match auction.is_winner(accounts.bidder.key) {
+        Some(val) => val,
+        None => {
+            msg!("User {:?} is not winner", accounts.bidder.key);
+            return Err(AuctionError::InvalidState.into());
+        }
+    };
 
     // Auction must have ended.
     if !auction.ended(clock.unix_timestamp)? {
         return Err(AuctionError::InvalidState.into());
     }
 
-    // The mint provided in this claim must match the one the auction was initialized with.
-    if auction.token_mint != *accounts.mint.key {
+    // The mint provided in this claim must match the one the bid was paid in.
+    let mint = pot_mint(native, &auction.token_mint, accounts.bidder_pot_token)?;
+    if mint != *accounts.mint.key {
         return Err(AuctionError::IncorrectMint.into());
     }
 
@@ -159,23 +186,82 @@ pub fn claim_bid(
     }
 
     // Confirm we're looking at the real SPL account for this bidder.
-    let mut bidder_pot = BidderPot::from_account_info(accounts.bidder_pot)?;
+    let bidder_pot = BidderPot::from_account_info(accounts.bidder_pot)?;
     if bidder_pot.bidder_pot != *accounts.bidder_pot_token.key {
         return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
     }
 
-    // Transfer SPL bid balance back to the user.
-    spl_token_transfer(TokenTransferParams {
-        source: accounts.bidder_pot_token.clone(),
-        destination: accounts.destination.clone(),
-        authority: accounts.auction.clone(),
-        authority_signer_seeds: auction_seeds,
+    // Work out what the winner owes, which is less than their bid in a uniform price auction. A bid
+    // in another currency is owed whole, as it is ranked at its value in the token mint.
+    assert_derivation(
+        program_id,
+        accounts.auction_extended,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+            EXTENDED.as_bytes(),
+        ],
+    )?;
+    let mut auction_extended = AuctionDataExtended::from_account_info(accounts.auction_extended)?;
+    let owed = if mint == auction.token_mint {
+        cmp::min(
+            auction.settlement_amount(winner_index, &auction_extended),
+            balance,
+        )
+    } else {
+        balance
+    };
+
+    // Transfer the bid balance owed to the destination.
+    pot_transfer(
+        native,
+        TokenTransferParams {
+            source: accounts.bidder_pot_token.clone(),
+            destination: accounts.destination.clone(),
+            authority: accounts.auction.clone(),
+            authority_signer_seeds: auction_seeds,
+            token_program: accounts.token_program.clone(),
+            amount: owed,
+        },
+    )?;
+
+    // Refund anything bid over what was owed, only ever to the bidder's own account.
+    let refund = balance - owed;
+    if refund > 0 {
+        assert_refund_account(native, accounts.bidder_token, accounts.bidder.key, &mint)?;
+
+        pot_transfer(
+            native,
+            TokenTransferParams {
+                source: accounts.bidder_pot_token.clone(),
+                destination: accounts.bidder_token.clone(),
+                authority: accounts.auction.clone(),
+                authority_signer_seeds: auction_seeds,
+                token_program: accounts.token_program.clone(),
+                amount: refund,
+            },
+        )?;
+    }
+
+    close_bidder_pot(BidderPotCloseParams {
+        pot: &bidder_pot,
+        bidder_pot: accounts.bidder_pot.clone(),
+        bidder_pot_token: accounts.bidder_pot_token.clone(),
+        payer: accounts.payer.clone(),
+        auction: accounts.auction.clone(),
+        auction_signer_seeds: auction_seeds,
         token_program: accounts.token_program.clone(),
-        amount: actual_account.amount,
     })?;
 
-    bidder_pot.emptied = true;
-    bidder_pot.serialize(&mut *accounts.bidder_pot.data.borrow_mut())?;
+    auction_extended.count_closed_pot();
+    auction_extended.serialize(&mut *accounts.auction_extended.data.borrow_mut())?;
+
+    emit(AuctionEvent::BidClaimed {
+        auction: *accounts.auction.key,
+        bidder: *accounts.bidder.key,
+        amount: owed,
+    });
 
     Ok(())
 }
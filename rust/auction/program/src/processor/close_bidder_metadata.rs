@@ -0,0 +1,130 @@
+//! Closes a bidder's metadata, and their bid commitment in a sealed bid auction or currency record
+//! in an auction accepting other currencies, returning the rent to whoever paid for the metadata.
+//! The metadata is kept until the auction itself has been closed, as redeeming won items relies on
+//! it even after the bid was claimed or cancelled. Anybody can do this for any bidder, as the rent
+//! only ever goes back to the payer.
+
+use crate::{
+    errors::AuctionError,
+    events::{emit, AuctionEvent},
+    processor::{BidderMetadata, BID_COMMITMENT_LEN, BID_CURRENCY_LEN},
+    utils::{assert_derivation, assert_owned_by, close_program_account},
+    CURRENCY, PREFIX,
+};
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct CloseBidderMetadataArgs {
+    pub resource: Pubkey,
+}
+
+struct Accounts<'a, 'b: 'a> {
+    bidder: &'a AccountInfo<'b>,
+    bidder_meta: &'a AccountInfo<'b>,
+    auction: &'a AccountInfo<'b>,
+    payer: &'a AccountInfo<'b>,
+    bidder_record: Option<&'a AccountInfo<'b>>,
+}
+
+fn parse_accounts<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<Accounts<'a, 'b>, ProgramError> {
+    let account_iter = &mut accounts.iter();
+    let accounts = Accounts {
+        bidder: next_account_info(account_iter)?,
+        bidder_meta: next_account_info(account_iter)?,
+        auction: next_account_info(account_iter)?,
+        payer: next_account_info(account_iter)?,
+        bidder_record: next_account_info(account_iter).ok(),
+    };
+
+    assert_owned_by(accounts.bidder_meta, program_id)?;
+
+    Ok(accounts)
+}
+
+pub fn close_bidder_metadata(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: CloseBidderMetadataArgs,
+) -> ProgramResult {
+    msg!("+ Processing CloseBidderMetadata");
+    let accounts = parse_accounts(program_id, accounts)?;
+
+    // The auction must have had its rent reclaimed already.
+    assert_derivation(
+        program_id,
+        accounts.auction,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+        ],
+    )?;
+    if accounts.auction.lamports() > 0 {
+        return Err(AuctionError::AuctionNotClosed.into());
+    }
+
+    assert_derivation(
+        program_id,
+        accounts.bidder_meta,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.auction.key.as_ref(),
+            accounts.bidder.key.as_ref(),
+            "metadata".as_bytes(),
+        ],
+    )?;
+    let bidder_metadata = BidderMetadata::from_account_info(accounts.bidder_meta)?;
+    if bidder_metadata.bidder_pubkey != *accounts.bidder.key {
+        return Err(AuctionError::MetadataInvalid.into());
+    }
+    if bidder_metadata.payer != *accounts.payer.key {
+        return Err(AuctionError::PayerMismatch.into());
+    }
+
+    close_program_account(accounts.bidder_meta, accounts.payer)?;
+
+    // The commitment of a sealed bid, or the currency record of an auction taking other
+    // currencies, was paid for along with the metadata, by the first bid. No auction has both.
+    if let Some(bidder_record) = accounts.bidder_record {
+        assert_owned_by(bidder_record, program_id)?;
+        let seed = match bidder_record.data_len() {
+            BID_COMMITMENT_LEN => "commitment",
+            BID_CURRENCY_LEN => CURRENCY,
+            _ => return Err(AuctionError::DataTypeMismatch.into()),
+        };
+        assert_derivation(
+            program_id,
+            bidder_record,
+            &[
+                PREFIX.as_bytes(),
+                program_id.as_ref(),
+                accounts.auction.key.as_ref(),
+                accounts.bidder.key.as_ref(),
+                seed.as_bytes(),
+            ],
+        )?;
+        close_program_account(bidder_record, accounts.payer)?;
+    }
+
+    emit(AuctionEvent::BidderMetadataClosed {
+        auction: *accounts.auction.key,
+        bidder: *accounts.bidder.key,
+    });
+
+    Ok(())
+}
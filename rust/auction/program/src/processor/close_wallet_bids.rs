@@ -0,0 +1,83 @@
+//! Closes the record of the bids a wallet funded in an auction capping wins per wallet, returning
+//! the rent to whoever paid for it. The record only matters while bids can be placed, but is kept
+//! until the auction itself has been closed, along with the bidder metadata. Anybody can do this
+//! for any wallet, as the rent only ever goes back to the payer.
+
+use crate::{
+    errors::AuctionError,
+    processor::WalletBids,
+    utils::{assert_derivation, assert_owned_by, close_program_account},
+    PREFIX,
+};
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct CloseWalletBidsArgs {
+    pub resource: Pubkey,
+}
+
+struct Accounts<'a, 'b: 'a> {
+    wallet_bids: &'a AccountInfo<'b>,
+    auction: &'a AccountInfo<'b>,
+    payer: &'a AccountInfo<'b>,
+}
+
+fn parse_accounts<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<Accounts<'a, 'b>, ProgramError> {
+    let account_iter = &mut accounts.iter();
+    let accounts = Accounts {
+        wallet_bids: next_account_info(account_iter)?,
+        auction: next_account_info(account_iter)?,
+        payer: next_account_info(account_iter)?,
+    };
+
+    assert_owned_by(accounts.wallet_bids, program_id)?;
+
+    Ok(accounts)
+}
+
+pub fn close_wallet_bids(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: CloseWalletBidsArgs,
+) -> ProgramResult {
+    msg!("+ Processing CloseWalletBids");
+    let accounts = parse_accounts(program_id, accounts)?;
+
+    // The auction must have had its rent reclaimed already.
+    assert_derivation(
+        program_id,
+        accounts.auction,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+        ],
+    )?;
+    if accounts.auction.lamports() > 0 {
+        return Err(AuctionError::AuctionNotClosed.into());
+    }
+
+    let wallet_bids =
+        WalletBids::from_auction_account(program_id, accounts.auction.key, accounts.wallet_bids)?;
+    if wallet_bids.payer != *accounts.payer.key {
+        return Err(AuctionError::PayerMismatch.into());
+    }
+
+    close_program_account(accounts.wallet_bids, accounts.payer)?;
+
+    Ok(())
+}
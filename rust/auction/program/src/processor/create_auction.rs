@@ -2,11 +2,17 @@ use mem::size_of;
 
 use crate::{
     errors::AuctionError,
+    events::{emit, AuctionEvent},
     processor::{
-        AuctionData, AuctionDataExtended, AuctionState, Bid, BidState, PriceFloor, WinnerLimit,
-        BASE_AUCTION_DATA_SIZE, MAX_AUCTION_DATA_EXTENDED_SIZE,
+        AuctionData, AuctionDataExtended, AuctionState, Bid, BidState, BidderGate, ExtensionPolicy,
+        PriceCurve, PriceFloor, WinnerLimit, BASE_AUCTION_DATA_SIZE, DUTCH_AUCTION_DATA_SIZE,
+        MAX_AUCTION_DATA_EXTENDED_SIZE, MAX_EXCHANGE_RATES, MAX_PAGED_WINNERS, PAGED_BID_DATA_SIZE,
+        SEALED_BID_DATA_SIZE,
+    },
+    utils::{
+        assert_derivation, assert_initialized, assert_owned_by, create_or_allocate_account_raw,
+        is_native_mint,
     },
-    utils::{assert_derivation, assert_owned_by, create_or_allocate_account_raw},
     EXTENDED, PREFIX,
 };
 
@@ -20,6 +26,7 @@ use {
         program_error::ProgramError,
         pubkey::Pubkey,
     },
+    spl_token::state::Mint,
     std::mem,
 };
 
@@ -32,7 +39,8 @@ pub struct CreateAuctionArgs {
     pub end_auction_at: Option<UnixTimestamp>,
     /// Gap time is how much time after the previous bid where the auction ends. See AuctionData.
     pub end_auction_gap: Option<UnixTimestamp>,
-    /// Token mint for the SPL token used for bidding.
+    /// Token mint for the SPL token used for bidding, or the system program to take bids in native
+    /// SOL. See utils::is_native_mint.
     pub token_mint: Pubkey,
     /// Authority
     pub authority: Pubkey,
@@ -40,6 +48,66 @@ pub struct CreateAuctionArgs {
     pub resource: Pubkey,
     /// Set a price floor.
     pub price_floor: PriceFloor,
+    /// Minimum raise over the lowest winning bid. See AuctionDataExtended.
+    pub tick_size: Option<u64>,
+    /// Minimum raise as a percentage inside the gap window. See AuctionDataExtended.
+    pub gap_tick_size_percentage: Option<u8>,
+    /// If set, run a Dutch auction with a falling price instead of an English auction. Each
+    /// winning place is sold to a bid meeting the price of the moment, at the amount bid, and the
+    /// auction ends once every place is sold. Requires capped winners and an end time, and no end
+    /// gap.
+    pub dutch_auction: Option<DutchAuctionArgs>,
+    /// If set, run a sealed bid auction where bids are revealed during this many seconds after the
+    /// end time. Requires capped winners and an end time, and no end gap.
+    pub reveal_period: Option<UnixTimestamp>,
+    /// If set, winners all pay the clearing price rather than their own bid. Only available to
+    /// English and sealed bid auctions. See AuctionDataExtended.
+    pub uniform_price: bool,
+    /// If set, bids close to the end push the end back. Requires an end time and no end gap, and
+    /// is not available to Dutch or sealed bid auctions. See AuctionDataExtended.
+    pub extension_policy: Option<ExtensionPolicy>,
+    /// If set, a bid at or above this price wins outright and ends the auction. Only available to
+    /// single winner English auctions that do not use uniform pricing. See AuctionDataExtended.
+    pub instant_sale_price: Option<u64>,
+    /// If set, store bids in BidPage accounts rather than the auction account, lifting the limit
+    /// its size puts on the number of winners. Only available to English auctions with at most
+    /// MAX_PAGED_WINNERS capped winners. See BidState::PagedEnglishAuction.
+    pub paged_bids: bool,
+    /// If set, keep an append-only history of every bid placed, cancelled and refunded. Not
+    /// available to Dutch auctions. See BidHistoryPage.
+    pub bid_history: bool,
+    /// If set, the auction starts on its own at this time, without waiting for the authority to
+    /// call StartAuction. See AuctionDataExtended.
+    pub start_auction_at: Option<UnixTimestamp>,
+    /// If set, only the bidders let in by this gate can place bids. See BidderGate.
+    pub bidder_gate: Option<BidderGate>,
+    /// If set, the most places any one wallet can win, counting the live bids of every bidder it
+    /// funds. Requires a bidder gate, which then admits wallets rather than bidders. See
+    /// WalletBids.
+    pub max_wins_per_wallet: Option<u8>,
+    /// Whether the authority can still cancel the auction once it has taken bids. See
+    /// CancelAuction.
+    pub cancellable_with_bids: bool,
+    /// Exchange rate accounts of other currencies to accept bids in. Only available to English
+    /// auctions in an SPL token that do not use uniform pricing. See AuctionDataExtended.
+    pub exchange_rates: Vec<Pubkey>,
+}
+
+/// Falling price settings for a Dutch auction. A single winner auction ends on its first sale,
+/// while one with more winners keeps selling a place to each bid meeting the price until none are
+/// left, so a drop of several items can be sold without a separate auction for each. See
+/// BidState::DutchAuction.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct DutchAuctionArgs {
+    /// Price at the moment the auction starts.
+    pub start_price: u64,
+    /// Price the auction has fallen to by its end time.
+    pub end_price: u64,
+    /// Shape of the fall from start price to end price.
+    pub curve: PriceCurve,
+    /// Seconds between price drops, only used by a stepped curve.
+    pub step: UnixTimestamp,
 }
 
 struct Accounts<'a, 'b: 'a> {
@@ -48,6 +116,7 @@ struct Accounts<'a, 'b: 'a> {
     payer: &'a AccountInfo<'b>,
     rent: &'a AccountInfo<'b>,
     system: &'a AccountInfo<'b>,
+    token_mint: &'a AccountInfo<'b>,
 }
 
 fn parse_accounts<'a, 'b: 'a>(
@@ -61,6 +130,7 @@ fn parse_accounts<'a, 'b: 'a>(
         auction_extended: next_account_info(account_iter)?,
         rent: next_account_info(account_iter)?,
         system: next_account_info(account_iter)?,
+        token_mint: next_account_info(account_iter)?,
     };
     Ok(accounts)
 }
@@ -85,17 +155,214 @@ pub fn create_auction(
     if auction_key != *accounts.auction.key {
         return Err(AuctionError::InvalidAuctionAccount.into());
     }
-    // The data must be large enough to hold at least the number of winners.
-    let auction_size = match args.winners {
-        WinnerLimit::Capped(n) => {
-            mem::size_of::<Bid>() * BidState::max_array_size_for(n) + BASE_AUCTION_DATA_SIZE
+
+    // Only the first word of a minimum price is read, so the rest must be left empty.
+    if let PriceFloor::MinimumPrice(min) = args.price_floor {
+        if min[1..].iter().any(|word| *word != 0) {
+            return Err(AuctionError::InvalidPriceFloor.into());
+        }
+    }
+
+    // A tick only means something on top of a real floor, which a blinded one isn't until the
+    // auction ends, and has to move the price when set.
+    if let Some(tick) = args.tick_size {
+        if tick == 0 {
+            return Err(AuctionError::InvalidTickSize.into());
+        }
+        let valid = match args.price_floor {
+            PriceFloor::MinimumPrice(min) => min[0] > 0 && min[0].checked_add(tick).is_some(),
+            PriceFloor::None(_) | PriceFloor::BlindedPrice(_) => false,
+        };
+        if !valid {
+            return Err(AuctionError::InvalidPriceFloor.into());
+        }
+    }
+
+    // Prices are in base units of the token mint, which has to be a real one. The floor is logged
+    // along with the decimals of the mint, where one worked out for another currency stands out.
+    if *accounts.token_mint.key != args.token_mint {
+        return Err(AuctionError::IncorrectMint.into());
+    }
+    let decimals = if is_native_mint(&args.token_mint) {
+        spl_token::native_mint::DECIMALS
+    } else {
+        assert_owned_by(accounts.token_mint, &spl_token::id())?;
+        let mint: Mint = assert_initialized(accounts.token_mint)?;
+        mint.decimals
+    };
+    if let PriceFloor::MinimumPrice(min) = args.price_floor {
+        msg!(
+            "Price floor of {:?} in base units of a mint of {:?} decimals",
+            min[0],
+            decimals
+        );
+    }
+
+    if let Some(percentage) = args.gap_tick_size_percentage {
+        if percentage > 100 {
+            return Err(AuctionError::InvalidGapTickSizePercentage.into());
+        }
+    }
+    if let Some(dutch) = &args.dutch_auction {
+        let valid = match (&args.winners, args.end_auction_at) {
+            (WinnerLimit::Capped(n), Some(end)) => {
+                *n > 0
+                    && end > 0
+                    && args.end_auction_gap.is_none()
+                    && dutch.start_price >= dutch.end_price
+                    && (dutch.curve != PriceCurve::Stepped || dutch.step > 0)
+            }
+            _ => false,
+        };
+        if !valid {
+            return Err(AuctionError::InvalidDutchAuctionSettings.into());
+        }
+    }
+
+    if let Some(reveal_period) = args.reveal_period {
+        let valid = match (&args.winners, args.end_auction_at) {
+            (WinnerLimit::Capped(n), Some(end)) => {
+                *n > 0
+                    && end > 0
+                    && reveal_period > 0
+                    && args.end_auction_gap.is_none()
+                    && args.dutch_auction.is_none()
+            }
+            _ => false,
+        };
+        if !valid {
+            return Err(AuctionError::InvalidSealedBidSettings.into());
+        }
+    }
+
+    // Only auctions that rank bids against each other have a clearing price.
+    if args.uniform_price {
+        match (&args.winners, &args.dutch_auction) {
+            (WinnerLimit::Capped(n), None) if *n > 0 => {}
+            _ => return Err(AuctionError::InvalidUniformPriceSettings.into()),
+        }
+    }
+
+    // Extensions replace the end gap, and would upset the fixed timing of Dutch and sealed bid
+    // auctions.
+    if let Some(policy) = &args.extension_policy {
+        let valid = args.end_auction_at.is_some()
+            && args.end_auction_gap.is_none()
+            && args.dutch_auction.is_none()
+            && args.reveal_period.is_none()
+            && policy.window > 0
+            && policy.extension > 0
+            && policy.max_extension.map_or(true, |max| max >= 0);
+        if !valid {
+            return Err(AuctionError::InvalidExtensionPolicy.into());
+        }
+    }
+
+    // Buy-now makes the bidder the sole winner, so needs an English auction with one place, where
+    // the winner pays their own bid.
+    if let Some(price) = args.instant_sale_price {
+        let floor = match args.price_floor {
+            PriceFloor::MinimumPrice(min) => min[0],
+            _ => 0,
+        };
+        let valid = match args.winners {
+            WinnerLimit::Capped(n) => {
+                n == 1
+                    && args.dutch_auction.is_none()
+                    && args.reveal_period.is_none()
+                    && !args.uniform_price
+                    && price > 0
+                    && price >= floor
+            }
+            WinnerLimit::Unlimited(_) => false,
+        };
+        if !valid {
+            return Err(AuctionError::InvalidInstantSalePrice.into());
+        }
+    }
+
+    if args.paged_bids {
+        let valid = match args.winners {
+            WinnerLimit::Capped(n) => {
+                n > 0
+                    && n <= MAX_PAGED_WINNERS
+                    && args.dutch_auction.is_none()
+                    && args.reveal_period.is_none()
+            }
+            WinnerLimit::Unlimited(_) => false,
+        };
+        if !valid {
+            return Err(AuctionError::InvalidPagedBidSettings.into());
+        }
+    }
+
+    if args.bid_history && args.dutch_auction.is_some() {
+        return Err(AuctionError::InvalidBidHistorySettings.into());
+    }
+
+    // Without a gate a wallet is any key, and one funding many bidders could simply pay from as
+    // many fresh wallets.
+    if let Some(max) = args.max_wins_per_wallet {
+        if max == 0 || args.bidder_gate.is_none() {
+            return Err(AuctionError::InvalidMaxWinsPerWallet.into());
+        }
+    }
+
+    // Bids in other currencies are escrowed whole and paid out in the currency they came in, so
+    // nothing can be taken off them after the fact, as uniform pricing, Dutch price drops and
+    // sealed bid reveals all do. Each currency needs its own SPL pot, so the token mint must be
+    // one too.
+    if !args.exchange_rates.is_empty() {
+        let unique = args
+            .exchange_rates
+            .iter()
+            .enumerate()
+            .all(|(i, rate)| !args.exchange_rates[..i].contains(rate));
+        let valid = unique
+            && args.exchange_rates.len() <= MAX_EXCHANGE_RATES
+            && !is_native_mint(&args.token_mint)
+            && args.dutch_auction.is_none()
+            && args.reveal_period.is_none()
+            && !args.uniform_price;
+        if !valid {
+            return Err(AuctionError::InvalidExchangeRateSettings.into());
+        }
+    }
+
+    // The data must be large enough to hold at least the number of winners, unless they are kept
+    // in pages.
+    let auction_size = match (&args.winners, &args.dutch_auction) {
+        (WinnerLimit::Capped(_), None) if args.paged_bids => {
+            BASE_AUCTION_DATA_SIZE + PAGED_BID_DATA_SIZE
+        }
+        (WinnerLimit::Capped(n), Some(_)) => {
+            mem::size_of::<Bid>() * n + BASE_AUCTION_DATA_SIZE + DUTCH_AUCTION_DATA_SIZE
         }
-        WinnerLimit::Unlimited(_) => BASE_AUCTION_DATA_SIZE,
+        (WinnerLimit::Capped(n), None) => {
+            let size =
+                mem::size_of::<Bid>() * BidState::max_array_size_for(*n) + BASE_AUCTION_DATA_SIZE;
+            match args.reveal_period {
+                Some(_) => size + SEALED_BID_DATA_SIZE,
+                None => size,
+            }
+        }
+        (WinnerLimit::Unlimited(_), _) => BASE_AUCTION_DATA_SIZE,
     };
 
-    let bid_state = match args.winners {
-        WinnerLimit::Capped(n) => BidState::new_english(n),
-        WinnerLimit::Unlimited(_) => BidState::new_open_edition(),
+    let bid_state = match (&args.winners, args.dutch_auction) {
+        (WinnerLimit::Capped(n), Some(dutch)) => BidState::new_dutch(
+            *n,
+            dutch.start_price,
+            dutch.end_price,
+            dutch.curve,
+            dutch.step,
+        ),
+        (WinnerLimit::Capped(n), None) => match args.reveal_period {
+            Some(reveal_period) => BidState::new_sealed_bid(*n, reveal_period),
+            None if args.paged_bids => BidState::new_paged_english(*n),
+            None => BidState::new_english(*n),
+        },
+        (WinnerLimit::Unlimited(_), _) => BidState::new_open_edition(),
     };
 
     // Create auction account with enough space for a winner tracking.
@@ -155,5 +422,30 @@ pub fn create_auction(
     }
     .serialize(&mut *accounts.auction.data.borrow_mut())?;
 
+    AuctionDataExtended {
+        total_uncancelled_bids: 0,
+        tick_size: args.tick_size,
+        gap_tick_size_percentage: args.gap_tick_size_percentage,
+        uniform_price: args.uniform_price,
+        extension_policy: args.extension_policy,
+        total_extension: 0,
+        instant_sale_price: args.instant_sale_price,
+        open_bidder_pots: Some(0),
+        bid_history_pages: if args.bid_history { Some(0) } else { None },
+        start_auction_at: args.start_auction_at,
+        bidder_gate: args.bidder_gate,
+        max_wins_per_wallet: args.max_wins_per_wallet,
+        cancellable_with_bids: args.cancellable_with_bids,
+        exchange_rates: args.exchange_rates,
+    }
+    .serialize(&mut *accounts.auction_extended.data.borrow_mut())?;
+
+    emit(AuctionEvent::AuctionCreated {
+        auction: *accounts.auction.key,
+        authority: args.authority,
+        resource: args.resource,
+        token_mint: args.token_mint,
+    });
+
     Ok(())
 }
@@ -1,5 +1,6 @@
 use crate::{
     errors::AuctionError,
+    events::{emit, AuctionEvent},
     processor::{AuctionData, AuctionState, Bid, BidState, PriceFloor, WinnerLimit},
     utils::{assert_derivation, assert_owned_by, assert_signer, create_or_allocate_account_raw},
     PREFIX,
@@ -100,18 +101,27 @@ pub fn end_auction<'a, 'b: 'a>(
         return Err(AuctionError::InvalidAuthority.into());
     }
 
-    // As long as it hasn't already ended.
-    if auction.ended_at.is_some() {
-        return Err(AuctionError::AuctionTransitionInvalid.into());
-    }
+    // As long as it hasn't already ended. A sealed bid auction is the exception, as its end time
+    // only closes bidding. It can be ended once the reveal window after it has closed, keeping the
+    // time bidding closed at, so a blinded price floor can still be revealed.
+    let ended_at = match (&auction.bid_state, auction.ended_at) {
+        (_, None) => clock.unix_timestamp,
+        (BidState::SealedBid { .. }, Some(end)) if auction.ended(clock.unix_timestamp)? => end,
+        _ => return Err(AuctionError::AuctionTransitionInvalid.into()),
+    };
 
     AuctionData {
-        ended_at: Some(clock.unix_timestamp),
+        ended_at: Some(ended_at),
         state: auction.state.end()?,
         price_floor: reveal(auction.price_floor, args.reveal)?,
         ..auction
     }
     .serialize(&mut *accounts.auction.data.borrow_mut())?;
 
+    emit(AuctionEvent::AuctionEnded {
+        auction: *accounts.auction.key,
+        ended_at,
+    });
+
     Ok(())
 }
@@ -14,24 +14,28 @@ use borsh::try_to_vec_with_schema;
 
 use crate::{
     errors::AuctionError,
+    events::{emit, AuctionEvent},
     processor::{
-        AuctionData, AuctionDataExtended, AuctionState, Bid, BidderMetadata, BidderPot, PriceFloor,
+        AuctionData, AuctionDataExtended, AuctionState, Bid, BidCommitment, BidCurrency,
+        BidHistoryAction, BidHistoryEntry, BidPageCreation, BidState, BidderGate, BidderMetadata,
+        BidderPot, ExchangeRate, PriceFloor, WalletBids, BID_COMMITMENT_LEN, BID_CURRENCY_LEN,
     },
     utils::{
-        assert_derivation, assert_initialized, assert_owned_by, assert_signer,
-        assert_token_program_matches_package, create_or_allocate_account_raw, spl_token_transfer,
-        TokenTransferParams,
+        assert_derivation, assert_initialized, assert_owned_by, assert_pot_balance, assert_signer,
+        assert_token_program_matches_package, create_or_allocate_account_raw, is_native_mint,
+        spl_token_transfer, TokenTransferParams,
     },
-    EXTENDED, PREFIX,
+    CURRENCY, EXTENDED, PREFIX,
 };
 
-use super::BIDDER_METADATA_LEN;
+use super::{wallet_bids_len, BIDDER_METADATA_LEN, BIDDER_POT_LEN};
 
 use {
     borsh::{BorshDeserialize, BorshSerialize},
     solana_program::{
         account_info::{next_account_info, AccountInfo},
         entrypoint::ProgramResult,
+        hash::Hash,
         msg,
         program::{invoke, invoke_signed},
         program_error::ProgramError,
@@ -44,7 +48,7 @@ use {
         sysvar::{clock::Clock, Sysvar},
     },
     spl_token::state::Account,
-    std::mem,
+    std::cmp,
 };
 
 /// Arguments for the PlaceBid instruction discriminant .
@@ -55,11 +59,21 @@ pub struct PlaceBidArgs {
     pub amount: u64,
     /// Resource being bid on.
     pub resource: Pubkey,
+    /// Required in a sealed bid auction, where amount is only the most the bidder escrows. Hash of
+    /// the big endian bid amount followed by the big endian salt, to be revealed with RevealBid.
+    pub commitment: Option<Hash>,
+    /// If set, raise the bidder's active bid to amount, paying in only what it takes to bring
+    /// their pot up to it. Only available to English auctions.
+    pub top_up: bool,
+    /// Merkle proof of the bidder's key, for an auction gated by a Merkle root. See BidderGate.
+    pub allowlist_proof: Vec<[u8; 32]>,
 }
 
 struct Accounts<'a, 'b: 'a> {
     auction: &'a AccountInfo<'b>,
     auction_extended: &'a AccountInfo<'b>,
+    bidder_commitment: Option<&'a AccountInfo<'b>>,
+    page_accounts: &'a [AccountInfo<'b>],
     bidder_meta: &'a AccountInfo<'b>,
     bidder_pot: &'a AccountInfo<'b>,
     bidder_pot_token: &'a AccountInfo<'b>,
@@ -94,11 +108,13 @@ fn parse_accounts<'a, 'b: 'a>(
         rent: next_account_info(account_iter)?,
         system: next_account_info(account_iter)?,
         token_program: next_account_info(account_iter)?,
+        bidder_commitment: next_account_info(account_iter).ok(),
+        // Pages follow the commitment, which is passed even though unused.
+        page_accounts: accounts.get(15..).unwrap_or_default(),
     };
 
     assert_owned_by(accounts.auction, program_id)?;
     assert_owned_by(accounts.auction_extended, program_id)?;
-    assert_owned_by(accounts.bidder_token, &spl_token::id())?;
 
     if !accounts.bidder_pot.data_is_empty() {
         assert_owned_by(accounts.bidder_pot, program_id)?;
@@ -107,8 +123,12 @@ fn parse_accounts<'a, 'b: 'a>(
         assert_owned_by(accounts.bidder_meta, program_id)?;
     }
 
-    assert_owned_by(accounts.mint, &spl_token::id())?;
-    assert_owned_by(accounts.bidder_pot_token, &spl_token::id())?;
+    // Native SOL bids are paid straight from the bidder's wallet into the pot itself.
+    if !is_native_mint(accounts.mint.key) {
+        assert_owned_by(accounts.bidder_token, &spl_token::id())?;
+        assert_owned_by(accounts.mint, &spl_token::id())?;
+        assert_owned_by(accounts.bidder_pot_token, &spl_token::id())?;
+    }
     assert_signer(accounts.bidder)?;
     assert_signer(accounts.payer)?;
     assert_signer(accounts.transfer_authority)?;
@@ -133,16 +153,205 @@ pub fn place_bid<'r, 'b: 'r>(
     // Load the auction and verify this bid is valid.
     let mut auction = AuctionData::from_account_info(accounts.auction)?;
 
+    let native = is_native_mint(&auction.token_mint);
+
     // Load the clock, used for various auction timing.
     let clock = Clock::from_account_info(accounts.clock_sysvar)?;
 
+    // Load the extended data, which holds the tick sizes a new bid must raise by and
+    // any scheduled start time.
+    assert_derivation(
+        program_id,
+        accounts.auction_extended,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+            EXTENDED.as_bytes(),
+        ],
+    )?;
+    let mut auction_extended: AuctionDataExtended =
+        AuctionDataExtended::from_account_info(accounts.auction_extended)?;
+
+    // An auction scheduled to start is started by the first bid placed after its start time.
+    if auction.start_if_scheduled(&auction_extended, clock.unix_timestamp)? {
+        emit(AuctionEvent::AuctionStarted {
+            auction: *accounts.auction.key,
+            started_at: auction_extended
+                .start_auction_at
+                .unwrap_or(clock.unix_timestamp),
+        });
+    }
+
+    // A cancelled auction takes no more bids.
+    if auction.state == AuctionState::Cancelled {
+        return Err(AuctionError::InvalidState.into());
+    }
+
     // Verify auction has not ended.
     if auction.ended(clock.unix_timestamp)? {
         auction.state = auction.state.end()?;
         auction.serialize(&mut *accounts.auction.data.borrow_mut())?;
-        msg!("Auction ended!");
+        emit(AuctionEvent::AuctionEnded {
+            auction: *accounts.auction.key,
+            ended_at: auction.ended_at.unwrap_or(clock.unix_timestamp),
+        });
         return Ok(());
     }
+
+    // A sealed bid auction in its reveal window has not ended, but takes no more bids.
+    if auction.bidding_closed(clock.unix_timestamp)? {
+        return Err(AuctionError::InvalidState.into());
+    }
+
+    // The mint provided in this bid must match the one the auction was initialized with, unless
+    // it is one of the other currencies the auction accepts.
+    let foreign = auction.token_mint != *accounts.mint.key;
+    if foreign && auction_extended.exchange_rates.is_empty() {
+        return Err(AuctionError::IncorrectMint.into());
+    }
+
+    // Only the ranked bids of an English auction can be raised in place, and only in the token
+    // mint, as the shortfall of the pot is worked out at face value.
+    if args.top_up {
+        match auction.bid_state {
+            BidState::EnglishAuction { .. } | BidState::PagedEnglishAuction { .. } if !foreign => {}
+            _ => return Err(AuctionError::InvalidState.into()),
+        }
+    }
+
+    // An auction accepting other currencies takes the bidder's currency record first, followed by
+    // the exchange rate of the currency for a bid in anything but the token mint.
+    let (bid_currency_info, page_accounts) = if auction_extended.exchange_rates.is_empty() {
+        (None, accounts.page_accounts)
+    } else {
+        let (info, rest) = accounts
+            .page_accounts
+            .split_first()
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        (Some(info), rest)
+    };
+    let (amount, page_accounts) = if foreign {
+        let (exchange_rate_info, rest) = page_accounts
+            .split_first()
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if !auction_extended
+            .exchange_rates
+            .contains(exchange_rate_info.key)
+        {
+            return Err(AuctionError::CurrencyNotAccepted.into());
+        }
+        assert_owned_by(exchange_rate_info, program_id)?;
+        let exchange_rate = ExchangeRate::from_account_info(exchange_rate_info)?;
+        if exchange_rate.mint != *accounts.mint.key
+            || exchange_rate.price_mint != auction.token_mint
+        {
+            return Err(AuctionError::CurrencyNotAccepted.into());
+        }
+        // A bid worth nothing in the token mint would hold a place for free.
+        let value = exchange_rate.normalize(args.amount)?;
+        if value == 0 {
+            return Err(AuctionError::BidTooSmall.into());
+        }
+        (value, rest)
+    } else {
+        (args.amount, page_accounts)
+    };
+
+    // An auction gated by a holder mint takes the holder account of whoever the gate admits ahead
+    // of any pages.
+    let (holder, page_accounts) = match auction_extended.bidder_gate {
+        Some(BidderGate::HolderMint(_)) => match page_accounts.split_first() {
+            Some((holder, rest)) => (Some(holder), rest),
+            None => (None, page_accounts),
+        },
+        _ => (None, page_accounts),
+    };
+
+    // The wallet a bid is paid from, the owner of the bidder's token account, or the bidder
+    // themselves for native SOL.
+    let wallet = if native {
+        *accounts.bidder.key
+    } else {
+        let bidder_token: Account = assert_initialized(accounts.bidder_token)?;
+        bidder_token.owner
+    };
+
+    // An auction capping the wins of each wallet lets wallets in through its gate rather than
+    // bidders, as a fresh bidder key is free to make, and a wallet funding many of them is who
+    // the cap is meant for.
+    if let Some(gate) = &auction_extended.bidder_gate {
+        let admitted = match auction_extended.max_wins_per_wallet {
+            Some(_) => &wallet,
+            None => accounts.bidder.key,
+        };
+        gate.assert_allowed(admitted, &args.allowlist_proof, holder)?;
+    }
+
+    // An auction capping the wins of each wallet takes the wallet's bids account next, and counts
+    // this bidder against the wallet the bid is paid from.
+    let page_accounts = match auction_extended.max_wins_per_wallet {
+        Some(max) => {
+            let (wallet_bids_info, rest) = page_accounts
+                .split_first()
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let wallet_bids_bump = assert_derivation(
+                program_id,
+                wallet_bids_info,
+                &[
+                    PREFIX.as_bytes(),
+                    program_id.as_ref(),
+                    accounts.auction.key.as_ref(),
+                    wallet.as_ref(),
+                    "wallet".as_bytes(),
+                ],
+            )?;
+            let mut wallet_bids = if wallet_bids_info.data_is_empty() {
+                create_or_allocate_account_raw(
+                    *program_id,
+                    wallet_bids_info,
+                    accounts.rent,
+                    accounts.system,
+                    accounts.payer,
+                    wallet_bids_len(max),
+                    &[
+                        PREFIX.as_bytes(),
+                        program_id.as_ref(),
+                        accounts.auction.key.as_ref(),
+                        wallet.as_ref(),
+                        "wallet".as_bytes(),
+                        &[wallet_bids_bump],
+                    ],
+                )?;
+                WalletBids {
+                    auction_pubkey: *accounts.auction.key,
+                    wallet,
+                    payer: *accounts.payer.key,
+                    bidders: vec![],
+                }
+            } else {
+                assert_owned_by(wallet_bids_info, program_id)?;
+                WalletBids::from_account_info(wallet_bids_info)?
+            };
+            // A top up raises a bid already counted, and has to come from the same wallet so the
+            // bid is only ever counted once.
+            if args.top_up && !wallet_bids.bidders.contains(accounts.bidder.key) {
+                return Err(AuctionError::WalletBidsMismatch.into());
+            }
+            wallet_bids.admit(accounts.bidder.key, max)?;
+            wallet_bids.serialize(&mut *wallet_bids_info.data.borrow_mut())?;
+            rest
+        }
+        None => page_accounts,
+    };
+
+    // The bid history pages of an auction that keeps one come ahead of the bid pages.
+    let (history_pages, bid_pages) = match auction_extended.bid_history_pages {
+        Some(_) => page_accounts.split_at(cmp::min(2, page_accounts.len())),
+        None => (&page_accounts[..0], page_accounts),
+    };
+    auction.load_bid_pages(program_id, accounts.auction.key, bid_pages)?;
+
     // Derive Metadata key and load it.
     let metadata_bump = assert_derivation(
         program_id,
@@ -156,8 +365,11 @@ pub fn place_bid<'r, 'b: 'r>(
         ],
     )?;
 
-    // If metadata doesn't exist, create it.
-    if accounts.bidder_meta.owner != program_id {
+    // If metadata doesn't exist, create it, remembering who to refund the rent to.
+    let metadata_payer = if accounts.bidder_meta.owner != program_id {
+        if args.top_up {
+            return Err(AuctionError::BidNotActive.into());
+        }
         create_or_allocate_account_raw(
             *program_id,
             accounts.bidder_meta,
@@ -176,13 +388,23 @@ pub fn place_bid<'r, 'b: 'r>(
                 &[metadata_bump],
             ],
         )?;
+        *accounts.payer.key
     } else {
-        // Verify the last bid was cancelled before continuing.
         let bidder_metadata: BidderMetadata =
             BidderMetadata::from_account_info(accounts.bidder_meta)?;
-        if bidder_metadata.cancelled == false {
+        if args.top_up {
+            // A top up raises the active bid, so there must be one to raise.
+            if bidder_metadata.cancelled {
+                return Err(AuctionError::BidNotActive.into());
+            }
+            if amount <= bidder_metadata.last_bid {
+                return Err(AuctionError::BidTooSmall.into());
+            }
+        } else if bidder_metadata.cancelled == false {
+            // Verify the last bid was cancelled before continuing.
             return Err(AuctionError::BidAlreadyActive.into());
         }
+        bidder_metadata.payer
     };
 
     // Derive Pot address, this account wraps/holds an SPL account to transfer tokens into and is
@@ -198,18 +420,29 @@ pub fn place_bid<'r, 'b: 'r>(
         ],
     )?;
 
-    // The account within the pot must be owned by us.
-    let actual_account: Account = assert_initialized(accounts.bidder_pot_token)?;
-    if actual_account.owner != *accounts.auction.key {
-        return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
-    }
+    // The account within the pot must be owned by us, or be the pot itself for native SOL.
+    if native {
+        if accounts.bidder_pot_token.key != accounts.bidder_pot.key {
+            return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
+        }
+    } else {
+        let actual_account: Account = assert_initialized(accounts.bidder_pot_token)?;
+        if actual_account.owner != *accounts.auction.key {
+            return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
+        }
 
-    if actual_account.delegate != COption::None {
-        return Err(AuctionError::DelegateShouldBeNone.into());
-    }
+        // Later instructions trust the pot to be in the currency the bid was accepted in.
+        if actual_account.mint != *accounts.mint.key {
+            return Err(AuctionError::IncorrectMint.into());
+        }
+
+        if actual_account.delegate != COption::None {
+            return Err(AuctionError::DelegateShouldBeNone.into());
+        }
 
-    if actual_account.close_authority != COption::None {
-        return Err(AuctionError::CloseAuthorityShouldBeNone.into());
+        if actual_account.close_authority != COption::None {
+            return Err(AuctionError::CloseAuthorityShouldBeNone.into());
+        }
     }
 
     // Derive and load Auction.
@@ -232,14 +465,29 @@ pub fn place_bid<'r, 'b: 'r>(
     if let PriceFloor::MinimumPrice(min) = auction.price_floor {
         msg!(
             "Amount is too small: {:?}, compared to price floor of {:?}",
-            args.amount,
+            amount,
             min[0]
         );
-        if args.amount < min[0] {
+        if amount < min[0] {
             return Err(AuctionError::BidTooSmall.into());
         }
     }
 
+    // In a Dutch auction the bid has to meet the current falling price instead.
+    if let Some(price) = auction.dutch_price(clock.unix_timestamp)? {
+        if amount < price {
+            msg!(
+                "Amount is too small: {:?}, compared to current price of {:?}",
+                amount,
+                price
+            );
+            return Err(AuctionError::BidTooSmall.into());
+        }
+    }
+
+    // Can't outbid the lowest winner by less than the tick size.
+    auction.assert_valid_raise(amount, &auction_extended, clock.unix_timestamp)?;
+
     let bump_authority_seeds = &[
         PREFIX.as_bytes(),
         program_id.as_ref(),
@@ -250,22 +498,34 @@ pub fn place_bid<'r, 'b: 'r>(
 
     // If the bidder pot account is empty, we need to generate one.
     if accounts.bidder_pot.data_is_empty() {
+        if args.top_up {
+            return Err(AuctionError::BidderPotDoesNotExist.into());
+        }
+
         create_or_allocate_account_raw(
             *program_id,
             accounts.bidder_pot,
             accounts.rent,
             accounts.system,
             accounts.payer,
-            mem::size_of::<BidderPot>(),
+            BIDDER_POT_LEN,
             bump_authority_seeds,
         )?;
 
-        // Attach SPL token address to pot account.
+        // Attach SPL token address to pot account, and remember who to refund the rent to.
         let mut pot = BidderPot::from_account_info(accounts.bidder_pot)?;
         pot.bidder_pot = *accounts.bidder_pot_token.key;
         pot.bidder_act = *accounts.bidder.key;
         pot.auction_act = *accounts.auction.key;
+        pot.payer = *accounts.payer.key;
         pot.serialize(&mut *accounts.bidder_pot.data.borrow_mut())?;
+
+        if let Some(open) = auction_extended.open_bidder_pots {
+            auction_extended.open_bidder_pots = Some(
+                open.checked_add(1)
+                    .ok_or(AuctionError::NumericalOverflowError)?,
+            );
+        }
     } else {
         // Already exists, verify that the pot contains the specified SPL address.
         let bidder_pot = BidderPot::from_account_info(accounts.bidder_pot)?;
@@ -274,62 +534,271 @@ pub fn place_bid<'r, 'b: 'r>(
         }
     }
 
-    // Update now we have new bid.
-    assert_derivation(
-        program_id,
-        accounts.auction_extended,
-        &[
-            PREFIX.as_bytes(),
-            program_id.as_ref(),
-            args.resource.as_ref(),
-            EXTENDED.as_bytes(),
-        ],
-    )?;
-    let mut auction_extended: AuctionDataExtended =
-        AuctionDataExtended::from_account_info(accounts.auction_extended)?;
-    auction_extended.total_uncancelled_bids = auction_extended
-        .total_uncancelled_bids
-        .checked_add(1)
-        .ok_or(AuctionError::NumericalOverflowError)?;
-    auction_extended.serialize(&mut *accounts.auction_extended.data.borrow_mut())?;
+    // Update now we have new bid, a top up is the same bid raised.
+    if !args.top_up {
+        auction_extended.total_uncancelled_bids = auction_extended
+            .total_uncancelled_bids
+            .checked_add(1)
+            .ok_or(AuctionError::NumericalOverflowError)?;
+    }
 
-    // Confirm payers SPL token balance is enough to pay the bid.
-    let account: Account = Account::unpack_from_slice(&accounts.bidder_token.data.borrow())?;
-    if account.amount.saturating_sub(args.amount) < 0 {
-        msg!(
-            "Amount is too small: {:?}, compared to account amount of {:?}",
-            args.amount,
-            account.amount
-        );
-        return Err(AuctionError::BalanceTooLow.into());
+    // A top up only pays in what the pot is short of the raised bid.
+    let payment = if args.top_up {
+        let escrowed = assert_pot_balance(
+            native,
+            accounts.auction,
+            accounts.bidder_pot,
+            accounts.bidder_pot_token,
+        )?;
+        args.amount.saturating_sub(escrowed)
+    } else {
+        args.amount
+    };
+
+    // A late bid pushes the end back, giving others the chance to respond.
+    auction.extend_for_bid(&mut auction_extended, clock.unix_timestamp)?;
+
+    if native {
+        // Native SOL bids are paid in lamports by the bidder, and held by the pot itself.
+        if accounts.bidder.lamports() < payment {
+            msg!(
+                "Amount is too small: {:?}, compared to account lamports of {:?}",
+                payment,
+                accounts.bidder.lamports()
+            );
+            return Err(AuctionError::BalanceTooLow.into());
+        }
+
+        invoke(
+            &system_instruction::transfer(accounts.bidder.key, accounts.bidder_pot.key, payment),
+            &[
+                accounts.bidder.clone(),
+                accounts.bidder_pot.clone(),
+                accounts.system.clone(),
+            ],
+        )?;
+    } else {
+        // Confirm payers SPL token balance is enough to pay the bid.
+        let account: Account = Account::unpack_from_slice(&accounts.bidder_token.data.borrow())?;
+        if account.amount.saturating_sub(payment) < 0 {
+            msg!(
+                "Amount is too small: {:?}, compared to account amount of {:?}",
+                payment,
+                account.amount
+            );
+            return Err(AuctionError::BalanceTooLow.into());
+        }
+
+        // Transfer amount of SPL token to bid account.
+        spl_token_transfer(TokenTransferParams {
+            source: accounts.bidder_token.clone(),
+            destination: accounts.bidder_pot_token.clone(),
+            authority: accounts.transfer_authority.clone(),
+            authority_signer_seeds: bump_authority_seeds,
+            token_program: accounts.token_program.clone(),
+            amount: payment,
+        })?;
+    }
+
+    if let Some(bid_currency_info) = bid_currency_info {
+        record_bid_currency(program_id, &accounts, bid_currency_info, args.amount)?;
     }
 
-    // Transfer amount of SPL token to bid account.
-    spl_token_transfer(TokenTransferParams {
-        source: accounts.bidder_token.clone(),
-        destination: accounts.bidder_pot_token.clone(),
-        authority: accounts.transfer_authority.clone(),
-        authority_signer_seeds: bump_authority_seeds,
-        token_program: accounts.token_program.clone(),
-        amount: args.amount,
-    })?;
+    let mut history = vec![BidHistoryEntry {
+        bidder: *accounts.bidder.key,
+        amount,
+        timestamp: clock.unix_timestamp,
+        action: BidHistoryAction::Placed,
+    }];
 
     // Serialize new Auction State
     auction.last_bid = Some(clock.unix_timestamp);
-    auction
-        .bid_state
-        .place_bid(Bid(*accounts.bidder.key, args.amount))?;
+    if let BidState::SealedBid { .. } = auction.bid_state {
+        // Sealed bids stay out of the bid state until they are revealed.
+        record_commitment(program_id, &accounts, &args)?;
+    } else {
+        let bid = Bid(*accounts.bidder.key, amount);
+        let evicted = if args.top_up {
+            auction.bid_state.raise_bid(bid)?
+        } else {
+            auction.bid_state.place_bid(bid)?
+        };
+        if let Some(evicted) = evicted {
+            // A bid too low to stay in the list would leave its funds stranded, refuse it instead.
+            if evicted.0 == *accounts.bidder.key {
+                return Err(AuctionError::BidTooSmall.into());
+            }
+            emit(AuctionEvent::BidEvicted {
+                auction: *accounts.auction.key,
+                bidder: evicted.0,
+                amount: evicted.1,
+            });
+            history.push(BidHistoryEntry {
+                bidder: evicted.0,
+                amount: evicted.1,
+                timestamp: clock.unix_timestamp,
+                action: BidHistoryAction::Evicted,
+            });
+        }
+    }
+
+    // Taking the last place in an auction that sells out ends it on the spot, as does meeting the
+    // buy-now price, which leaves this bid as the sole winner.
+    let instant_sale = auction_extended
+        .instant_sale_price
+        .map_or(false, |price| amount >= price);
+    emit(AuctionEvent::BidPlaced {
+        auction: *accounts.auction.key,
+        bidder: *accounts.bidder.key,
+        amount,
+        timestamp: clock.unix_timestamp,
+    });
+    if auction.bid_state.sold_out() || instant_sale {
+        auction.ended_at = Some(clock.unix_timestamp);
+        auction.state = auction.state.end()?;
+        emit(AuctionEvent::AuctionEnded {
+            auction: *accounts.auction.key,
+            ended_at: clock.unix_timestamp,
+        });
+    }
+    auction.store_bid_pages(
+        program_id,
+        accounts.auction.key,
+        bid_pages,
+        Some(BidPageCreation {
+            payer: accounts.payer,
+            rent: accounts.rent,
+            system: accounts.system,
+        }),
+    )?;
     auction.serialize(&mut *accounts.auction.data.borrow_mut())?;
 
+    auction_extended.record_bid_history(
+        program_id,
+        accounts.auction.key,
+        history_pages,
+        BidPageCreation {
+            payer: accounts.payer,
+            rent: accounts.rent,
+            system: accounts.system,
+        },
+        history,
+    )?;
+    auction_extended.serialize(&mut *accounts.auction_extended.data.borrow_mut())?;
+
     // Update latest metadata with results from the bid.
     BidderMetadata {
         bidder_pubkey: *accounts.bidder.key,
         auction_pubkey: *accounts.auction.key,
-        last_bid: args.amount,
+        last_bid: amount,
         last_bid_timestamp: clock.unix_timestamp,
         cancelled: false,
+        payer: metadata_payer,
+    }
+    .save(accounts.bidder_meta)?;
+
+    Ok(())
+}
+
+/// Store the commitment of a sealed bid in its PDA, creating it on the bidder's first bid.
+fn record_commitment<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &Accounts<'a, 'b>,
+    args: &PlaceBidArgs,
+) -> ProgramResult {
+    let (bidder_commitment, commitment) = match (accounts.bidder_commitment, args.commitment) {
+        (Some(info), Some(commitment)) => (info, commitment),
+        _ => return Err(AuctionError::BidCommitmentMissing.into()),
+    };
+
+    let commitment_bump = assert_derivation(
+        program_id,
+        bidder_commitment,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.auction.key.as_ref(),
+            accounts.bidder.key.as_ref(),
+            "commitment".as_bytes(),
+        ],
+    )?;
+
+    if bidder_commitment.owner != program_id {
+        create_or_allocate_account_raw(
+            *program_id,
+            bidder_commitment,
+            accounts.rent,
+            accounts.system,
+            accounts.payer,
+            BID_COMMITMENT_LEN,
+            &[
+                PREFIX.as_bytes(),
+                program_id.as_ref(),
+                accounts.auction.key.as_ref(),
+                accounts.bidder.key.as_ref(),
+                "commitment".as_bytes(),
+                &[commitment_bump],
+            ],
+        )?;
+    }
+
+    BidCommitment {
+        bidder_pubkey: *accounts.bidder.key,
+        auction_pubkey: *accounts.auction.key,
+        commitment,
+        revealed: false,
+    }
+    .serialize(&mut *bidder_commitment.data.borrow_mut())?;
+
+    Ok(())
+}
+
+/// Store the currency and amount escrowed for the bid in the bidder's currency record, creating it
+/// on the bidder's first bid.
+fn record_bid_currency<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &Accounts<'a, 'b>,
+    bid_currency_info: &'a AccountInfo<'b>,
+    amount: u64,
+) -> ProgramResult {
+    let bid_currency_bump = assert_derivation(
+        program_id,
+        bid_currency_info,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.auction.key.as_ref(),
+            accounts.bidder.key.as_ref(),
+            CURRENCY.as_bytes(),
+        ],
+    )?;
+
+    if bid_currency_info.owner != program_id {
+        create_or_allocate_account_raw(
+            *program_id,
+            bid_currency_info,
+            accounts.rent,
+            accounts.system,
+            accounts.payer,
+            BID_CURRENCY_LEN,
+            &[
+                PREFIX.as_bytes(),
+                program_id.as_ref(),
+                accounts.auction.key.as_ref(),
+                accounts.bidder.key.as_ref(),
+                CURRENCY.as_bytes(),
+                &[bid_currency_bump],
+            ],
+        )?;
+    }
+
+    BidCurrency {
+        bidder_pubkey: *accounts.bidder.key,
+        auction_pubkey: *accounts.auction.key,
+        mint: *accounts.mint.key,
+        amount,
     }
-    .serialize(&mut *accounts.bidder_meta.data.borrow_mut())?;
+    .serialize(&mut *bid_currency_info.data.borrow_mut())?;
 
     Ok(())
 }
@@ -0,0 +1,160 @@
+//! Closes the AuctionData and AuctionDataExtended accounts of a finished auction, returning their
+//! rent to a destination of the authority's choosing. Every bidder pot has to be closed first, by
+//! claiming the winning bids and refunding the losing ones, as both need the auction to exist.
+//!
+//! Auctions created before bidder pots were counted can't be closed, as there is no way to tell
+//! whether funds are still held for them.
+//!
+//! The bid pages of a paged auction, then the bid history pages of an auction keeping a history,
+//! are closed along with it, each passed after the fixed accounts together with the payer that gets
+//! its rent back.
+
+use crate::{
+    errors::AuctionError,
+    events::{emit, AuctionEvent},
+    processor::{
+        assert_bid_history_page_derivation, assert_bid_page_derivation, AuctionData,
+        AuctionDataExtended, BidHistoryPage, BidPage, BidState,
+    },
+    utils::{assert_derivation, assert_owned_by, assert_signer, close_program_account},
+    EXTENDED, PREFIX,
+};
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        sysvar::{clock::Clock, Sysvar},
+    },
+};
+
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct ReclaimAuctionRentArgs {
+    pub resource: Pubkey,
+}
+
+struct Accounts<'a, 'b: 'a> {
+    authority: &'a AccountInfo<'b>,
+    auction: &'a AccountInfo<'b>,
+    auction_extended: &'a AccountInfo<'b>,
+    destination: &'a AccountInfo<'b>,
+    clock_sysvar: &'a AccountInfo<'b>,
+    bid_pages: &'a [AccountInfo<'b>],
+}
+
+fn parse_accounts<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<Accounts<'a, 'b>, ProgramError> {
+    let account_iter = &mut accounts.iter();
+    let accounts = Accounts {
+        authority: next_account_info(account_iter)?,
+        auction: next_account_info(account_iter)?,
+        auction_extended: next_account_info(account_iter)?,
+        destination: next_account_info(account_iter)?,
+        clock_sysvar: next_account_info(account_iter)?,
+        bid_pages: &accounts[5..],
+    };
+
+    assert_owned_by(accounts.auction, program_id)?;
+    assert_owned_by(accounts.auction_extended, program_id)?;
+    assert_signer(accounts.authority)?;
+
+    Ok(accounts)
+}
+
+pub fn reclaim_auction_rent(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: ReclaimAuctionRentArgs,
+) -> ProgramResult {
+    msg!("+ Processing ReclaimAuctionRent");
+    let accounts = parse_accounts(program_id, accounts)?;
+    let clock = Clock::from_account_info(accounts.clock_sysvar)?;
+
+    assert_derivation(
+        program_id,
+        accounts.auction,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+        ],
+    )?;
+    let auction = AuctionData::from_account_info(accounts.auction)?;
+
+    if auction.authority != *accounts.authority.key {
+        return Err(AuctionError::InvalidAuthority.into());
+    }
+
+    if !auction.ended(clock.unix_timestamp)? {
+        return Err(AuctionError::InvalidState.into());
+    }
+
+    assert_derivation(
+        program_id,
+        accounts.auction_extended,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+            EXTENDED.as_bytes(),
+        ],
+    )?;
+    let auction_extended = AuctionDataExtended::from_account_info(accounts.auction_extended)?;
+
+    if auction_extended.open_bidder_pots != Some(0) {
+        return Err(AuctionError::BidderPotsStillOpen.into());
+    }
+
+    // Every page has to go with the auction, as nothing could close them once it is gone.
+    let pages = match auction.bid_state {
+        BidState::PagedEnglishAuction { pages, .. } => pages as usize,
+        _ => 0,
+    };
+    let history_pages = auction_extended.bid_history_pages.unwrap_or(0) as usize;
+    if accounts.bid_pages.len() != (pages + history_pages) * 2 {
+        return Err(AuctionError::BidPageMissing.into());
+    }
+    let (bid_pages, history_page_accounts) = accounts.bid_pages.split_at(pages * 2);
+    for (page, page_accounts) in bid_pages.chunks(2).enumerate() {
+        let page_info = &page_accounts[0];
+        let payer = &page_accounts[1];
+        assert_owned_by(page_info, program_id)?;
+        assert_bid_page_derivation(program_id, page_info, accounts.auction.key, page as u32)?;
+        if BidPage::from_account_info(page_info)?.payer != *payer.key {
+            return Err(AuctionError::PayerMismatch.into());
+        }
+        close_program_account(page_info, payer)?;
+    }
+    for (page, page_accounts) in history_page_accounts.chunks(2).enumerate() {
+        let page_info = &page_accounts[0];
+        let payer = &page_accounts[1];
+        assert_owned_by(page_info, program_id)?;
+        assert_bid_history_page_derivation(
+            program_id,
+            page_info,
+            accounts.auction.key,
+            page as u32,
+        )?;
+        if BidHistoryPage::from_account_info(page_info)?.payer != *payer.key {
+            return Err(AuctionError::PayerMismatch.into());
+        }
+        close_program_account(page_info, payer)?;
+    }
+
+    close_program_account(accounts.auction_extended, accounts.destination)?;
+    close_program_account(accounts.auction, accounts.destination)?;
+
+    emit(AuctionEvent::AuctionClosed {
+        auction: *accounts.auction.key,
+        destination: *accounts.destination.key,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,309 @@
+//! Refunds a bid that was evicted from the bid state when a higher bid pushed it out of the list.
+//! Anybody can do this for any bidder, as the funds only ever go back to the bidder's own token
+//! account, so an evicted bidder doesn't need to be around to get their money back.
+//!
+//! The bidder is marked as cancelled once refunded, which lets them bid again, and in an auction
+//! capping wins per wallet frees up the place they held for the wallet. The emptied pot and its SPL
+//! account are closed, with their rent going back to the pot's payer. An auction keeping a bid
+//! history records the refund, with whoever sends the refund paying for a new history page.
+
+use crate::{
+    errors::AuctionError,
+    events::{emit, AuctionEvent},
+    processor::{
+        release_wallet_bid, AuctionData, AuctionDataExtended, AuctionState, BidCommitment,
+        BidHistoryAction, BidHistoryEntry, BidPageCreation, BidState, BidderMetadata, BidderPot,
+    },
+    utils::{
+        assert_derivation, assert_owned_by, assert_pot_balance, assert_refund_account,
+        assert_token_program_matches_package, close_bidder_pot, is_native_mint, pot_mint,
+        pot_transfer, BidderPotCloseParams, TokenTransferParams,
+    },
+    EXTENDED, PREFIX,
+};
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        sysvar::{clock::Clock, Sysvar},
+    },
+    std::cmp,
+};
+
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct RefundEvictedBidArgs {
+    pub resource: Pubkey,
+}
+
+struct Accounts<'a, 'b: 'a> {
+    auction: &'a AccountInfo<'b>,
+    auction_extended: &'a AccountInfo<'b>,
+    bidder_meta: &'a AccountInfo<'b>,
+    bidder_pot: &'a AccountInfo<'b>,
+    bidder_pot_token: &'a AccountInfo<'b>,
+    bidder: &'a AccountInfo<'b>,
+    bidder_token: &'a AccountInfo<'b>,
+    mint: &'a AccountInfo<'b>,
+    token_program: &'a AccountInfo<'b>,
+    payer: &'a AccountInfo<'b>,
+    bidder_commitment: &'a AccountInfo<'b>,
+    clock_sysvar: &'a AccountInfo<'b>,
+    rent: &'a AccountInfo<'b>,
+    system: &'a AccountInfo<'b>,
+    history_payer: &'a AccountInfo<'b>,
+    page_accounts: &'a [AccountInfo<'b>],
+}
+
+fn parse_accounts<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<Accounts<'a, 'b>, ProgramError> {
+    let account_iter = &mut accounts.iter();
+    let accounts = Accounts {
+        bidder: next_account_info(account_iter)?,
+        bidder_token: next_account_info(account_iter)?,
+        bidder_pot: next_account_info(account_iter)?,
+        bidder_pot_token: next_account_info(account_iter)?,
+        bidder_meta: next_account_info(account_iter)?,
+        auction: next_account_info(account_iter)?,
+        auction_extended: next_account_info(account_iter)?,
+        mint: next_account_info(account_iter)?,
+        token_program: next_account_info(account_iter)?,
+        payer: next_account_info(account_iter)?,
+        // Passed even when unused, so the accounts after it keep their place.
+        bidder_commitment: next_account_info(account_iter)?,
+        clock_sysvar: next_account_info(account_iter)?,
+        rent: next_account_info(account_iter)?,
+        system: next_account_info(account_iter)?,
+        history_payer: next_account_info(account_iter)?,
+        page_accounts: &accounts[15..],
+    };
+
+    assert_owned_by(accounts.auction, program_id)?;
+    assert_owned_by(accounts.auction_extended, program_id)?;
+    assert_owned_by(accounts.bidder_meta, program_id)?;
+    if !is_native_mint(accounts.mint.key) {
+        assert_owned_by(accounts.mint, &spl_token::id())?;
+    }
+    assert_owned_by(accounts.bidder_pot, program_id)?;
+    assert_token_program_matches_package(accounts.token_program)?;
+
+    Ok(accounts)
+}
+
+pub fn refund_evicted_bid(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: RefundEvictedBidArgs,
+) -> ProgramResult {
+    msg!("+ Processing RefundEvictedBid");
+    let accounts = parse_accounts(program_id, accounts)?;
+
+    // Derive and load Auction.
+    let auction_bump = assert_derivation(
+        program_id,
+        accounts.auction,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+        ],
+    )?;
+
+    let auction_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        args.resource.as_ref(),
+        &[auction_bump],
+    ];
+
+    assert_derivation(
+        program_id,
+        accounts.auction_extended,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+            EXTENDED.as_bytes(),
+        ],
+    )?;
+    let mut auction_extended = AuctionDataExtended::from_account_info(accounts.auction_extended)?;
+
+    // An auction capping the wins of each wallet takes the wallet bids account the bid was counted
+    // against ahead of the pages, and frees up the place the bid held.
+    let page_accounts = match auction_extended.max_wins_per_wallet {
+        Some(_) => {
+            let (wallet_bids_info, rest) = accounts
+                .page_accounts
+                .split_first()
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            release_wallet_bid(
+                program_id,
+                accounts.auction.key,
+                wallet_bids_info,
+                accounts.bidder.key,
+            )?;
+            rest
+        }
+        None => accounts.page_accounts,
+    };
+
+    // The bid history pages of an auction that keeps one come ahead of the bid pages.
+    let (history_pages, bid_pages) = match auction_extended.bid_history_pages {
+        Some(_) => page_accounts.split_at(cmp::min(2, page_accounts.len())),
+        None => (&page_accounts[..0], page_accounts),
+    };
+
+    let mut auction = AuctionData::from_account_info(accounts.auction)?;
+    auction.load_bid_pages(program_id, accounts.auction.key, bid_pages)?;
+    let native = is_native_mint(&auction.token_mint);
+    let mint = pot_mint(native, &auction.token_mint, accounts.bidder_pot_token)?;
+    if mint != *accounts.mint.key {
+        return Err(AuctionError::IncorrectMint.into());
+    }
+
+    // Derive Metadata key and load it, a cancelled bid has already had its funds returned.
+    assert_derivation(
+        program_id,
+        accounts.bidder_meta,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.auction.key.as_ref(),
+            accounts.bidder.key.as_ref(),
+            "metadata".as_bytes(),
+        ],
+    )?;
+    let mut bidder_metadata = BidderMetadata::from_account_info(accounts.bidder_meta)?;
+    if bidder_metadata.cancelled {
+        return Err(AuctionError::BidNotEvicted.into());
+    }
+
+    // Only ranked auctions evict bids, and an evicted bid is one that is no longer held in the bid
+    // state. A sealed bid is also missing until revealed, so it must have been revealed as well.
+    let evicted = match auction.bid_state {
+        BidState::EnglishAuction { .. } | BidState::PagedEnglishAuction { .. } => {
+            !auction.bid_state.has_bid(accounts.bidder.key)
+        }
+        BidState::SealedBid { .. } => {
+            let bidder_commitment = accounts.bidder_commitment;
+            assert_owned_by(bidder_commitment, program_id)?;
+            assert_derivation(
+                program_id,
+                bidder_commitment,
+                &[
+                    PREFIX.as_bytes(),
+                    program_id.as_ref(),
+                    accounts.auction.key.as_ref(),
+                    accounts.bidder.key.as_ref(),
+                    "commitment".as_bytes(),
+                ],
+            )?;
+            let commitment = BidCommitment::from_account_info(bidder_commitment)?;
+            commitment.revealed && !auction.bid_state.has_bid(accounts.bidder.key)
+        }
+        BidState::OpenEdition { .. } | BidState::DutchAuction { .. } => false,
+    };
+    if !evicted {
+        return Err(AuctionError::BidNotEvicted.into());
+    }
+
+    // Derive Pot address and confirm we're looking at the real SPL account for this bidder.
+    assert_derivation(
+        program_id,
+        accounts.bidder_pot,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.auction.key.as_ref(),
+            accounts.bidder.key.as_ref(),
+        ],
+    )?;
+    let bidder_pot = BidderPot::from_account_info(accounts.bidder_pot)?;
+    if bidder_pot.bidder_pot != *accounts.bidder_pot_token.key {
+        return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
+    }
+
+    let balance = assert_pot_balance(
+        native,
+        accounts.auction,
+        accounts.bidder_pot,
+        accounts.bidder_pot_token,
+    )?;
+
+    // Without the bidder's signature, the refund can only go to an account they own.
+    assert_refund_account(native, accounts.bidder_token, accounts.bidder.key, &mint)?;
+
+    // Recorded ahead of moving any funds, as in CancelBid.
+    let clock = Clock::from_account_info(accounts.clock_sysvar)?;
+    auction_extended.record_bid_history(
+        program_id,
+        accounts.auction.key,
+        history_pages,
+        BidPageCreation {
+            payer: accounts.history_payer,
+            rent: accounts.rent,
+            system: accounts.system,
+        },
+        vec![BidHistoryEntry {
+            bidder: *accounts.bidder.key,
+            amount: bidder_metadata.last_bid,
+            timestamp: clock.unix_timestamp,
+            action: BidHistoryAction::Refunded,
+        }],
+    )?;
+
+    pot_transfer(
+        native,
+        TokenTransferParams {
+            source: accounts.bidder_pot_token.clone(),
+            destination: accounts.bidder_token.clone(),
+            authority: accounts.auction.clone(),
+            authority_signer_seeds: auction_seeds,
+            token_program: accounts.token_program.clone(),
+            amount: balance,
+        },
+    )?;
+
+    close_bidder_pot(BidderPotCloseParams {
+        pot: &bidder_pot,
+        bidder_pot: accounts.bidder_pot.clone(),
+        bidder_pot_token: accounts.bidder_pot_token.clone(),
+        payer: accounts.payer.clone(),
+        auction: accounts.auction.clone(),
+        auction_signer_seeds: auction_seeds,
+        token_program: accounts.token_program.clone(),
+    })?;
+
+    bidder_metadata.cancelled = true;
+    bidder_metadata.save(accounts.bidder_meta)?;
+
+    // The evicted bid no longer counts towards the uncancelled bids, though once ended we want
+    // uncancelled bids to retain their pre-ending count, as in CancelBid.
+    if auction.state != AuctionState::Ended
+        && auction.state != AuctionState::Cancelled
+        && auction_extended.total_uncancelled_bids > 0
+    {
+        auction_extended.total_uncancelled_bids = auction_extended
+            .total_uncancelled_bids
+            .checked_sub(1)
+            .ok_or(AuctionError::NumericalOverflowError)?;
+    }
+    auction_extended.count_closed_pot();
+    auction_extended.serialize(&mut *accounts.auction_extended.data.borrow_mut())?;
+
+    emit(AuctionEvent::BidRefunded {
+        auction: *accounts.auction.key,
+        bidder: *accounts.bidder.key,
+        amount: bidder_metadata.last_bid,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
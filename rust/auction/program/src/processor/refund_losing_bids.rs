@@ -0,0 +1,319 @@
+//! Refunds losing bids in bulk once an auction has ended, so that bidders don't each have to come
+//! back and cancel their bids. Anybody can run this as a crank, as the funds only ever go back to
+//! the associated token account of each bidder, or their wallet in a native SOL auction.
+//!
+//! Bids are passed as groups of accounts, one group per bidder, following the fixed accounts, the
+//! bid history pages of an auction keeping a history and the bid pages of a paged auction. Each bid
+//! is refunded in the currency it was paid in, and left cancelled as if the bidder had cancelled it
+//! themselves. Each refunded pot is closed along with its SPL account, with their rent going back
+//! to the pot's payer. Pots that have already been closed, by this or by CancelBid, are skipped so
+//! a crank can safely retry a batch. In an auction capping wins per wallet, the place each bid held
+//! is freed up for the wallet.
+
+use crate::{
+    errors::AuctionError,
+    events::{emit, AuctionEvent},
+    processor::{
+        release_wallet_bid, AuctionData, AuctionDataExtended, BidHistoryAction, BidHistoryEntry,
+        BidPageCreation, BidderMetadata, BidderPot,
+    },
+    utils::{
+        assert_derivation, assert_owned_by, assert_pot_balance,
+        assert_token_program_matches_package, close_program_account, is_native_mint, pot_mint,
+        pot_transfer, spl_token_close, spl_token_transfer, TokenCloseParams, TokenTransferParams,
+    },
+    EXTENDED, PREFIX,
+};
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        sysvar::{clock::Clock, Sysvar},
+    },
+    std::{cmp, str::FromStr},
+};
+
+/// Number of accounts passed for each bid being refunded, ahead of the wallet bids account of an
+/// auction capping wins per wallet.
+pub const REFUND_ACCOUNTS_PER_BID: usize = 5;
+
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct RefundLosingBidsArgs {
+    pub resource: Pubkey,
+}
+
+struct Accounts<'a, 'b: 'a> {
+    auction: &'a AccountInfo<'b>,
+    auction_extended: &'a AccountInfo<'b>,
+    clock_sysvar: &'a AccountInfo<'b>,
+    token_program: &'a AccountInfo<'b>,
+    history_payer: &'a AccountInfo<'b>,
+    rent: &'a AccountInfo<'b>,
+    system: &'a AccountInfo<'b>,
+    bids: &'a [AccountInfo<'b>],
+}
+
+fn parse_accounts<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<Accounts<'a, 'b>, ProgramError> {
+    let account_iter = &mut accounts.iter();
+    let parsed = Accounts {
+        auction: next_account_info(account_iter)?,
+        auction_extended: next_account_info(account_iter)?,
+        clock_sysvar: next_account_info(account_iter)?,
+        token_program: next_account_info(account_iter)?,
+        history_payer: next_account_info(account_iter)?,
+        rent: next_account_info(account_iter)?,
+        system: next_account_info(account_iter)?,
+        bids: &accounts[7..],
+    };
+
+    assert_owned_by(parsed.auction, program_id)?;
+    assert_owned_by(parsed.auction_extended, program_id)?;
+    assert_token_program_matches_package(parsed.token_program)?;
+
+    Ok(parsed)
+}
+
+pub fn refund_losing_bids(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: RefundLosingBidsArgs,
+) -> ProgramResult {
+    msg!("+ Processing RefundLosingBids");
+    let accounts = parse_accounts(program_id, accounts)?;
+    let clock = Clock::from_account_info(accounts.clock_sysvar)?;
+
+    // Derive and load Auction.
+    let auction_bump = assert_derivation(
+        program_id,
+        accounts.auction,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+        ],
+    )?;
+
+    let auction_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        args.resource.as_ref(),
+        &[auction_bump],
+    ];
+
+    assert_derivation(
+        program_id,
+        accounts.auction_extended,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+            EXTENDED.as_bytes(),
+        ],
+    )?;
+    let mut auction_extended = AuctionDataExtended::from_account_info(accounts.auction_extended)?;
+
+    // The bid history pages of an auction that keeps one come ahead of the bid pages.
+    let (history_pages, page_accounts) = match auction_extended.bid_history_pages {
+        Some(_) => accounts.bids.split_at(cmp::min(2, accounts.bids.len())),
+        None => (&accounts.bids[..0], accounts.bids),
+    };
+
+    let mut auction = AuctionData::from_account_info(accounts.auction)?;
+    let bids = auction.load_bid_pages(program_id, accounts.auction.key, page_accounts)?;
+
+    // Until the auction ends there are no losers, only bids that may yet be outbid.
+    if !auction.ended(clock.unix_timestamp)? {
+        return Err(AuctionError::InvalidState.into());
+    }
+    let native = is_native_mint(&auction.token_mint);
+
+    // An auction capping the wins of each wallet takes the wallet bids account each bid was counted
+    // against after the rest of its accounts, and frees up the place the bid held.
+    let accounts_per_bid =
+        REFUND_ACCOUNTS_PER_BID + auction_extended.max_wins_per_wallet.is_some() as usize;
+    if bids.is_empty() || bids.len() % accounts_per_bid != 0 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    // Let's avoid importing the entire ATA library here just to get an ID.
+    let associated_token_program =
+        Pubkey::from_str("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL").unwrap();
+
+    // Pots emptied by this batch, closed along with their native SOL refunds once the batch has
+    // been through. Both move lamports outside of any program call, which the runtime would hold
+    // against the calls made for the next bid or for a new history page.
+    let mut closed_pots: Vec<(&AccountInfo, &AccountInfo, &AccountInfo, u64)> = vec![];
+    let mut history = vec![];
+    for bid in bids.chunks(accounts_per_bid) {
+        let bidder_meta = &bid[0];
+        let bidder_pot = &bid[1];
+        let bidder_pot_token = &bid[2];
+        let destination = &bid[3];
+        let payer = &bid[4];
+
+        // Already refunded and closed, nothing left to do for this bid.
+        if bidder_pot.lamports() == 0
+            || closed_pots
+                .iter()
+                .any(|(pot, ..)| pot.key == bidder_pot.key)
+        {
+            msg!("Skipping already closed pot {}", bidder_pot.key);
+            continue;
+        }
+
+        assert_owned_by(bidder_meta, program_id)?;
+        assert_owned_by(bidder_pot, program_id)?;
+
+        // Derive Metadata key from the bidder it records.
+        let bidder_metadata = BidderMetadata::from_account_info(bidder_meta)?;
+        let bidder = bidder_metadata.bidder_pubkey;
+        assert_derivation(
+            program_id,
+            bidder_meta,
+            &[
+                PREFIX.as_bytes(),
+                program_id.as_ref(),
+                accounts.auction.key.as_ref(),
+                bidder.as_ref(),
+                "metadata".as_bytes(),
+            ],
+        )?;
+
+        // Winners are paid out through ClaimBid instead.
+        if auction.is_winner(&bidder).is_some() {
+            return Err(AuctionError::WinningBidCannotBeRefunded.into());
+        }
+
+        // Derive Pot address and confirm we're looking at the real SPL account for this bidder.
+        assert_derivation(
+            program_id,
+            bidder_pot,
+            &[
+                PREFIX.as_bytes(),
+                program_id.as_ref(),
+                accounts.auction.key.as_ref(),
+                bidder.as_ref(),
+            ],
+        )?;
+        let pot = BidderPot::from_account_info(bidder_pot)?;
+        if pot.bidder_pot != *bidder_pot_token.key {
+            return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
+        }
+
+        let balance = assert_pot_balance(native, accounts.auction, bidder_pot, bidder_pot_token)?;
+        let mint = pot_mint(native, &auction.token_mint, bidder_pot_token)?;
+
+        // Funds only go back to the bidder's wallet for native SOL, or their associated token
+        // account in the currency they bid in otherwise, which is the mint of the pot itself.
+        if native {
+            if *destination.key != bidder {
+                return Err(AuctionError::RefundAccountOwnerMismatch.into());
+            }
+        } else {
+            assert_owned_by(destination, &spl_token::id())?;
+            assert_derivation(
+                &associated_token_program,
+                destination,
+                &[
+                    bidder.as_ref(),
+                    accounts.token_program.key.as_ref(),
+                    mint.as_ref(),
+                ],
+            )?;
+        }
+
+        if pot.payer != *payer.key {
+            return Err(AuctionError::PayerMismatch.into());
+        }
+
+        // Pots emptied before they were closed on refund only have their rent left to return.
+        if !native {
+            if balance > 0 {
+                spl_token_transfer(TokenTransferParams {
+                    source: bidder_pot_token.clone(),
+                    destination: destination.clone(),
+                    authority: accounts.auction.clone(),
+                    authority_signer_seeds: auction_seeds,
+                    token_program: accounts.token_program.clone(),
+                    amount: balance,
+                })?;
+            }
+            spl_token_close(TokenCloseParams {
+                account: bidder_pot_token.clone(),
+                destination: payer.clone(),
+                authority: accounts.auction.clone(),
+                authority_signer_seeds: auction_seeds,
+                token_program: accounts.token_program.clone(),
+            })?;
+        }
+        closed_pots.push((bidder_pot, destination, payer, balance));
+        auction_extended.count_closed_pot();
+        history.push(BidHistoryEntry {
+            bidder,
+            amount: bidder_metadata.last_bid,
+            timestamp: clock.unix_timestamp,
+            action: BidHistoryAction::Refunded,
+        });
+        emit(AuctionEvent::BidRefunded {
+            auction: *accounts.auction.key,
+            bidder,
+            amount: bidder_metadata.last_bid,
+            timestamp: clock.unix_timestamp,
+        });
+
+        // The bid is left cancelled, the same as a bid the bidder cancelled themselves.
+        BidderMetadata {
+            cancelled: true,
+            ..bidder_metadata
+        }
+        .save(bidder_meta)?;
+
+        if let Some(wallet_bids_info) = bid.get(REFUND_ACCOUNTS_PER_BID) {
+            release_wallet_bid(program_id, accounts.auction.key, wallet_bids_info, &bidder)?;
+        }
+    }
+
+    // Whoever runs the crank pays for a new history page, as the payers of the pots need not be
+    // around to sign.
+    auction_extended.record_bid_history(
+        program_id,
+        accounts.auction.key,
+        history_pages,
+        BidPageCreation {
+            payer: accounts.history_payer,
+            rent: accounts.rent,
+            system: accounts.system,
+        },
+        history,
+    )?;
+
+    for (bidder_pot, destination, payer, balance) in closed_pots {
+        if native && balance > 0 {
+            pot_transfer(
+                native,
+                TokenTransferParams {
+                    source: bidder_pot.clone(),
+                    destination: destination.clone(),
+                    authority: accounts.auction.clone(),
+                    authority_signer_seeds: auction_seeds,
+                    token_program: accounts.token_program.clone(),
+                    amount: balance,
+                },
+            )?;
+        }
+        close_program_account(bidder_pot, payer)?;
+    }
+
+    auction_extended.serialize(&mut *accounts.auction_extended.data.borrow_mut())?;
+
+    Ok(())
+}
@@ -0,0 +1,291 @@
+//! Reveals a sealed bid once bidding has closed on a sealed bid auction. The revealed amount must
+//! match the commitment made when bidding, and fit inside the amount escrowed in the pot. Any
+//! excess escrow is returned to the bidder, and the bid is ranked into the winners list.
+//!
+//! Bids that are never revealed never enter the bid state, so once the auction ends they are
+//! treated as losing bids and can be refunded through CancelBid. An auction keeping a bid history
+//! records any bid the reveal pushes out of the winners list, with the payer of the reveal paying
+//! for a new history page.
+
+use crate::{
+    errors::AuctionError,
+    events::{emit, AuctionEvent},
+    processor::{
+        AuctionData, AuctionDataExtended, Bid, BidCommitment, BidHistoryAction, BidHistoryEntry,
+        BidPageCreation, BidState, BidderMetadata, BidderPot,
+    },
+    utils::{
+        assert_derivation, assert_owned_by, assert_pot_balance, assert_signer,
+        assert_token_program_matches_package, is_native_mint, pot_transfer, TokenTransferParams,
+    },
+    EXTENDED, PREFIX,
+};
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        hash, msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        sysvar::{clock::Clock, Sysvar},
+    },
+};
+
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct RevealBidArgs {
+    /// The resource being auctioned. See AuctionData.
+    pub resource: Pubkey,
+    /// The amount that was committed to when bidding.
+    pub amount: u64,
+    /// The salt that was hashed together with the amount when bidding.
+    pub salt: u64,
+}
+
+struct Accounts<'a, 'b: 'a> {
+    auction: &'a AccountInfo<'b>,
+    auction_extended: &'a AccountInfo<'b>,
+    bidder_commitment: &'a AccountInfo<'b>,
+    bidder_meta: &'a AccountInfo<'b>,
+    bidder_pot: &'a AccountInfo<'b>,
+    bidder_pot_token: &'a AccountInfo<'b>,
+    bidder: &'a AccountInfo<'b>,
+    bidder_token: &'a AccountInfo<'b>,
+    clock_sysvar: &'a AccountInfo<'b>,
+    token_program: &'a AccountInfo<'b>,
+    payer: &'a AccountInfo<'b>,
+    rent: &'a AccountInfo<'b>,
+    system: &'a AccountInfo<'b>,
+    history_pages: &'a [AccountInfo<'b>],
+}
+
+fn parse_accounts<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<Accounts<'a, 'b>, ProgramError> {
+    let account_iter = &mut accounts.iter();
+    let accounts = Accounts {
+        bidder: next_account_info(account_iter)?,
+        bidder_token: next_account_info(account_iter)?,
+        bidder_pot: next_account_info(account_iter)?,
+        bidder_pot_token: next_account_info(account_iter)?,
+        bidder_meta: next_account_info(account_iter)?,
+        bidder_commitment: next_account_info(account_iter)?,
+        auction: next_account_info(account_iter)?,
+        clock_sysvar: next_account_info(account_iter)?,
+        token_program: next_account_info(account_iter)?,
+        auction_extended: next_account_info(account_iter)?,
+        payer: next_account_info(account_iter)?,
+        rent: next_account_info(account_iter)?,
+        system: next_account_info(account_iter)?,
+        history_pages: &accounts[13..],
+    };
+
+    assert_owned_by(accounts.auction, program_id)?;
+    assert_owned_by(accounts.auction_extended, program_id)?;
+    assert_owned_by(accounts.bidder_meta, program_id)?;
+    assert_owned_by(accounts.bidder_commitment, program_id)?;
+    assert_owned_by(accounts.bidder_pot, program_id)?;
+    assert_signer(accounts.bidder)?;
+    assert_token_program_matches_package(accounts.token_program)?;
+
+    Ok(accounts)
+}
+
+pub fn reveal_bid(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: RevealBidArgs,
+) -> ProgramResult {
+    msg!("+ Processing RevealBid");
+    let accounts = parse_accounts(program_id, accounts)?;
+    let clock = Clock::from_account_info(accounts.clock_sysvar)?;
+
+    // Derive and load Auction.
+    let auction_bump = assert_derivation(
+        program_id,
+        accounts.auction,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+        ],
+    )?;
+
+    let auction_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        args.resource.as_ref(),
+        &[auction_bump],
+    ];
+
+    assert_derivation(
+        program_id,
+        accounts.auction_extended,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+            EXTENDED.as_bytes(),
+        ],
+    )?;
+    let mut auction_extended = AuctionDataExtended::from_account_info(accounts.auction_extended)?;
+
+    let mut auction = AuctionData::from_account_info(accounts.auction)?;
+
+    // Only sealed bid auctions have anything to reveal.
+    match auction.bid_state {
+        BidState::SealedBid { .. } => {}
+        _ => return Err(AuctionError::InvalidState.into()),
+    }
+
+    // Bids can only be revealed inside the reveal window, after bidding closes but before the
+    // auction ends.
+    if !auction.bidding_closed(clock.unix_timestamp)? || auction.ended(clock.unix_timestamp)? {
+        return Err(AuctionError::InvalidState.into());
+    }
+
+    // Derive Metadata key and load it, a cancelled bid has nothing left to reveal.
+    assert_derivation(
+        program_id,
+        accounts.bidder_meta,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.auction.key.as_ref(),
+            accounts.bidder.key.as_ref(),
+            "metadata".as_bytes(),
+        ],
+    )?;
+    let mut bidder_metadata = BidderMetadata::from_account_info(accounts.bidder_meta)?;
+    if bidder_metadata.cancelled {
+        return Err(AuctionError::InvalidState.into());
+    }
+
+    // Derive the commitment and check the reveal matches it.
+    assert_derivation(
+        program_id,
+        accounts.bidder_commitment,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.auction.key.as_ref(),
+            accounts.bidder.key.as_ref(),
+            "commitment".as_bytes(),
+        ],
+    )?;
+    let mut commitment = BidCommitment::from_account_info(accounts.bidder_commitment)?;
+    if commitment.revealed {
+        return Err(AuctionError::BidAlreadyRevealed.into());
+    }
+
+    let reveal_hash = hash::hashv(&[&args.amount.to_be_bytes(), &args.salt.to_be_bytes()]);
+    if reveal_hash != commitment.commitment {
+        return Err(AuctionError::InvalidReveal.into());
+    }
+
+    if args.amount == 0 {
+        return Err(AuctionError::BidTooSmall.into());
+    }
+
+    // Bidding only checked the escrow against the price floor, the sealed amount has to meet it
+    // too. A floor that is still blinded is applied to the winners once revealed at the end.
+    if args.amount < auction.floor() {
+        msg!(
+            "Amount is too small: {:?}, compared to price floor of {:?}",
+            args.amount,
+            auction.floor()
+        );
+        return Err(AuctionError::BidTooSmall.into());
+    }
+
+    // Derive Pot address and confirm we're looking at the real SPL account for this bidder.
+    assert_derivation(
+        program_id,
+        accounts.bidder_pot,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.auction.key.as_ref(),
+            accounts.bidder.key.as_ref(),
+        ],
+    )?;
+    let bidder_pot = BidderPot::from_account_info(accounts.bidder_pot)?;
+    if bidder_pot.bidder_pot != *accounts.bidder_pot_token.key {
+        return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
+    }
+
+    let native = is_native_mint(&auction.token_mint);
+    let balance = assert_pot_balance(
+        native,
+        accounts.auction,
+        accounts.bidder_pot,
+        accounts.bidder_pot_token,
+    )?;
+
+    // The revealed bid has to be covered by what was escrowed.
+    if balance < args.amount {
+        return Err(AuctionError::BalanceTooLow.into());
+    }
+
+    // Rank the revealed bid against the others. A bid pushed out of the list, even this one, keeps
+    // its escrow in the pot until refunded with RefundEvictedBid.
+    let evicted = auction
+        .bid_state
+        .place_bid(Bid(*accounts.bidder.key, args.amount))?;
+    let mut history = vec![];
+    if let Some(evicted) = evicted {
+        emit(AuctionEvent::BidEvicted {
+            auction: *accounts.auction.key,
+            bidder: evicted.0,
+            amount: evicted.1,
+        });
+        history.push(BidHistoryEntry {
+            bidder: evicted.0,
+            amount: evicted.1,
+            timestamp: clock.unix_timestamp,
+            action: BidHistoryAction::Evicted,
+        });
+    }
+    auction.serialize(&mut *accounts.auction.data.borrow_mut())?;
+
+    // Recorded ahead of moving any funds, as in CancelBid.
+    auction_extended.record_bid_history(
+        program_id,
+        accounts.auction.key,
+        accounts.history_pages,
+        BidPageCreation {
+            payer: accounts.payer,
+            rent: accounts.rent,
+            system: accounts.system,
+        },
+        history,
+    )?;
+    auction_extended.serialize(&mut *accounts.auction_extended.data.borrow_mut())?;
+
+    // Return whatever was escrowed over the revealed amount.
+    let excess = balance - args.amount;
+    if excess > 0 {
+        pot_transfer(
+            native,
+            TokenTransferParams {
+                source: accounts.bidder_pot_token.clone(),
+                destination: accounts.bidder_token.clone(),
+                authority: accounts.auction.clone(),
+                authority_signer_seeds: auction_seeds,
+                token_program: accounts.token_program.clone(),
+                amount: excess,
+            },
+        )?;
+    }
+
+    commitment.revealed = true;
+    commitment.serialize(&mut *accounts.bidder_commitment.data.borrow_mut())?;
+
+    bidder_metadata.last_bid = args.amount;
+    bidder_metadata.save(accounts.bidder_meta)?;
+
+    Ok(())
+}
@@ -2,6 +2,7 @@
 
 use crate::{
     errors::AuctionError,
+    events::{emit, AuctionEvent},
     processor::{AuctionData, BASE_AUCTION_DATA_SIZE},
     utils::assert_owned_by,
     PREFIX,
@@ -37,5 +38,11 @@ pub fn set_authority(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramRe
 
     auction.authority = *new_authority.key;
     auction.serialize(&mut *auction_act.data.borrow_mut())?;
+
+    emit(AuctionEvent::AuthorityChanged {
+        auction: *auction_act.key,
+        authority: *new_authority.key,
+    });
+
     Ok(())
 }
@@ -1,8 +1,13 @@
+//! Starts an auction. Until its scheduled start time, if it has one, only the authority can do
+//! this. Once that time has passed anybody can, and the end time is counted from the scheduled
+//! start so it doesn't matter who gets there first or when.
+
 use crate::{
     errors::AuctionError,
-    processor::{AuctionData, AuctionState, Bid, BidState, WinnerLimit},
+    events::{emit, AuctionEvent},
+    processor::{AuctionData, AuctionDataExtended, AuctionState, Bid, BidState, WinnerLimit},
     utils::{assert_derivation, assert_owned_by, assert_signer, create_or_allocate_account_raw},
-    PREFIX,
+    EXTENDED, PREFIX,
 };
 
 use {
@@ -23,6 +28,7 @@ struct Accounts<'a, 'b: 'a> {
     authority: &'a AccountInfo<'b>,
     auction: &'a AccountInfo<'b>,
     clock_sysvar: &'a AccountInfo<'b>,
+    auction_extended: Option<&'a AccountInfo<'b>>,
 }
 
 fn parse_accounts<'a, 'b: 'a>(
@@ -34,8 +40,12 @@ fn parse_accounts<'a, 'b: 'a>(
         authority: next_account_info(account_iter)?,
         auction: next_account_info(account_iter)?,
         clock_sysvar: next_account_info(account_iter)?,
+        auction_extended: next_account_info(account_iter).ok(),
     };
     assert_owned_by(accounts.auction, program_id)?;
+    if let Some(auction_extended) = accounts.auction_extended {
+        assert_owned_by(auction_extended, program_id)?;
+    }
     assert_signer(accounts.authority)?;
     Ok(accounts)
 }
@@ -70,27 +80,46 @@ pub fn start_auction<'a, 'b: 'a>(
     // Initialise a new auction. The end time is calculated relative to now.
     let mut auction = AuctionData::from_account_info(accounts.auction)?;
 
-    // Check authority is correct.
-    if auction.authority != *accounts.authority.key {
-        return Err(AuctionError::InvalidAuthority.into());
-    }
-
-    // Calculate the relative end time.
-    let ended_at = if let Some(end_auction_at) = auction.end_auction_at {
-        match clock.unix_timestamp.checked_add(end_auction_at) {
-            Some(val) => Some(val),
-            None => return Err(AuctionError::NumericalOverflowError.into()),
+    // Past its scheduled start time anybody can start the auction, so the extended data holding
+    // that time is only needed when the signer is not the authority.
+    let scheduled_start = match accounts.auction_extended {
+        Some(auction_extended) => {
+            assert_derivation(
+                program_id,
+                auction_extended,
+                &[
+                    PREFIX.as_bytes(),
+                    program_id.as_ref(),
+                    args.resource.as_ref(),
+                    EXTENDED.as_bytes(),
+                ],
+            )?;
+            let auction_extended = AuctionDataExtended::from_account_info(auction_extended)?;
+            if auction.start_if_scheduled(&auction_extended, clock.unix_timestamp)? {
+                auction_extended.start_auction_at
+            } else {
+                None
+            }
         }
-    } else {
-        None
+        None => None,
     };
 
-    AuctionData {
-        ended_at,
-        state: auction.state.start()?,
-        ..auction
+    if scheduled_start.is_none() {
+        // Check authority is correct.
+        if auction.authority != *accounts.authority.key {
+            return Err(AuctionError::InvalidAuthority.into());
+        }
+
+        // Calculate the relative end time.
+        auction.start_from(clock.unix_timestamp)?;
     }
-    .serialize(&mut *accounts.auction.data.borrow_mut())?;
+
+    auction.serialize(&mut *accounts.auction.data.borrow_mut())?;
+
+    emit(AuctionEvent::AuctionStarted {
+        auction: *accounts.auction.key,
+        started_at: scheduled_start.unwrap_or(clock.unix_timestamp),
+    });
 
     Ok(())
 }
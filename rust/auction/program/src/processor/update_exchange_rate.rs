@@ -0,0 +1,58 @@
+//! Sets the price of a bid currency for the auctions that accept it, in the same way as
+//! token-vault's UpdateExternalPriceAccount. The exchange rate account is created by the caller,
+//! owned by this program, and signs for its own updates.
+//!
+//! Once set, the mints of a rate are fixed, so an auction listing the rate can't have it turned
+//! into the price of some other currency. Only the price moves.
+
+use crate::{
+    errors::AuctionError,
+    events::{emit, AuctionEvent},
+    processor::ExchangeRate,
+    utils::{assert_owned_by, assert_signer},
+};
+
+use {
+    borsh::BorshSerialize,
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        pubkey::Pubkey,
+    },
+};
+
+pub fn update_exchange_rate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: ExchangeRate,
+) -> ProgramResult {
+    msg!("+ Processing UpdateExchangeRate");
+    let account_iter = &mut accounts.iter();
+    let exchange_rate_info = next_account_info(account_iter)?;
+
+    assert_owned_by(exchange_rate_info, program_id)?;
+    assert_signer(exchange_rate_info)?;
+
+    // A zero price would let bids in this currency in for free.
+    if args.price_per_token == 0 || args.mint == args.price_mint {
+        return Err(AuctionError::InvalidExchangeRate.into());
+    }
+
+    let exchange_rate = ExchangeRate::from_account_info(exchange_rate_info)?;
+    let unset = exchange_rate.mint == Pubkey::default();
+    if !unset && (exchange_rate.mint != args.mint || exchange_rate.price_mint != args.price_mint) {
+        return Err(AuctionError::InvalidExchangeRate.into());
+    }
+
+    args.serialize(&mut *exchange_rate_info.data.borrow_mut())?;
+
+    emit(AuctionEvent::ExchangeRateUpdated {
+        exchange_rate: *exchange_rate_info.key,
+        mint: args.mint,
+        price_mint: args.price_mint,
+        price_per_token: args.price_per_token,
+    });
+
+    Ok(())
+}
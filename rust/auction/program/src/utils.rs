@@ -1,7 +1,7 @@
 use solana_program::program_pack::IsInitialized;
 
 use {
-    crate::errors::AuctionError,
+    crate::{errors::AuctionError, processor::BidderPot},
     solana_program::{
         account_info::AccountInfo,
         entrypoint::ProgramResult,
@@ -10,12 +10,20 @@ use {
         program_error::ProgramError,
         program_pack::Pack,
         pubkey::Pubkey,
-        system_instruction,
+        system_instruction, system_program,
         sysvar::{rent::Rent, Sysvar},
     },
+    spl_token::state::Account,
     std::convert::TryInto,
 };
 
+/// Whether an auction mint marks it as taking bids in native SOL rather than an SPL token. The
+/// system program id stands in for the mint of such auctions, and their bidder pots hold the
+/// lamports bid themselves, standing in for the SPL account of the pot wherever one is expected.
+pub fn is_native_mint(mint: &Pubkey) -> bool {
+    *mint == system_program::id()
+}
+
 pub fn assert_initialized<T: Pack + IsInitialized>(
     account_info: &AccountInfo,
 ) -> Result<T, ProgramError> {
@@ -166,6 +174,205 @@ pub fn spl_token_transfer(params: TokenTransferParams<'_, '_>) -> ProgramResult
     result.map_err(|_| AuctionError::TokenTransferFailed.into())
 }
 
+/// Loads the balance of a bidder pot, after confirming the account holding it belongs to the
+/// auction. A native SOL pot holds whatever it has over its own rent exemption.
+pub fn assert_pot_balance(
+    native: bool,
+    auction: &AccountInfo,
+    bidder_pot: &AccountInfo,
+    bidder_pot_token: &AccountInfo,
+) -> Result<u64, ProgramError> {
+    if native {
+        if bidder_pot_token.key != bidder_pot.key {
+            return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
+        }
+        let rent = Rent::get()?.minimum_balance(bidder_pot.data_len());
+        return Ok(bidder_pot.lamports().saturating_sub(rent));
+    }
+
+    assert_owned_by(bidder_pot_token, &spl_token::id())?;
+    let actual_account: Account = assert_initialized(bidder_pot_token)?;
+    if actual_account.owner != *auction.key {
+        return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
+    }
+
+    Ok(actual_account.amount)
+}
+
+/// The mint a bid was escrowed in. Bids are in the auction's token mint, unless the auction
+/// accepts other currencies, in which case the SPL account of the pot tells which one. PlaceBid
+/// only ever escrows accepted currencies.
+pub fn pot_mint(
+    native: bool,
+    token_mint: &Pubkey,
+    bidder_pot_token: &AccountInfo,
+) -> Result<Pubkey, ProgramError> {
+    if native {
+        return Ok(*token_mint);
+    }
+
+    let actual_account: Account = assert_initialized(bidder_pot_token)?;
+    Ok(actual_account.mint)
+}
+
+/// Checks an account can take a refund on behalf of a bidder that isn't around to sign for it. In
+/// a native SOL auction that is the bidder's own wallet, otherwise any of their token accounts in
+/// the auction's mint.
+pub fn assert_refund_account(
+    native: bool,
+    refund: &AccountInfo,
+    bidder: &Pubkey,
+    mint: &Pubkey,
+) -> ProgramResult {
+    if native {
+        if refund.key != bidder {
+            return Err(AuctionError::RefundAccountOwnerMismatch.into());
+        }
+        return Ok(());
+    }
+
+    assert_owned_by(refund, &spl_token::id())?;
+    let refund_account: Account = assert_initialized(refund)?;
+    if refund_account.owner != *bidder {
+        return Err(AuctionError::RefundAccountOwnerMismatch.into());
+    }
+    if refund_account.mint != *mint {
+        return Err(AuctionError::IncorrectMint.into());
+    }
+
+    Ok(())
+}
+
+/// Pay out of a bidder pot. A native SOL pot is owned by this program, so its lamports are moved
+/// directly, while tokens are moved out of an SPL pot with the auction's signature.
+pub fn pot_transfer(native: bool, params: TokenTransferParams<'_, '_>) -> ProgramResult {
+    if !native {
+        return spl_token_transfer(params);
+    }
+
+    let TokenTransferParams {
+        source,
+        destination,
+        amount,
+        ..
+    } = params;
+    **source.lamports.borrow_mut() = source
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(AuctionError::BalanceTooLow)?;
+    **destination.lamports.borrow_mut() = destination
+        .lamports()
+        .checked_add(amount)
+        .ok_or(AuctionError::NumericalOverflowError)?;
+
+    Ok(())
+}
+
+///TokenCloseParams
+pub struct TokenCloseParams<'a: 'b, 'b> {
+    /// account
+    pub account: AccountInfo<'a>,
+    /// destination
+    pub destination: AccountInfo<'a>,
+    /// authority
+    pub authority: AccountInfo<'a>,
+    /// authority_signer_seeds
+    pub authority_signer_seeds: &'b [&'b [u8]],
+    /// token_program
+    pub token_program: AccountInfo<'a>,
+}
+
+/// Close an empty SPL token account, sending its rent to the destination.
+#[inline(always)]
+pub fn spl_token_close(params: TokenCloseParams<'_, '_>) -> ProgramResult {
+    let TokenCloseParams {
+        account,
+        destination,
+        authority,
+        authority_signer_seeds,
+        token_program,
+    } = params;
+
+    let result = invoke_signed(
+        &spl_token::instruction::close_account(
+            token_program.key,
+            account.key,
+            destination.key,
+            authority.key,
+            &[],
+        )?,
+        &[account, destination, authority, token_program],
+        &[authority_signer_seeds],
+    );
+
+    result.map_err(|_| AuctionError::TokenCloseFailed.into())
+}
+
+/// Close an account owned by this program, sending its rent to the destination. The data is zeroed
+/// so the account can't be read again before the runtime purges it.
+pub fn close_program_account(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+    let lamports = account.lamports();
+    **destination.lamports.borrow_mut() = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(AuctionError::NumericalOverflowError)?;
+    **account.lamports.borrow_mut() = 0;
+
+    for byte in account.data.borrow_mut().iter_mut() {
+        *byte = 0;
+    }
+
+    Ok(())
+}
+
+///BidderPotCloseParams
+pub struct BidderPotCloseParams<'a: 'b, 'b> {
+    /// pot
+    pub pot: &'b BidderPot,
+    /// bidder_pot
+    pub bidder_pot: AccountInfo<'a>,
+    /// bidder_pot_token
+    pub bidder_pot_token: AccountInfo<'a>,
+    /// payer
+    pub payer: AccountInfo<'a>,
+    /// auction
+    pub auction: AccountInfo<'a>,
+    /// auction_signer_seeds
+    pub auction_signer_seeds: &'b [&'b [u8]],
+    /// token_program
+    pub token_program: AccountInfo<'a>,
+}
+
+/// Close an emptied bidder pot along with its SPL token account, returning the rent of both to the
+/// account that paid for the pot. A native SOL pot has no SPL account to close.
+pub fn close_bidder_pot(params: BidderPotCloseParams<'_, '_>) -> ProgramResult {
+    let BidderPotCloseParams {
+        pot,
+        bidder_pot,
+        bidder_pot_token,
+        payer,
+        auction,
+        auction_signer_seeds,
+        token_program,
+    } = params;
+
+    if pot.payer != *payer.key {
+        return Err(AuctionError::PayerMismatch.into());
+    }
+
+    if bidder_pot_token.key != bidder_pot.key {
+        spl_token_close(TokenCloseParams {
+            account: bidder_pot_token,
+            destination: payer.clone(),
+            authority: auction,
+            authority_signer_seeds: auction_signer_seeds,
+            token_program,
+        })?;
+    }
+
+    close_program_account(&bidder_pot, &payer)
+}
+
 /// TokenMintToParams
 pub struct TokenCreateAccount<'a> {
     /// payer
@@ -1,4 +1,13 @@
-use solana_program::{hash::Hash, program_pack::Pack, pubkey::Pubkey, system_instruction};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    program_pack::Pack,
+    program_stubs::{self, SyscallStubs},
+    pubkey::Pubkey,
+    system_instruction, system_program, sysvar,
+};
 use solana_program_test::*;
 use solana_sdk::{
     account::Account,
@@ -10,9 +19,13 @@ use spl_auction::{
     instruction,
     processor::{
         CancelBidArgs, ClaimBidArgs, CreateAuctionArgs, EndAuctionArgs, PlaceBidArgs, PriceFloor,
-        StartAuctionArgs, WinnerLimit,
+        RefundLosingBidsArgs, StartAuctionArgs, WinnerLimit,
     },
 };
+use std::{
+    str::FromStr,
+    sync::{Mutex, Once, RwLock},
+};
 
 pub async fn get_account(banks_client: &mut BanksClient, pubkey: &Pubkey) -> Account {
     banks_client
@@ -118,6 +131,44 @@ pub async fn mint_tokens(
     Ok(())
 }
 
+fn associated_token_program() -> Pubkey {
+    Pubkey::from_str("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL").unwrap()
+}
+
+/// Creates the associated token account of `wallet` for `mint`, returning its address.
+pub async fn create_associated_token_account(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Pubkey, TransportError> {
+    let (address, _) = Pubkey::find_program_address(
+        &[wallet.as_ref(), spl_token::id().as_ref(), mint.as_ref()],
+        &associated_token_program(),
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[Instruction {
+            program_id: associated_token_program(),
+            accounts: vec![
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new(address, false),
+                AccountMeta::new_readonly(*wallet, false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(sysvar::rent::id(), false),
+            ],
+            data: vec![],
+        }],
+        Some(&payer.pubkey()),
+        &[payer],
+        *recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await?;
+    Ok(address)
+}
+
 pub async fn get_token_balance(banks_client: &mut BanksClient, token: &Pubkey) -> u64 {
     let token_account = banks_client.get_account(*token).await.unwrap().unwrap();
     let account_info: spl_token::state::Account =
@@ -132,6 +183,38 @@ pub async fn get_token_supply(banks_client: &mut BanksClient, mint: &Pubkey) ->
     account_info.supply
 }
 
+/// Arguments for a plain capped English auction, for tests to adjust before creating it.
+pub fn create_auction_args(
+    authority: &Pubkey,
+    resource: &Pubkey,
+    mint: &Pubkey,
+    max_winners: usize,
+) -> CreateAuctionArgs {
+    CreateAuctionArgs {
+        authority: *authority,
+        end_auction_at: None,
+        end_auction_gap: None,
+        resource: *resource,
+        token_mint: *mint,
+        winners: WinnerLimit::Capped(max_winners),
+        price_floor: PriceFloor::None([0u8; 32]),
+        tick_size: None,
+        gap_tick_size_percentage: None,
+        dutch_auction: None,
+        reveal_period: None,
+        uniform_price: false,
+        extension_policy: None,
+        instant_sale_price: None,
+        paged_bids: false,
+        bid_history: false,
+        start_auction_at: None,
+        bidder_gate: None,
+        max_wins_per_wallet: None,
+        cancellable_with_bids: false,
+        exchange_rates: vec![],
+    }
+}
+
 pub async fn create_auction(
     banks_client: &mut BanksClient,
     program_id: &Pubkey,
@@ -140,20 +223,29 @@ pub async fn create_auction(
     resource: &Pubkey,
     mint_keypair: &Pubkey,
     max_winners: usize,
+) -> Result<(), TransportError> {
+    create_auction_with_args(
+        banks_client,
+        program_id,
+        payer,
+        recent_blockhash,
+        create_auction_args(&payer.pubkey(), resource, mint_keypair, max_winners),
+    )
+    .await
+}
+
+pub async fn create_auction_with_args(
+    banks_client: &mut BanksClient,
+    program_id: &Pubkey,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    args: CreateAuctionArgs,
 ) -> Result<(), TransportError> {
     let transaction = Transaction::new_signed_with_payer(
         &[instruction::create_auction_instruction(
             *program_id,
             payer.pubkey(),
-            CreateAuctionArgs {
-                authority: payer.pubkey(),
-                end_auction_at: None,
-                end_auction_gap: None,
-                resource: *resource,
-                token_mint: *mint_keypair,
-                winners: WinnerLimit::Capped(max_winners),
-                price_floor: PriceFloor::None([0u8; 32]),
-            },
+            args,
         )],
         Some(&payer.pubkey()),
         &[payer],
@@ -235,6 +327,9 @@ pub async fn place_bid(
             PlaceBidArgs {
                 amount,
                 resource: *resource,
+                commitment: None,
+                top_up: false,
+                allowlist_proof: vec![],
             },
         )],
         Some(&payer.pubkey()),
@@ -263,6 +358,7 @@ pub async fn cancel_bid(
             bidder.pubkey(),
             bidder_spl_account.pubkey(),
             *mint,
+            payer.pubkey(),
             CancelBidArgs {
                 resource: *resource,
             },
@@ -313,15 +409,18 @@ pub async fn claim_bid(
     seller: &Pubkey,
     resource: &Pubkey,
     mint: &Pubkey,
+    bidder_token: &Pubkey,
 ) -> Result<(), TransportError> {
     let transaction = Transaction::new_signed_with_payer(
         &[instruction::claim_bid_instruction(
             *program_id,
-            authority.pubkey(),
             *seller,
+            authority.pubkey(),
             bidder.pubkey(),
             bidder_spl_account.pubkey(),
             *mint,
+            *bidder_token,
+            payer.pubkey(),
             ClaimBidArgs {
                 resource: *resource,
             },
@@ -333,3 +432,126 @@ pub async fn claim_bid(
     banks_client.process_transaction(transaction).await?;
     Ok(())
 }
+
+/// Refunds a batch of losing bids, each given as its bidder, pot token account, destination, pot
+/// payer and wallet bids account, passing the bid history pages of an auction keeping a history.
+pub async fn refund_losing_bids(
+    banks_client: &mut BanksClient,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    payer: &Keypair,
+    history_pages: &[Pubkey],
+    bids: &[(Pubkey, Pubkey, Pubkey, Pubkey, Option<Pubkey>)],
+    resource: &Pubkey,
+) -> Result<(), TransportError> {
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::refund_losing_bids_instruction(
+            *program_id,
+            payer.pubkey(),
+            history_pages,
+            &[],
+            bids,
+            RefundLosingBidsArgs {
+                resource: *resource,
+            },
+        )],
+        Some(&payer.pubkey()),
+        &[payer],
+        *recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await?;
+    Ok(())
+}
+
+/// Sends an instruction with further accounts appended, such as the bid pages of a paged auction.
+pub async fn process_with_accounts(
+    banks_client: &mut BanksClient,
+    recent_blockhash: &Hash,
+    payer: &Keypair,
+    mut instruction: Instruction,
+    accounts: &[AccountMeta],
+    signers: &[&Keypair],
+) -> Result<(), TransportError> {
+    instruction.accounts.extend_from_slice(accounts);
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &signers.to_vec(),
+        *recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await?;
+    Ok(())
+}
+
+/// Lines logged by programs since capture_logs was first called, by every test in the process.
+static LOGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+/// Stubs ProgramTest installed, which CapturingStubs hands everything on to.
+static INNER_STUBS: RwLock<Option<Box<dyn SyscallStubs>>> = RwLock::new(None);
+static CAPTURE_LOGS: Once = Once::new();
+
+/// Syscall stubs that keep a copy of every logged line, handing everything on to the stubs that
+/// ProgramTest installed so programs run as before.
+struct CapturingStubs;
+
+impl CapturingStubs {
+    fn inner<T>(&self, call: impl FnOnce(&dyn SyscallStubs) -> T) -> T {
+        call(INNER_STUBS.read().unwrap().as_deref().unwrap())
+    }
+}
+
+impl SyscallStubs for CapturingStubs {
+    fn sol_log(&self, message: &str) {
+        LOGS.lock().unwrap().push(message.to_string());
+        self.inner(|stubs| stubs.sol_log(message));
+    }
+    fn sol_log_compute_units(&self) {
+        self.inner(|stubs| stubs.sol_log_compute_units());
+    }
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        self.inner(|stubs| stubs.sol_invoke_signed(instruction, account_infos, signers_seeds))
+    }
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner(|stubs| stubs.sol_get_clock_sysvar(var_addr))
+    }
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner(|stubs| stubs.sol_get_epoch_schedule_sysvar(var_addr))
+    }
+    fn sol_get_fees_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner(|stubs| stubs.sol_get_fees_sysvar(var_addr))
+    }
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner(|stubs| stubs.sol_get_rent_sysvar(var_addr))
+    }
+    unsafe fn sol_memcpy(&self, dst: *mut u8, src: *const u8, n: usize) {
+        self.inner(|stubs| stubs.sol_memcpy(dst, src, n));
+    }
+    unsafe fn sol_memmove(&self, dst: *mut u8, src: *const u8, n: usize) {
+        self.inner(|stubs| stubs.sol_memmove(dst, src, n));
+    }
+    unsafe fn sol_memcmp(&self, s1: *const u8, s2: *const u8, n: usize, result: *mut i32) {
+        self.inner(|stubs| stubs.sol_memcmp(s1, s2, n, result));
+    }
+    unsafe fn sol_memset(&self, s: *mut u8, c: u8, n: usize) {
+        self.inner(|stubs| stubs.sol_memset(s, c, n));
+    }
+}
+
+/// Starts keeping the lines programs log, which BanksClient doesn't hand back. This has to come
+/// after ProgramTest has started, as it installs its own stubs the first time around. Programs
+/// running in other tests meanwhile wait on the inner stubs until they're in place.
+pub fn capture_logs() {
+    CAPTURE_LOGS.call_once(|| {
+        let mut inner = INNER_STUBS.write().unwrap();
+        *inner = Some(program_stubs::set_syscall_stubs(Box::new(CapturingStubs)));
+    });
+}
+
+/// Every line captured so far, in the order it was logged.
+pub fn captured_logs() -> Vec<String> {
+    LOGS.lock().unwrap().clone()
+}
@@ -1,26 +1,33 @@
 #![allow(warnings)]
 
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::borsh::try_from_slice_unchecked;
+use solana_program::{borsh::try_from_slice_unchecked, hash::hashv};
 use solana_program_test::*;
 use solana_sdk::program_pack::Pack;
 use solana_sdk::{
     account::Account,
+    clock::UnixTimestamp,
     hash::Hash,
-    instruction::{AccountMeta, Instruction},
+    instruction::{AccountMeta, Instruction, InstructionError},
     pubkey::Pubkey,
     signature::{Keypair, Signer},
     system_instruction, system_program,
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
     transport::TransportError,
 };
 use spl_auction::{
+    errors::AuctionError,
+    events::{decoder::decode_event, AuctionEvent},
     instruction,
     processor::{
-        process_instruction, AuctionData, AuctionState, Bid, BidState, BidderPot, CancelBidArgs,
-        CreateAuctionArgs, PlaceBidArgs, PriceFloor, StartAuctionArgs, WinnerLimit,
+        process_instruction, AuctionData, AuctionState, Bid, BidCurrency, BidHistoryAction,
+        BidHistoryPage, BidPage, BidState, BidderGate, BidderMetadata, BidderPot,
+        CancelAuctionArgs, CancelBidArgs, ClaimBidArgs, CreateAuctionArgs, DutchAuctionArgs,
+        ExchangeRate, ExtensionPolicy, PlaceBidArgs, PriceCurve, PriceFloor,
+        ReclaimAuctionRentArgs, RefundEvictedBidArgs, RevealBidArgs, StartAuctionArgs, WalletBids,
+        WinnerLimit, BIDS_PER_PAGE, EXCHANGE_RATE_LEN, EXCHANGE_RATE_SCALE, MAX_PAGED_WINNERS,
     },
-    PREFIX,
+    EXTENDED, PREFIX,
 };
 use std::mem;
 
@@ -41,6 +48,25 @@ async fn setup_auction(
     Pubkey,
     Pubkey,
     Hash,
+) {
+    setup_auction_with(start, max_winners, |_| {}).await
+}
+
+/// Same as setup_auction, letting the test change the arguments the auction is created with.
+async fn setup_auction_with(
+    start: bool,
+    max_winners: usize,
+    configure: impl FnOnce(&mut CreateAuctionArgs),
+) -> (
+    Pubkey,
+    BanksClient,
+    Vec<(Keypair, Keypair, Pubkey)>,
+    Keypair,
+    Pubkey,
+    Pubkey,
+    Pubkey,
+    Pubkey,
+    Hash,
 ) {
     // Create a program to attach accounts to.
     let program_id = Pubkey::new_unique();
@@ -62,14 +88,20 @@ async fn setup_auction(
     let (auction_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
 
     // Run Create Auction instruction.
-    let err = helpers::create_auction(
+    let mut args = helpers::create_auction_args(
+        &payer.pubkey(),
+        &resource,
+        &mint_keypair.pubkey(),
+        max_winners,
+    );
+    configure(&mut args);
+    let end_auction_at = args.end_auction_at;
+    let err = helpers::create_auction_with_args(
         &mut banks_client,
         &program_id,
         &payer,
         &recent_blockhash,
-        &resource,
-        &mint_keypair.pubkey(),
-        max_winners,
+        args,
     )
     .await
     .unwrap();
@@ -147,7 +179,7 @@ async fn setup_auction(
     assert_eq!(auction.authority, payer.pubkey());
     assert_eq!(auction.last_bid, None);
     assert_eq!(auction.state as i32, AuctionState::create() as i32);
-    assert_eq!(auction.end_auction_at, None);
+    assert_eq!(auction.end_auction_at, end_auction_at);
 
     // Start Auction.
     if start {
@@ -175,6 +207,44 @@ async fn setup_auction(
     );
 }
 
+/// Rent held by the pot of a bidder and its token account, which closing the pot returns.
+async fn pot_rent(banks_client: &mut BanksClient, bidder: &(Keypair, Keypair, Pubkey)) -> u64 {
+    let pot_token = helpers::get_account(banks_client, &bidder.1.pubkey()).await;
+    let pot = helpers::get_account(banks_client, &bidder.2).await;
+    pot_token.lamports + pot.lamports
+}
+
+/// Checks that the pot of a bidder and its token account are gone, and that the payer holds the
+/// balance expected once the rent was returned, less the fee of the single signature transaction
+/// that closed them.
+async fn assert_pot_closed(
+    banks_client: &mut BanksClient,
+    bidder: &(Keypair, Keypair, Pubkey),
+    payer: &Keypair,
+    expected_balance: u64,
+) {
+    assert!(banks_client
+        .get_account(bidder.1.pubkey())
+        .await
+        .unwrap()
+        .is_none());
+    assert!(banks_client.get_account(bidder.2).await.unwrap().is_none());
+
+    let (fee_calculator, _, _) = banks_client.get_fees().await.unwrap();
+    assert_eq!(
+        banks_client.get_balance(payer.pubkey()).await.unwrap(),
+        expected_balance - fee_calculator.lamports_per_signature
+    );
+}
+
+/// Checks that a transaction failed on its first instruction with the given auction error.
+fn assert_auction_error(err: TransportError, error: AuctionError) {
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+    );
+}
+
 /// Used to drive tests in the functions below.
 #[derive(Debug)]
 enum Action {
@@ -449,6 +519,10 @@ async fn test_correct_runs() {
 
                     // For each winning bid, claim into auction.
                     for (index, bid) in results {
+                        let pot_rent = pot_rent(&mut banks_client, &bidders[index.0]).await;
+                        let payer_balance =
+                            banks_client.get_balance(payer.pubkey()).await.unwrap();
+
                         let err = helpers::claim_bid(
                             &mut banks_client,
                             &recent_blockhash,
@@ -460,18 +534,21 @@ async fn test_correct_runs() {
                             &collection.pubkey(),
                             &resource,
                             &mint,
+                            &bidders[index.0].0.pubkey(),
                         )
                         .await;
                         println!("{:?}", err);
                         err.expect("claim_bid");
 
-                        // Bid pot should be empty
-                        let balance = helpers::get_token_balance(
+                        // The pot and its token account are closed, with the rent going back
+                        // to the payer, less the fee for the claim.
+                        assert_pot_closed(
                             &mut banks_client,
-                            &bidders[index.0].1.pubkey(),
+                            &bidders[index.0],
+                            &payer,
+                            payer_balance + pot_rent,
                         )
                         .await;
-                        assert_eq!(balance, 0);
                     }
 
                     // Total claimed balance should match what we expect
@@ -694,3 +771,2960 @@ async fn test_incorrect_runs() {
     }
 }
 */
+
+/// A winner of a uniform price auction only pays the clearing price, the highest losing bid, and
+/// has whatever they bid over it refunded when the bid is claimed.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_uniform_price_claim_refunds_excess() {
+    let (
+        program_id,
+        mut banks_client,
+        bidders,
+        payer,
+        resource,
+        mint,
+        mint_authority,
+        auction_pubkey,
+        recent_blockhash,
+    ) = setup_auction_with(true, 1, |args| args.uniform_price = true).await;
+
+    // The bidder token accounts are owned by the payer, which transfers from them.
+    for (index, amount) in [(0, 1_000), (1, 3_000)].iter() {
+        helpers::place_bid(
+            &mut banks_client,
+            &recent_blockhash,
+            &program_id,
+            &payer,
+            &bidders[*index].0,
+            &bidders[*index].1,
+            &payer,
+            &resource,
+            &mint,
+            *amount,
+        )
+        .await
+        .expect("place_bid");
+    }
+
+    helpers::end_auction(
+        &mut banks_client,
+        &program_id,
+        &recent_blockhash,
+        &payer,
+        &resource,
+    )
+    .await
+    .expect("end_auction");
+
+    let collection = Keypair::new();
+    helpers::create_token_account(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &collection,
+        &mint,
+        &payer.pubkey(),
+    )
+    .await
+    .unwrap();
+
+    // Refunds only go to a token account of the bidder's own.
+    let winner = &bidders[1];
+    let refund = Keypair::new();
+    helpers::create_token_account(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &refund,
+        &mint,
+        &winner.0.pubkey(),
+    )
+    .await
+    .unwrap();
+
+    let pot_rent = pot_rent(&mut banks_client, winner).await;
+    let payer_balance = banks_client.get_balance(payer.pubkey()).await.unwrap();
+
+    helpers::claim_bid(
+        &mut banks_client,
+        &recent_blockhash,
+        &program_id,
+        &payer,
+        &payer,
+        &winner.0,
+        &winner.1,
+        &collection.pubkey(),
+        &resource,
+        &mint,
+        &refund.pubkey(),
+    )
+    .await
+    .expect("claim_bid");
+
+    assert_eq!(
+        helpers::get_token_balance(&mut banks_client, &collection.pubkey()).await,
+        1_000
+    );
+    assert_eq!(
+        helpers::get_token_balance(&mut banks_client, &refund.pubkey()).await,
+        2_000
+    );
+    assert_pot_closed(&mut banks_client, winner, &payer, payer_balance + pot_rent).await;
+}
+
+/// The bids of a paged auction spill onto a second page once the first is full, and bids move back
+/// across the boundary as higher ones are cancelled.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_paged_bids_cross_page_boundary() {
+    let (
+        program_id,
+        mut banks_client,
+        bidders,
+        payer,
+        resource,
+        mint,
+        mint_authority,
+        auction_pubkey,
+        recent_blockhash,
+    ) = setup_auction_with(true, MAX_PAGED_WINNERS, |args| args.paged_bids = true).await;
+
+    let pages: Vec<AccountMeta> = (0..2)
+        .map(|page| {
+            AccountMeta::new(
+                instruction::bid_page_pubkey(program_id, auction_pubkey, page),
+                false,
+            )
+        })
+        .collect();
+
+    // Fill the first page and put one more bid on the second, each from a new bidder paying out of
+    // the first bidder's token account, which the payer owns.
+    let source = bidders[0].0.pubkey();
+    let mut paged_bidders = vec![];
+    for amount in 1..=(BIDS_PER_PAGE as u64 + 1) {
+        let bidder = Keypair::new();
+        let pot_token = Keypair::new();
+        helpers::create_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &pot_token,
+            &mint,
+            &auction_pubkey,
+        )
+        .await
+        .unwrap();
+
+        helpers::process_with_accounts(
+            &mut banks_client,
+            &recent_blockhash,
+            &payer,
+            instruction::place_bid_instruction(
+                program_id,
+                bidder.pubkey(),
+                source,
+                pot_token.pubkey(),
+                mint,
+                payer.pubkey(),
+                payer.pubkey(),
+                PlaceBidArgs {
+                    amount,
+                    resource,
+                    commitment: None,
+                    top_up: false,
+                    allowlist_proof: vec![],
+                },
+            ),
+            &pages,
+            &[&payer, &bidder],
+        )
+        .await
+        .expect("place_bid");
+
+        paged_bidders.push((bidder, pot_token));
+    }
+
+    // Pages hold the highest bid first, leaving the lowest alone on the second page.
+    let first: BidPage = try_from_slice_unchecked(
+        &helpers::get_account(&mut banks_client, &pages[0].pubkey)
+            .await
+            .data,
+    )
+    .unwrap();
+    let second: BidPage = try_from_slice_unchecked(
+        &helpers::get_account(&mut banks_client, &pages[1].pubkey)
+            .await
+            .data,
+    )
+    .unwrap();
+    assert_eq!(first.bids.len(), BIDS_PER_PAGE);
+    assert_eq!(
+        first.bids[0],
+        Bid(
+            paged_bidders[BIDS_PER_PAGE].0.pubkey(),
+            BIDS_PER_PAGE as u64 + 1
+        )
+    );
+    assert_eq!(second.bids, vec![Bid(paged_bidders[0].0.pubkey(), 1)]);
+
+    // Cancelling the highest bid pulls the lowest back onto the first page.
+    let (top_bidder, top_pot_token) = &paged_bidders[BIDS_PER_PAGE];
+    helpers::process_with_accounts(
+        &mut banks_client,
+        &recent_blockhash,
+        &payer,
+        instruction::cancel_bid_instruction(
+            program_id,
+            top_bidder.pubkey(),
+            source,
+            top_pot_token.pubkey(),
+            mint,
+            payer.pubkey(),
+            CancelBidArgs { resource },
+        ),
+        &pages,
+        &[&payer, top_bidder],
+    )
+    .await
+    .expect("cancel_bid");
+
+    let first: BidPage = try_from_slice_unchecked(
+        &helpers::get_account(&mut banks_client, &pages[0].pubkey)
+            .await
+            .data,
+    )
+    .unwrap();
+    let second: BidPage = try_from_slice_unchecked(
+        &helpers::get_account(&mut banks_client, &pages[1].pubkey)
+            .await
+            .data,
+    )
+    .unwrap();
+    assert_eq!(first.bids.len(), BIDS_PER_PAGE);
+    assert_eq!(first.bids[0].1, BIDS_PER_PAGE as u64);
+    assert_eq!(
+        first.bids[BIDS_PER_PAGE - 1],
+        Bid(paged_bidders[0].0.pubkey(), 1)
+    );
+    assert!(second.bids.is_empty());
+
+    // The winners are still found through the pages once the auction ends.
+    helpers::end_auction(
+        &mut banks_client,
+        &program_id,
+        &recent_blockhash,
+        &payer,
+        &resource,
+    )
+    .await
+    .expect("end_auction");
+
+    let collection = Keypair::new();
+    helpers::create_token_account(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &collection,
+        &mint,
+        &payer.pubkey(),
+    )
+    .await
+    .unwrap();
+
+    let (winner, winner_pot_token) = &paged_bidders[BIDS_PER_PAGE - 1];
+    helpers::process_with_accounts(
+        &mut banks_client,
+        &recent_blockhash,
+        &payer,
+        instruction::claim_bid_instruction(
+            program_id,
+            collection.pubkey(),
+            payer.pubkey(),
+            winner.pubkey(),
+            winner_pot_token.pubkey(),
+            mint,
+            source,
+            payer.pubkey(),
+            ClaimBidArgs { resource },
+        ),
+        &pages,
+        &[&payer],
+    )
+    .await
+    .expect("claim_bid");
+
+    assert_eq!(
+        helpers::get_token_balance(&mut banks_client, &collection.pubkey()).await,
+        BIDS_PER_PAGE as u64
+    );
+}
+
+/// Once every place is taken a bid has to beat the lowest winner by the tick size, or by the gap
+/// tick size percentage of it inside the gap window before the end.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_raise_by_tick_or_gap_percentage() {
+    // The first auction ends well after its gap window, the second is already inside it.
+    for (end_auction_at, too_small, enough) in [(3_600, 119, 120), (30, 164, 165)].iter() {
+        let (
+            program_id,
+            mut banks_client,
+            bidders,
+            payer,
+            resource,
+            mint,
+            _,
+            auction_pubkey,
+            recent_blockhash,
+        ) = setup_auction_with(true, 1, |args| {
+            args.price_floor = PriceFloor::MinimumPrice([100, 0, 0, 0]);
+            args.tick_size = Some(10);
+            args.gap_tick_size_percentage = Some(50);
+            args.end_auction_at = Some(*end_auction_at);
+            args.end_auction_gap = Some(60);
+        })
+        .await;
+
+        for (bidder, amount, accepted) in
+            [(0, 110, true), (1, *too_small, false), (1, *enough, true)].iter()
+        {
+            let result = helpers::place_bid(
+                &mut banks_client,
+                &recent_blockhash,
+                &program_id,
+                &payer,
+                &bidders[*bidder].0,
+                &bidders[*bidder].1,
+                &payer,
+                &resource,
+                &mint,
+                *amount,
+            )
+            .await;
+            match result {
+                Ok(()) => assert!(accepted, "bid of {}", amount),
+                Err(err) => {
+                    assert!(!accepted, "bid of {}", amount);
+                    assert_auction_error(err, AuctionError::BidIncrementTooSmall);
+                }
+            }
+        }
+
+        let auction: AuctionData = try_from_slice_unchecked(
+            &helpers::get_account(&mut banks_client, &auction_pubkey)
+                .await
+                .data,
+        )
+        .unwrap();
+        assert_eq!(auction.bid_state.lowest_winning_bid(), Some(*enough));
+
+        // A percentage over the whole of the lowest bid is refused.
+        let mut args =
+            helpers::create_auction_args(&payer.pubkey(), &Pubkey::new_unique(), &mint, 1);
+        args.gap_tick_size_percentage = Some(101);
+        let err = helpers::create_auction_with_args(
+            &mut banks_client,
+            &program_id,
+            &payer,
+            &recent_blockhash,
+            args,
+        )
+        .await
+        .unwrap_err();
+        assert_auction_error(err, AuctionError::InvalidGapTickSizePercentage);
+    }
+}
+
+/// A bid landing within the window of an extension policy pushes the stored end back by the
+/// extension, until the total reaches the cap.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_late_bids_extend_end() {
+    let (
+        program_id,
+        mut banks_client,
+        bidders,
+        payer,
+        resource,
+        mint,
+        _,
+        auction_pubkey,
+        recent_blockhash,
+    ) = setup_auction_with(true, 1, |args| {
+        args.end_auction_at = Some(60);
+        args.extension_policy = Some(ExtensionPolicy {
+            window: 120,
+            extension: 30,
+            max_extension: Some(45),
+        });
+    })
+    .await;
+
+    let auction: AuctionData = try_from_slice_unchecked(
+        &helpers::get_account(&mut banks_client, &auction_pubkey)
+            .await
+            .data,
+    )
+    .unwrap();
+    let end = auction.ended_at.unwrap();
+
+    // The second bid only gets what is left under the cap, the third nothing.
+    for (bidder, extended) in [(0, 30), (1, 45), (2, 45)].iter() {
+        helpers::place_bid(
+            &mut banks_client,
+            &recent_blockhash,
+            &program_id,
+            &payer,
+            &bidders[*bidder].0,
+            &bidders[*bidder].1,
+            &payer,
+            &resource,
+            &mint,
+            (*bidder as u64 + 1) * 100,
+        )
+        .await
+        .expect("place_bid");
+        let auction: AuctionData = try_from_slice_unchecked(
+            &helpers::get_account(&mut banks_client, &auction_pubkey)
+                .await
+                .data,
+        )
+        .unwrap();
+        assert_eq!(auction.ended_at, Some(end + extended));
+    }
+
+    // A policy replaces the end gap, and needs an end time to push back.
+    let configs: [fn(&mut CreateAuctionArgs); 2] = [
+        |args| args.end_auction_gap = Some(60),
+        |args| args.end_auction_at = None,
+    ];
+    for configure in configs.iter() {
+        let mut args =
+            helpers::create_auction_args(&payer.pubkey(), &Pubkey::new_unique(), &mint, 1);
+        args.end_auction_at = Some(60);
+        args.extension_policy = Some(ExtensionPolicy {
+            window: 120,
+            extension: 30,
+            max_extension: None,
+        });
+        configure(&mut args);
+        let err = helpers::create_auction_with_args(
+            &mut banks_client,
+            &program_id,
+            &payer,
+            &recent_blockhash,
+            args,
+        )
+        .await
+        .unwrap_err();
+        assert_auction_error(err, AuctionError::InvalidExtensionPolicy);
+    }
+}
+
+/// A bid meeting the buy-now price ends the auction on the spot with its bidder as the sole winner,
+/// who can then no longer back out.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_instant_sale_ends_auction() {
+    let (
+        program_id,
+        mut banks_client,
+        bidders,
+        payer,
+        resource,
+        mint,
+        _,
+        auction_pubkey,
+        recent_blockhash,
+    ) = setup_auction_with(true, 1, |args| args.instant_sale_price = Some(1_000)).await;
+
+    for (bidder, amount) in [(0, 500), (1, 1_000)].iter() {
+        helpers::place_bid(
+            &mut banks_client,
+            &recent_blockhash,
+            &program_id,
+            &payer,
+            &bidders[*bidder].0,
+            &bidders[*bidder].1,
+            &payer,
+            &resource,
+            &mint,
+            *amount,
+        )
+        .await
+        .expect("place_bid");
+    }
+
+    let auction: AuctionData = try_from_slice_unchecked(
+        &helpers::get_account(&mut banks_client, &auction_pubkey)
+            .await
+            .data,
+    )
+    .unwrap();
+    assert_eq!(auction.is_winner(&bidders[1].0.pubkey()), Some(0));
+    assert_eq!(auction.is_winner(&bidders[0].0.pubkey()), None);
+    assert_eq!(auction.state as i32, AuctionState::Ended as i32);
+
+    let err = helpers::cancel_bid(
+        &mut banks_client,
+        &recent_blockhash,
+        &program_id,
+        &payer,
+        &bidders[1].0,
+        &bidders[1].1,
+        &resource,
+        &mint,
+    )
+    .await
+    .unwrap_err();
+    assert_auction_error(err, AuctionError::InvalidState);
+    helpers::cancel_bid(
+        &mut banks_client,
+        &recent_blockhash,
+        &program_id,
+        &payer,
+        &bidders[0].0,
+        &bidders[0].1,
+        &resource,
+        &mint,
+    )
+    .await
+    .expect("cancel_bid");
+
+    // Buy-now needs a single winner, and a price the floor doesn't already exceed.
+    let configs: [fn(&mut CreateAuctionArgs); 2] = [
+        |args| args.winners = WinnerLimit::Capped(2),
+        |args| args.price_floor = PriceFloor::MinimumPrice([2_000, 0, 0, 0]),
+    ];
+    for configure in configs.iter() {
+        let mut args =
+            helpers::create_auction_args(&payer.pubkey(), &Pubkey::new_unique(), &mint, 1);
+        args.instant_sale_price = Some(1_000);
+        configure(&mut args);
+        let err = helpers::create_auction_with_args(
+            &mut banks_client,
+            &program_id,
+            &payer,
+            &recent_blockhash,
+            args,
+        )
+        .await
+        .unwrap_err();
+        assert_auction_error(err, AuctionError::InvalidInstantSalePrice);
+    }
+}
+
+/// A top up raises a bidder's active bid in place, paying into the pot only the difference over
+/// what it already holds.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_top_up_pays_difference() {
+    let (
+        program_id,
+        mut banks_client,
+        bidders,
+        payer,
+        resource,
+        mint,
+        _,
+        auction_pubkey,
+        recent_blockhash,
+    ) = setup_auction(true, 1).await;
+
+    for (bidder, amount) in [(0, 100), (1, 120)].iter() {
+        helpers::place_bid(
+            &mut banks_client,
+            &recent_blockhash,
+            &program_id,
+            &payer,
+            &bidders[*bidder].0,
+            &bidders[*bidder].1,
+            &payer,
+            &resource,
+            &mint,
+            *amount,
+        )
+        .await
+        .expect("place_bid");
+    }
+
+    let top_up = |bidder: &(Keypair, Keypair, Pubkey), amount: u64| {
+        Transaction::new_signed_with_payer(
+            &[instruction::place_bid_instruction(
+                program_id,
+                bidder.0.pubkey(),
+                bidder.0.pubkey(),
+                bidder.1.pubkey(),
+                mint,
+                payer.pubkey(),
+                payer.pubkey(),
+                PlaceBidArgs {
+                    amount,
+                    resource,
+                    commitment: None,
+                    top_up: true,
+                    allowlist_proof: vec![],
+                },
+            )],
+            Some(&payer.pubkey()),
+            &[&payer, &bidder.0],
+            recent_blockhash,
+        )
+    };
+
+    // There has to be an active bid to raise, and the top up has to raise it.
+    let err = banks_client
+        .process_transaction(top_up(&bidders[2], 150))
+        .await
+        .unwrap_err();
+    assert_auction_error(err, AuctionError::BidNotActive);
+    let err = banks_client
+        .process_transaction(top_up(&bidders[0], 100))
+        .await
+        .unwrap_err();
+    assert_auction_error(err, AuctionError::BidTooSmall);
+
+    let balance = helpers::get_token_balance(&mut banks_client, &bidders[0].0.pubkey()).await;
+    banks_client
+        .process_transaction(top_up(&bidders[0], 150))
+        .await
+        .expect("top up");
+    assert_eq!(
+        helpers::get_token_balance(&mut banks_client, &bidders[0].0.pubkey()).await,
+        balance - 50
+    );
+    assert_eq!(
+        helpers::get_token_balance(&mut banks_client, &bidders[0].1.pubkey()).await,
+        150
+    );
+
+    // The raised bid takes the place of the old one rather than being added next to it.
+    let auction: AuctionData = try_from_slice_unchecked(
+        &helpers::get_account(&mut banks_client, &auction_pubkey)
+            .await
+            .data,
+    )
+    .unwrap();
+    assert_eq!(auction.is_winner(&bidders[0].0.pubkey()), Some(0));
+    assert_eq!(auction.bid_state.lowest_winning_bid(), Some(150));
+    match auction.bid_state {
+        BidState::EnglishAuction { bids, .. } => assert_eq!(
+            bids,
+            vec![
+                Bid(bidders[1].0.pubkey(), 120),
+                Bid(bidders[0].0.pubkey(), 150)
+            ]
+        ),
+        _ => panic!("not an English auction"),
+    }
+}
+
+/// An auction with a scheduled start can be started by anybody once the time has passed, either
+/// through StartAuction or by the first bid, with its end counted from the scheduled start. Before
+/// then it takes no bids, and only the authority can start it.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_scheduled_start() {
+    let (
+        program_id,
+        mut banks_client,
+        bidders,
+        payer,
+        resource,
+        mint,
+        _,
+        auction_pubkey,
+        recent_blockhash,
+    ) = setup_auction_with(false, 1, |args| args.start_auction_at = Some(i64::MAX / 2)).await;
+
+    let stranger = Keypair::new();
+    let start = |resource: Pubkey| {
+        let (auction_extended, _) = Pubkey::find_program_address(
+            &[
+                PREFIX.as_bytes(),
+                program_id.as_ref(),
+                resource.as_ref(),
+                EXTENDED.as_bytes(),
+            ],
+            &program_id,
+        );
+        (
+            instruction::start_auction_instruction(
+                program_id,
+                stranger.pubkey(),
+                StartAuctionArgs { resource },
+            ),
+            [AccountMeta::new_readonly(auction_extended, false)],
+        )
+    };
+
+    // Nothing happens ahead of the scheduled start.
+    let err = helpers::place_bid(
+        &mut banks_client,
+        &recent_blockhash,
+        &program_id,
+        &payer,
+        &bidders[0].0,
+        &bidders[0].1,
+        &payer,
+        &resource,
+        &mint,
+        100,
+    )
+    .await
+    .unwrap_err();
+    assert_auction_error(err, AuctionError::InvalidState);
+    let (start_instruction, start_accounts) = start(resource);
+    let err = helpers::process_with_accounts(
+        &mut banks_client,
+        &recent_blockhash,
+        &payer,
+        start_instruction,
+        &start_accounts,
+        &[&payer, &stranger],
+    )
+    .await
+    .unwrap_err();
+    assert_auction_error(err, AuctionError::InvalidAuthority);
+
+    // Two auctions whose start has passed, one started by the stranger and one by a bid.
+    let scheduled = [Pubkey::new_unique(), Pubkey::new_unique()];
+    for resource in scheduled.iter() {
+        let mut args = helpers::create_auction_args(&payer.pubkey(), resource, &mint, 1);
+        args.start_auction_at = Some(1_000);
+        args.end_auction_at = Some(4_000_000_000);
+        helpers::create_auction_with_args(
+            &mut banks_client,
+            &program_id,
+            &payer,
+            &recent_blockhash,
+            args,
+        )
+        .await
+        .expect("create_auction");
+    }
+
+    let (start_instruction, start_accounts) = start(scheduled[0]);
+    helpers::process_with_accounts(
+        &mut banks_client,
+        &recent_blockhash,
+        &payer,
+        start_instruction,
+        &start_accounts,
+        &[&payer, &stranger],
+    )
+    .await
+    .expect("start_auction");
+
+    let bid_auction = Pubkey::find_program_address(
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            scheduled[1].as_ref(),
+        ],
+        &program_id,
+    )
+    .0;
+    let bidder = Keypair::new();
+    let pot_token = Keypair::new();
+    helpers::create_token_account(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &pot_token,
+        &mint,
+        &bid_auction,
+    )
+    .await
+    .unwrap();
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[instruction::place_bid_instruction(
+                program_id,
+                bidder.pubkey(),
+                bidders[0].0.pubkey(),
+                pot_token.pubkey(),
+                mint,
+                payer.pubkey(),
+                payer.pubkey(),
+                PlaceBidArgs {
+                    amount: 100,
+                    resource: scheduled[1],
+                    commitment: None,
+                    top_up: false,
+                    allowlist_proof: vec![],
+                },
+            )],
+            Some(&payer.pubkey()),
+            &[&payer, &bidder],
+            recent_blockhash,
+        ))
+        .await
+        .expect("place_bid");
+
+    for resource in scheduled.iter() {
+        let (started_pubkey, _) = Pubkey::find_program_address(
+            &[PREFIX.as_bytes(), program_id.as_ref(), resource.as_ref()],
+            &program_id,
+        );
+        let auction: AuctionData = try_from_slice_unchecked(
+            &helpers::get_account(&mut banks_client, &started_pubkey)
+                .await
+                .data,
+        )
+        .unwrap();
+        assert_eq!(auction.ended_at, Some(4_000_001_000));
+        assert_eq!(auction.state as i32, AuctionState::Started as i32);
+    }
+
+    // The auction still waiting on its start is left as it was.
+    let auction: AuctionData = try_from_slice_unchecked(
+        &helpers::get_account(&mut banks_client, &auction_pubkey)
+            .await
+            .data,
+    )
+    .unwrap();
+    assert_eq!(auction.state as i32, AuctionState::Created as i32);
+}
+
+/// A gated auction only takes bids from the bidders its gate lets in, either with a Merkle proof
+/// of their key or with a token account of theirs holding the gate mint.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_bidder_gates() {
+    // A two leaf tree, each leaf being the proof of the other.
+    let allowed = [Keypair::new(), Keypair::new()];
+    let leaves = [
+        hashv(&[allowed[0].pubkey().as_ref()]).to_bytes(),
+        hashv(&[allowed[1].pubkey().as_ref()]).to_bytes(),
+    ];
+    let root = if leaves[0] <= leaves[1] {
+        hashv(&[&leaves[0], &leaves[1]]).to_bytes()
+    } else {
+        hashv(&[&leaves[1], &leaves[0]]).to_bytes()
+    };
+    let (program_id, mut banks_client, bidders, payer, resource, mint, _, _, recent_blockhash) =
+        setup_auction_with(true, 1, |args| {
+            args.bidder_gate = Some(BidderGate::MerkleRoot(root))
+        })
+        .await;
+    let stranger = Keypair::new();
+    let bid = |bidder: &Keypair, proof: Vec<[u8; 32]>| {
+        Transaction::new_signed_with_payer(
+            &[instruction::place_bid_instruction(
+                program_id,
+                bidder.pubkey(),
+                bidders[0].0.pubkey(),
+                bidders[0].1.pubkey(),
+                mint,
+                payer.pubkey(),
+                payer.pubkey(),
+                PlaceBidArgs {
+                    amount: 100,
+                    resource,
+                    commitment: None,
+                    top_up: false,
+                    allowlist_proof: proof,
+                },
+            )],
+            Some(&payer.pubkey()),
+            &[&payer, bidder],
+            recent_blockhash,
+        )
+    };
+
+    for (bidder, proof) in [
+        (&allowed[0], vec![]),
+        (&stranger, vec![leaves[1]]),
+        (&allowed[0], vec![leaves[0]]),
+    ]
+    .iter()
+    {
+        let err = banks_client
+            .process_transaction(bid(bidder, proof.clone()))
+            .await
+            .unwrap_err();
+        assert_auction_error(err, AuctionError::BidderNotAllowed);
+    }
+    banks_client
+        .process_transaction(bid(&allowed[0], vec![leaves[1]]))
+        .await
+        .expect("place_bid");
+
+    // Gated by the mint bids are paid in, which the payer holds through the token accounts of the
+    // bidders it funds.
+    let (program_id, mut banks_client, bidders, payer, resource, mint, _, _, recent_blockhash) =
+        setup_auction_with(true, 1, |args| {
+            args.bidder_gate = Some(BidderGate::HolderMint(args.token_mint))
+        })
+        .await;
+    let empty = Keypair::new();
+    helpers::create_token_account(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &empty,
+        &mint,
+        &stranger.pubkey(),
+    )
+    .await
+    .unwrap();
+    let bid = |bidder: &Keypair| {
+        instruction::place_bid_instruction(
+            program_id,
+            bidder.pubkey(),
+            bidders[0].0.pubkey(),
+            bidders[0].1.pubkey(),
+            mint,
+            payer.pubkey(),
+            payer.pubkey(),
+            PlaceBidArgs {
+                amount: 100,
+                resource,
+                commitment: None,
+                top_up: false,
+                allowlist_proof: vec![],
+            },
+        )
+    };
+
+    // Without a holder account, with someone else's, and with an empty one of their own.
+    for holder in [
+        vec![],
+        vec![AccountMeta::new_readonly(bidders[1].0.pubkey(), false)],
+        vec![AccountMeta::new_readonly(empty.pubkey(), false)],
+    ]
+    .iter()
+    {
+        let err = helpers::process_with_accounts(
+            &mut banks_client,
+            &recent_blockhash,
+            &payer,
+            bid(&stranger),
+            holder,
+            &[&payer, &stranger],
+        )
+        .await
+        .unwrap_err();
+        assert_auction_error(err, AuctionError::BidderNotAllowed);
+    }
+    helpers::process_with_accounts(
+        &mut banks_client,
+        &recent_blockhash,
+        &payer,
+        bid(&payer),
+        &[AccountMeta::new_readonly(bidders[1].0.pubkey(), false)],
+        &[&payer],
+    )
+    .await
+    .expect("place_bid");
+}
+
+/// Only the authority can cancel an auction, and once a bid has landed only if the auction was
+/// created to allow it. A cancelled auction takes no more bids and refunds every bid in full,
+/// including the one that was winning.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_cancel_auction_refunds_every_bid() {
+    for cancellable_with_bids in [false, true].iter() {
+        let (
+            program_id,
+            mut banks_client,
+            bidders,
+            payer,
+            resource,
+            mint,
+            _,
+            auction_pubkey,
+            recent_blockhash,
+        ) = setup_auction_with(true, 1, |args| {
+            args.cancellable_with_bids = *cancellable_with_bids
+        })
+        .await;
+        let cancel = |authority: &Keypair| {
+            Transaction::new_signed_with_payer(
+                &[instruction::cancel_auction_instruction(
+                    program_id,
+                    authority.pubkey(),
+                    CancelAuctionArgs { resource },
+                )],
+                Some(&payer.pubkey()),
+                &[&payer, authority],
+                recent_blockhash,
+            )
+        };
+
+        let balances = [
+            helpers::get_token_balance(&mut banks_client, &bidders[0].0.pubkey()).await,
+            helpers::get_token_balance(&mut banks_client, &bidders[1].0.pubkey()).await,
+        ];
+        for (bidder, amount) in [(0, 100), (1, 200)].iter() {
+            helpers::place_bid(
+                &mut banks_client,
+                &recent_blockhash,
+                &program_id,
+                &payer,
+                &bidders[*bidder].0,
+                &bidders[*bidder].1,
+                &payer,
+                &resource,
+                &mint,
+                *amount,
+            )
+            .await
+            .expect("place_bid");
+        }
+
+        let err = banks_client
+            .process_transaction(cancel(&bidders[0].0))
+            .await
+            .unwrap_err();
+        assert_auction_error(err, AuctionError::InvalidAuthority);
+
+        if !cancellable_with_bids {
+            let err = banks_client
+                .process_transaction(cancel(&payer))
+                .await
+                .unwrap_err();
+            assert_auction_error(err, AuctionError::AuctionHasBids);
+            continue;
+        }
+
+        banks_client
+            .process_transaction(cancel(&payer))
+            .await
+            .expect("cancel_auction");
+        let auction: AuctionData = try_from_slice_unchecked(
+            &helpers::get_account(&mut banks_client, &auction_pubkey)
+                .await
+                .data,
+        )
+        .unwrap();
+        assert_eq!(auction.state as i32, AuctionState::Cancelled as i32);
+
+        let err = helpers::place_bid(
+            &mut banks_client,
+            &recent_blockhash,
+            &program_id,
+            &payer,
+            &bidders[2].0,
+            &bidders[2].1,
+            &payer,
+            &resource,
+            &mint,
+            300,
+        )
+        .await
+        .unwrap_err();
+        assert_auction_error(err, AuctionError::InvalidState);
+
+        for (bidder, balance) in bidders.iter().zip(balances.iter()) {
+            helpers::cancel_bid(
+                &mut banks_client,
+                &recent_blockhash,
+                &program_id,
+                &payer,
+                &bidder.0,
+                &bidder.1,
+                &resource,
+                &mint,
+            )
+            .await
+            .expect("cancel_bid");
+            assert_eq!(
+                helpers::get_token_balance(&mut banks_client, &bidder.0.pubkey()).await,
+                *balance
+            );
+        }
+    }
+}
+
+/// With a tick size set, the first bid on an open place must already clear the floor by a tick.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_tick_size_applies_over_floor() {
+    let (
+        program_id,
+        mut banks_client,
+        bidders,
+        payer,
+        resource,
+        mint,
+        mint_authority,
+        auction_pubkey,
+        recent_blockhash,
+    ) = setup_auction_with(true, 2, |args| {
+        args.price_floor = PriceFloor::MinimumPrice([100, 0, 0, 0]);
+        args.tick_size = Some(10);
+    })
+    .await;
+
+    for (amount, accepted) in [(100, false), (109, false), (110, true)].iter() {
+        let result = helpers::place_bid(
+            &mut banks_client,
+            &recent_blockhash,
+            &program_id,
+            &payer,
+            &bidders[0].0,
+            &bidders[0].1,
+            &payer,
+            &resource,
+            &mint,
+            *amount,
+        )
+        .await;
+        assert_eq!(result.is_ok(), *accepted, "bid of {}", amount);
+    }
+
+    let auction: AuctionData = try_from_slice_unchecked(
+        &helpers::get_account(&mut banks_client, &auction_pubkey)
+            .await
+            .data,
+    )
+    .unwrap();
+    assert_eq!(auction.bid_state.amount(0), 110);
+}
+
+/// A tick size needs a price floor to count from, in base units of a real token mint.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_create_auction_rejects_invalid_floor() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("spl_auction", program_id, processor!(process_instruction));
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (mint_keypair, _) = helpers::create_mint(&mut banks_client, &payer, &recent_blockhash)
+        .await
+        .unwrap();
+
+    let configs: [fn(&mut CreateAuctionArgs); 3] = [
+        |args| args.tick_size = Some(10),
+        |args| {
+            args.price_floor = PriceFloor::MinimumPrice([100, 0, 0, 0]);
+            args.tick_size = Some(0);
+        },
+        |args| {
+            args.price_floor = PriceFloor::MinimumPrice([100, 0, 0, 0]);
+            args.token_mint = Pubkey::new_unique();
+        },
+    ];
+    for configure in configs.iter() {
+        let mut args = helpers::create_auction_args(
+            &payer.pubkey(),
+            &Pubkey::new_unique(),
+            &mint_keypair.pubkey(),
+            1,
+        );
+        configure(&mut args);
+        assert!(helpers::create_auction_with_args(
+            &mut banks_client,
+            &program_id,
+            &payer,
+            &recent_blockhash,
+            args,
+        )
+        .await
+        .is_err());
+    }
+}
+
+/// A started Dutch auction running from time 1_000 to 1_100, falling in price from 1_000 to 100.
+fn dutch_auction(curve: PriceCurve, step: i64) -> AuctionData {
+    AuctionData {
+        authority: Pubkey::new_unique(),
+        token_mint: Pubkey::new_unique(),
+        last_bid: None,
+        ended_at: Some(1_100),
+        end_auction_at: Some(100),
+        end_auction_gap: None,
+        price_floor: PriceFloor::None([0; 32]),
+        state: AuctionState::Started,
+        bid_state: BidState::new_dutch(1, 1_000, 100, curve, step),
+    }
+}
+
+/// The price of a Dutch auction holds at the start price until it starts and at the end price once
+/// it is over, falling along the curve in between.
+#[tokio::test]
+async fn test_dutch_price_decay() {
+    let linear = dutch_auction(PriceCurve::Linear, 0);
+    for (now, price) in [
+        (900, 1_000),
+        (1_000, 1_000),
+        (1_050, 550),
+        (1_099, 109),
+        (1_100, 100),
+        (2_000, 100),
+    ]
+    .iter()
+    {
+        assert_eq!(
+            linear.dutch_price(*now).unwrap(),
+            Some(*price),
+            "at {}",
+            now
+        );
+    }
+
+    let stepped = dutch_auction(PriceCurve::Stepped, 30);
+    for (now, price) in [
+        (1_029, 1_000),
+        (1_030, 730),
+        (1_059, 730),
+        (1_060, 460),
+        (1_099, 190),
+        (1_100, 100),
+    ]
+    .iter()
+    {
+        assert_eq!(
+            stepped.dutch_price(*now).unwrap(),
+            Some(*price),
+            "at {}",
+            now
+        );
+    }
+
+    let english = AuctionData {
+        bid_state: BidState::new_english(1),
+        ..linear
+    };
+    assert_eq!(english.dutch_price(1_050).unwrap(), None);
+}
+
+/// Each bid meeting the price of a Dutch auction buys a place outright, which the bidder can't take
+/// back, and selling the last place ends the auction ahead of its end time.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_dutch_auction_ends_on_sale() {
+    let (
+        program_id,
+        mut banks_client,
+        bidders,
+        payer,
+        resource,
+        mint,
+        mint_authority,
+        auction_pubkey,
+        recent_blockhash,
+    ) = setup_auction_with(true, 2, |args| {
+        args.end_auction_at = Some(3_600);
+        args.dutch_auction = Some(DutchAuctionArgs {
+            start_price: 1_000,
+            end_price: 100,
+            curve: PriceCurve::Linear,
+            step: 0,
+        });
+    })
+    .await;
+
+    // The clock stands still in the test bank, so the price is still the start price.
+    let err = helpers::place_bid(
+        &mut banks_client,
+        &recent_blockhash,
+        &program_id,
+        &payer,
+        &bidders[0].0,
+        &bidders[0].1,
+        &payer,
+        &resource,
+        &mint,
+        999,
+    )
+    .await
+    .unwrap_err();
+    assert_auction_error(err, AuctionError::BidTooSmall);
+
+    for (bidder, amount) in [(0, 1_000), (1, 1_200)].iter() {
+        helpers::place_bid(
+            &mut banks_client,
+            &recent_blockhash,
+            &program_id,
+            &payer,
+            &bidders[*bidder].0,
+            &bidders[*bidder].1,
+            &payer,
+            &resource,
+            &mint,
+            *amount,
+        )
+        .await
+        .expect("place_bid");
+
+        // Only the sale of the last place ends the auction.
+        let auction: AuctionData = try_from_slice_unchecked(
+            &helpers::get_account(&mut banks_client, &auction_pubkey)
+                .await
+                .data,
+        )
+        .unwrap();
+        let sold_out = *bidder == 1;
+        assert_eq!(auction.ended(0).unwrap(), sold_out);
+        assert_eq!(auction.state == AuctionState::Ended, sold_out);
+
+        if !sold_out {
+            let err = helpers::cancel_bid(
+                &mut banks_client,
+                &recent_blockhash,
+                &program_id,
+                &payer,
+                &bidders[0].0,
+                &bidders[0].1,
+                &resource,
+                &mint,
+            )
+            .await
+            .unwrap_err();
+            assert_auction_error(err, AuctionError::DutchAuctionBidFinal);
+        }
+    }
+
+    // Buyers win in the order they bought, each paying their own bid.
+    let auction: AuctionData = try_from_slice_unchecked(
+        &helpers::get_account(&mut banks_client, &auction_pubkey)
+            .await
+            .data,
+    )
+    .unwrap();
+    assert_eq!(auction.is_winner(&bidders[0].0.pubkey()), Some(0));
+    assert_eq!(auction.is_winner(&bidders[1].0.pubkey()), Some(1));
+    assert_eq!(auction.is_winner(&bidders[2].0.pubkey()), None);
+
+    let collection = Keypair::new();
+    helpers::create_token_account(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &collection,
+        &mint,
+        &payer.pubkey(),
+    )
+    .await
+    .unwrap();
+    for bidder in bidders[..2].iter() {
+        helpers::claim_bid(
+            &mut banks_client,
+            &recent_blockhash,
+            &program_id,
+            &payer,
+            &payer,
+            &bidder.0,
+            &bidder.1,
+            &collection.pubkey(),
+            &resource,
+            &mint,
+            &bidder.0.pubkey(),
+        )
+        .await
+        .expect("claim_bid");
+    }
+    assert_eq!(
+        helpers::get_token_balance(&mut banks_client, &collection.pubkey()).await,
+        2_200
+    );
+}
+
+/// Creates an exchange rate pricing `mint` in `price_mint`, for auctions to list.
+async fn create_exchange_rate(
+    banks_client: &mut BanksClient,
+    program_id: &Pubkey,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    exchange_rate: &Keypair,
+    args: ExchangeRate,
+) {
+    let rent = banks_client.get_rent().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &exchange_rate.pubkey(),
+                rent.minimum_balance(EXCHANGE_RATE_LEN),
+                EXCHANGE_RATE_LEN as u64,
+                program_id,
+            ),
+            instruction::update_exchange_rate_instruction(
+                *program_id,
+                exchange_rate.pubkey(),
+                args,
+            ),
+        ],
+        Some(&payer.pubkey()),
+        &[payer, exchange_rate],
+        *recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+}
+
+/// Bids in another accepted currency rank by their value in the token mint, while the bidder's
+/// currency record keeps what was actually escrowed. A currency the auction does not list is
+/// turned away.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_bids_in_accepted_currencies() {
+    let accepted_rate = Keypair::new();
+    let accepted_rate_pubkey = accepted_rate.pubkey();
+    let (
+        program_id,
+        mut banks_client,
+        bidders,
+        payer,
+        resource,
+        mint,
+        mint_authority,
+        auction_pubkey,
+        recent_blockhash,
+    ) = setup_auction_with(true, 2, |args| {
+        args.exchange_rates = vec![accepted_rate_pubkey]
+    })
+    .await;
+
+    // One unit of the other currency is worth two of the token mint.
+    let (foreign_mint, foreign_mint_authority) =
+        helpers::create_mint(&mut banks_client, &payer, &recent_blockhash)
+            .await
+            .unwrap();
+    let rate = ExchangeRate {
+        price_per_token: 2 * EXCHANGE_RATE_SCALE as u64,
+        price_mint: mint,
+        mint: foreign_mint.pubkey(),
+    };
+    create_exchange_rate(
+        &mut banks_client,
+        &program_id,
+        &payer,
+        &recent_blockhash,
+        &accepted_rate,
+        rate.clone(),
+    )
+    .await;
+    let unlisted_rate = Keypair::new();
+    create_exchange_rate(
+        &mut banks_client,
+        &program_id,
+        &payer,
+        &recent_blockhash,
+        &unlisted_rate,
+        rate,
+    )
+    .await;
+
+    // As elsewhere, each bidder's keypair doubles as their token account, which the payer owns.
+    let mut foreign_bidders = vec![];
+    for _ in 0..2 {
+        let bidder = Keypair::new();
+        let pot_token = Keypair::new();
+        helpers::create_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &bidder,
+            &foreign_mint.pubkey(),
+            &payer.pubkey(),
+        )
+        .await
+        .unwrap();
+        helpers::create_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &pot_token,
+            &foreign_mint.pubkey(),
+            &auction_pubkey,
+        )
+        .await
+        .unwrap();
+        helpers::mint_tokens(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &foreign_mint.pubkey(),
+            &bidder.pubkey(),
+            &foreign_mint_authority,
+            10_000,
+        )
+        .await
+        .unwrap();
+        foreign_bidders.push((bidder, pot_token));
+    }
+
+    let bid = |bidder: &Keypair, pot_token: &Keypair, mint: Pubkey, amount: u64| {
+        instruction::place_bid_instruction(
+            program_id,
+            bidder.pubkey(),
+            bidder.pubkey(),
+            pot_token.pubkey(),
+            mint,
+            payer.pubkey(),
+            payer.pubkey(),
+            PlaceBidArgs {
+                amount,
+                resource,
+                commitment: None,
+                top_up: false,
+                allowlist_proof: vec![],
+            },
+        )
+    };
+    let currency_record = |bidder: &Keypair| {
+        AccountMeta::new(
+            instruction::bid_currency_pubkey(program_id, auction_pubkey, bidder.pubkey()),
+            false,
+        )
+    };
+
+    // A bid in the token mint, followed by a higher valued one in the other currency.
+    let (native_bidder, native_pot_token, _) = &bidders[0];
+    helpers::process_with_accounts(
+        &mut banks_client,
+        &recent_blockhash,
+        &payer,
+        bid(native_bidder, native_pot_token, mint, 1_000),
+        &[currency_record(native_bidder)],
+        &[&payer, native_bidder],
+    )
+    .await
+    .expect("place_bid");
+
+    let (foreign_bidder, foreign_pot_token) = &foreign_bidders[0];
+    helpers::process_with_accounts(
+        &mut banks_client,
+        &recent_blockhash,
+        &payer,
+        bid(
+            foreign_bidder,
+            foreign_pot_token,
+            foreign_mint.pubkey(),
+            600,
+        ),
+        &[
+            currency_record(foreign_bidder),
+            AccountMeta::new_readonly(accepted_rate.pubkey(), false),
+        ],
+        &[&payer, foreign_bidder],
+    )
+    .await
+    .expect("place_bid");
+
+    let auction: AuctionData = try_from_slice_unchecked(
+        &helpers::get_account(&mut banks_client, &auction_pubkey)
+            .await
+            .data,
+    )
+    .unwrap();
+    assert_eq!(auction.is_winner(&foreign_bidder.pubkey()), Some(0));
+    assert_eq!(auction.bid_state.amount(0), 1_200);
+    assert_eq!(auction.is_winner(&native_bidder.pubkey()), Some(1));
+    assert_eq!(auction.bid_state.amount(1), 1_000);
+
+    for (bidder, mint, amount) in [
+        (native_bidder, mint, 1_000),
+        (foreign_bidder, foreign_mint.pubkey(), 600),
+    ]
+    .iter()
+    {
+        let record: BidCurrency = try_from_slice_unchecked(
+            &helpers::get_account(&mut banks_client, &currency_record(bidder).pubkey)
+                .await
+                .data,
+        )
+        .unwrap();
+        assert_eq!(record.mint, *mint);
+        assert_eq!(record.amount, *amount);
+    }
+    assert_eq!(
+        helpers::get_token_balance(&mut banks_client, &foreign_pot_token.pubkey()).await,
+        600
+    );
+
+    // The same currency through a rate the auction does not list.
+    let (unlisted_bidder, unlisted_pot_token) = &foreign_bidders[1];
+    let err = helpers::process_with_accounts(
+        &mut banks_client,
+        &recent_blockhash,
+        &payer,
+        bid(
+            unlisted_bidder,
+            unlisted_pot_token,
+            foreign_mint.pubkey(),
+            1_000,
+        ),
+        &[
+            currency_record(unlisted_bidder),
+            AccountMeta::new_readonly(unlisted_rate.pubkey(), false),
+        ],
+        &[&payer, unlisted_bidder],
+    )
+    .await
+    .unwrap_err();
+    assert_auction_error(err, AuctionError::CurrencyNotAccepted);
+}
+
+/// Once an auction has ended anybody can refund its losing bids into the associated token
+/// accounts of their bidders, closing the pots and leaving the bids cancelled. A winner in the
+/// batch turns the whole batch away, while pots that were already closed are skipped.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_refund_losing_bids() {
+    let (
+        program_id,
+        mut banks_client,
+        bidders,
+        payer,
+        resource,
+        mint,
+        mint_authority,
+        auction_pubkey,
+        recent_blockhash,
+    ) = setup_auction(true, 1).await;
+
+    for (bidder, amount) in [(0, 1_000), (1, 2_000), (2, 3_000)].iter() {
+        helpers::place_bid(
+            &mut banks_client,
+            &recent_blockhash,
+            &program_id,
+            &payer,
+            &bidders[*bidder].0,
+            &bidders[*bidder].1,
+            &payer,
+            &resource,
+            &mint,
+            *amount,
+        )
+        .await
+        .expect("place_bid");
+    }
+    helpers::end_auction(
+        &mut banks_client,
+        &program_id,
+        &recent_blockhash,
+        &payer,
+        &resource,
+    )
+    .await
+    .expect("end_auction");
+
+    let mut refunds = vec![];
+    for (bidder, pot_token, _) in bidders[..3].iter() {
+        let destination = helpers::create_associated_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &bidder.pubkey(),
+            &mint,
+        )
+        .await
+        .expect("create_associated_token_account");
+        refunds.push((
+            bidder.pubkey(),
+            pot_token.pubkey(),
+            destination,
+            payer.pubkey(),
+            None,
+        ));
+    }
+
+    // The winner is paid out through ClaimBid, so a batch holding it refunds nobody.
+    let err = helpers::refund_losing_bids(
+        &mut banks_client,
+        &recent_blockhash,
+        &program_id,
+        &payer,
+        &[],
+        &refunds[1..],
+        &resource,
+    )
+    .await
+    .unwrap_err();
+    assert_auction_error(err, AuctionError::WinningBidCannotBeRefunded);
+
+    let balance = banks_client.get_balance(payer.pubkey()).await.unwrap()
+        + pot_rent(&mut banks_client, &bidders[0]).await
+        + pot_rent(&mut banks_client, &bidders[1]).await;
+    helpers::refund_losing_bids(
+        &mut banks_client,
+        &recent_blockhash,
+        &program_id,
+        &payer,
+        &[],
+        &refunds[..2],
+        &resource,
+    )
+    .await
+    .expect("refund_losing_bids");
+
+    for (index, amount) in [(0, 1_000), (1, 2_000)].iter() {
+        assert_pot_closed(&mut banks_client, &bidders[*index], &payer, balance).await;
+        assert_eq!(
+            helpers::get_token_balance(&mut banks_client, &refunds[*index].2).await,
+            *amount
+        );
+
+        let (bidder_meta, _) = Pubkey::find_program_address(
+            &[
+                PREFIX.as_bytes(),
+                program_id.as_ref(),
+                auction_pubkey.as_ref(),
+                refunds[*index].0.as_ref(),
+                "metadata".as_bytes(),
+            ],
+            &program_id,
+        );
+        let metadata: BidderMetadata = try_from_slice_unchecked(
+            &helpers::get_account(&mut banks_client, &bidder_meta)
+                .await
+                .data,
+        )
+        .unwrap();
+        assert!(metadata.cancelled);
+    }
+
+    // Retrying part of the batch finds the pot closed and leaves it be.
+    helpers::refund_losing_bids(
+        &mut banks_client,
+        &recent_blockhash,
+        &program_id,
+        &payer,
+        &[],
+        &refunds[..1],
+        &resource,
+    )
+    .await
+    .expect("refund_losing_bids");
+    assert_eq!(
+        helpers::get_token_balance(&mut banks_client, &refunds[0].2).await,
+        1_000
+    );
+}
+
+/// Bidders that the wallet of a wallet bids account funds with a bid still live.
+async fn wallet_bidders(banks_client: &mut BanksClient, wallet_bids: &Pubkey) -> Vec<Pubkey> {
+    let wallet_bids: WalletBids =
+        try_from_slice_unchecked(&helpers::get_account(banks_client, wallet_bids).await.data)
+            .unwrap();
+    wallet_bids.bidders
+}
+
+/// A wallet funding more bidders than the auction allows it places is turned away, until a bid it
+/// funded is cancelled or refunded and gives its place up. Every bidder here is funded by the
+/// payer, which owns their token accounts, and is let in through the gate by it.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_max_wins_per_wallet() {
+    let (
+        program_id,
+        mut banks_client,
+        bidders,
+        payer,
+        resource,
+        mint,
+        mint_authority,
+        auction_pubkey,
+        recent_blockhash,
+    ) = setup_auction_with(true, 1, |args| {
+        // A single leaf tree letting in only the payer, the authority of the auction.
+        args.bidder_gate = Some(BidderGate::MerkleRoot(
+            hashv(&[args.authority.as_ref()]).to_bytes(),
+        ));
+        args.max_wins_per_wallet = Some(2);
+    })
+    .await;
+
+    let wallet_bids = AccountMeta::new(
+        instruction::wallet_bids_pubkey(program_id, auction_pubkey, payer.pubkey()),
+        false,
+    );
+    let bid = |bidder: &(Keypair, Keypair, Pubkey), amount: u64| {
+        instruction::place_bid_instruction(
+            program_id,
+            bidder.0.pubkey(),
+            bidder.0.pubkey(),
+            bidder.1.pubkey(),
+            mint,
+            payer.pubkey(),
+            payer.pubkey(),
+            PlaceBidArgs {
+                amount,
+                resource,
+                commitment: None,
+                top_up: false,
+                allowlist_proof: vec![],
+            },
+        )
+    };
+
+    for (bidder, amount) in [(0, 1_000), (1, 2_000)].iter() {
+        helpers::process_with_accounts(
+            &mut banks_client,
+            &recent_blockhash,
+            &payer,
+            bid(&bidders[*bidder], *amount),
+            &[wallet_bids.clone()],
+            &[&payer, &bidders[*bidder].0],
+        )
+        .await
+        .expect("place_bid");
+    }
+
+    // Both places of the wallet are taken.
+    let err = helpers::process_with_accounts(
+        &mut banks_client,
+        &recent_blockhash,
+        &payer,
+        bid(&bidders[2], 2_500),
+        &[wallet_bids.clone()],
+        &[&payer, &bidders[2].0],
+    )
+    .await
+    .unwrap_err();
+    assert_auction_error(err, AuctionError::WalletWinLimitReached);
+
+    // Cancelling a bid gives its place to the next bidder the wallet funds.
+    helpers::process_with_accounts(
+        &mut banks_client,
+        &recent_blockhash,
+        &payer,
+        instruction::cancel_bid_instruction(
+            program_id,
+            bidders[0].0.pubkey(),
+            bidders[0].0.pubkey(),
+            bidders[0].1.pubkey(),
+            mint,
+            payer.pubkey(),
+            CancelBidArgs { resource },
+        ),
+        &[wallet_bids.clone()],
+        &[&payer, &bidders[0].0],
+    )
+    .await
+    .expect("cancel_bid");
+    assert_eq!(
+        wallet_bidders(&mut banks_client, &wallet_bids.pubkey).await,
+        vec![bidders[1].0.pubkey()]
+    );
+
+    helpers::process_with_accounts(
+        &mut banks_client,
+        &recent_blockhash,
+        &payer,
+        bid(&bidders[2], 3_000),
+        &[wallet_bids.clone()],
+        &[&payer, &bidders[2].0],
+    )
+    .await
+    .expect("place_bid");
+    assert_eq!(
+        wallet_bidders(&mut banks_client, &wallet_bids.pubkey).await,
+        vec![bidders[1].0.pubkey(), bidders[2].0.pubkey()]
+    );
+
+    // Refunding the losing bid once the auction has ended frees its place as well.
+    helpers::end_auction(
+        &mut banks_client,
+        &program_id,
+        &recent_blockhash,
+        &payer,
+        &resource,
+    )
+    .await
+    .expect("end_auction");
+    let destination = helpers::create_associated_token_account(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &bidders[1].0.pubkey(),
+        &mint,
+    )
+    .await
+    .expect("create_associated_token_account");
+    helpers::refund_losing_bids(
+        &mut banks_client,
+        &recent_blockhash,
+        &program_id,
+        &payer,
+        &[],
+        &[(
+            bidders[1].0.pubkey(),
+            bidders[1].1.pubkey(),
+            destination,
+            payer.pubkey(),
+            Some(wallet_bids.pubkey),
+        )],
+        &resource,
+    )
+    .await
+    .expect("refund_losing_bids");
+    assert_eq!(
+        wallet_bidders(&mut banks_client, &wallet_bids.pubkey).await,
+        vec![bidders[2].0.pubkey()]
+    );
+}
+
+/// Starts the test over with the given accounts carried across, leaving out any that don't exist,
+/// and the end of the auction moved back by `elapsed`. The clock of a test stays put, so this
+/// stands in for that much time passing.
+async fn restart_later(
+    program_id: Pubkey,
+    banks_client: &mut BanksClient,
+    auction_pubkey: &Pubkey,
+    carried: &[Pubkey],
+    elapsed: UnixTimestamp,
+) -> (BanksClient, Hash) {
+    let mut program_test =
+        ProgramTest::new("spl_auction", program_id, processor!(process_instruction));
+    for pubkey in carried {
+        let mut account = match banks_client.get_account(*pubkey).await.unwrap() {
+            Some(account) => account,
+            None => continue,
+        };
+        if pubkey == auction_pubkey {
+            let mut auction: AuctionData = try_from_slice_unchecked(&account.data).unwrap();
+            auction.ended_at = auction.ended_at.map(|end| end - elapsed);
+            auction.serialize(&mut account.data.as_mut_slice()).unwrap();
+        }
+        program_test.add_account(*pubkey, account);
+    }
+    let (banks_client, _, recent_blockhash) = program_test.start().await;
+    (banks_client, recent_blockhash)
+}
+
+/// A sealed bid auction takes commitments until bidding closes, then has them revealed during its
+/// reveal window, returning whatever was escrowed over the bid. A reveal pushing a bid out of the
+/// winners list records the eviction in the bid history. Bids never revealed are refunded once
+/// the window closes and the auction can be ended.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_sealed_bids() {
+    let reveal_period = 86_400;
+    let (
+        program_id,
+        mut banks_client,
+        bidders,
+        payer,
+        resource,
+        mint,
+        _,
+        auction_pubkey,
+        recent_blockhash,
+    ) = setup_auction_with(true, 1, |args| {
+        args.end_auction_at = Some(60);
+        args.reveal_period = Some(reveal_period);
+        args.bid_history = true;
+    })
+    .await;
+
+    let history_pages = [
+        instruction::bid_history_page_pubkey(program_id, auction_pubkey, 0),
+        instruction::bid_history_page_pubkey(program_id, auction_pubkey, 1),
+    ];
+    let history_accounts = history_pages
+        .iter()
+        .map(|page| AccountMeta::new(*page, false))
+        .collect::<Vec<_>>();
+
+    // One bid more than the bid state holds is committed, each escrowing 50 more than it bids and
+    // paying out of the first bidder's token account, which the payer owns. The last bidder never
+    // reveals theirs.
+    let source = bidders[0].0.pubkey();
+    let mut sealed_bidders = vec![];
+    for n in 1..=10u64 {
+        let bidder = Keypair::new();
+        let pot_token = Keypair::new();
+        helpers::create_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &pot_token,
+            &mint,
+            &auction_pubkey,
+        )
+        .await
+        .unwrap();
+        helpers::process_with_accounts(
+            &mut banks_client,
+            &recent_blockhash,
+            &payer,
+            instruction::place_bid_instruction(
+                program_id,
+                bidder.pubkey(),
+                source,
+                pot_token.pubkey(),
+                mint,
+                payer.pubkey(),
+                payer.pubkey(),
+                PlaceBidArgs {
+                    amount: n * 100 + 50,
+                    resource,
+                    commitment: Some(hashv(&[&(n * 100).to_be_bytes(), &n.to_be_bytes()])),
+                    top_up: false,
+                    allowlist_proof: vec![],
+                },
+            ),
+            &history_accounts,
+            &[&payer, &bidder],
+        )
+        .await
+        .expect("place_bid");
+        sealed_bidders.push((bidder, pot_token, n));
+    }
+
+    let reveal = |bidder: &Keypair, pot_token: &Keypair, amount: u64, salt: u64| {
+        instruction::reveal_bid_instruction(
+            program_id,
+            bidder.pubkey(),
+            source,
+            pot_token.pubkey(),
+            payer.pubkey(),
+            RevealBidArgs {
+                resource,
+                amount,
+                salt,
+            },
+        )
+    };
+    let cancel = |bidder: &Keypair, pot_token: &Keypair| {
+        instruction::cancel_bid_instruction(
+            program_id,
+            bidder.pubkey(),
+            source,
+            pot_token.pubkey(),
+            mint,
+            payer.pubkey(),
+            CancelBidArgs { resource },
+        )
+    };
+
+    // Nothing can be revealed, nor the auction ended, while bidding is open.
+    let (bidder, pot_token, n) = &sealed_bidders[0];
+    let err = helpers::process_with_accounts(
+        &mut banks_client,
+        &recent_blockhash,
+        &payer,
+        reveal(bidder, pot_token, n * 100, *n),
+        &history_accounts,
+        &[&payer, bidder],
+    )
+    .await
+    .unwrap_err();
+    assert_auction_error(err, AuctionError::InvalidState);
+    let err = helpers::end_auction(
+        &mut banks_client,
+        &program_id,
+        &recent_blockhash,
+        &payer,
+        &resource,
+    )
+    .await
+    .unwrap_err();
+    assert_auction_error(err, AuctionError::AuctionTransitionInvalid);
+
+    // Everything the rest of the test touches is carried across each move of the clock.
+    let pda = |bidder: &Keypair, suffix: &str| {
+        Pubkey::find_program_address(
+            &[
+                PREFIX.as_bytes(),
+                program_id.as_ref(),
+                auction_pubkey.as_ref(),
+                bidder.pubkey().as_ref(),
+                suffix.as_bytes(),
+            ],
+            &program_id,
+        )
+        .0
+    };
+    let (auction_extended, _) = Pubkey::find_program_address(
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            resource.as_ref(),
+            EXTENDED.as_bytes(),
+        ],
+        &program_id,
+    );
+    let mut carried = vec![
+        payer.pubkey(),
+        mint,
+        source,
+        auction_pubkey,
+        auction_extended,
+    ];
+    carried.extend_from_slice(&history_pages);
+    for (bidder, pot_token, _) in sealed_bidders.iter() {
+        carried.extend_from_slice(&[
+            pot_token.pubkey(),
+            pda(bidder, ""),
+            pda(bidder, "metadata"),
+            pda(bidder, "commitment"),
+        ]);
+    }
+
+    // Move into the reveal window.
+    let (mut banks_client, recent_blockhash) =
+        restart_later(program_id, &mut banks_client, &auction_pubkey, &carried, 61).await;
+
+    let err = helpers::process_with_accounts(
+        &mut banks_client,
+        &recent_blockhash,
+        &payer,
+        instruction::place_bid_instruction(
+            program_id,
+            bidder.pubkey(),
+            source,
+            pot_token.pubkey(),
+            mint,
+            payer.pubkey(),
+            payer.pubkey(),
+            PlaceBidArgs {
+                amount: 1000,
+                resource,
+                commitment: Some(hashv(&[&1000u64.to_be_bytes(), &0u64.to_be_bytes()])),
+                top_up: false,
+                allowlist_proof: vec![],
+            },
+        ),
+        &history_accounts,
+        &[&payer, bidder],
+    )
+    .await
+    .unwrap_err();
+    assert_auction_error(err, AuctionError::InvalidState);
+
+    let err = helpers::process_with_accounts(
+        &mut banks_client,
+        &recent_blockhash,
+        &payer,
+        reveal(bidder, pot_token, n * 100, n + 1),
+        &history_accounts,
+        &[&payer, bidder],
+    )
+    .await
+    .unwrap_err();
+    assert_auction_error(err, AuctionError::InvalidReveal);
+
+    // Each reveal returns the excess escrow, the last pushing the lowest bid out of the list.
+    for (bidder, pot_token, n) in sealed_bidders[..9].iter() {
+        let balance = helpers::get_token_balance(&mut banks_client, &source).await;
+        helpers::process_with_accounts(
+            &mut banks_client,
+            &recent_blockhash,
+            &payer,
+            reveal(bidder, pot_token, n * 100, *n),
+            &history_accounts,
+            &[&payer, bidder],
+        )
+        .await
+        .expect("reveal_bid");
+        assert_eq!(
+            helpers::get_token_balance(&mut banks_client, &source).await,
+            balance + 50
+        );
+        assert_eq!(
+            helpers::get_token_balance(&mut banks_client, &pot_token.pubkey()).await,
+            n * 100
+        );
+    }
+
+    let page: BidHistoryPage = try_from_slice_unchecked(
+        &helpers::get_account(&mut banks_client, &history_pages[0])
+            .await
+            .data,
+    )
+    .unwrap();
+    let evictions = page
+        .entries
+        .iter()
+        .filter(|entry| entry.action == BidHistoryAction::Evicted)
+        .map(|entry| (entry.bidder, entry.amount))
+        .collect::<Vec<_>>();
+    assert_eq!(evictions, vec![(sealed_bidders[0].0.pubkey(), 100)]);
+
+    // The window has to close before the auction ends, and before unrevealed bids are refunded.
+    let err = helpers::end_auction(
+        &mut banks_client,
+        &program_id,
+        &recent_blockhash,
+        &payer,
+        &resource,
+    )
+    .await
+    .unwrap_err();
+    assert_auction_error(err, AuctionError::AuctionTransitionInvalid);
+    let (silent, silent_pot_token, _) = &sealed_bidders[9];
+    let err = helpers::process_with_accounts(
+        &mut banks_client,
+        &recent_blockhash,
+        &payer,
+        cancel(silent, silent_pot_token),
+        &history_accounts,
+        &[&payer, silent],
+    )
+    .await
+    .unwrap_err();
+    assert_auction_error(err, AuctionError::InvalidState);
+
+    let (mut banks_client, recent_blockhash) = restart_later(
+        program_id,
+        &mut banks_client,
+        &auction_pubkey,
+        &carried,
+        reveal_period,
+    )
+    .await;
+    let ended_at = helpers::get_account(&mut banks_client, &auction_pubkey).await;
+    let ended_at = try_from_slice_unchecked::<AuctionData>(&ended_at.data)
+        .unwrap()
+        .ended_at;
+
+    helpers::end_auction(
+        &mut banks_client,
+        &program_id,
+        &recent_blockhash,
+        &payer,
+        &resource,
+    )
+    .await
+    .expect("end_auction");
+    let auction: AuctionData = try_from_slice_unchecked(
+        &helpers::get_account(&mut banks_client, &auction_pubkey)
+            .await
+            .data,
+    )
+    .unwrap();
+    assert!(auction.is_winner(&sealed_bidders[8].0.pubkey()).is_some());
+    assert_eq!(auction.ended_at, ended_at);
+    assert_eq!(auction.state as i32, AuctionState::Ended as i32);
+
+    // The bid never revealed gets its whole escrow back.
+    let balance = helpers::get_token_balance(&mut banks_client, &source).await;
+    helpers::process_with_accounts(
+        &mut banks_client,
+        &recent_blockhash,
+        &payer,
+        cancel(silent, silent_pot_token),
+        &history_accounts,
+        &[&payer, silent],
+    )
+    .await
+    .expect("cancel_bid");
+    assert_eq!(
+        helpers::get_token_balance(&mut banks_client, &source).await,
+        balance + 1050
+    );
+}
+
+/// Anybody can refund a bid pushed out of the bid state, without the bidder's signature, but only
+/// into a token account of the bidder's own and only once the bid has really been evicted.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_refund_evicted_bid_by_anyone() {
+    let (
+        program_id,
+        mut banks_client,
+        bidders,
+        payer,
+        resource,
+        mint,
+        _,
+        auction_pubkey,
+        recent_blockhash,
+    ) = setup_auction(true, 1).await;
+
+    // One bid more than the bid state holds, each from a new bidder paying out of the first
+    // bidder's token account, which the payer owns. The lowest bid is evicted by the last.
+    let source = bidders[0].0.pubkey();
+    let mut evicting_bidders = vec![];
+    for amount in (1..=9).map(|n| n * 100) {
+        let bidder = Keypair::new();
+        let pot_token = Keypair::new();
+        helpers::create_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &pot_token,
+            &mint,
+            &auction_pubkey,
+        )
+        .await
+        .unwrap();
+        helpers::process_with_accounts(
+            &mut banks_client,
+            &recent_blockhash,
+            &payer,
+            instruction::place_bid_instruction(
+                program_id,
+                bidder.pubkey(),
+                source,
+                pot_token.pubkey(),
+                mint,
+                payer.pubkey(),
+                payer.pubkey(),
+                PlaceBidArgs {
+                    amount,
+                    resource,
+                    commitment: None,
+                    top_up: false,
+                    allowlist_proof: vec![],
+                },
+            ),
+            &[],
+            &[&payer, &bidder],
+        )
+        .await
+        .expect("place_bid");
+        evicting_bidders.push((bidder, pot_token));
+    }
+
+    // A stranger with nothing to do with the auction sends the refunds.
+    let stranger = Keypair::new();
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &payer.pubkey(),
+                &stranger.pubkey(),
+                1_000_000_000,
+            )],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        ))
+        .await
+        .unwrap();
+    let refund = |bidder: &Keypair, refund: Pubkey, pot_token: &Keypair| {
+        Transaction::new_signed_with_payer(
+            &[instruction::refund_evicted_bid_instruction(
+                program_id,
+                bidder.pubkey(),
+                refund,
+                pot_token.pubkey(),
+                mint,
+                payer.pubkey(),
+                stranger.pubkey(),
+                RefundEvictedBidArgs { resource },
+            )],
+            Some(&stranger.pubkey()),
+            &[&stranger],
+            recent_blockhash,
+        )
+    };
+
+    let (evicted, evicted_pot_token) = &evicting_bidders[0];
+    let (held, held_pot_token) = &evicting_bidders[1];
+    let evicted_refund = Keypair::new();
+    let held_refund = Keypair::new();
+    for (account, owner) in [(&evicted_refund, evicted), (&held_refund, held)].iter() {
+        helpers::create_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            account,
+            &mint,
+            &owner.pubkey(),
+        )
+        .await
+        .unwrap();
+    }
+
+    let err = banks_client
+        .process_transaction(refund(held, held_refund.pubkey(), held_pot_token))
+        .await
+        .unwrap_err();
+    assert_auction_error(err, AuctionError::BidNotEvicted);
+    let err = banks_client
+        .process_transaction(refund(evicted, source, evicted_pot_token))
+        .await
+        .unwrap_err();
+    assert_auction_error(err, AuctionError::RefundAccountOwnerMismatch);
+
+    // The emptied pot is closed, its rent going back to the payer of the pot rather than to whoever
+    // sent the refund.
+    let (evicted_pot, _) = Pubkey::find_program_address(
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            auction_pubkey.as_ref(),
+            evicted.pubkey().as_ref(),
+        ],
+        &program_id,
+    );
+    let closed = [evicted_pot, evicted_pot_token.pubkey()];
+    let mut pot_rent = 0;
+    for account in closed.iter() {
+        pot_rent += helpers::get_account(&mut banks_client, account)
+            .await
+            .lamports;
+    }
+    let payer_balance = banks_client.get_balance(payer.pubkey()).await.unwrap();
+    banks_client
+        .process_transaction(refund(evicted, evicted_refund.pubkey(), evicted_pot_token))
+        .await
+        .expect("refund_evicted_bid");
+    assert_eq!(
+        helpers::get_token_balance(&mut banks_client, &evicted_refund.pubkey()).await,
+        100
+    );
+    for account in closed.iter() {
+        assert!(banks_client.get_account(*account).await.unwrap().is_none());
+    }
+    assert_eq!(
+        banks_client.get_balance(payer.pubkey()).await.unwrap(),
+        payer_balance + pot_rent
+    );
+}
+
+/// Bids in a native SOL auction are paid in lamports straight from the bidder's wallet into their
+/// pot, which gives them back on a cancel and pays them out on a claim.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_native_sol_bids() {
+    let (program_id, mut banks_client, _, payer, resource, _, _, auction_pubkey, recent_blockhash) =
+        setup_auction_with(true, 1, |args| args.token_mint = system_program::id()).await;
+    let mint = system_program::id();
+
+    // Two bidders holding SOL only, with a seller to be paid.
+    let bidders = [Keypair::new(), Keypair::new()];
+    let seller = Keypair::new();
+    let funding = [&bidders[0], &bidders[1], &seller]
+        .iter()
+        .map(|wallet| {
+            system_instruction::transfer(&payer.pubkey(), &wallet.pubkey(), 1_000_000_000)
+        })
+        .collect::<Vec<_>>();
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &funding,
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let pot = |bidder: &Keypair| {
+        Pubkey::find_program_address(
+            &[
+                PREFIX.as_bytes(),
+                program_id.as_ref(),
+                auction_pubkey.as_ref(),
+                bidder.pubkey().as_ref(),
+            ],
+            &program_id,
+        )
+        .0
+    };
+    let place_bid = |bidder: &Keypair, pot_token: Pubkey, amount: u64| {
+        Transaction::new_signed_with_payer(
+            &[instruction::place_bid_instruction(
+                program_id,
+                bidder.pubkey(),
+                bidder.pubkey(),
+                pot_token,
+                mint,
+                bidder.pubkey(),
+                payer.pubkey(),
+                PlaceBidArgs {
+                    amount,
+                    resource,
+                    commitment: None,
+                    top_up: false,
+                    allowlist_proof: vec![],
+                },
+            )],
+            Some(&payer.pubkey()),
+            &[&payer, bidder],
+            recent_blockhash,
+        )
+    };
+
+    // The pot itself holds the lamports, there is no token account to escrow them in.
+    let err = banks_client
+        .process_transaction(place_bid(&bidders[0], Pubkey::new_unique(), 1_000))
+        .await
+        .unwrap_err();
+    assert_auction_error(err, AuctionError::BidderPotTokenAccountOwnerMismatch);
+
+    for (bidder, amount) in bidders.iter().zip([1_000, 2_000].iter()) {
+        let balance = banks_client.get_balance(bidder.pubkey()).await.unwrap();
+        banks_client
+            .process_transaction(place_bid(bidder, pot(bidder), *amount))
+            .await
+            .expect("place_bid");
+        assert_eq!(
+            banks_client.get_balance(bidder.pubkey()).await.unwrap(),
+            balance - amount
+        );
+        let pot_account = helpers::get_account(&mut banks_client, &pot(bidder)).await;
+        let rent = banks_client.get_rent().await.unwrap();
+        assert_eq!(
+            pot_account.lamports,
+            rent.minimum_balance(pot_account.data.len()) + amount
+        );
+    }
+
+    // A cancel gives the bid back to the bidder's wallet and closes the pot.
+    let balance = banks_client.get_balance(bidders[0].pubkey()).await.unwrap();
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[instruction::cancel_bid_instruction(
+                program_id,
+                bidders[0].pubkey(),
+                bidders[0].pubkey(),
+                pot(&bidders[0]),
+                mint,
+                payer.pubkey(),
+                CancelBidArgs { resource },
+            )],
+            Some(&payer.pubkey()),
+            &[&payer, &bidders[0]],
+            recent_blockhash,
+        ))
+        .await
+        .expect("cancel_bid");
+    assert_eq!(
+        banks_client.get_balance(bidders[0].pubkey()).await.unwrap(),
+        balance + 1_000
+    );
+    assert!(banks_client
+        .get_account(pot(&bidders[0]))
+        .await
+        .unwrap()
+        .is_none());
+
+    helpers::end_auction(
+        &mut banks_client,
+        &program_id,
+        &recent_blockhash,
+        &payer,
+        &resource,
+    )
+    .await
+    .expect("end_auction");
+
+    // The winning bid is paid out to the seller in lamports.
+    let balance = banks_client.get_balance(seller.pubkey()).await.unwrap();
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[instruction::claim_bid_instruction(
+                program_id,
+                seller.pubkey(),
+                payer.pubkey(),
+                bidders[1].pubkey(),
+                pot(&bidders[1]),
+                mint,
+                bidders[1].pubkey(),
+                payer.pubkey(),
+                ClaimBidArgs { resource },
+            )],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        ))
+        .await
+        .expect("claim_bid");
+    assert_eq!(
+        banks_client.get_balance(seller.pubkey()).await.unwrap(),
+        balance + 2_000
+    );
+    assert!(banks_client
+        .get_account(pot(&bidders[1]))
+        .await
+        .unwrap()
+        .is_none());
+}
+
+/// An auction keeping a bid history records bids refunded without their bidder, both evicted ones
+/// and losing ones refunded by the crank. Its history pages are closed along with the auction,
+/// which can't be closed without them.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_bid_history_records_refunds() {
+    let (
+        program_id,
+        mut banks_client,
+        bidders,
+        payer,
+        resource,
+        mint,
+        mint_authority,
+        auction_pubkey,
+        recent_blockhash,
+    ) = setup_auction_with(true, 1, |args| args.bid_history = true).await;
+
+    // Everything here fits on the first page, passed along with the page that would follow it.
+    let history_pages = [
+        instruction::bid_history_page_pubkey(program_id, auction_pubkey, 0),
+        instruction::bid_history_page_pubkey(program_id, auction_pubkey, 1),
+    ];
+    let history_accounts = history_pages
+        .iter()
+        .map(|page| AccountMeta::new(*page, false))
+        .collect::<Vec<_>>();
+
+    // One bid more than the bid state holds, each from a new bidder paying out of the first
+    // bidder's token account, which the payer owns. The lowest bid is evicted by the last.
+    let source = bidders[0].0.pubkey();
+    let mut history_bidders = vec![];
+    for amount in (1..=9).map(|n| n * 100) {
+        let bidder = Keypair::new();
+        let pot_token = Keypair::new();
+        helpers::create_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &pot_token,
+            &mint,
+            &auction_pubkey,
+        )
+        .await
+        .unwrap();
+        helpers::process_with_accounts(
+            &mut banks_client,
+            &recent_blockhash,
+            &payer,
+            instruction::place_bid_instruction(
+                program_id,
+                bidder.pubkey(),
+                source,
+                pot_token.pubkey(),
+                mint,
+                payer.pubkey(),
+                payer.pubkey(),
+                PlaceBidArgs {
+                    amount,
+                    resource,
+                    commitment: None,
+                    top_up: false,
+                    allowlist_proof: vec![],
+                },
+            ),
+            &history_accounts,
+            &[&payer, &bidder],
+        )
+        .await
+        .expect("place_bid");
+        history_bidders.push((bidder, pot_token));
+    }
+
+    // The evicted bid goes back to a token account of the bidder's own.
+    let (evicted, evicted_pot_token) = &history_bidders[0];
+    let refund = Keypair::new();
+    helpers::create_token_account(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &refund,
+        &mint,
+        &evicted.pubkey(),
+    )
+    .await
+    .unwrap();
+    helpers::process_with_accounts(
+        &mut banks_client,
+        &recent_blockhash,
+        &payer,
+        instruction::refund_evicted_bid_instruction(
+            program_id,
+            evicted.pubkey(),
+            refund.pubkey(),
+            evicted_pot_token.pubkey(),
+            mint,
+            payer.pubkey(),
+            payer.pubkey(),
+            RefundEvictedBidArgs { resource },
+        ),
+        &history_accounts,
+        &[&payer],
+    )
+    .await
+    .expect("refund_evicted_bid");
+
+    helpers::end_auction(
+        &mut banks_client,
+        &program_id,
+        &recent_blockhash,
+        &payer,
+        &resource,
+    )
+    .await
+    .expect("end_auction");
+
+    // Every other bid but the winning one loses, refunded by the crank in two batches.
+    let mut losers = vec![];
+    for (bidder, pot_token) in history_bidders[1..8].iter() {
+        let destination = helpers::create_associated_token_account(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            &bidder.pubkey(),
+            &mint,
+        )
+        .await
+        .expect("create_associated_token_account");
+        losers.push((
+            bidder.pubkey(),
+            pot_token.pubkey(),
+            destination,
+            payer.pubkey(),
+            None,
+        ));
+    }
+    for batch in losers.chunks(4) {
+        helpers::refund_losing_bids(
+            &mut banks_client,
+            &recent_blockhash,
+            &program_id,
+            &payer,
+            &history_pages,
+            batch,
+            &resource,
+        )
+        .await
+        .expect("refund_losing_bids");
+    }
+
+    let page: BidHistoryPage = try_from_slice_unchecked(
+        &helpers::get_account(&mut banks_client, &history_pages[0])
+            .await
+            .data,
+    )
+    .unwrap();
+    assert_eq!(page.payer, payer.pubkey());
+    let refunds = page
+        .entries
+        .iter()
+        .filter(|entry| entry.action == BidHistoryAction::Refunded)
+        .map(|entry| (entry.bidder, entry.amount))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        refunds,
+        history_bidders[..8]
+            .iter()
+            .zip(1..)
+            .map(|((bidder, _), n)| (bidder.pubkey(), n * 100))
+            .collect::<Vec<_>>()
+    );
+
+    // The winner is paid out, leaving no pots open.
+    let collection = Keypair::new();
+    helpers::create_token_account(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &collection,
+        &mint,
+        &payer.pubkey(),
+    )
+    .await
+    .unwrap();
+    let (winner, winner_pot_token) = &history_bidders[8];
+    helpers::claim_bid(
+        &mut banks_client,
+        &recent_blockhash,
+        &program_id,
+        &payer,
+        &payer,
+        winner,
+        winner_pot_token,
+        &collection.pubkey(),
+        &resource,
+        &mint,
+        &source,
+    )
+    .await
+    .expect("claim_bid");
+
+    let reclaim = |history_pages: &[(Pubkey, Pubkey)]| {
+        instruction::reclaim_auction_rent_instruction(
+            program_id,
+            payer.pubkey(),
+            payer.pubkey(),
+            &[],
+            history_pages,
+            ReclaimAuctionRentArgs { resource },
+        )
+    };
+    let err = helpers::process_with_accounts(
+        &mut banks_client,
+        &recent_blockhash,
+        &payer,
+        reclaim(&[]),
+        &[],
+        &[&payer],
+    )
+    .await
+    .unwrap_err();
+    assert_auction_error(err, AuctionError::BidPageMissing);
+
+    helpers::process_with_accounts(
+        &mut banks_client,
+        &recent_blockhash,
+        &payer,
+        reclaim(&[(history_pages[0], payer.pubkey())]),
+        &[],
+        &[&payer],
+    )
+    .await
+    .expect("reclaim_auction_rent");
+    for closed in [history_pages[0], auction_pubkey].iter() {
+        assert!(banks_client.get_account(*closed).await.unwrap().is_none());
+    }
+}
+
+/// Events the program logged about an auction, decoded from the lines captured since
+/// capture_logs, leaving out those of auctions run by other tests meanwhile.
+fn auction_events(auction_pubkey: &Pubkey) -> Vec<AuctionEvent> {
+    helpers::captured_logs()
+        .iter()
+        .filter_map(|line| decode_event(line))
+        .filter(|event| {
+            let auction = match event {
+                AuctionEvent::AuctionCreated { auction, .. }
+                | AuctionEvent::AuctionStarted { auction, .. }
+                | AuctionEvent::BidPlaced { auction, .. }
+                | AuctionEvent::BidCancelled { auction, .. }
+                | AuctionEvent::BidEvicted { auction, .. }
+                | AuctionEvent::AuctionEnded { auction, .. }
+                | AuctionEvent::BidClaimed { auction, .. }
+                | AuctionEvent::AuctionCancelled { auction }
+                | AuctionEvent::BidRefunded { auction, .. }
+                | AuctionEvent::AuctionClosed { auction, .. }
+                | AuctionEvent::BidderMetadataClosed { auction, .. }
+                | AuctionEvent::AuthorityChanged { auction, .. } => auction,
+                AuctionEvent::ExchangeRateUpdated { .. } => return false,
+            };
+            auction == auction_pubkey
+        })
+        .collect()
+}
+
+/// Bidding, cancelling and ending an auction log an event for each step, which read back from the
+/// program's logs in the order they happened, down to the refund of the bid that lost.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_events_decode_from_logs() {
+    let (
+        program_id,
+        mut banks_client,
+        bidders,
+        payer,
+        resource,
+        mint,
+        mint_authority,
+        auction_pubkey,
+        recent_blockhash,
+    ) = setup_auction(false, 1).await;
+    helpers::capture_logs();
+
+    helpers::start_auction(
+        &mut banks_client,
+        &program_id,
+        &recent_blockhash,
+        &payer,
+        &resource,
+    )
+    .await
+    .expect("start_auction");
+    for (bidder, amount) in [(0, 1_000), (1, 2_000)].iter() {
+        helpers::place_bid(
+            &mut banks_client,
+            &recent_blockhash,
+            &program_id,
+            &payer,
+            &bidders[*bidder].0,
+            &bidders[*bidder].1,
+            &payer,
+            &resource,
+            &mint,
+            *amount,
+        )
+        .await
+        .expect("place_bid");
+    }
+    helpers::cancel_bid(
+        &mut banks_client,
+        &recent_blockhash,
+        &program_id,
+        &payer,
+        &bidders[0].0,
+        &bidders[0].1,
+        &resource,
+        &mint,
+    )
+    .await
+    .expect("cancel_bid");
+    helpers::place_bid(
+        &mut banks_client,
+        &recent_blockhash,
+        &program_id,
+        &payer,
+        &bidders[2].0,
+        &bidders[2].1,
+        &payer,
+        &resource,
+        &mint,
+        3_000,
+    )
+    .await
+    .expect("place_bid");
+    helpers::end_auction(
+        &mut banks_client,
+        &program_id,
+        &recent_blockhash,
+        &payer,
+        &resource,
+    )
+    .await
+    .expect("end_auction");
+
+    let destination = helpers::create_associated_token_account(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &bidders[1].0.pubkey(),
+        &mint,
+    )
+    .await
+    .expect("create_associated_token_account");
+    helpers::refund_losing_bids(
+        &mut banks_client,
+        &recent_blockhash,
+        &program_id,
+        &payer,
+        &[],
+        &[(
+            bidders[1].0.pubkey(),
+            bidders[1].1.pubkey(),
+            destination,
+            payer.pubkey(),
+            None,
+        )],
+        &resource,
+    )
+    .await
+    .expect("refund_losing_bids");
+
+    let events = auction_events(&auction_pubkey);
+    assert_eq!(events.len(), 7, "{:?}", events);
+    assert!(matches!(events[0], AuctionEvent::AuctionStarted { .. }));
+    let bids = [
+        (1, 0, 1_000, "placed"),
+        (2, 1, 2_000, "placed"),
+        (3, 0, 1_000, "cancelled"),
+        (4, 2, 3_000, "placed"),
+        (6, 1, 2_000, "refunded"),
+    ];
+    for (index, bidder, amount, action) in bids.iter() {
+        let expected = bidders[*bidder].0.pubkey();
+        let (logged, logged_bidder, logged_amount) = match events[*index] {
+            AuctionEvent::BidPlaced { bidder, amount, .. } => ("placed", bidder, amount),
+            AuctionEvent::BidCancelled { bidder, amount, .. } => ("cancelled", bidder, amount),
+            AuctionEvent::BidRefunded { bidder, amount, .. } => ("refunded", bidder, amount),
+            ref event => panic!("unexpected event {:?}", event),
+        };
+        assert_eq!(
+            (logged, logged_bidder, logged_amount),
+            (*action, expected, *amount)
+        );
+    }
+    assert!(matches!(events[5], AuctionEvent::AuctionEnded { .. }));
+}
@@ -390,6 +390,70 @@ pub enum MetaplexError {
     /// Invalid Auction Manager Status
     #[error("Invalid Auction Manager Status")]
     InvalidStatus,
+
+    /// Auctions that keep their bids in pages are not supported
+    #[error("Auctions that keep their bids in pages are not supported")]
+    PagedBidsNotSupported,
+
+    /// A native SOL bid is held by the bidder pot itself, which must be given as its token account too
+    #[error("A native SOL bid is held by the bidder pot itself, which must be given as its token account too")]
+    BidderPotTokenAccountMismatch,
+
+    /// The auction was cancelled, so there is nothing for bidders to redeem
+    #[error("The auction was cancelled, so there is nothing for bidders to redeem")]
+    AuctionCancelled,
+
+    /// The bid currency record given is not the one of the winner being paid out
+    #[error("The bid currency record given is not the one of the winner being paid out")]
+    BidCurrencyMismatch,
+
+    /// The payout split has too many recipients, a duplicate or empty cut, or gives away more than 10000 basis points
+    #[error("The payout split has too many recipients, a duplicate or empty cut, or gives away more than 10000 basis points")]
+    InvalidPayoutSplit,
+
+    /// Invalid payout split index, or one given together with a creator index
+    #[error("Invalid payout split index, or one given together with a creator index")]
+    InvalidPayoutSplitIndex,
+
+    /// The conversion rate must have a price, and convert between two different mints that can't change once set
+    #[error("The conversion rate must have a price, and convert between two different mints that can't change once set")]
+    InvalidConversionRate,
+
+    /// The swap pool, its conversion rate or its holdings do not match the ones provided
+    #[error("The swap pool, its conversion rate or its holdings do not match the ones provided")]
+    SwapPoolMismatch,
+
+    /// The payout is too small to be worth anything once converted
+    #[error("The payout is too small to be worth anything once converted")]
+    ConvertedAmountTooSmall,
+
+    /// The authority given is not the authority of the swap pool
+    #[error("The authority given is not the authority of the swap pool")]
+    SwapPoolAuthorityMismatch,
+
+    /// The destination is in another mint than the accept payment account, so the payout must be converted through a swap route
+    #[error("The destination is in another mint than the accept payment account, so the payout must be converted through a swap route")]
+    SwapRouteRequired,
+
+    /// The auction manager is not a fixed price listing
+    #[error("The auction manager is not a fixed price listing")]
+    NotAFixedPriceListing,
+
+    /// Every item of the fixed price listing has been sold or taken back
+    #[error("Every item of the fixed price listing has been sold or taken back")]
+    FixedPriceListingSoldOut,
+
+    /// A fixed price listing can only sell token only transfers, printing tokens and single limited editions, without participation, under a fresh listing key that signs
+    #[error("A fixed price listing can only sell token only transfers, printing tokens and single limited editions, without participation, under a fresh listing key that signs")]
+    InvalidFixedPriceListing,
+
+    /// Limited editions are only minted as they are bought from a fixed price listing
+    #[error("Limited editions are only minted as they are bought from a fixed price listing")]
+    LimitedEditionOnlyOnFixedPriceListing,
+
+    /// Settings can only change how safety deposit boxes are used if the boxes are passed along with validation tickets that do not exist yet
+    #[error("Settings can only change how safety deposit boxes are used if the boxes are passed along with validation tickets that do not exist yet")]
+    SettingsChangeNotShownUnvalidated,
 }
 
 impl PrintProgramError for MetaplexError {
@@ -27,6 +27,37 @@ pub struct EmptyPaymentAccountArgs {
 
     /// index in the metadata creator list, can be None if metadata has no creator list.
     pub creator_index: Option<u8>,
+
+    /// index in the auction manager's payout split recipients, when paying out one of them instead
+    /// of the auctioneer. Leave the creator index as None when using this.
+    pub payout_split_index: Option<u8>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct SetConversionRateArgs {
+    /// Price of one unit of the mint in units of the price mint, scaled by CONVERSION_RATE_SCALE.
+    pub price_per_token: u64,
+    pub mint: Pubkey,
+    pub price_mint: Pubkey,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct WithdrawFromSwapPoolArgs {
+    pub amount: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct EmptyPaymentAccountsArgs {
+    /// One entry per payout, in the same order as the groups of payout accounts.
+    pub payouts: Vec<BatchedPayoutArgs>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct BatchedPayoutArgs {
+    pub payout: EmptyPaymentAccountArgs,
+    /// Whether the payout is converted through a swap pool, in which case the accounts of the swap
+    /// route follow the payout's own and the recipient signs the batch.
+    pub swap_route: bool,
 }
 #[derive(BorshSerialize, BorshDeserialize, Clone)]
 pub enum ProxyCallAddress {
@@ -48,6 +79,7 @@ pub enum MetaplexInstruction {
     ///   1. `[]` Combined vault account with authority set to auction manager account (this will be checked)
     ///           Note in addition that this vault account should have authority set to this program's pda of ['metaplex', auction_key]
     ///   2. `[]` Auction with auctioned item being set to the vault given and authority set to this program's pda of ['metaplex', auction_key]
+    ///           For a fixed price listing, a fresh `[signer]` listing key instead, which only serves to derive the auction manager
     ///   3. `[]` Authority for the Auction Manager
     ///   4. `[signer]` Payer
     ///   5. `[]` Accept payment account of same token mint as the auction for taking payment for open editions, owner should be auction manager key
@@ -192,6 +224,7 @@ pub enum MetaplexInstruction {
     RedeemParticipationBid,
 
     /// If the auction manager is in Validated state, it can invoke the start command via calling this command here.
+    /// A fixed price listing has no auction to start, and goes straight to Running.
     ///
     ///   0. `[writable]` Auction manager
     ///   1. `[writable]` Auction
@@ -210,19 +243,23 @@ pub enum MetaplexInstruction {
     /// funds at any time without any danger to the user of losing out on their NFT, because the AM will honor their bid with an NFT
     /// at ANY time.
     ///
-    ///   0. `[writable]` The accept payment account on the auction manager
+    ///   0. `[writable]` The accept payment account on the auction manager, or for a bid in another currency
+    ///           accepted by the auction, an account of that currency owned by the auction manager
     ///   1. `[writable]` The bidder pot token account
     ///   2. `[writable]` The bidder pot pda account [seed of ['auction', program_id, auction key, bidder key] -
     ///           relative to the auction program, not auction manager
     ///   3. `[writable]` Auction manager
     ///   4. `[]` The auction
     ///   5. `[]` The bidder wallet
-    ///   6. `[]` Token mint of the auction
+    ///   6. `[]` Token mint the bid was paid in
     ///   7. `[]` Vault
     ///   8. `[]` Store
     ///   9. `[]` Auction program
     ///   10. `[]` Clock sysvar
     ///   11. `[]` Token program
+    ///   12. `[]` Auction extended (pda relative to auction of ['auction', program id, vault key, 'extended'])
+    ///   13. `[writable]` The bidder's token account, refunded any amount bid over the clearing price of a uniform price auction
+    ///   14. `[writable]` The payer of the bidder pot, refunded the rent of the pot accounts closed once claimed
     ClaimBid,
 
     /// At any time, the auction manager authority may empty whatever funds are in the accept payment account
@@ -244,10 +281,15 @@ pub enum MetaplexInstruction {
     /// For the participation NFT, there is no winning config, but the total is figured by summing the winning bids and subtracting
     /// from the total escrow amount present.
     ///
-    ///   0. `[writable]` The accept payment account on the auction manager
+    /// An auction manager with a payout split pays each of its recipients out of the auctioneer's share, by passing the
+    /// recipient's payout split index and no creator index. The destination must then be an Associated Token Account of the
+    /// recipient, and its payout ticket is keyed with 'split-' and the payout split index in place of the creator index.
+    ///
+    ///   0. `[writable]` The accept payment account on the auction manager, or for an auction accepting other
+    ///           currencies, the account of the auction manager the bids in one of them were claimed into
     ///   1. `[writable]` The destination account of same mint type as the accept payment account. Must be an Associated Token Account.
     ///   2. `[writable]` Auction manager
-    ///   3. `[writable]` Payout ticket info to keep track of this artist or auctioneer's payment, pda of [metaplex, auction manager, winning config index OR 'participation', safety deposit key]
+    ///   3. `[writable]` Payout ticket info to keep track of this artist or auctioneer's payment, pda of [metaplex, auction manager, winning config index OR 'participation', safety deposit key, destination owner], followed by the destination mint for payouts in another currency
     ///   4. `[signer]` payer
     ///   5. `[]` The metadata
     ///   6. `[]` The master edition of the metadata (optional if exists)
@@ -255,10 +297,24 @@ pub enum MetaplexInstruction {
     ///   7. `[]` Safety deposit box account
     ///   8. `[]` The store of the auction manager
     ///   9. `[]` The vault
-    ///   10. `[]` Auction
+    ///   10. `[]` Auction, or the listing key of a fixed price listing
     ///   11. `[]` Token program
     ///   12. `[]` System program
     ///   13. `[]` Rent sysvar
+    ///   14. `[]` Auction extended (pda relative to auction of ['auction', program id, vault key, 'extended']),
+    ///           or the system program for a fixed price listing
+    ///   15. `[]` The bid currency record of the winner of the winning config, for an auction accepting other currencies
+    ///           (pda relative to auction of ['auction', program id, auction key, bidder key, 'currency']), or the system
+    ///           program when passing the accounts after it without needing one
+    ///
+    /// A recipient who wants to be paid in another currency can have their payout converted through a swap pool, by
+    /// signing for it and passing the accounts below. The destination is then of the swap pool's price mint, and the
+    /// payout ticket still counts what was paid in the accept payment account's mint.
+    ///   16. `[]` Swap pool whose conversion rate converts from the accept payment account's mint
+    ///   17. `[]` Conversion rate of the swap pool
+    ///   18. `[writable]` The swap pool's from holding, that the payout is paid into
+    ///   19. `[writable]` The swap pool's to holding, that the converted payout is paid from
+    ///   20. `[signer]` The owner of the destination account
     EmptyPaymentAccount(EmptyPaymentAccountArgs),
 
     /// Given a signer wallet, create a store with pda ['metaplex', wallet] (if it does not exist) and/or update it
@@ -361,6 +417,142 @@ pub enum MetaplexInstruction {
     /// 4. `[]` Auction program
     /// 5. `[]` Clock sysvar
     DecommissionAuctionManager,
+
+    /// Cancel the auction of a Validated or Running auction manager, for when the consignment is
+    /// pulled. The auction is left without winners so every bid can be refunded, and the auction
+    /// manager moves straight to Disbursing, from where the auctioneer can take back the items
+    /// with RedeemUnusedWinningConfigItemsAsAuctioneer.
+    ///
+    /// Once bids have landed, this only works if the auction was created to allow it.
+    ///
+    /// 0. `[writable]` Auction Manager
+    /// 1. `[writable]` Auction
+    /// 2. `[Signer]` Authority of the Auction Manager
+    /// 3. `[]` Store
+    /// 4. `[]` Auction program
+    /// 5. `[]` Clock sysvar
+    /// 6. `[]` Auction extended account
+    CancelAuction,
+
+    /// Does the work of many EmptyPaymentAccount calls out of one accept payment account at once, for settling an
+    /// auction with many winners, items and creators in far fewer transactions. The shared accounts are checked once,
+    /// then each payout is checked and paid as EmptyPaymentAccount would, on the same payout ticket, so that resending
+    /// a batch or mixing it with single calls never pays anybody twice. Fit as many payouts in as the transaction
+    /// size and compute budget allow. A payout converted through a swap pool needs its recipient to sign the batch,
+    /// and one to a destination in another mint than the accept payment account's is rejected without a swap route.
+    ///
+    ///   0. `[writable]` The accept payment account on the auction manager, or for an auction accepting other
+    ///           currencies, the account of the auction manager the bids in one of them were claimed into
+    ///   1. `[]` Auction manager
+    ///   2. `[signer]` payer
+    ///   3. `[]` The auction manager's store
+    ///   4. `[]` The vault
+    ///   5. `[]` Auction, or the listing key of a fixed price listing
+    ///   6. `[]` Token program
+    ///   7. `[]` System program
+    ///   8. `[]` Rent sysvar
+    ///   9. `[]` Auction extended (pda relative to auction of ['auction', program id, vault key, 'extended']),
+    ///           or the system program for a fixed price listing
+    ///
+    /// Followed by a group of 6 accounts for each payout in the args, in the same order:
+    ///   0. `[writable]` The destination account, as in EmptyPaymentAccount
+    ///   1. `[writable]` The payout ticket, as in EmptyPaymentAccount
+    ///   2. `[]` The metadata
+    ///   3. `[]` The master edition of the metadata, or the system program if it has none
+    ///   4. `[]` Safety deposit box account
+    ///   5. `[]` The bid currency record of the winner, for a winning config in an auction accepting other currencies,
+    ///           or the system program otherwise
+    ///
+    /// Followed, for a payout with a swap route, by the 5 accounts of the route, as in EmptyPaymentAccount:
+    ///   6. `[]` Swap pool whose conversion rate converts from the accept payment account's mint
+    ///   7. `[]` Conversion rate of the swap pool
+    ///   8. `[writable]` The swap pool's from holding, that the payout is paid into
+    ///   9. `[writable]` The swap pool's to holding, that the converted payout is paid from
+    ///   10. `[signer]` The owner of the destination account
+    EmptyPaymentAccounts(EmptyPaymentAccountsArgs),
+
+    /// Creates or updates a conversion rate, the price of one currency in another for converting payouts out of an
+    /// accept payment account through a swap pool. Like token-vault's UpdateExternalPriceAccount, the conversion
+    /// rate is an account created by the caller with this program as its owner and MAX_CONVERSION_RATE_SIZE bytes,
+    /// and it signs for its own updates. Its mints can't change once set, only the price.
+    ///
+    ///   0. `[writable, signer]` Conversion rate
+    SetConversionRate(SetConversionRateArgs),
+
+    /// Creates a swap pool, which converts payouts in the mint of a conversion rate into its price mint out of the
+    /// liquidity the pool holds. Stock the pool by transferring price mint tokens into its to holding.
+    ///
+    ///   0. `[writable]` Uninitialized swap pool, pda of ['metaplex', program id, authority, conversion rate]
+    ///   1. `[signer]` Authority of the swap pool, who may withdraw from its holdings
+    ///   2. `[signer]` Payer
+    ///   3. `[]` Conversion rate
+    ///   4. `[]` From holding, a token account of the conversion rate's mint owned by the swap pool
+    ///   5. `[]` To holding, a token account of the conversion rate's price mint owned by the swap pool
+    ///   6. `[]` System
+    ///   7. `[]` Rent sysvar
+    InitSwapPool,
+
+    /// Withdraws from either holding of a swap pool, the converted payouts it has gathered or its unused liquidity.
+    ///
+    ///   0. `[]` Swap pool
+    ///   1. `[signer]` Authority of the swap pool
+    ///   2. `[writable]` The holding of the swap pool to withdraw from
+    ///   3. `[writable]` Destination token account of the holding's mint
+    ///   4. `[]` Token program
+    WithdrawFromSwapPool(WithdrawFromSwapPoolArgs),
+
+    /// Buys the next unsold winning config of a Running fixed price listing, paying the fixed price into the accept payment
+    /// account and taking every item of the config out of the vault. The authority of the auction manager can take unsold
+    /// items back the same way without paying, leaving nothing owed on them. Proceeds are paid out with EmptyPaymentAccount,
+    /// passing the listing key for the auction and the system program for the auction extended.
+    ///
+    ///   0. `[writable]` Auction manager
+    ///   1. `[]` Store
+    ///   2. `[writable]` Vault
+    ///   3. `[writable]` Fraction mint of the vault
+    ///   4. `[writable]` The accept payment account for the auction manager
+    ///   5. `[writable]` The token account the buyer pays from, of the accept payment mint
+    ///   6. `[writable, signer]` Buyer, transfer authority of the account paid from, and payer for any limited editions
+    ///   7. `[]` PDA-based Transfer authority to move the tokens from the store to the destination seed ['vault', program_id]
+    ///           but please note that this is a PDA relative to the Token Vault program, with the 'vault' prefix
+    ///   8. `[]` Token program
+    ///   9. `[]` Token vault program
+    ///   10. `[]` Rent sysvar
+    ///   11. `[]` Token metadata program
+    ///   12. `[]` System program
+    ///
+    /// Followed by a group of 3 accounts for each item of the winning config being bought, in the same order:
+    ///   0. `[writable]` Safety deposit box account
+    ///   1. `[writable]` Safety deposit token storage account
+    ///   2. `[writable]` Destination account of the buyer, for the item's tokens
+    ///
+    /// A LimitedEdition item bought by anyone but the authority has its authorization token burned for an edition
+    /// right away, and its group goes on with 7 more accounts:
+    ///   3. `[writable]` New metadata (pda of ['metadata', token metadata program id, new mint id])
+    ///   4. `[writable]` New edition (pda of ['metadata', token metadata program id, new mint id, 'edition'])
+    ///   5. `[writable]` Master edition
+    ///   6. `[writable]` New mint of the buyer's, holding one token, with the buyer as mint authority
+    ///   7. `[writable]` Printing mint of the master edition
+    ///   8. `[]` Master metadata
+    ///   9. `[]` Update authority of the master metadata, which the new metadata is given
+    BuyFixedPrice,
+
+    /// Replaces the settings of an auction manager that is still Initialized, checked as at InitAuctionManager. An auction
+    /// manager can't be turned from an auction into a fixed price listing or back. Only safety deposit boxes that have not
+    /// been validated can change how they are used, and each of them must be passed to show it. A validated participation
+    /// config can't change either.
+    ///
+    ///   0. `[writable]` Auction manager
+    ///   1. `[signer]` Authority of the auction manager
+    ///   2. `[]` Vault
+    ///   3. `[]` Auction, or the listing key of a fixed price listing
+    ///   4. `[]` Store
+    ///
+    /// Followed by a group of 2 accounts for each safety deposit box whose use in the winning configs changes:
+    ///   0. `[]` Safety deposit box account
+    ///   1. `[]` Its safety deposit validation ticket, pda of ['metaplex', program id, auction manager key, safety deposit key],
+    ///           which must not exist yet
+    UpdateAuctionManagerSettings(AuctionManagerSettings),
 }
 
 /// Creates an InitAuctionManager instruction
@@ -381,7 +573,7 @@ pub fn create_init_auction_manager_instruction(
         accounts: vec![
             AccountMeta::new(auction_manager, false),
             AccountMeta::new_readonly(vault, false),
-            AccountMeta::new_readonly(auction, false),
+            AccountMeta::new_readonly(auction, settings.fixed_price.is_some()),
             AccountMeta::new_readonly(auction_manager_authority, false),
             AccountMeta::new_readonly(payer, true),
             AccountMeta::new_readonly(accept_payment_account_key, false),
@@ -666,6 +858,31 @@ pub fn create_start_auction_instruction(
     }
 }
 
+/// Creates a CancelAuction instruction
+#[allow(clippy::too_many_arguments)]
+pub fn create_cancel_auction_instruction(
+    program_id: Pubkey,
+    auction_manager: Pubkey,
+    auction: Pubkey,
+    auction_manager_authority: Pubkey,
+    store: Pubkey,
+    auction_extended: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(auction_manager, false),
+            AccountMeta::new(auction, false),
+            AccountMeta::new_readonly(auction_manager_authority, true),
+            AccountMeta::new_readonly(store, false),
+            AccountMeta::new_readonly(spl_auction::id(), false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(auction_extended, false),
+        ],
+        data: MetaplexInstruction::CancelAuction.try_to_vec().unwrap(),
+    }
+}
+
 /// Creates an SetStore instruction
 pub fn create_set_store_instruction(
     program_id: Pubkey,
@@ -739,3 +956,196 @@ pub fn create_populate_participation_printing_account_instruction(
             .unwrap(),
     }
 }
+
+/// Creates a SetConversionRate instruction
+pub fn create_set_conversion_rate_instruction(
+    program_id: Pubkey,
+    conversion_rate: Pubkey,
+    price_per_token: u64,
+    mint: Pubkey,
+    price_mint: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![AccountMeta::new(conversion_rate, true)],
+        data: MetaplexInstruction::SetConversionRate(SetConversionRateArgs {
+            price_per_token,
+            mint,
+            price_mint,
+        })
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// Creates an InitSwapPool instruction
+pub fn create_init_swap_pool_instruction(
+    program_id: Pubkey,
+    swap_pool: Pubkey,
+    authority: Pubkey,
+    payer: Pubkey,
+    conversion_rate: Pubkey,
+    from_holding: Pubkey,
+    to_holding: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(swap_pool, false),
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new_readonly(payer, true),
+            AccountMeta::new_readonly(conversion_rate, false),
+            AccountMeta::new_readonly(from_holding, false),
+            AccountMeta::new_readonly(to_holding, false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+        ],
+        data: MetaplexInstruction::InitSwapPool.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a WithdrawFromSwapPool instruction
+pub fn create_withdraw_from_swap_pool_instruction(
+    program_id: Pubkey,
+    swap_pool: Pubkey,
+    authority: Pubkey,
+    holding: Pubkey,
+    destination: Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(swap_pool, false),
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(holding, false),
+            AccountMeta::new(destination, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: MetaplexInstruction::WithdrawFromSwapPool(WithdrawFromSwapPoolArgs { amount })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Derives the address of the swap pool of an authority for a conversion rate
+pub fn swap_pool_pubkey(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    conversion_rate: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            authority.as_ref(),
+            conversion_rate.as_ref(),
+        ],
+        program_id,
+    )
+    .0
+}
+
+/// The accounts a limited edition bought off a fixed price listing is minted with.
+pub struct LimitedEditionAccounts {
+    pub new_metadata: Pubkey,
+    pub new_edition: Pubkey,
+    pub master_edition: Pubkey,
+    pub new_mint: Pubkey,
+    pub printing_mint: Pubkey,
+    pub master_metadata: Pubkey,
+    pub master_update_authority: Pubkey,
+}
+
+/// Creates a BuyFixedPrice instruction. Items are the safety deposit box, its token store, the
+/// buyer's destination account and, for a limited edition, the accounts to mint it with, for each
+/// item of the winning config being bought.
+#[allow(clippy::too_many_arguments)]
+pub fn create_buy_fixed_price_instruction(
+    program_id: Pubkey,
+    auction_manager: Pubkey,
+    store: Pubkey,
+    vault: Pubkey,
+    fraction_mint: Pubkey,
+    accept_payment: Pubkey,
+    payment_account: Pubkey,
+    buyer: Pubkey,
+    transfer_authority: Pubkey,
+    items: &[(Pubkey, Pubkey, Pubkey, Option<LimitedEditionAccounts>)],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(auction_manager, false),
+        AccountMeta::new_readonly(store, false),
+        AccountMeta::new(vault, false),
+        AccountMeta::new(fraction_mint, false),
+        AccountMeta::new(accept_payment, false),
+        AccountMeta::new(payment_account, false),
+        AccountMeta::new(buyer, true),
+        AccountMeta::new_readonly(transfer_authority, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(spl_token_vault::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(spl_token_metadata::id(), false),
+        AccountMeta::new_readonly(solana_program::system_program::id(), false),
+    ];
+    for (safety_deposit_box, safety_deposit_token_store, destination, limited_edition) in items {
+        accounts.push(AccountMeta::new(*safety_deposit_box, false));
+        accounts.push(AccountMeta::new(*safety_deposit_token_store, false));
+        accounts.push(AccountMeta::new(*destination, false));
+        if let Some(edition) = limited_edition {
+            accounts.push(AccountMeta::new(edition.new_metadata, false));
+            accounts.push(AccountMeta::new(edition.new_edition, false));
+            accounts.push(AccountMeta::new(edition.master_edition, false));
+            accounts.push(AccountMeta::new(edition.new_mint, false));
+            accounts.push(AccountMeta::new(edition.printing_mint, false));
+            accounts.push(AccountMeta::new_readonly(edition.master_metadata, false));
+            accounts.push(AccountMeta::new_readonly(
+                edition.master_update_authority,
+                false,
+            ));
+        }
+    }
+
+    Instruction {
+        program_id,
+        accounts,
+        data: MetaplexInstruction::BuyFixedPrice.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates an UpdateAuctionManagerSettings instruction. Safety deposit boxes are the safety deposit
+/// box and its validation ticket for each box whose use in the winning configs changes.
+#[allow(clippy::too_many_arguments)]
+pub fn create_update_auction_manager_settings_instruction(
+    program_id: Pubkey,
+    auction_manager: Pubkey,
+    auction_manager_authority: Pubkey,
+    vault: Pubkey,
+    auction: Pubkey,
+    store: Pubkey,
+    safety_deposit_boxes: &[(Pubkey, Pubkey)],
+    settings: AuctionManagerSettings,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(auction_manager, false),
+        AccountMeta::new_readonly(auction_manager_authority, true),
+        AccountMeta::new_readonly(vault, false),
+        AccountMeta::new_readonly(auction, false),
+        AccountMeta::new_readonly(store, false),
+    ];
+    for (safety_deposit_box, safety_deposit_validation_ticket) in safety_deposit_boxes {
+        accounts.push(AccountMeta::new_readonly(*safety_deposit_box, false));
+        accounts.push(AccountMeta::new_readonly(
+            *safety_deposit_validation_ticket,
+            false,
+        ));
+    }
+
+    Instruction {
+        program_id,
+        accounts,
+        data: MetaplexInstruction::UpdateAuctionManagerSettings(settings)
+            .try_to_vec()
+            .unwrap(),
+    }
+}
@@ -1,37 +1,49 @@
 use {
     crate::instruction::MetaplexInstruction,
     borsh::BorshDeserialize,
+    buy_fixed_price::process_buy_fixed_price,
+    cancel_auction::process_cancel_auction,
     claim_bid::process_claim_bid,
     decommission_auction_manager::process_decommission_auction_manager,
-    empty_payment_account::process_empty_payment_account,
+    empty_payment_account::{process_empty_payment_account, process_empty_payment_accounts},
     init_auction_manager::process_init_auction_manager,
+    init_swap_pool::process_init_swap_pool,
     populate_participation_printing_account::process_populate_participation_printing_account,
     redeem_bid::process_redeem_bid,
     redeem_full_rights_transfer_bid::process_full_rights_transfer_bid,
     redeem_participation_bid::process_redeem_participation_bid,
     redeem_unused_winning_config_items_as_auctioneer::process_redeem_unused_winning_config_items_as_auctioneer,
+    set_conversion_rate::process_set_conversion_rate,
     set_store::process_set_store,
     set_whitelisted_creator::process_set_whitelisted_creator,
     solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, msg, pubkey::Pubkey},
     start_auction::process_start_auction,
+    update_auction_manager_settings::process_update_auction_manager_settings,
     validate_participation::process_validate_participation,
     validate_safety_deposit_box::process_validate_safety_deposit_box,
+    withdraw_from_swap_pool::process_withdraw_from_swap_pool,
 };
 
+pub mod buy_fixed_price;
+pub mod cancel_auction;
 pub mod claim_bid;
 pub mod decommission_auction_manager;
 pub mod empty_payment_account;
 pub mod init_auction_manager;
+pub mod init_swap_pool;
 pub mod populate_participation_printing_account;
 pub mod redeem_bid;
 pub mod redeem_full_rights_transfer_bid;
 pub mod redeem_participation_bid;
 pub mod redeem_unused_winning_config_items_as_auctioneer;
+pub mod set_conversion_rate;
 pub mod set_store;
 pub mod set_whitelisted_creator;
 pub mod start_auction;
+pub mod update_auction_manager_settings;
 pub mod validate_participation;
 pub mod validate_safety_deposit_box;
+pub mod withdraw_from_swap_pool;
 
 pub fn process_instruction<'a>(
     program_id: &'a Pubkey,
@@ -96,5 +108,33 @@ pub fn process_instruction<'a>(
             msg!("Instruction: Decomission Auction Manager");
             process_decommission_auction_manager(program_id, accounts)
         }
+        MetaplexInstruction::CancelAuction => {
+            msg!("Instruction: Cancel Auction");
+            process_cancel_auction(program_id, accounts)
+        }
+        MetaplexInstruction::EmptyPaymentAccounts(args) => {
+            msg!("Instruction: Empty Payment Accounts");
+            process_empty_payment_accounts(program_id, accounts, args)
+        }
+        MetaplexInstruction::SetConversionRate(args) => {
+            msg!("Instruction: Set Conversion Rate");
+            process_set_conversion_rate(program_id, accounts, args)
+        }
+        MetaplexInstruction::InitSwapPool => {
+            msg!("Instruction: Init Swap Pool");
+            process_init_swap_pool(program_id, accounts)
+        }
+        MetaplexInstruction::WithdrawFromSwapPool(args) => {
+            msg!("Instruction: Withdraw From Swap Pool");
+            process_withdraw_from_swap_pool(program_id, accounts, args)
+        }
+        MetaplexInstruction::BuyFixedPrice => {
+            msg!("Instruction: Buy Fixed Price");
+            process_buy_fixed_price(program_id, accounts)
+        }
+        MetaplexInstruction::UpdateAuctionManagerSettings(auction_manager_settings) => {
+            msg!("Instruction: Update Auction Manager Settings");
+            process_update_auction_manager_settings(program_id, accounts, auction_manager_settings)
+        }
     }
 }
@@ -0,0 +1,260 @@
+//! Sells the winning configs of a fixed price listing one at a time, in order. The buyer pays the
+//! fixed price straight into the accept payment account and takes the items of the config out of
+//! the vault in the same instruction, so there are no bids to claim or redeem afterwards.
+//!
+//! A limited edition item is an authorization token that is redeemed with the token metadata
+//! program as soon as the buyer holds it, minting them the edition onto a fresh mint of their own.
+//! This is what lets an open edition be sold off a listing, one edition per winning config.
+//!
+//! The authority of the auction manager takes unsold items back through here as well, without
+//! paying, and keeps the authorization tokens of any limited editions as they are. Those configs
+//! are left with no money pushed to the accept payment account, which is what EmptyPaymentAccount
+//! goes by to know nothing is owed on them.
+
+use {
+    crate::{
+        error::MetaplexError,
+        state::{AuctionManager, AuctionManagerStatus, Store, WinningConfigType, PREFIX},
+        utils::{
+            assert_derivation, assert_initialized, assert_owned_by, assert_rent_exempt,
+            assert_signer, assert_store_safety_vault_manager_match, mint_edition_via_token,
+            spl_token_transfer, transfer_safety_deposit_box_items,
+        },
+    },
+    borsh::BorshSerialize,
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        rent::Rent,
+        sysvar::Sysvar,
+    },
+    spl_token::state::Account,
+    spl_token_vault::state::SafetyDepositBox,
+};
+
+/// Number of accounts passed for each item of the winning config being bought.
+pub const BUY_FIXED_PRICE_ACCOUNTS_PER_ITEM: usize = 3;
+
+/// Number of further accounts passed for a limited edition item, to mint the edition with.
+pub const BUY_FIXED_PRICE_ACCOUNTS_PER_LIMITED_EDITION: usize = 7;
+
+pub fn process_buy_fixed_price<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let auction_manager_info = next_account_info(account_info_iter)?;
+    let store_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let fraction_mint_info = next_account_info(account_info_iter)?;
+    let accept_payment_info = next_account_info(account_info_iter)?;
+    let payment_info = next_account_info(account_info_iter)?;
+    let buyer_info = next_account_info(account_info_iter)?;
+    let transfer_authority_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let token_vault_program_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let token_metadata_program_info = next_account_info(account_info_iter)?;
+    let system_info = next_account_info(account_info_iter)?;
+    let item_accounts = &accounts[13..];
+
+    let rent = &Rent::from_account_info(rent_info)?;
+    let mut auction_manager = AuctionManager::from_account_info(auction_manager_info)?;
+    let store = Store::from_account_info(store_info)?;
+
+    assert_signer(buyer_info)?;
+    assert_owned_by(auction_manager_info, program_id)?;
+    assert_owned_by(store_info, program_id)?;
+    assert_owned_by(vault_info, &store.token_vault_program)?;
+
+    let price = auction_manager
+        .settings
+        .fixed_price
+        .ok_or(MetaplexError::NotAFixedPriceListing)?;
+
+    if auction_manager.state.status != AuctionManagerStatus::Running {
+        return Err(MetaplexError::InvalidStatus.into());
+    }
+
+    if auction_manager.store != *store_info.key {
+        return Err(MetaplexError::AuctionManagerStoreMismatch.into());
+    }
+
+    if auction_manager.vault != *vault_info.key {
+        return Err(MetaplexError::AuctionManagerVaultMismatch.into());
+    }
+
+    if auction_manager.accept_payment != *accept_payment_info.key {
+        return Err(MetaplexError::AcceptPaymentMismatch.into());
+    }
+
+    if store.token_program != *token_program_info.key {
+        return Err(MetaplexError::AuctionManagerTokenProgramMismatch.into());
+    }
+
+    if store.token_vault_program != *token_vault_program_info.key {
+        return Err(MetaplexError::AuctionManagerTokenVaultProgramMismatch.into());
+    }
+
+    if store.token_metadata_program != *token_metadata_program_info.key {
+        return Err(MetaplexError::AuctionManagerTokenMetadataProgramMismatch.into());
+    }
+
+    // Configs go in order, skipping any the authority has already taken back.
+    let winning_index = auction_manager
+        .state
+        .winning_config_states
+        .iter()
+        .position(|state| {
+            !state.money_pushed_to_accept_payment && !state.items.iter().any(|item| item.claimed)
+        })
+        .ok_or(MetaplexError::FixedPriceListingSoldOut)?;
+    let winning_config = auction_manager.settings.winning_configs[winning_index].clone();
+    let taking_back = *buyer_info.key == auction_manager.authority;
+
+    // Limited editions are only minted for a buyer, so only they pass the accounts to mint with.
+    let mints_edition = |item_index: usize| {
+        !taking_back
+            && winning_config.items[item_index].winning_config_type
+                == WinningConfigType::LimitedEdition
+    };
+    let expected_accounts = (0..winning_config.items.len())
+        .map(|item_index| {
+            if mints_edition(item_index) {
+                BUY_FIXED_PRICE_ACCOUNTS_PER_ITEM + BUY_FIXED_PRICE_ACCOUNTS_PER_LIMITED_EDITION
+            } else {
+                BUY_FIXED_PRICE_ACCOUNTS_PER_ITEM
+            }
+        })
+        .sum::<usize>();
+    if item_accounts.len() != expected_accounts {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    if !taking_back {
+        spl_token_transfer(
+            payment_info.clone(),
+            accept_payment_info.clone(),
+            price,
+            buyer_info.clone(),
+            &[],
+            token_program_info.clone(),
+        )?;
+        auction_manager.state.winning_config_states[winning_index].money_pushed_to_accept_payment =
+            true;
+    }
+
+    let auction_bump_seed = assert_derivation(
+        program_id,
+        auction_manager_info,
+        &[PREFIX.as_bytes(), auction_manager.auction.as_ref()],
+    )?;
+
+    let auction_auth_seeds = &[
+        PREFIX.as_bytes(),
+        auction_manager.auction.as_ref(),
+        &[auction_bump_seed],
+    ];
+
+    let mut offset = 0;
+    for (item_index, item) in winning_config.items.iter().enumerate() {
+        let item_infos = &item_accounts[offset..];
+        offset += BUY_FIXED_PRICE_ACCOUNTS_PER_ITEM;
+        let safety_deposit_info = &item_infos[0];
+        let safety_deposit_token_store_info = &item_infos[1];
+        let destination_info = &item_infos[2];
+
+        assert_owned_by(safety_deposit_info, &store.token_vault_program)?;
+        assert_owned_by(safety_deposit_token_store_info, token_program_info.key)?;
+        assert_owned_by(destination_info, token_program_info.key)?;
+        assert_store_safety_vault_manager_match(
+            &auction_manager,
+            safety_deposit_info,
+            vault_info,
+            &store.token_vault_program,
+        )?;
+
+        let safety_deposit = SafetyDepositBox::from_account_info(safety_deposit_info)?;
+        if safety_deposit.order != item.safety_deposit_box_index {
+            return Err(MetaplexError::SafetyDepositIndexMismatch.into());
+        }
+
+        // Settings checks hold a limited edition to one authorization token, as only one is burned
+        // for the edition and any others would be left with the buyer.
+        if mints_edition(item_index) && item.amount != 1 {
+            return Err(MetaplexError::InvalidFixedPriceListing.into());
+        }
+
+        // Without a bid to go by, the items can only go to the buyer themselves.
+        let destination: Account = assert_initialized(destination_info)?;
+        if destination.owner != *buyer_info.key {
+            return Err(MetaplexError::IncorrectOwner.into());
+        }
+        // looking out for you!
+        assert_rent_exempt(rent, destination_info)?;
+
+        transfer_safety_deposit_box_items(
+            token_vault_program_info.clone(),
+            destination_info.clone(),
+            safety_deposit_info.clone(),
+            safety_deposit_token_store_info.clone(),
+            vault_info.clone(),
+            fraction_mint_info.clone(),
+            auction_manager_info.clone(),
+            transfer_authority_info.clone(),
+            rent_info.clone(),
+            item.amount as u64,
+            auction_auth_seeds,
+        )?;
+
+        if mints_edition(item_index) {
+            offset += BUY_FIXED_PRICE_ACCOUNTS_PER_LIMITED_EDITION;
+            let new_metadata_info = &item_infos[3];
+            let new_edition_info = &item_infos[4];
+            let master_edition_info = &item_infos[5];
+            let new_mint_info = &item_infos[6];
+            let printing_mint_info = &item_infos[7];
+            let master_metadata_info = &item_infos[8];
+            let master_update_authority_info = &item_infos[9];
+
+            if *printing_mint_info.key != safety_deposit.token_mint {
+                return Err(MetaplexError::SafetyDepositBoxMasterMintMismatch.into());
+            }
+
+            // The token metadata program checks the master edition and metadata against the
+            // printing mint, and burns the authorization token the buyer now holds.
+            mint_edition_via_token(
+                token_metadata_program_info.clone(),
+                new_metadata_info.clone(),
+                new_edition_info.clone(),
+                master_edition_info.clone(),
+                new_mint_info.clone(),
+                buyer_info.clone(),
+                printing_mint_info.clone(),
+                destination_info.clone(),
+                master_update_authority_info.clone(),
+                master_metadata_info.clone(),
+                token_program_info.clone(),
+                system_info.clone(),
+                rent_info.clone(),
+            )?;
+        }
+
+        auction_manager.state.winning_config_states[winning_index].items[item_index].claimed = true;
+    }
+
+    let open_claims = auction_manager
+        .state
+        .winning_config_states
+        .iter()
+        .any(|state| state.items.iter().any(|item| !item.claimed));
+    if !open_claims {
+        auction_manager.state.status = AuctionManagerStatus::Finished
+    }
+
+    auction_manager.serialize(&mut *auction_manager_info.data.borrow_mut())?;
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use {
+    crate::{
+        error::MetaplexError,
+        state::{AuctionManager, AuctionManagerStatus, Store, PREFIX},
+        utils::{
+            assert_authority_correct, assert_derivation, assert_owned_by, assert_signer,
+            cancel_auction,
+        },
+    },
+    borsh::BorshSerialize,
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        pubkey::Pubkey,
+    },
+};
+
+pub fn process_cancel_auction<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let auction_manager_info = next_account_info(account_info_iter)?;
+    let auction_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let store_info = next_account_info(account_info_iter)?;
+    let auction_program_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+    let auction_extended_info = next_account_info(account_info_iter)?;
+
+    assert_owned_by(auction_manager_info, program_id)?;
+    assert_owned_by(store_info, program_id)?;
+    assert_signer(authority_info)?;
+
+    let mut auction_manager = AuctionManager::from_account_info(auction_manager_info)?;
+    let store = Store::from_account_info(store_info)?;
+    assert_authority_correct(&auction_manager, authority_info)?;
+
+    // An auction manager that never got validated is taken apart with DecommissionAuctionManager.
+    if auction_manager.state.status != AuctionManagerStatus::Validated
+        && auction_manager.state.status != AuctionManagerStatus::Running
+    {
+        return Err(MetaplexError::InvalidStatus.into());
+    }
+
+    if auction_manager.store != *store_info.key {
+        return Err(MetaplexError::AuctionManagerStoreMismatch.into());
+    }
+
+    if *auction_program_info.key != store.auction_program {
+        return Err(MetaplexError::AuctionManagerAuctionProgramMismatch.into());
+    }
+
+    if auction_manager.auction != *auction_info.key {
+        return Err(MetaplexError::AuctionManagerAuctionMismatch.into());
+    }
+
+    let bump_seed = assert_derivation(
+        program_id,
+        auction_manager_info,
+        &[PREFIX.as_bytes(), auction_info.key.as_ref()],
+    )?;
+
+    let authority_seeds = &[PREFIX.as_bytes(), auction_info.key.as_ref(), &[bump_seed]];
+
+    // The auction program checks the auction can still be cancelled.
+    cancel_auction(
+        auction_manager.vault,
+        auction_info.clone(),
+        auction_extended_info.clone(),
+        auction_manager_info.clone(),
+        auction_program_info.clone(),
+        clock_info.clone(),
+        authority_seeds,
+    )?;
+
+    auction_manager.state.status = AuctionManagerStatus::Disbursing;
+
+    auction_manager.serialize(&mut *auction_manager_info.data.borrow_mut())?;
+
+    Ok(())
+}
@@ -2,7 +2,7 @@ use {
     crate::{
         error::MetaplexError,
         state::{AuctionManager, AuctionManagerStatus, Store, PREFIX},
-        utils::{assert_derivation, assert_owned_by},
+        utils::{assert_currency_accept_payment, assert_derivation, assert_owned_by},
     },
     borsh::BorshSerialize,
     solana_program::{
@@ -10,6 +10,7 @@ use {
         entrypoint::ProgramResult,
         program::invoke_signed,
         pubkey::Pubkey,
+        system_program,
     },
     spl_auction::{
         instruction::claim_bid_instruction,
@@ -29,6 +30,9 @@ pub fn issue_claim_bid<'a>(
     token_mint: AccountInfo<'a>,
     clock: AccountInfo<'a>,
     token_program: AccountInfo<'a>,
+    auction_extended: AccountInfo<'a>,
+    bidder_token: AccountInfo<'a>,
+    bidder_pot_payer: AccountInfo<'a>,
     vault: Pubkey,
     signer_seeds: &[&[u8]],
 ) -> ProgramResult {
@@ -40,6 +44,8 @@ pub fn issue_claim_bid<'a>(
             *bidder.key,
             *bidder_pot_token_acct.key,
             *token_mint.key,
+            *bidder_token.key,
+            *bidder_pot_payer.key,
             ClaimBidArgs { resource: vault },
         ),
         &[
@@ -53,6 +59,9 @@ pub fn issue_claim_bid<'a>(
             bidder_pot,
             accept_payment,
             token_program,
+            auction_extended,
+            bidder_token,
+            bidder_pot_payer,
         ],
         &[&signer_seeds],
     )?;
@@ -74,6 +83,9 @@ pub fn process_claim_bid(program_id: &Pubkey, accounts: &[AccountInfo]) -> Progr
     let auction_program_info = next_account_info(account_info_iter)?;
     let clock_info = next_account_info(account_info_iter)?;
     let token_program_info = next_account_info(account_info_iter)?;
+    let auction_extended_info = next_account_info(account_info_iter)?;
+    let bidder_token_info = next_account_info(account_info_iter)?;
+    let bidder_pot_payer_info = next_account_info(account_info_iter)?;
 
     let mut auction_manager = AuctionManager::from_account_info(auction_manager_info)?;
     let store = Store::from_account_info(store_info)?;
@@ -82,9 +94,17 @@ pub fn process_claim_bid(program_id: &Pubkey, accounts: &[AccountInfo]) -> Progr
     assert_owned_by(auction_info, &store.auction_program)?;
     assert_owned_by(auction_manager_info, program_id)?;
     assert_owned_by(accept_payment_info, &spl_token::id())?;
-    assert_owned_by(bidder_pot_token_info, &spl_token::id())?;
     assert_owned_by(bidder_pot_info, &store.auction_program)?;
-    assert_owned_by(token_mint_info, &spl_token::id())?;
+    // A native SOL pot holds the lamports bid itself, and stands in for its SPL account, while the
+    // system program stands in for the mint.
+    if auction.token_mint == system_program::id() {
+        if bidder_pot_token_info.key != bidder_pot_info.key {
+            return Err(MetaplexError::BidderPotTokenAccountMismatch.into());
+        }
+    } else {
+        assert_owned_by(bidder_pot_token_info, &spl_token::id())?;
+        assert_owned_by(token_mint_info, &spl_token::id())?;
+    }
     assert_owned_by(vault_info, &store.token_vault_program)?;
     assert_owned_by(store_info, program_id)?;
 
@@ -104,8 +124,14 @@ pub fn process_claim_bid(program_id: &Pubkey, accounts: &[AccountInfo]) -> Progr
         return Err(MetaplexError::AuctionManagerTokenProgramMismatch.into());
     }
 
+    // Bids in another currency than the auction's go to an account of the auction manager in that
+    // currency instead, the auction program checks the bid was paid in it.
     if auction_manager.accept_payment != *accept_payment_info.key {
-        return Err(MetaplexError::AcceptPaymentMismatch.into());
+        let accept_payment =
+            assert_currency_accept_payment(auction_manager_info, accept_payment_info)?;
+        if accept_payment.mint != *token_mint_info.key {
+            return Err(MetaplexError::AcceptPaymentMintMismatch.into());
+        }
     }
 
     if auction_manager.vault != *vault_info.key {
@@ -148,6 +174,9 @@ pub fn process_claim_bid(program_id: &Pubkey, accounts: &[AccountInfo]) -> Progr
         token_mint_info.clone(),
         clock_info.clone(),
         token_program_info.clone(),
+        auction_extended_info.clone(),
+        bidder_token_info.clone(),
+        bidder_pot_payer_info.clone(),
         *vault_info.key,
         authority_seeds,
     )?;
@@ -3,11 +3,14 @@ use solana_program::msg;
 use {
     crate::{
         error::MetaplexError,
-        instruction::EmptyPaymentAccountArgs,
-        state::{AuctionManager, Key, PayoutTicket, Store, MAX_PAYOUT_TICKET_SIZE, PREFIX},
+        instruction::{EmptyPaymentAccountArgs, EmptyPaymentAccountsArgs},
+        state::{
+            AuctionManager, ConversionRate, Key, PayoutTicket, Store, SwapPool,
+            MAX_PAYOUT_TICKET_SIZE, PREFIX,
+        },
         utils::{
-            assert_derivation, assert_initialized, assert_owned_by, assert_rent_exempt,
-            create_or_allocate_account_raw, spl_token_transfer,
+            assert_currency_accept_payment, assert_derivation, assert_initialized, assert_owned_by,
+            assert_rent_exempt, assert_signer, create_or_allocate_account_raw, spl_token_transfer,
         },
     },
     borsh::BorshSerialize,
@@ -20,7 +23,7 @@ use {
         rent::Rent,
         sysvar::Sysvar,
     },
-    spl_auction::processor::AuctionData,
+    spl_auction::processor::{AuctionData, AuctionDataExtended, BidCurrency},
     spl_token::state::Account,
     spl_token_metadata::state::{MasterEdition, Metadata},
     spl_token_vault::state::SafetyDepositBox,
@@ -60,6 +63,31 @@ fn assert_winning_config_safety_deposit_validity(
     Ok(())
 }
 
+fn assert_associated_token_account(
+    owner: &Pubkey,
+    destination_info: &AccountInfo,
+    destination: &Account,
+    store: &Store,
+) -> ProgramResult {
+    if destination.owner != *owner {
+        return Err(MetaplexError::IncorrectOwner.into());
+    }
+
+    // Let's avoid importing the entire ATA library here just to get a helper and an ID.
+    // Assert destination is, in fact, an ATA.
+    assert_derivation(
+        &Pubkey::from_str("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL").unwrap(),
+        destination_info,
+        &[
+            owner.as_ref(),
+            store.token_program.as_ref(),
+            destination.mint.as_ref(),
+        ],
+    )?;
+
+    Ok(())
+}
+
 fn assert_destination_ownership_validity(
     auction_manager: &AuctionManager,
     metadata: &Metadata,
@@ -67,25 +95,29 @@ fn assert_destination_ownership_validity(
     destination: &Account,
     store: &Store,
     creator_index: Option<u8>,
+    payout_split_index: Option<u8>,
 ) -> ProgramResult {
-    if let Some(creators) = &metadata.data.creators {
+    if let Some(split_index) = payout_split_index {
+        // A payout split recipient takes from the auctioneer's share, never from a creator's.
+        if creator_index.is_some() {
+            return Err(MetaplexError::InvalidPayoutSplitIndex.into());
+        }
+        let recipient = match &auction_manager.settings.payout_split {
+            Some(split) if (split_index as usize) < split.recipients.len() => {
+                split.recipients[split_index as usize]
+            }
+            _ => return Err(MetaplexError::InvalidPayoutSplitIndex.into()),
+        };
+        assert_associated_token_account(&recipient.address, destination_info, destination, store)?;
+    } else if let Some(creators) = &metadata.data.creators {
         if let Some(index) = creator_index {
             if (index as usize) < creators.len() {
                 let creator = &creators[index as usize];
-                if destination.owner != creator.address {
-                    return Err(MetaplexError::IncorrectOwner.into());
-                }
-
-                // Let's avoid importing the entire ATA library here just to get a helper and an ID.
-                // Assert destination is, in fact, an ATA.
-                assert_derivation(
-                    &Pubkey::from_str("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL").unwrap(),
+                assert_associated_token_account(
+                    &creator.address,
                     destination_info,
-                    &[
-                        creator.address.as_ref(),
-                        &store.token_program.as_ref(),
-                        &destination.mint.as_ref(),
-                    ],
+                    destination,
+                    store,
                 )?;
             } else {
                 return Err(MetaplexError::InvalidCreatorIndex.into());
@@ -108,13 +140,17 @@ fn assert_destination_ownership_validity(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn calculate_owed_amount(
     auction_manager: &AuctionManager,
-    auction: &AuctionData,
+    sale: &Sale,
     metadata: &Metadata,
+    accept_payment: &Account,
+    bid_currency: &Option<BidCurrency>,
     winning_config_index: &Option<u8>,
     winning_config_item_index: &Option<u8>,
     creator_index: &Option<u8>,
+    payout_split_index: &Option<u8>,
 ) -> Result<u64, ProgramError> {
     let primary_sale_happened = match winning_config_index {
         Some(val) => {
@@ -135,16 +171,48 @@ fn calculate_owed_amount(
         }
     };
 
-    let mut amount_available_to_split: u128 = match winning_config_index {
-        Some(index) => auction.bid_state.amount(*index as usize) as u128,
-        None => {
-            // this means the amount owed is the amount collected from participation nft bids.
-            if let Some(state) = &auction_manager.state.participation_state {
+    let mut amount_available_to_split: u128 = match (winning_config_index, bid_currency, sale) {
+        // In an auction accepting other currencies each payment account only pays out the bids
+        // claimed into it, which are owed whole.
+        (Some(_), Some(currency), _) => {
+            if currency.mint == accept_payment.mint {
+                currency.amount as u128
+            } else {
+                0
+            }
+        }
+        // In a uniform price auction the winner only paid the clearing price, not their bid.
+        (
+            Some(index),
+            None,
+            Sale::Auction {
+                auction,
+                auction_extended,
+            },
+        ) => auction.settlement_amount(*index as usize, auction_extended) as u128,
+        // Only configs that were bought paid anything, those the authority took back did not.
+        (Some(index), None, Sale::FixedPrice(price)) => {
+            if auction_manager.state.winning_config_states[*index as usize]
+                .money_pushed_to_accept_payment
+            {
+                *price as u128
+            } else {
+                0
+            }
+        }
+        (None, _, Sale::Auction { auction, .. }) => {
+            // this means the amount owed is the amount collected from participation nft bids,
+            // which are only ever paid in the auction's token mint.
+            if accept_payment.mint != auction.token_mint {
+                0
+            } else if let Some(state) = &auction_manager.state.participation_state {
                 state.collected_to_accept_payment as u128
             } else {
                 0
             }
         }
+        // A fixed price listing has no participation.
+        (None, _, Sale::FixedPrice(_)) => 0,
     };
 
     if winning_config_index.is_some() {
@@ -159,6 +227,9 @@ fn calculate_owed_amount(
     if creator_index.is_some() {
         msg!("Creator index {:?}", creator_index.unwrap());
     }
+    if payout_split_index.is_some() {
+        msg!("Payout split index {:?}", payout_split_index.unwrap());
+    }
 
     msg!("Amount available to split {:?}", amount_available_to_split);
     let numerator: u128 = match creator_index {
@@ -193,7 +264,14 @@ fn calculate_owed_amount(
             Some(creators) => (creators[*index as usize].share as u128) * 100u128,
             None => return Err(MetaplexError::CreatorIndexExpected.into()),
         },
-        None => 10000,
+        // The same goes for the auctioneer's share when a payout split divides it further, the
+        // recipients each take their basis points and the auctioneer keeps the rest.
+        None => match (payout_split_index, &auction_manager.settings.payout_split) {
+            (Some(index), Some(split)) => split.recipients[*index as usize].basis_points as u128,
+            (None, Some(split)) => (10000 - split.total_basis_points()) as u128,
+            (None, None) => 10000,
+            (Some(_), None) => return Err(MetaplexError::InvalidPayoutSplitIndex.into()),
+        },
     };
 
     msg!("Artist further multiplier {:?}", artist_further_multiplier);
@@ -259,35 +337,141 @@ fn calculate_owed_amount(
     Ok(final_amount_available_to_split as u64)
 }
 
-pub fn process_empty_payment_account(
+/// Number of accounts passed for each payout in an EmptyPaymentAccounts batch.
+pub const EMPTY_PAYMENT_ACCOUNTS_PER_PAYOUT: usize = 6;
+
+/// Number of further accounts passed for a payout with a swap route in an EmptyPaymentAccounts
+/// batch.
+pub const EMPTY_PAYMENT_ACCOUNTS_PER_SWAP_ROUTE: usize = 5;
+
+/// The accounts and state shared by every payout out of one accept payment account, loaded and
+/// checked once so a batch of payouts doesn't pay for it again and again.
+struct Settlement<'a, 'b: 'a> {
+    accept_payment_info: &'a AccountInfo<'b>,
+    auction_manager_info: &'a AccountInfo<'b>,
+    payer_info: &'a AccountInfo<'b>,
+    vault_info: &'a AccountInfo<'b>,
+    auction_info: &'a AccountInfo<'b>,
+    token_program_info: &'a AccountInfo<'b>,
+    system_info: &'a AccountInfo<'b>,
+    rent_info: &'a AccountInfo<'b>,
+    rent: Rent,
+    auction_manager: AuctionManager,
+    store: Store,
+    sale: Sale,
+    accept_payment: Account,
+    foreign_accept_payment: bool,
+}
+
+/// What the proceeds in the accept payment account were taken in.
+enum Sale {
+    Auction {
+        auction: Box<AuctionData>,
+        auction_extended: AuctionDataExtended,
+    },
+    /// A fixed price listing, where each winning config bought paid in the fixed price.
+    FixedPrice(u64),
+}
+
+/// The accounts of a single payout, to one creator, payout split recipient or the auctioneer.
+struct PayoutAccounts<'a, 'b: 'a> {
+    destination_info: &'a AccountInfo<'b>,
+    payout_ticket_info: &'a AccountInfo<'b>,
+    metadata_info: &'a AccountInfo<'b>,
+    master_edition_info: &'a AccountInfo<'b>,
+    safety_deposit_info: &'a AccountInfo<'b>,
+    bid_currency_info: Option<&'a AccountInfo<'b>>,
+    swap_route: Option<SwapRouteAccounts<'a, 'b>>,
+}
+
+/// The accounts of a swap pool a payout is converted through, for a recipient paid in another
+/// currency than the accept payment account's.
+struct SwapRouteAccounts<'a, 'b: 'a> {
+    swap_pool_info: &'a AccountInfo<'b>,
+    conversion_rate_info: &'a AccountInfo<'b>,
+    from_holding_info: &'a AccountInfo<'b>,
+    to_holding_info: &'a AccountInfo<'b>,
+    recipient_info: &'a AccountInfo<'b>,
+}
+
+/// A swap route checked against the payout it converts, with the bump of its swap pool.
+struct SwapRoute {
+    swap_pool: SwapPool,
+    conversion_rate: ConversionRate,
+    bump: u8,
+}
+
+fn load_swap_route(
     program_id: &Pubkey,
-    accounts: &[AccountInfo],
-    args: EmptyPaymentAccountArgs,
-) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
-    let accept_payment_info = next_account_info(account_info_iter)?;
-    let destination_info = next_account_info(account_info_iter)?;
-    let auction_manager_info = next_account_info(account_info_iter)?;
-    let payout_ticket_info = next_account_info(account_info_iter)?;
-    let payer_info = next_account_info(account_info_iter)?;
-    let metadata_info = next_account_info(account_info_iter)?;
-    let master_edition_info = next_account_info(account_info_iter)?;
-    let safety_deposit_info = next_account_info(account_info_iter)?;
-    let store_info = next_account_info(account_info_iter)?;
-    let vault_info = next_account_info(account_info_iter)?;
-    let auction_info = next_account_info(account_info_iter)?;
-    let token_program_info = next_account_info(account_info_iter)?;
-    let system_info = next_account_info(account_info_iter)?;
-    let rent_info = next_account_info(account_info_iter)?;
+    route: &SwapRouteAccounts,
+    accept_payment: &Account,
+    destination: &Account,
+) -> Result<SwapRoute, ProgramError> {
+    // Only the recipient gets to choose to be paid in another currency.
+    assert_signer(route.recipient_info)?;
+    if *route.recipient_info.key != destination.owner {
+        return Err(MetaplexError::IncorrectOwner.into());
+    }
+
+    assert_owned_by(route.swap_pool_info, program_id)?;
+    assert_owned_by(route.conversion_rate_info, program_id)?;
+
+    let swap_pool = SwapPool::from_account_info(route.swap_pool_info)?;
+    if swap_pool.key != Key::SwapPoolV1
+        || swap_pool.conversion_rate != *route.conversion_rate_info.key
+        || swap_pool.from_holding != *route.from_holding_info.key
+        || swap_pool.to_holding != *route.to_holding_info.key
+    {
+        return Err(MetaplexError::SwapPoolMismatch.into());
+    }
+
+    let conversion_rate = ConversionRate::from_account_info(route.conversion_rate_info)?;
+    if conversion_rate.key != Key::ConversionRateV1 {
+        return Err(MetaplexError::InvalidConversionRate.into());
+    }
+    if conversion_rate.mint != accept_payment.mint {
+        return Err(MetaplexError::SwapPoolMismatch.into());
+    }
+    if conversion_rate.price_mint != destination.mint {
+        return Err(MetaplexError::AcceptPaymentMintMismatch.into());
+    }
+
+    let bump = assert_derivation(
+        program_id,
+        route.swap_pool_info,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            swap_pool.authority.as_ref(),
+            swap_pool.conversion_rate.as_ref(),
+        ],
+    )?;
 
-    let rent = &Rent::from_account_info(&rent_info)?;
+    Ok(SwapRoute {
+        swap_pool,
+        conversion_rate,
+        bump,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn load_settlement<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accept_payment_info: &'a AccountInfo<'b>,
+    auction_manager_info: &'a AccountInfo<'b>,
+    payer_info: &'a AccountInfo<'b>,
+    store_info: &'a AccountInfo<'b>,
+    vault_info: &'a AccountInfo<'b>,
+    auction_info: &'a AccountInfo<'b>,
+    token_program_info: &'a AccountInfo<'b>,
+    system_info: &'a AccountInfo<'b>,
+    rent_info: &'a AccountInfo<'b>,
+    auction_extended_info: &'a AccountInfo<'b>,
+) -> Result<Settlement<'a, 'b>, ProgramError> {
+    let rent = Rent::from_account_info(rent_info)?;
 
     let auction_manager = AuctionManager::from_account_info(auction_manager_info)?;
     let store = Store::from_account_info(store_info)?;
-    let safety_deposit = SafetyDepositBox::from_account_info(safety_deposit_info)?;
-    let metadata = Metadata::from_account_info(metadata_info)?;
-    let auction = AuctionData::from_account_info(auction_info)?;
-    let destination: Account = assert_initialized(destination_info)?;
     let accept_payment: Account = assert_initialized(accept_payment_info)?;
 
     if auction_manager.store != *store_info.key {
@@ -299,6 +483,54 @@ pub fn process_empty_payment_account(
         accept_payment.amount
     );
 
+    if *token_program_info.key != store.token_program {
+        return Err(MetaplexError::AuctionManagerTokenProgramMismatch.into());
+    }
+
+    assert_owned_by(auction_manager_info, program_id)?;
+    assert_owned_by(accept_payment_info, token_program_info.key)?;
+    assert_owned_by(store_info, program_id)?;
+    assert_owned_by(vault_info, &store.token_vault_program)?;
+
+    // further assert that the vault and auction are correctly matched to the auction manager
+    if auction_manager.vault != *vault_info.key {
+        return Err(MetaplexError::AuctionManagerVaultMismatch.into());
+    }
+
+    if auction_manager.auction != *auction_info.key {
+        return Err(MetaplexError::AuctionManagerAuctionMismatch.into());
+    }
+
+    let foreign_accept_payment = auction_manager.accept_payment != *accept_payment_info.key;
+
+    // A fixed price listing takes each payment straight into the accept payment account, so
+    // there are no bids to wait on, and nothing to pay out of any other account.
+    if let Some(price) = auction_manager.settings.fixed_price {
+        if foreign_accept_payment {
+            return Err(MetaplexError::AcceptPaymentMismatch.into());
+        }
+
+        return Ok(Settlement {
+            accept_payment_info,
+            auction_manager_info,
+            payer_info,
+            vault_info,
+            auction_info,
+            token_program_info,
+            system_info,
+            rent_info,
+            rent,
+            auction_manager,
+            store,
+            sale: Sale::FixedPrice(price),
+            accept_payment,
+            foreign_accept_payment,
+        });
+    }
+
+    let auction = AuctionData::from_account_info(auction_info)?;
+    let auction_extended = AuctionDataExtended::from_account_info(auction_extended_info)?;
+
     // Before continuing further, assert all bid monies have been pushed to the main escrow
     // account so that we have a complete (less the unredeemed participation nft bids) accounting
     // to work with
@@ -308,29 +540,80 @@ pub fn process_empty_payment_account(
         }
     }
 
-    if *token_program_info.key != store.token_program {
-        return Err(MetaplexError::AuctionManagerTokenProgramMismatch.into());
+    assert_owned_by(auction_info, &store.auction_program)?;
+    assert_owned_by(auction_extended_info, &store.auction_program)?;
+    assert_derivation(
+        &store.auction_program,
+        auction_extended_info,
+        &[
+            spl_auction::PREFIX.as_bytes(),
+            store.auction_program.as_ref(),
+            vault_info.key.as_ref(),
+            spl_auction::EXTENDED.as_bytes(),
+        ],
+    )?;
+
+    // make sure the accept payment account is right, an auction accepting other currencies also
+    // pays out of the accounts the bids in those were claimed into.
+    if foreign_accept_payment {
+        if auction_extended.exchange_rates.is_empty() {
+            return Err(MetaplexError::AcceptPaymentMismatch.into());
+        }
+        assert_currency_accept_payment(auction_manager_info, accept_payment_info)?;
     }
 
-    assert_owned_by(auction_manager_info, program_id)?;
+    Ok(Settlement {
+        accept_payment_info,
+        auction_manager_info,
+        payer_info,
+        vault_info,
+        auction_info,
+        token_program_info,
+        system_info,
+        rent_info,
+        rent,
+        auction_manager,
+        store,
+        sale: Sale::Auction {
+            auction: Box::new(auction),
+            auction_extended,
+        },
+        accept_payment,
+        foreign_accept_payment,
+    })
+}
+
+fn pay_out<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    settlement: &Settlement<'a, 'b>,
+    payout: &PayoutAccounts<'a, 'b>,
+    args: &EmptyPaymentAccountArgs,
+) -> ProgramResult {
+    let auction_manager = &settlement.auction_manager;
+    let store = &settlement.store;
+    let auction_manager_info = settlement.auction_manager_info;
+    let destination_info = payout.destination_info;
+    let payout_ticket_info = payout.payout_ticket_info;
+    let safety_deposit_info = payout.safety_deposit_info;
+
+    let safety_deposit = SafetyDepositBox::from_account_info(safety_deposit_info)?;
+    let metadata = Metadata::from_account_info(payout.metadata_info)?;
+    let destination: Account = assert_initialized(destination_info)?;
+
     if !payout_ticket_info.data_is_empty() {
         assert_owned_by(payout_ticket_info, program_id)?;
     }
-    assert_owned_by(destination_info, token_program_info.key)?;
-    assert_owned_by(accept_payment_info, token_program_info.key)?;
-    assert_owned_by(metadata_info, &store.token_metadata_program)?;
-    if *master_edition_info.key != solana_program::system_program::id() {
-        assert_owned_by(master_edition_info, &store.token_metadata_program)?;
+    assert_owned_by(destination_info, settlement.token_program_info.key)?;
+    assert_owned_by(payout.metadata_info, &store.token_metadata_program)?;
+    if *payout.master_edition_info.key != solana_program::system_program::id() {
+        assert_owned_by(payout.master_edition_info, &store.token_metadata_program)?;
     }
     assert_owned_by(safety_deposit_info, &store.token_vault_program)?;
-    assert_owned_by(store_info, program_id)?;
-    assert_owned_by(vault_info, &store.token_vault_program)?;
-    assert_owned_by(auction_info, &store.auction_program)?;
-    assert_rent_exempt(rent, destination_info)?;
+    assert_rent_exempt(&settlement.rent, destination_info)?;
 
     // Assert the winning config points to the safety deposit you sent up
     assert_winning_config_safety_deposit_validity(
-        &auction_manager,
+        auction_manager,
         &safety_deposit,
         args.winning_config_index,
         args.winning_config_item_index,
@@ -339,31 +622,24 @@ pub fn process_empty_payment_account(
     // assert the destination account matches the ownership expected to creator or auction manager authority
     // given in the argument's creator index
     assert_destination_ownership_validity(
-        &auction_manager,
+        auction_manager,
         &metadata,
         destination_info,
         &destination,
-        &store,
+        store,
         args.creator_index,
+        args.payout_split_index,
     )?;
 
-    // further assert that the vault and safety deposit are correctly matched to the auction manager
-    if auction_manager.vault != *vault_info.key {
-        return Err(MetaplexError::AuctionManagerVaultMismatch.into());
-    }
-
-    if auction_manager.auction != *auction_info.key {
-        return Err(MetaplexError::AuctionManagerAuctionMismatch.into());
-    }
-
-    if safety_deposit.vault != *vault_info.key {
+    if safety_deposit.vault != *settlement.vault_info.key {
         return Err(MetaplexError::SafetyDepositBoxVaultMismatch.into());
     }
 
     // assert that the metadata sent up is the metadata in the safety deposit
     if metadata.mint != safety_deposit.token_mint {
         // Could be a limited edition, in which case printing tokens or auth tokens were offered, not the original.
-        let master_edition: MasterEdition = MasterEdition::from_account_info(master_edition_info)?;
+        let master_edition: MasterEdition =
+            MasterEdition::from_account_info(payout.master_edition_info)?;
         if master_edition.printing_mint != safety_deposit.token_mint
             && master_edition.one_time_printing_authorization_mint != safety_deposit.token_mint
         {
@@ -371,14 +647,55 @@ pub fn process_empty_payment_account(
         }
     }
 
-    // make sure the accept payment account is right
-    if auction_manager.accept_payment != *accept_payment_info.key {
-        return Err(MetaplexError::AcceptPaymentMismatch.into());
-    }
+    // A winner in an auction accepting other currencies is paid out according to the record of
+    // the currency they bid in.
+    let bid_currency = match (args.winning_config_index, &settlement.sale) {
+        (
+            Some(index),
+            Sale::Auction {
+                auction,
+                auction_extended,
+            },
+        ) if !auction_extended.exchange_rates.is_empty() => {
+            let bid_currency_info = payout
+                .bid_currency_info
+                .ok_or(MetaplexError::BidCurrencyMismatch)?;
+            let winner = auction
+                .winner_at(index as usize)
+                .ok_or(MetaplexError::InvalidWinningConfigIndex)?;
+            assert_owned_by(bid_currency_info, &store.auction_program)?;
+            assert_derivation(
+                &store.auction_program,
+                bid_currency_info,
+                &[
+                    spl_auction::PREFIX.as_bytes(),
+                    store.auction_program.as_ref(),
+                    settlement.auction_info.key.as_ref(),
+                    winner.as_ref(),
+                    spl_auction::CURRENCY.as_bytes(),
+                ],
+            )
+            .map_err(|_| MetaplexError::BidCurrencyMismatch)?;
+            Some(BidCurrency::from_account_info(bid_currency_info)?)
+        }
+        _ => None,
+    };
 
-    if destination.mint != accept_payment.mint {
-        return Err(MetaplexError::AcceptPaymentMintMismatch.into());
-    }
+    // A payout converted through a swap pool lands in the pool's price mint instead.
+    let swap_route = match &payout.swap_route {
+        Some(route) => Some(load_swap_route(
+            program_id,
+            route,
+            &settlement.accept_payment,
+            &destination,
+        )?),
+        None => {
+            if destination.mint != settlement.accept_payment.mint {
+                return Err(MetaplexError::SwapRouteRequired.into());
+            }
+            None
+        }
+    };
 
     let winning_config_index_key: String = match args.winning_config_index {
         Some(val) => val.to_string(),
@@ -390,9 +707,19 @@ pub fn process_empty_payment_account(
         None => "0".to_owned(),
     };
 
-    let creator_index_key: String = match args.creator_index {
-        Some(val) => val.to_string(),
-        None => "auctioneer".to_owned(),
+    let creator_index_key: String = match (args.creator_index, args.payout_split_index) {
+        (Some(val), _) => val.to_string(),
+        (None, Some(val)) => format!("split-{}", val),
+        (None, None) => "auctioneer".to_owned(),
+    };
+
+    // Payouts in another currency are kept on tickets of their own. An empty seed leaves the
+    // address of the ticket for the token mint as it always was. A converted payout is still paid
+    // out of the accept payment account, so it shares the ticket of an unconverted one.
+    let currency_key: &[u8] = if settlement.foreign_accept_payment {
+        settlement.accept_payment.mint.as_ref()
+    } else {
+        &[]
     };
 
     let payout_bump = assert_derivation(
@@ -406,6 +733,7 @@ pub fn process_empty_payment_account(
             creator_index_key.as_bytes(),
             &safety_deposit_info.key.as_ref(),
             &destination.owner.as_ref(),
+            currency_key,
         ],
     )?;
 
@@ -417,6 +745,7 @@ pub fn process_empty_payment_account(
         creator_index_key.as_bytes(),
         &safety_deposit_info.key.as_ref(),
         &destination.owner.as_ref(),
+        currency_key,
         &[payout_bump],
     ];
 
@@ -424,9 +753,9 @@ pub fn process_empty_payment_account(
         create_or_allocate_account_raw(
             *program_id,
             payout_ticket_info,
-            rent_info,
-            system_info,
-            payer_info,
+            settlement.rent_info,
+            settlement.system_info,
+            settlement.payer_info,
             MAX_PAYOUT_TICKET_SIZE,
             payout_seeds,
         )?;
@@ -437,12 +766,15 @@ pub fn process_empty_payment_account(
     payout_ticket.key = Key::PayoutTicketV1;
 
     let amount = calculate_owed_amount(
-        &auction_manager,
-        &auction,
+        auction_manager,
+        &settlement.sale,
         &metadata,
+        &settlement.accept_payment,
+        &bid_currency,
         &args.winning_config_index,
         &args.winning_config_item_index,
         &args.creator_index,
+        &args.payout_split_index,
     )?;
 
     let final_amount = amount
@@ -467,17 +799,212 @@ pub fn process_empty_payment_account(
             &[bump_seed],
         ];
 
-        spl_token_transfer(
-            accept_payment_info.clone(),
-            destination_info.clone(),
-            final_amount,
-            auction_manager_info.clone(),
-            authority_seeds,
-            token_program_info.clone(),
-        )?;
+        match (&payout.swap_route, &swap_route) {
+            (Some(route_accounts), Some(route)) => {
+                let converted_amount = route.conversion_rate.convert(final_amount)?;
+                if converted_amount == 0 {
+                    return Err(MetaplexError::ConvertedAmountTooSmall.into());
+                }
+
+                // The pool takes the payout, and pays its converted value out of its liquidity.
+                spl_token_transfer(
+                    settlement.accept_payment_info.clone(),
+                    route_accounts.from_holding_info.clone(),
+                    final_amount,
+                    auction_manager_info.clone(),
+                    authority_seeds,
+                    settlement.token_program_info.clone(),
+                )?;
+
+                let swap_pool_seeds = &[
+                    PREFIX.as_bytes(),
+                    program_id.as_ref(),
+                    route.swap_pool.authority.as_ref(),
+                    route.swap_pool.conversion_rate.as_ref(),
+                    &[route.bump],
+                ];
+
+                spl_token_transfer(
+                    route_accounts.to_holding_info.clone(),
+                    destination_info.clone(),
+                    converted_amount,
+                    route_accounts.swap_pool_info.clone(),
+                    swap_pool_seeds,
+                    settlement.token_program_info.clone(),
+                )?;
+            }
+            _ => {
+                spl_token_transfer(
+                    settlement.accept_payment_info.clone(),
+                    destination_info.clone(),
+                    final_amount,
+                    auction_manager_info.clone(),
+                    authority_seeds,
+                    settlement.token_program_info.clone(),
+                )?;
+            }
+        }
     }
 
     payout_ticket.serialize(&mut *payout_ticket_info.data.borrow_mut())?;
 
     Ok(())
 }
+
+pub fn process_empty_payment_account<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: EmptyPaymentAccountArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let accept_payment_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let auction_manager_info = next_account_info(account_info_iter)?;
+    let payout_ticket_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let metadata_info = next_account_info(account_info_iter)?;
+    let master_edition_info = next_account_info(account_info_iter)?;
+    let safety_deposit_info = next_account_info(account_info_iter)?;
+    let store_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let auction_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let system_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let auction_extended_info = next_account_info(account_info_iter)?;
+    let bid_currency_info = next_account_info(account_info_iter)
+        .ok()
+        .filter(|info| *info.key != solana_program::system_program::id());
+    let swap_route = match next_account_info(account_info_iter) {
+        Ok(swap_pool_info) => Some(SwapRouteAccounts {
+            swap_pool_info,
+            conversion_rate_info: next_account_info(account_info_iter)?,
+            from_holding_info: next_account_info(account_info_iter)?,
+            to_holding_info: next_account_info(account_info_iter)?,
+            recipient_info: next_account_info(account_info_iter)?,
+        }),
+        Err(_) => None,
+    };
+
+    let settlement = load_settlement(
+        program_id,
+        accept_payment_info,
+        auction_manager_info,
+        payer_info,
+        store_info,
+        vault_info,
+        auction_info,
+        token_program_info,
+        system_info,
+        rent_info,
+        auction_extended_info,
+    )?;
+
+    pay_out(
+        program_id,
+        &settlement,
+        &PayoutAccounts {
+            destination_info,
+            payout_ticket_info,
+            metadata_info,
+            master_edition_info,
+            safety_deposit_info,
+            bid_currency_info,
+            swap_route,
+        },
+        &args,
+    )
+}
+
+/// Works through a batch of payouts out of one accept payment account, as EmptyPaymentAccount
+/// would one at a time. Each payout keeps its own PayoutTicket, so a batch that is resent, or that
+/// overlaps with payouts already made, only ever pays out what is still owed.
+pub fn process_empty_payment_accounts<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: EmptyPaymentAccountsArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let accept_payment_info = next_account_info(account_info_iter)?;
+    let auction_manager_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let store_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let auction_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let system_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let auction_extended_info = next_account_info(account_info_iter)?;
+    let payout_accounts = &accounts[10..];
+
+    let expected_accounts: usize = args
+        .payouts
+        .iter()
+        .map(|payout| match payout.swap_route {
+            true => EMPTY_PAYMENT_ACCOUNTS_PER_PAYOUT + EMPTY_PAYMENT_ACCOUNTS_PER_SWAP_ROUTE,
+            false => EMPTY_PAYMENT_ACCOUNTS_PER_PAYOUT,
+        })
+        .sum();
+    if args.payouts.is_empty() || payout_accounts.len() != expected_accounts {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let settlement = load_settlement(
+        program_id,
+        accept_payment_info,
+        auction_manager_info,
+        payer_info,
+        store_info,
+        vault_info,
+        auction_info,
+        token_program_info,
+        system_info,
+        rent_info,
+        auction_extended_info,
+    )?;
+
+    let mut remaining = payout_accounts;
+    for payout_args in args.payouts.iter() {
+        let (payout, rest) = remaining.split_at(EMPTY_PAYMENT_ACCOUNTS_PER_PAYOUT);
+        remaining = rest;
+
+        // Payouts that have no bid currency record to pass it fill its place with the system
+        // program, as with a missing master edition.
+        let bid_currency_info = if *payout[5].key == solana_program::system_program::id() {
+            None
+        } else {
+            Some(&payout[5])
+        };
+
+        let swap_route = if payout_args.swap_route {
+            let (route, rest) = remaining.split_at(EMPTY_PAYMENT_ACCOUNTS_PER_SWAP_ROUTE);
+            remaining = rest;
+            Some(SwapRouteAccounts {
+                swap_pool_info: &route[0],
+                conversion_rate_info: &route[1],
+                from_holding_info: &route[2],
+                to_holding_info: &route[3],
+                recipient_info: &route[4],
+            })
+        } else {
+            None
+        };
+
+        pay_out(
+            program_id,
+            &settlement,
+            &PayoutAccounts {
+                destination_info: &payout[0],
+                payout_ticket_info: &payout[1],
+                metadata_info: &payout[2],
+                master_edition_info: &payout[3],
+                safety_deposit_info: &payout[4],
+                bid_currency_info,
+                swap_route,
+            },
+            &payout_args.payout,
+        )?;
+    }
+
+    Ok(())
+}
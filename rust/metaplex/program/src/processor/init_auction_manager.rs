@@ -3,10 +3,11 @@ use {
         error::MetaplexError,
         state::{
             AuctionManager, AuctionManagerSettings, AuctionManagerStatus, Key, ParticipationState,
-            Store, WinningConfigState, WinningConfigStateItem, MAX_AUCTION_MANAGER_SIZE, PREFIX,
+            Store, MAX_AUCTION_MANAGER_SIZE, PREFIX,
         },
         utils::{
-            assert_derivation, assert_initialized, assert_owned_by, create_or_allocate_account_raw,
+            assert_derivation, assert_initialized, assert_owned_by, assert_signer,
+            common_settings_checks, create_or_allocate_account_raw,
         },
     },
     borsh::BorshSerialize,
@@ -16,11 +17,23 @@ use {
         program_option::COption,
         pubkey::Pubkey,
     },
-    spl_auction::processor::{AuctionData, AuctionState},
+    spl_auction::processor::{AuctionData, AuctionState, BidState},
     spl_token::state::Account,
     spl_token_vault::state::{Vault, VaultState},
 };
 
+/// A fixed price listing has no auction account, so the account in its place is a fresh key the
+/// auction manager is derived from, signing so that nobody else can take it.
+fn assert_fixed_price_listing_key(listing_info: &AccountInfo) -> ProgramResult {
+    assert_signer(listing_info)?;
+
+    if !listing_info.data_is_empty() {
+        return Err(MetaplexError::InvalidFixedPriceListing.into());
+    }
+
+    Ok(())
+}
+
 pub fn process_init_auction_manager(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -39,48 +52,65 @@ pub fn process_init_auction_manager(
     let rent_info = next_account_info(account_info_iter)?;
 
     let vault = Vault::from_account_info(vault_info)?;
-    let auction = AuctionData::from_account_info(auction_info)?;
     let accept_payment: Account = assert_initialized(accept_payment_info)?;
     // Assert it is real
     let store = Store::from_account_info(store_info)?;
 
     assert_owned_by(vault_info, &store.token_vault_program)?;
-    assert_owned_by(auction_info, &store.auction_program)?;
     assert_owned_by(store_info, program_id)?;
     assert_owned_by(accept_payment_info, &store.token_program)?;
 
-    if auction.state != AuctionState::Created {
-        return Err(MetaplexError::AuctionMustBeCreated.into());
-    }
+    let auction = match auction_manager_settings.fixed_price {
+        Some(_) => {
+            assert_fixed_price_listing_key(auction_info)?;
+            None
+        }
+        None => {
+            let auction = AuctionData::from_account_info(auction_info)?;
+            assert_owned_by(auction_info, &store.auction_program)?;
+
+            if auction.state != AuctionState::Created {
+                return Err(MetaplexError::AuctionMustBeCreated.into());
+            }
+
+            // Redemption looks winners up in the auction account, which does not hold the bids of
+            // a paged auction.
+            if let BidState::PagedEnglishAuction { .. } = auction.bid_state {
+                return Err(MetaplexError::PagedBidsNotSupported.into());
+            }
+
+            if auction.authority != *auction_manager_info.key {
+                return Err(MetaplexError::AuctionAuthorityMismatch.into());
+            }
+
+            assert_derivation(
+                &store.auction_program,
+                auction_info,
+                &[
+                    spl_auction::PREFIX.as_bytes(),
+                    store.auction_program.as_ref(),
+                    vault_info.key.as_ref(),
+                ],
+            )?;
+
+            if auction.token_mint != accept_payment.mint {
+                return Err(MetaplexError::AuctionAcceptPaymentMintMismatch.into());
+            }
+
+            Some(auction)
+        }
+    };
 
     if vault.authority != *auction_manager_info.key {
         return Err(MetaplexError::VaultAuthorityMismatch.into());
     }
 
-    if auction.authority != *auction_manager_info.key {
-        return Err(MetaplexError::AuctionAuthorityMismatch.into());
-    }
-
     let bump_seed = assert_derivation(
         program_id,
         auction_manager_info,
         &[PREFIX.as_bytes(), &auction_info.key.as_ref()],
     )?;
 
-    assert_derivation(
-        &store.auction_program,
-        auction_info,
-        &[
-            spl_auction::PREFIX.as_bytes(),
-            &store.auction_program.as_ref(),
-            &vault_info.key.as_ref(),
-        ],
-    )?;
-
-    if auction.token_mint != accept_payment.mint {
-        return Err(MetaplexError::AuctionAcceptPaymentMintMismatch.into());
-    }
-
     if accept_payment.owner != *auction_manager_info.key {
         return Err(MetaplexError::AcceptPaymentOwnerMismatch.into());
     }
@@ -101,46 +131,8 @@ pub fn process_init_auction_manager(
         return Err(MetaplexError::VaultCannotEmpty.into());
     }
 
-    if auction_manager_settings.winning_configs.len() != auction.num_possible_winners() as usize {
-        return Err(MetaplexError::WinnerAmountMismatch.into());
-    }
-
-    let mut winning_config_states: Vec<WinningConfigState> = vec![];
-    let mut winning_item_count: u8 = 0;
-    for winning_config in &auction_manager_settings.winning_configs {
-        let mut winning_config_state_items = vec![];
-        let mut safety_deposit_box_found_lookup: Vec<bool> = vec![];
-        for _ in 0..vault.token_type_count {
-            safety_deposit_box_found_lookup.push(false)
-        }
-        for item in &winning_config.items {
-            // If this blows then they have more than 255 total items which is unacceptable in current impl
-            winning_item_count = winning_item_count
-                .checked_add(1)
-                .ok_or(MetaplexError::NumericalOverflowError)?;
-
-            // Should never have same deposit index appear twice in one config.
-            let lookup = safety_deposit_box_found_lookup[item.safety_deposit_box_index as usize];
-            if lookup {
-                return Err(MetaplexError::DuplicateWinningConfigItemDetected.into());
-            } else {
-                safety_deposit_box_found_lookup[item.safety_deposit_box_index as usize] = true
-            }
-
-            if item.safety_deposit_box_index > vault.token_type_count {
-                return Err(MetaplexError::InvalidSafetyDepositBox.into());
-            }
-
-            winning_config_state_items.push(WinningConfigStateItem {
-                claimed: false,
-                primary_sale_happened: false,
-            })
-        }
-        winning_config_states.push(WinningConfigState {
-            items: winning_config_state_items,
-            money_pushed_to_accept_payment: false,
-        })
-    }
+    let winning_config_states =
+        common_settings_checks(&auction_manager_settings, &vault, auction.as_ref())?;
 
     let authority_seeds = &[PREFIX.as_bytes(), &auction_info.key.as_ref(), &[bump_seed]];
 
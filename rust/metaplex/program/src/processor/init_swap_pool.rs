@@ -0,0 +1,85 @@
+use {
+    crate::{
+        error::MetaplexError,
+        state::{ConversionRate, Key, SwapPool, MAX_SWAP_POOL_SIZE, PREFIX},
+        utils::{
+            assert_derivation, assert_owned_by, assert_signer, assert_swap_pool_holding,
+            create_or_allocate_account_raw,
+        },
+    },
+    borsh::BorshSerialize,
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        pubkey::Pubkey,
+    },
+};
+
+pub fn process_init_swap_pool<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let swap_pool_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let conversion_rate_info = next_account_info(account_info_iter)?;
+    let from_holding_info = next_account_info(account_info_iter)?;
+    let to_holding_info = next_account_info(account_info_iter)?;
+    let system_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    assert_signer(authority_info)?;
+    assert_signer(payer_info)?;
+    assert_owned_by(conversion_rate_info, program_id)?;
+
+    let conversion_rate = ConversionRate::from_account_info(conversion_rate_info)?;
+    if conversion_rate.key != Key::ConversionRateV1 {
+        return Err(MetaplexError::InvalidConversionRate.into());
+    }
+
+    let swap_pool_bump = assert_derivation(
+        program_id,
+        swap_pool_info,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            authority_info.key.as_ref(),
+            conversion_rate_info.key.as_ref(),
+        ],
+    )?;
+
+    if !swap_pool_info.data_is_empty() {
+        return Err(MetaplexError::AlreadyInitialized.into());
+    }
+
+    assert_swap_pool_holding(swap_pool_info, from_holding_info, &conversion_rate.mint)?;
+    assert_swap_pool_holding(swap_pool_info, to_holding_info, &conversion_rate.price_mint)?;
+
+    create_or_allocate_account_raw(
+        *program_id,
+        swap_pool_info,
+        rent_info,
+        system_info,
+        payer_info,
+        MAX_SWAP_POOL_SIZE,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            authority_info.key.as_ref(),
+            conversion_rate_info.key.as_ref(),
+            &[swap_pool_bump],
+        ],
+    )?;
+
+    let mut swap_pool = SwapPool::from_account_info(swap_pool_info)?;
+    swap_pool.key = Key::SwapPoolV1;
+    swap_pool.authority = *authority_info.key;
+    swap_pool.conversion_rate = *conversion_rate_info.key;
+    swap_pool.from_holding = *from_holding_info.key;
+    swap_pool.to_holding = *to_holding_info.key;
+    swap_pool.serialize(&mut *swap_pool_info.data.borrow_mut())?;
+
+    Ok(())
+}
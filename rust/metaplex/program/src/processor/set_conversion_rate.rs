@@ -0,0 +1,49 @@
+use {
+    crate::{
+        error::MetaplexError,
+        instruction::SetConversionRateArgs,
+        state::{ConversionRate, Key},
+        utils::{assert_owned_by, assert_signer},
+    },
+    borsh::BorshSerialize,
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        pubkey::Pubkey,
+    },
+};
+
+pub fn process_set_conversion_rate<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: SetConversionRateArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let conversion_rate_info = next_account_info(account_info_iter)?;
+
+    assert_owned_by(conversion_rate_info, program_id)?;
+    assert_signer(conversion_rate_info)?;
+
+    // A zero price would pay converted payouts out with nothing.
+    if args.price_per_token == 0 || args.mint == args.price_mint {
+        return Err(MetaplexError::InvalidConversionRate.into());
+    }
+
+    let mut conversion_rate = ConversionRate::from_account_info(conversion_rate_info)?;
+
+    // Mints can only be set once, the swap pools using this rate hold accounts in them.
+    if conversion_rate.key == Key::ConversionRateV1
+        && (conversion_rate.mint != args.mint || conversion_rate.price_mint != args.price_mint)
+    {
+        return Err(MetaplexError::InvalidConversionRate.into());
+    }
+
+    conversion_rate.key = Key::ConversionRateV1;
+    conversion_rate.mint = args.mint;
+    conversion_rate.price_mint = args.price_mint;
+    conversion_rate.price_per_token = args.price_per_token;
+    conversion_rate.serialize(&mut *conversion_rate_info.data.borrow_mut())?;
+
+    Ok(())
+}
@@ -48,7 +48,6 @@ pub fn process_start_auction(program_id: &Pubkey, accounts: &[AccountInfo]) -> P
     let store = Store::from_account_info(store_info)?;
 
     assert_authority_correct(&auction_manager, authority_info)?;
-    assert_owned_by(auction_info, &store.auction_program)?;
     assert_owned_by(auction_manager_info, program_id)?;
     assert_owned_by(store_info, program_id)?;
 
@@ -68,22 +67,27 @@ pub fn process_start_auction(program_id: &Pubkey, accounts: &[AccountInfo]) -> P
         return Err(MetaplexError::AuctionManagerMustBeValidated.into());
     }
 
-    let seeds = &[PREFIX.as_bytes(), &auction_manager.auction.as_ref()];
-    let (_, bump_seed) = Pubkey::find_program_address(seeds, &program_id);
-    let authority_seeds = &[
-        PREFIX.as_bytes(),
-        &auction_manager.auction.as_ref(),
-        &[bump_seed],
-    ];
+    // A fixed price listing has no auction to start, it opens for sale right away.
+    if auction_manager.settings.fixed_price.is_none() {
+        assert_owned_by(auction_info, &store.auction_program)?;
 
-    issue_start_auction(
-        auction_program_info.clone(),
-        auction_manager_info.clone(),
-        auction_info.clone(),
-        clock_info.clone(),
-        auction_manager.vault,
-        authority_seeds,
-    )?;
+        let seeds = &[PREFIX.as_bytes(), auction_manager.auction.as_ref()];
+        let (_, bump_seed) = Pubkey::find_program_address(seeds, program_id);
+        let authority_seeds = &[
+            PREFIX.as_bytes(),
+            auction_manager.auction.as_ref(),
+            &[bump_seed],
+        ];
+
+        issue_start_auction(
+            auction_program_info.clone(),
+            auction_manager_info.clone(),
+            auction_info.clone(),
+            clock_info.clone(),
+            auction_manager.vault,
+            authority_seeds,
+        )?;
+    }
 
     auction_manager.state.status = AuctionManagerStatus::Running;
 
@@ -0,0 +1,206 @@
+//! Rewrites the settings of an auction manager that is still Initialized, so that a mistake in them
+//! doesn't mean decommissioning the auction manager and building a new vault.
+//!
+//! Validation checked each safety deposit box against the settings as they were, so only boxes that
+//! have not been validated yet can change how they are used. The caller shows this by passing every
+//! such box along with its validation ticket, which must not exist. Boxes used the same way keep
+//! their validation, and what it recorded in the winning config states.
+
+use {
+    crate::{
+        error::MetaplexError,
+        state::{
+            AuctionManager, AuctionManagerSettings, AuctionManagerStatus, ParticipationState,
+            Store, WinningConfigType, PREFIX,
+        },
+        utils::{
+            assert_authority_correct, assert_derivation, assert_owned_by,
+            assert_store_safety_vault_manager_match, common_settings_checks,
+        },
+    },
+    borsh::BorshSerialize,
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    spl_auction::processor::AuctionData,
+    spl_token_vault::state::{SafetyDepositBox, Vault},
+};
+
+/// Number of accounts passed for each safety deposit box whose use in the settings changes.
+pub const UPDATE_SETTINGS_ACCOUNTS_PER_BOX: usize = 2;
+
+/// Every place a safety deposit box is used in the winning configs, as (winning config index,
+/// amount, type).
+fn safety_deposit_box_usage(
+    settings: &AuctionManagerSettings,
+    safety_deposit_box_index: u8,
+) -> Vec<(usize, u8, WinningConfigType)> {
+    settings
+        .winning_configs
+        .iter()
+        .enumerate()
+        .flat_map(|(i, config)| {
+            config
+                .items
+                .iter()
+                .filter(move |item| item.safety_deposit_box_index == safety_deposit_box_index)
+                .map(move |item| (i, item.amount, item.winning_config_type))
+        })
+        .collect()
+}
+
+pub fn process_update_auction_manager_settings(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    auction_manager_settings: AuctionManagerSettings,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let auction_manager_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let auction_info = next_account_info(account_info_iter)?;
+    let store_info = next_account_info(account_info_iter)?;
+    let box_accounts = &accounts[5..];
+
+    let mut auction_manager = AuctionManager::from_account_info(auction_manager_info)?;
+    let store = Store::from_account_info(store_info)?;
+    let vault = Vault::from_account_info(vault_info)?;
+
+    assert_owned_by(auction_manager_info, program_id)?;
+    assert_owned_by(store_info, program_id)?;
+    assert_owned_by(vault_info, &store.token_vault_program)?;
+    assert_authority_correct(&auction_manager, authority_info)?;
+
+    if auction_manager.state.status != AuctionManagerStatus::Initialized {
+        return Err(MetaplexError::InvalidStatus.into());
+    }
+
+    if auction_manager.store != *store_info.key {
+        return Err(MetaplexError::AuctionManagerStoreMismatch.into());
+    }
+
+    if auction_manager.vault != *vault_info.key {
+        return Err(MetaplexError::AuctionManagerVaultMismatch.into());
+    }
+
+    if auction_manager.auction != *auction_info.key {
+        return Err(MetaplexError::AuctionManagerAuctionMismatch.into());
+    }
+
+    // The auction manager stays what it was created as, an auction or a fixed price listing, which
+    // the consistency checks hold the new settings to.
+    let auction = match auction_manager.settings.fixed_price {
+        Some(_) => None,
+        None => {
+            assert_owned_by(auction_info, &store.auction_program)?;
+            Some(AuctionData::from_account_info(auction_info)?)
+        }
+    };
+
+    let mut winning_config_states =
+        common_settings_checks(&auction_manager_settings, &vault, auction.as_ref())?;
+
+    if box_accounts.len() % UPDATE_SETTINGS_ACCOUNTS_PER_BOX != 0 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let mut unvalidated = vec![false; vault.token_type_count as usize];
+    for box_infos in box_accounts.chunks(UPDATE_SETTINGS_ACCOUNTS_PER_BOX) {
+        let safety_deposit_info = &box_infos[0];
+        let safety_deposit_validation_ticket_info = &box_infos[1];
+
+        assert_owned_by(safety_deposit_info, &store.token_vault_program)?;
+        assert_store_safety_vault_manager_match(
+            &auction_manager,
+            safety_deposit_info,
+            vault_info,
+            &store.token_vault_program,
+        )?;
+        let safety_deposit = SafetyDepositBox::from_account_info(safety_deposit_info)?;
+
+        assert_derivation(
+            program_id,
+            safety_deposit_validation_ticket_info,
+            &[
+                PREFIX.as_bytes(),
+                program_id.as_ref(),
+                auction_manager_info.key.as_ref(),
+                safety_deposit_info.key.as_ref(),
+            ],
+        )?;
+        if !safety_deposit_validation_ticket_info.data_is_empty() {
+            return Err(MetaplexError::AlreadyValidated.into());
+        }
+
+        unvalidated[safety_deposit.order as usize] = true;
+    }
+
+    for index in 0..vault.token_type_count {
+        if safety_deposit_box_usage(&auction_manager.settings, index)
+            != safety_deposit_box_usage(&auction_manager_settings, index)
+            && !unvalidated[index as usize]
+        {
+            return Err(MetaplexError::SettingsChangeNotShownUnvalidated.into());
+        }
+    }
+
+    // Carry over what validation recorded for boxes that are used as they were.
+    for (i, winning_config) in auction_manager_settings.winning_configs.iter().enumerate() {
+        let previous_config = match auction_manager.settings.winning_configs.get(i) {
+            Some(config) => config,
+            None => break,
+        };
+        for (j, item) in winning_config.items.iter().enumerate() {
+            if let Some(k) = previous_config.items.iter().position(|previous| {
+                previous.safety_deposit_box_index == item.safety_deposit_box_index
+            }) {
+                winning_config_states[i].items[j].primary_sale_happened =
+                    auction_manager.state.winning_config_states[i].items[k].primary_sale_happened;
+            }
+        }
+    }
+
+    if auction_manager_settings.participation_config
+        != auction_manager.settings.participation_config
+    {
+        if let Some(state) = &auction_manager.state.participation_state {
+            if state.validated {
+                return Err(MetaplexError::AlreadyValidated.into());
+            }
+        }
+
+        auction_manager.state.participation_state = auction_manager_settings
+            .participation_config
+            .as_ref()
+            .map(|_| ParticipationState {
+                collected_to_accept_payment: 0,
+                validated: false,
+                primary_sale_happened: false,
+                printing_authorization_token_account: None,
+            });
+    }
+
+    // Dropping the last unvalidated boxes can leave everything left already validated.
+    let all_winning_config_items: usize = winning_config_states
+        .iter()
+        .map(|state| state.items.len())
+        .sum();
+    let participation_okay = match &auction_manager.state.participation_state {
+        Some(state) => state.validated,
+        None => true,
+    };
+    if auction_manager.state.winning_config_items_validated as usize == all_winning_config_items
+        && participation_okay
+    {
+        auction_manager.state.status = AuctionManagerStatus::Validated
+    }
+
+    auction_manager.state.winning_config_states = winning_config_states;
+    auction_manager.settings = auction_manager_settings;
+    auction_manager.serialize(&mut *auction_manager_info.data.borrow_mut())?;
+
+    Ok(())
+}
@@ -297,7 +297,7 @@ pub fn process_validate_safety_deposit_box(
                 return Err(MetaplexError::NotEnoughTokensToSupplyWinners.into());
             }
         }
-        WinningConfigType::Printing => {
+        WinningConfigType::Printing | WinningConfigType::LimitedEdition => {
             if edition_key != *edition_info.key {
                 return Err(MetaplexError::InvalidEditionAddress.into());
             }
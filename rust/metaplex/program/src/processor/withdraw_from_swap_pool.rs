@@ -0,0 +1,74 @@
+use {
+    crate::{
+        error::MetaplexError,
+        instruction::WithdrawFromSwapPoolArgs,
+        state::{SwapPool, PREFIX},
+        utils::{assert_derivation, assert_owned_by, assert_signer, spl_token_transfer},
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        pubkey::Pubkey,
+    },
+};
+
+pub fn process_withdraw_from_swap_pool<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: WithdrawFromSwapPoolArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let swap_pool_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let holding_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    assert_signer(authority_info)?;
+    assert_owned_by(swap_pool_info, program_id)?;
+
+    if *token_program_info.key != spl_token::id() {
+        return Err(MetaplexError::InvalidTokenProgram.into());
+    }
+
+    let swap_pool = SwapPool::from_account_info(swap_pool_info)?;
+    if swap_pool.authority != *authority_info.key {
+        return Err(MetaplexError::SwapPoolAuthorityMismatch.into());
+    }
+
+    // Either holding can be emptied, the one gathering converted payouts or the unused liquidity.
+    if swap_pool.from_holding != *holding_info.key && swap_pool.to_holding != *holding_info.key {
+        return Err(MetaplexError::SwapPoolMismatch.into());
+    }
+
+    let swap_pool_bump = assert_derivation(
+        program_id,
+        swap_pool_info,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            swap_pool.authority.as_ref(),
+            swap_pool.conversion_rate.as_ref(),
+        ],
+    )?;
+
+    let swap_pool_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        swap_pool.authority.as_ref(),
+        swap_pool.conversion_rate.as_ref(),
+        &[swap_pool_bump],
+    ];
+
+    spl_token_transfer(
+        holding_info.clone(),
+        destination_info.clone(),
+        args.amount,
+        swap_pool_info.clone(),
+        swap_pool_seeds,
+        token_program_info.clone(),
+    )?;
+
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use {
-    crate::utils::try_from_slice_checked,
+    crate::{error::MetaplexError, utils::try_from_slice_checked},
     borsh::{BorshDeserialize, BorshSerialize},
     solana_program::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey},
 };
@@ -8,7 +8,7 @@ pub const PREFIX: &str = "metaplex";
 
 pub const MAX_WINNERS: usize = 200;
 pub const MAX_WINNER_SIZE: usize = 6 * MAX_WINNERS;
-// Add 150 padding for future keys and booleans
+// Keep padding for future keys and booleans
 // DONT TRUST MEM SIZE OF! IT DOESNT SIZE THINGS PROPERLY! TRUST YOUR OWN MIND AND ITS COUNTING ABILITY!
 pub const MAX_AUCTION_MANAGER_SIZE: usize = 1 + // key
     32 + // store
@@ -34,14 +34,24 @@ pub const MAX_AUCTION_MANAGER_SIZE: usize = 1 + // key
     1 + // participation non winner constraint
     1 + // u8 participation_config's safety deposit box index 
     9 + // option<u64> participation fixed price in borsh is a u8 for option and actual u64
-    150; // padding;
-         // Add padding for future booleans/enums
+    1 + // Whether or not payout split exists
+    4 + // u32 borsh uses to determine number of elements in payout split recipients vec
+    MAX_PAYOUT_SPLIT_RECIPIENTS * (32 + 2) + // recipient and its basis points
+    9 + // option<u64> fixed price of a fixed price listing
+    34; // padding;
+        // Add padding for future booleans/enums
 pub const MAX_STORE_SIZE: usize = 2 + 32 + 32 + 32 + 32 + 100;
 pub const MAX_WHITELISTED_CREATOR_SIZE: usize = 2 + 32 + 10;
 pub const MAX_PAYOUT_TICKET_SIZE: usize = 1 + 32 + 8;
 pub const MAX_VALIDATION_TICKET_SIZE: usize = 1 + 32 + 10;
 pub const MAX_BID_REDEMPTION_TICKET_SIZE: usize = 3;
 pub const MAX_AUTHORITY_LOOKUP_SIZE: usize = 33;
+/// Enough for a marketplace fee, a curator fee and a consigner.
+pub const MAX_PAYOUT_SPLIT_RECIPIENTS: usize = 3;
+pub const MAX_CONVERSION_RATE_SIZE: usize = 1 + 8 + 32 + 32 + 50;
+pub const MAX_SWAP_POOL_SIZE: usize = 1 + 32 + 32 + 32 + 32 + 50;
+/// Conversion rate prices are fixed point numbers with this many units to one.
+pub const CONVERSION_RATE_SCALE: u128 = 1_000_000_000;
 
 #[repr(C)]
 #[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug, Copy)]
@@ -54,6 +64,8 @@ pub enum Key {
     PayoutTicketV1,
     SafetyDepositValidationTicketV1,
     AuctionManagerV1,
+    ConversionRateV1,
+    SwapPoolV1,
 }
 
 /// An Auction Manager can support an auction that is an English auction and limited edition and open edition
@@ -114,6 +126,40 @@ pub struct AuctionManagerSettings {
     /// The participation config is separated because it is structurally a bit different,
     /// having different options and also because it has no real "winning place" in the array.
     pub participation_config: Option<ParticipationConfig>,
+
+    /// Splits what the auctioneer is owed on a sale, after creator royalties, with the recipients
+    /// listed here. Each recipient is paid out on its own PayoutTicket through EmptyPaymentAccount.
+    pub payout_split: Option<PayoutSplit>,
+
+    /// Makes this a fixed price listing instead of an auction. Each winning config is then one unit
+    /// of inventory, sold in order at this price through BuyFixedPrice, and there is no spl-auction
+    /// account behind the auction manager.
+    pub fixed_price: Option<u64>,
+}
+
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct PayoutSplit {
+    /// Each recipient takes its basis points of the auctioneer's share, and the auctioneer keeps
+    /// whatever is left. At most MAX_PAYOUT_SPLIT_RECIPIENTS, taking no more than 10000 together.
+    pub recipients: Vec<PayoutSplitRecipient>,
+}
+
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug, Copy)]
+pub struct PayoutSplitRecipient {
+    pub address: Pubkey,
+    pub basis_points: u16,
+}
+
+impl PayoutSplit {
+    /// Basis points of the auctioneer's share taken by all recipients together.
+    pub fn total_basis_points(&self) -> u32 {
+        self.recipients
+            .iter()
+            .map(|recipient| recipient.basis_points as u32)
+            .sum()
+    }
 }
 
 #[repr(C)]
@@ -195,6 +241,9 @@ pub enum WinningConfigType {
     FullRightsTransfer,
     /// Means you are using authorization tokens to print off editions during the auction
     Printing,
+    /// Means you are selling authorization tokens on a fixed price listing, each of which is redeemed
+    /// for a limited edition as soon as it is bought, so the buyer walks away with the edition itself.
+    LimitedEdition,
 }
 
 #[repr(C)]
@@ -359,3 +408,68 @@ impl SafetyDepositValidationTicket {
         Ok(store)
     }
 }
+
+/// The price of one currency in another, for paying out of an accept payment account in a currency
+/// other than its own. Like token-vault's ExternalPriceAccount, it is created by the caller, owned by
+/// this program and signs for its own updates through SetConversionRate.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, Copy, Debug)]
+pub struct ConversionRate {
+    pub key: Key,
+    /// Price of one unit of the mint in units of the price mint, scaled by CONVERSION_RATE_SCALE.
+    pub price_per_token: u64,
+    /// Mint of the currency being converted from, the mint of the accept payment account.
+    pub mint: Pubkey,
+    /// Mint of the currency being converted to.
+    pub price_mint: Pubkey,
+}
+
+impl ConversionRate {
+    pub fn from_account_info(a: &AccountInfo) -> Result<ConversionRate, ProgramError> {
+        let cr: ConversionRate = try_from_slice_checked(
+            &a.data.borrow_mut(),
+            Key::ConversionRateV1,
+            MAX_CONVERSION_RATE_SIZE,
+        )?;
+
+        Ok(cr)
+    }
+
+    /// Converts an amount of the mint to the price mint, rounding down.
+    pub fn convert(&self, amount: u64) -> Result<u64, ProgramError> {
+        let converted = (amount as u128)
+            .checked_mul(self.price_per_token as u128)
+            .ok_or(MetaplexError::NumericalOverflowError)?
+            / CONVERSION_RATE_SCALE;
+        if converted > u64::MAX as u128 {
+            return Err(MetaplexError::NumericalOverflowError.into());
+        }
+
+        Ok(converted as u64)
+    }
+}
+
+/// A pool of liquidity in the price mint of a conversion rate, which takes payouts in the mint of
+/// the rate and pays out their converted value from its own holding. The authority stocks the pool by
+/// transferring into the to holding, and takes out what it has gathered with WithdrawFromSwapPool.
+/// Pda of ['metaplex', program id, authority, conversion rate].
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, Copy, Debug)]
+pub struct SwapPool {
+    pub key: Key,
+    pub authority: Pubkey,
+    pub conversion_rate: Pubkey,
+    /// Token account of the rate's mint, held by the pool, that converted payouts are paid into.
+    pub from_holding: Pubkey,
+    /// Token account of the rate's price mint, held by the pool, that converted payouts are paid from.
+    pub to_holding: Pubkey,
+}
+
+impl SwapPool {
+    pub fn from_account_info(a: &AccountInfo) -> Result<SwapPool, ProgramError> {
+        let sp: SwapPool =
+            try_from_slice_checked(&a.data.borrow_mut(), Key::SwapPoolV1, MAX_SWAP_POOL_SIZE)?;
+
+        Ok(sp)
+    }
+}
@@ -2,9 +2,10 @@ use {
     crate::{
         error::MetaplexError,
         state::{
-            AuctionManager, AuctionManagerStatus, BidRedemptionTicket, Key,
-            OriginalAuthorityLookup, Store, WhitelistedCreator, WinningConfigItem,
-            MAX_BID_REDEMPTION_TICKET_SIZE, PREFIX,
+            AuctionManager, AuctionManagerSettings, AuctionManagerStatus, BidRedemptionTicket, Key,
+            OriginalAuthorityLookup, PayoutSplit, Store, WhitelistedCreator, WinningConfigItem,
+            WinningConfigState, WinningConfigStateItem, WinningConfigType,
+            MAX_BID_REDEMPTION_TICKET_SIZE, MAX_PAYOUT_SPLIT_RECIPIENTS, PREFIX,
         },
     },
     arrayref::array_ref,
@@ -16,21 +17,28 @@ use {
         msg,
         program::{invoke, invoke_signed},
         program_error::ProgramError,
+        program_option::COption,
         program_pack::{IsInitialized, Pack},
         pubkey::Pubkey,
         system_instruction,
         sysvar::{rent::Rent, Sysvar},
     },
     spl_auction::{
-        instruction::end_auction_instruction,
-        processor::{end_auction::EndAuctionArgs, AuctionData, AuctionState, BidderMetadata},
+        instruction::{cancel_auction_instruction, end_auction_instruction},
+        processor::{
+            cancel_auction::CancelAuctionArgs, end_auction::EndAuctionArgs, AuctionData,
+            AuctionState, BidderMetadata,
+        },
+    },
+    spl_token::{
+        instruction::{set_authority, AuthorityType},
+        state::Account,
     },
-    spl_token::instruction::{set_authority, AuthorityType},
     spl_token_metadata::{
-        instruction::update_metadata_accounts,
+        instruction::{mint_new_edition_from_master_edition_via_token, update_metadata_accounts},
         state::{Metadata, EDITION},
     },
-    spl_token_vault::instruction::create_withdraw_tokens_instruction,
+    spl_token_vault::{instruction::create_withdraw_tokens_instruction, state::Vault},
     std::convert::TryInto,
 };
 
@@ -165,6 +173,86 @@ pub fn assert_authority_correct(
 
     Ok(())
 }
+/// Checks a payout split can be paid out: each recipient appears once and takes some share, and
+/// together they take no more than the whole of the auctioneer's share.
+pub fn assert_valid_payout_split(payout_split: &Option<PayoutSplit>) -> ProgramResult {
+    if let Some(split) = payout_split {
+        let recipients = &split.recipients;
+        if recipients.is_empty() || recipients.len() > MAX_PAYOUT_SPLIT_RECIPIENTS {
+            return Err(MetaplexError::InvalidPayoutSplit.into());
+        }
+
+        for (i, recipient) in recipients.iter().enumerate() {
+            if recipient.basis_points == 0
+                || recipients[..i]
+                    .iter()
+                    .any(|other| other.address == recipient.address)
+            {
+                return Err(MetaplexError::InvalidPayoutSplit.into());
+            }
+        }
+
+        if split.total_basis_points() > 10000 {
+            return Err(MetaplexError::InvalidPayoutSplit.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks an account the auction manager takes payment into for a currency other than the auction's
+/// token mint, for auctions that accept bids in other currencies. Like accept_payment, it has to be
+/// held by the auction manager with nobody else able to move or close it.
+pub fn assert_currency_accept_payment(
+    auction_manager_info: &AccountInfo,
+    accept_payment_info: &AccountInfo,
+) -> Result<Account, ProgramError> {
+    let accept_payment: Account = assert_initialized(accept_payment_info)?;
+
+    if accept_payment.owner != *auction_manager_info.key {
+        return Err(MetaplexError::AcceptPaymentOwnerMismatch.into());
+    }
+
+    if accept_payment.delegate != COption::None {
+        return Err(MetaplexError::DelegateShouldBeNone.into());
+    }
+
+    if accept_payment.close_authority != COption::None {
+        return Err(MetaplexError::CloseAuthorityShouldBeNone.into());
+    }
+
+    Ok(accept_payment)
+}
+
+/// Checks a token account a swap pool holds one of its currencies in. Like accept_payment, it has to
+/// be held by the pool with nobody else able to move or close it.
+pub fn assert_swap_pool_holding(
+    swap_pool_info: &AccountInfo,
+    holding_info: &AccountInfo,
+    mint: &Pubkey,
+) -> ProgramResult {
+    assert_owned_by(holding_info, &spl_token::id())?;
+    let holding: Account = assert_initialized(holding_info)?;
+
+    if holding.owner != *swap_pool_info.key {
+        return Err(MetaplexError::SwapPoolMismatch.into());
+    }
+
+    if holding.mint != *mint {
+        return Err(MetaplexError::SwapPoolMismatch.into());
+    }
+
+    if holding.delegate != COption::None {
+        return Err(MetaplexError::DelegateShouldBeNone.into());
+    }
+
+    if holding.close_authority != COption::None {
+        return Err(MetaplexError::CloseAuthorityShouldBeNone.into());
+    }
+
+    Ok(())
+}
+
 /// Create account almost from scratch, lifted from
 /// https://github.com/solana-labs/solana-program-library/blob/7d4873c61721aca25464d42cc5ef651a7923ca79/associated-token-account/program/src/processor.rs#L51-L98
 #[inline(always)]
@@ -284,6 +372,65 @@ pub fn transfer_metadata_ownership<'a>(
     Ok(())
 }
 
+/// Burns an authorization token the owner holds to mint them a limited edition of its master
+/// edition, onto a fresh mint of theirs holding one token.
+#[allow(clippy::too_many_arguments)]
+pub fn mint_edition_via_token<'a>(
+    token_metadata_program: AccountInfo<'a>,
+    new_metadata: AccountInfo<'a>,
+    new_edition: AccountInfo<'a>,
+    master_edition: AccountInfo<'a>,
+    new_mint: AccountInfo<'a>,
+    owner: AccountInfo<'a>,
+    printing_mint: AccountInfo<'a>,
+    token_account: AccountInfo<'a>,
+    master_update_authority: AccountInfo<'a>,
+    master_metadata: AccountInfo<'a>,
+    token_program: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    rent: AccountInfo<'a>,
+) -> ProgramResult {
+    let mut instruction = mint_new_edition_from_master_edition_via_token(
+        *token_metadata_program.key,
+        *new_metadata.key,
+        *new_edition.key,
+        *master_edition.key,
+        *new_mint.key,
+        *owner.key,
+        *printing_mint.key,
+        *token_account.key,
+        *owner.key,
+        *owner.key,
+        *master_update_authority.key,
+        *master_metadata.key,
+        None,
+    );
+    // The update authority of the master metadata is only copied onto the new metadata, and the
+    // artist is not around to sign for every edition sold.
+    instruction.accounts[9].is_signer = false;
+
+    invoke(
+        &instruction,
+        &[
+            new_metadata,
+            new_edition,
+            master_edition,
+            new_mint,
+            printing_mint,
+            token_account,
+            owner,
+            master_update_authority,
+            master_metadata,
+            token_program,
+            system_program,
+            rent,
+            token_metadata_program,
+        ],
+    )?;
+
+    Ok(())
+}
+
 pub fn transfer_mint_authority<'a>(
     new_authority_seeds: &[&[u8]],
     new_authority_key: &Pubkey,
@@ -410,6 +557,7 @@ pub fn common_redeem_checks(
             last_bid: 0,
             last_bid_timestamp: 0,
             cancelled: false,
+            payer: *bidder_info.key,
         };
 
         if *bidder_info.key != auction_manager.authority {
@@ -514,7 +662,16 @@ pub fn common_redeem_checks(
         return Err(MetaplexError::AuctionManagerTokenMetadataProgramMismatch.into());
     }
 
-    if auction.state != AuctionState::Ended {
+    // A cancelled auction has no winners, and only the auctioneer can take back the items.
+    if auction.state == AuctionState::Cancelled && overwrite_win_index.is_none() {
+        return Err(MetaplexError::AuctionCancelled.into());
+    }
+
+    // A Dutch auction that has sold every place is over even if nothing has moved its state yet.
+    if auction.state != AuctionState::Ended
+        && auction.state != AuctionState::Cancelled
+        && !auction.bid_state.sold_out()
+    {
         return Err(MetaplexError::AuctionHasNotEnded.into());
     }
 
@@ -669,6 +826,93 @@ pub fn common_winning_config_checks(
     })
 }
 
+/// Checks auction manager settings are consistent with the vault and the auction they are for, or
+/// with being a fixed price listing when there is no auction, and lays out a fresh state for their
+/// winning configs. Used both when the settings are first written and when they are updated.
+pub fn common_settings_checks(
+    settings: &AuctionManagerSettings,
+    vault: &Vault,
+    auction: Option<&AuctionData>,
+) -> Result<Vec<WinningConfigState>, ProgramError> {
+    match (settings.fixed_price, auction) {
+        (None, Some(auction)) => {
+            if settings.winning_configs.len() != auction.num_possible_winners() as usize {
+                return Err(MetaplexError::WinnerAmountMismatch.into());
+            }
+
+            for winning_config in &settings.winning_configs {
+                for item in &winning_config.items {
+                    if item.winning_config_type == WinningConfigType::LimitedEdition {
+                        return Err(MetaplexError::LimitedEditionOnlyOnFixedPriceListing.into());
+                    }
+                }
+            }
+        }
+        // Every winning config of a listing is a unit the buyer takes whole, through transfers out
+        // of the vault, with a limited edition minted off each authorization token of one.
+        (Some(_), None) => {
+            if settings.winning_configs.is_empty() || settings.participation_config.is_some() {
+                return Err(MetaplexError::InvalidFixedPriceListing.into());
+            }
+
+            for winning_config in &settings.winning_configs {
+                for item in &winning_config.items {
+                    let valid = match item.winning_config_type {
+                        WinningConfigType::TokenOnlyTransfer | WinningConfigType::Printing => true,
+                        WinningConfigType::LimitedEdition => item.amount == 1,
+                        WinningConfigType::FullRightsTransfer => false,
+                    };
+                    if !valid {
+                        return Err(MetaplexError::InvalidFixedPriceListing.into());
+                    }
+                }
+            }
+        }
+        _ => return Err(MetaplexError::InvalidFixedPriceListing.into()),
+    }
+
+    assert_valid_payout_split(&settings.payout_split)?;
+
+    let mut winning_config_states: Vec<WinningConfigState> = vec![];
+    let mut winning_item_count: u8 = 0;
+    for winning_config in &settings.winning_configs {
+        let mut winning_config_state_items = vec![];
+        let mut safety_deposit_box_found_lookup: Vec<bool> = vec![];
+        for _ in 0..vault.token_type_count {
+            safety_deposit_box_found_lookup.push(false)
+        }
+        for item in &winning_config.items {
+            // If this blows then they have more than 255 total items which is unacceptable in current impl
+            winning_item_count = winning_item_count
+                .checked_add(1)
+                .ok_or(MetaplexError::NumericalOverflowError)?;
+
+            if item.safety_deposit_box_index >= vault.token_type_count {
+                return Err(MetaplexError::InvalidSafetyDepositBox.into());
+            }
+
+            // Should never have same deposit index appear twice in one config.
+            let lookup = safety_deposit_box_found_lookup[item.safety_deposit_box_index as usize];
+            if lookup {
+                return Err(MetaplexError::DuplicateWinningConfigItemDetected.into());
+            } else {
+                safety_deposit_box_found_lookup[item.safety_deposit_box_index as usize] = true
+            }
+
+            winning_config_state_items.push(WinningConfigStateItem {
+                claimed: false,
+                primary_sale_happened: false,
+            })
+        }
+        winning_config_states.push(WinningConfigState {
+            items: winning_config_state_items,
+            money_pushed_to_accept_payment: false,
+        })
+    }
+
+    Ok(winning_config_states)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn shift_authority_back_to_originating_user<'a>(
     program_id: &Pubkey,
@@ -722,6 +966,14 @@ pub fn spl_token_transfer<'a: 'b, 'b>(
     authority_signer_seeds: &'b [&'b [u8]],
     token_program: AccountInfo<'a>,
 ) -> ProgramResult {
+    // An authority signing for itself has no seeds, which the runtime would otherwise try to derive
+    // a program address from.
+    let signer_seeds = [authority_signer_seeds];
+    let signers_seeds: &[&[&[u8]]] = if authority_signer_seeds.is_empty() {
+        &[]
+    } else {
+        &signer_seeds
+    };
     let result = invoke_signed(
         &spl_token::instruction::transfer(
             token_program.key,
@@ -732,7 +984,7 @@ pub fn spl_token_transfer<'a: 'b, 'b>(
             amount,
         )?,
         &[source, destination, authority, token_program],
-        &[authority_signer_seeds],
+        signers_seeds,
     );
 
     result.map_err(|_| MetaplexError::TokenTransferFailed.into())
@@ -833,3 +1085,25 @@ pub fn end_auction<'a: 'b, 'b>(
 
     Ok(())
 }
+
+pub fn cancel_auction<'a: 'b, 'b>(
+    resource: Pubkey,
+    auction: AccountInfo<'a>,
+    auction_extended: AccountInfo<'a>,
+    authority: AccountInfo<'a>,
+    auction_program: AccountInfo<'a>,
+    clock: AccountInfo<'a>,
+    authority_signer_seeds: &'b [&'b [u8]],
+) -> ProgramResult {
+    invoke_signed(
+        &cancel_auction_instruction(
+            *auction_program.key,
+            *authority.key,
+            CancelAuctionArgs { resource },
+        ),
+        &[auction, auction_extended, authority, auction_program, clock],
+        &[authority_signer_seeds],
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,2084 @@
+#![allow(warnings)]
+
+use borsh::BorshSerialize;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction, system_program, sysvar,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    hash::Hash,
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+    transport::TransportError,
+};
+use spl_auction::processor::{
+    AuctionData, AuctionDataExtended, AuctionState, Bid, BidState, BidderMetadata, PriceCurve,
+    PriceFloor, BASE_AUCTION_DATA_SIZE, BIDDER_METADATA_LEN, MAX_AUCTION_DATA_EXTENDED_SIZE,
+};
+use spl_metaplex::{
+    error::MetaplexError,
+    instruction::{
+        create_buy_fixed_price_instruction, create_cancel_auction_instruction,
+        create_init_swap_pool_instruction, create_redeem_bid_instruction,
+        create_set_conversion_rate_instruction, create_update_auction_manager_settings_instruction,
+        swap_pool_pubkey, BatchedPayoutArgs, EmptyPaymentAccountArgs, EmptyPaymentAccountsArgs,
+        LimitedEditionAccounts, MetaplexInstruction, ProxyCallAddress,
+        RedeemUnusedWinningConfigItemsAsAuctioneerArgs,
+    },
+    state::{
+        AuctionManager, AuctionManagerSettings, AuctionManagerState, AuctionManagerStatus, Key,
+        PayoutSplit, PayoutSplitRecipient, PayoutTicket, SafetyDepositValidationTicket, Store,
+        WinningConfig, WinningConfigItem, WinningConfigState, WinningConfigStateItem,
+        WinningConfigType, CONVERSION_RATE_SCALE, MAX_AUCTION_MANAGER_SIZE,
+        MAX_CONVERSION_RATE_SIZE, MAX_STORE_SIZE, MAX_VALIDATION_TICKET_SIZE, PREFIX,
+    },
+};
+use spl_token_metadata::state::{
+    Creator, Data, Edition, MasterEdition, Metadata, EDITION, MAX_MASTER_EDITION_LEN,
+    MAX_METADATA_LEN,
+};
+use spl_token_vault::state::{
+    SafetyDepositBox, Vault, VaultState, MAX_SAFETY_DEPOSIT_SIZE, MAX_VAULT_SIZE,
+};
+use std::{mem, str::FromStr};
+
+mod helpers;
+
+/// What a fixed price listing sold its one item for, in the accept payment account's mint.
+const PRICE: u64 = 1_000;
+
+/// How much the swap pool is stocked with, in its price mint.
+const STOCK: u64 = 5_000;
+
+/// How many tokens each safety deposit box of a fixed price listing holds, and how many times the
+/// price its buyer can pay.
+const SHOP_STOCK: u64 = 5;
+
+/// A fixed price listing whose one item has sold, its proceeds waiting in the accept payment
+/// account for the item's only creator, along with a conversion rate from the accept payment
+/// account's mint to the mint the creator wants to be paid in.
+struct Listing {
+    program_id: Pubkey,
+    banks_client: BanksClient,
+    payer: Keypair,
+    recent_blockhash: Hash,
+    mint_authority: Keypair,
+    accept_mint: Pubkey,
+    price_mint: Pubkey,
+    auction_manager: Pubkey,
+    accept_payment: Pubkey,
+    store: Pubkey,
+    vault: Pubkey,
+    listing: Pubkey,
+    metadata: Pubkey,
+    safety_deposit: Pubkey,
+    creator: Keypair,
+    destination: Pubkey,
+    conversion_rate: Keypair,
+}
+
+/// The accounts of the swap pool a payout is converted through.
+struct SwapRoute {
+    swap_pool: Pubkey,
+    conversion_rate: Pubkey,
+    from_holding: Pubkey,
+    to_holding: Pubkey,
+}
+
+/// The associated token account of an owner for a mint, which creators and payout split
+/// recipients are paid into.
+fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), spl_token::id().as_ref(), mint.as_ref()],
+        &Pubkey::from_str("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL").unwrap(),
+    )
+    .0
+}
+
+/// The state of the other programs is written straight into the accounts, as paying out only
+/// reads it.
+async fn setup_listing() -> Listing {
+    setup_listing_with(|_| {}).await
+}
+
+/// Same as setup_listing, letting the test change the auction manager it starts from. Each
+/// recipient of a payout split is given an associated token account of the accept payment
+/// account's mint.
+async fn setup_listing_with(configure: impl FnOnce(&mut AuctionManager)) -> Listing {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("spl_metaplex", program_id, None);
+
+    let mint_authority = Keypair::new();
+    let accept_mint = Pubkey::new_unique();
+    let price_mint = Pubkey::new_unique();
+    for mint in [accept_mint, price_mint].iter() {
+        program_test.add_packable_account(
+            *mint,
+            Rent::default().minimum_balance(spl_token::state::Mint::LEN),
+            &helpers::mint(&mint_authority.pubkey()),
+            &spl_token::id(),
+        );
+    }
+
+    // The creator is paid into their associated token account of the price mint.
+    let creator = Keypair::new();
+    let destination = associated_token_address(&creator.pubkey(), &price_mint);
+    program_test.add_packable_account(
+        destination,
+        Rent::default().minimum_balance(spl_token::state::Account::LEN),
+        &helpers::token_account(&price_mint, &creator.pubkey()),
+        &spl_token::id(),
+    );
+
+    let store = Pubkey::new_unique();
+    program_test.add_account(
+        store,
+        helpers::program_account(
+            &Store {
+                key: Key::StoreV1,
+                public: true,
+                auction_program: spl_auction::id(),
+                token_vault_program: spl_token_vault::id(),
+                token_metadata_program: spl_token_metadata::id(),
+                token_program: spl_token::id(),
+            },
+            MAX_STORE_SIZE,
+            &program_id,
+        ),
+    );
+
+    let item_mint = Pubkey::new_unique();
+    let metadata = Pubkey::new_unique();
+    program_test.add_account(
+        metadata,
+        helpers::program_account(
+            &Metadata {
+                key: spl_token_metadata::state::Key::MetadataV1,
+                update_authority: creator.pubkey(),
+                mint: item_mint,
+                data: Data {
+                    name: "Item".to_owned(),
+                    symbol: "ITEM".to_owned(),
+                    uri: "https://example.com".to_owned(),
+                    seller_fee_basis_points: 500,
+                    creators: Some(vec![Creator {
+                        address: creator.pubkey(),
+                        verified: true,
+                        share: 100,
+                    }]),
+                },
+                primary_sale_happened: false,
+                is_mutable: true,
+            },
+            MAX_METADATA_LEN,
+            &spl_token_metadata::id(),
+        ),
+    );
+
+    let vault = Pubkey::new_unique();
+    program_test.add_account(
+        vault,
+        Account {
+            lamports: Rent::default().minimum_balance(MAX_VAULT_SIZE),
+            data: vec![0; MAX_VAULT_SIZE],
+            owner: spl_token_vault::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let safety_deposit = Pubkey::new_unique();
+    program_test.add_account(
+        safety_deposit,
+        helpers::program_account(
+            &SafetyDepositBox {
+                key: spl_token_vault::state::Key::SafetyDepositBoxV1,
+                vault,
+                token_mint: item_mint,
+                store: Pubkey::new_unique(),
+                order: 0,
+            },
+            MAX_SAFETY_DEPOSIT_SIZE,
+            &spl_token_vault::id(),
+        ),
+    );
+
+    // The listing has sold its only winning config, on a primary sale, so all of the price goes to
+    // the creator.
+    let listing = Pubkey::new_unique();
+    let (auction_manager, _) =
+        Pubkey::find_program_address(&[PREFIX.as_bytes(), listing.as_ref()], &program_id);
+    let accept_payment = Keypair::new();
+    let mut auction_manager_data = AuctionManager {
+        key: Key::AuctionManagerV1,
+        store,
+        authority: Pubkey::new_unique(),
+        auction: listing,
+        vault,
+        accept_payment: accept_payment.pubkey(),
+        state: AuctionManagerState {
+            status: AuctionManagerStatus::Disbursing,
+            winning_config_items_validated: 1,
+            winning_config_states: vec![WinningConfigState {
+                items: vec![WinningConfigStateItem {
+                    primary_sale_happened: false,
+                    claimed: true,
+                }],
+                money_pushed_to_accept_payment: true,
+            }],
+            participation_state: None,
+        },
+        settings: AuctionManagerSettings {
+            winning_configs: vec![WinningConfig {
+                items: vec![WinningConfigItem {
+                    safety_deposit_box_index: 0,
+                    amount: 1,
+                    winning_config_type: WinningConfigType::TokenOnlyTransfer,
+                }],
+            }],
+            participation_config: None,
+            payout_split: None,
+            fixed_price: Some(PRICE),
+        },
+    };
+    configure(&mut auction_manager_data);
+    if let Some(split) = &auction_manager_data.settings.payout_split {
+        for recipient in split.recipients.iter() {
+            program_test.add_packable_account(
+                associated_token_address(&recipient.address, &accept_mint),
+                Rent::default().minimum_balance(spl_token::state::Account::LEN),
+                &helpers::token_account(&accept_mint, &recipient.address),
+                &spl_token::id(),
+            );
+        }
+    }
+    program_test.add_account(
+        auction_manager,
+        helpers::program_account(&auction_manager_data, MAX_AUCTION_MANAGER_SIZE, &program_id),
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    helpers::create_token_account(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &accept_payment,
+        &accept_mint,
+        &auction_manager,
+    )
+    .await
+    .unwrap();
+    helpers::mint_tokens(
+        &mut banks_client,
+        &payer,
+        &recent_blockhash,
+        &accept_mint,
+        &accept_payment.pubkey(),
+        &mint_authority,
+        PRICE,
+    )
+    .await
+    .unwrap();
+
+    // One unit of the accept payment account's mint is worth two of the price mint.
+    let conversion_rate = Keypair::new();
+    create_conversion_rate(
+        &mut banks_client,
+        &program_id,
+        &payer,
+        &recent_blockhash,
+        &conversion_rate,
+        2 * CONVERSION_RATE_SCALE as u64,
+        &accept_mint,
+        &price_mint,
+    )
+    .await;
+
+    Listing {
+        program_id,
+        banks_client,
+        payer,
+        recent_blockhash,
+        mint_authority,
+        accept_mint,
+        price_mint,
+        auction_manager,
+        accept_payment: accept_payment.pubkey(),
+        store,
+        vault,
+        listing,
+        metadata,
+        safety_deposit,
+        creator,
+        destination,
+        conversion_rate,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn create_conversion_rate(
+    banks_client: &mut BanksClient,
+    program_id: &Pubkey,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    conversion_rate: &Keypair,
+    price_per_token: u64,
+    mint: &Pubkey,
+    price_mint: &Pubkey,
+) {
+    helpers::process(
+        banks_client,
+        recent_blockhash,
+        payer,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &conversion_rate.pubkey(),
+                Rent::default().minimum_balance(MAX_CONVERSION_RATE_SIZE),
+                MAX_CONVERSION_RATE_SIZE as u64,
+                program_id,
+            ),
+            create_set_conversion_rate_instruction(
+                *program_id,
+                conversion_rate.pubkey(),
+                price_per_token,
+                *mint,
+                *price_mint,
+            ),
+        ],
+        &[payer, conversion_rate],
+    )
+    .await
+    .unwrap();
+}
+
+/// Creates the holdings of a swap pool for the listing's conversion rate, which the pool has yet to
+/// be initialized with.
+async fn create_holdings(listing: &mut Listing, authority: &Keypair) -> SwapRoute {
+    let swap_pool = swap_pool_pubkey(
+        &listing.program_id,
+        &authority.pubkey(),
+        &listing.conversion_rate.pubkey(),
+    );
+    let from_holding = Keypair::new();
+    let to_holding = Keypair::new();
+    for (holding, mint) in [
+        (&from_holding, listing.accept_mint),
+        (&to_holding, listing.price_mint),
+    ]
+    .iter()
+    {
+        helpers::create_token_account(
+            &mut listing.banks_client,
+            &listing.payer,
+            &listing.recent_blockhash,
+            holding,
+            mint,
+            &swap_pool,
+        )
+        .await
+        .unwrap();
+    }
+
+    SwapRoute {
+        swap_pool,
+        conversion_rate: listing.conversion_rate.pubkey(),
+        from_holding: from_holding.pubkey(),
+        to_holding: to_holding.pubkey(),
+    }
+}
+
+/// The payout ticket of a recipient's share of the listing's only winning config, for the creator
+/// at an index, for a payout split recipient at an index, or for the auctioneer given neither.
+fn payout_ticket_pubkey(
+    listing: &Listing,
+    recipient: &Pubkey,
+    creator_index: Option<u8>,
+    payout_split_index: Option<u8>,
+) -> Pubkey {
+    let creator_index_key = match (creator_index, payout_split_index) {
+        (Some(index), _) => index.to_string(),
+        (None, Some(index)) => format!("split-{}", index),
+        (None, None) => "auctioneer".to_owned(),
+    };
+    Pubkey::find_program_address(
+        &[
+            PREFIX.as_bytes(),
+            listing.auction_manager.as_ref(),
+            b"0",
+            b"0",
+            creator_index_key.as_bytes(),
+            listing.safety_deposit.as_ref(),
+            recipient.as_ref(),
+            &[],
+        ],
+        &listing.program_id,
+    )
+    .0
+}
+
+/// An EmptyPaymentAccount paying a recipient's share of the listing's only winning config into a
+/// destination they own. See payout_ticket_pubkey.
+fn payout_instruction(
+    listing: &Listing,
+    destination: Pubkey,
+    recipient: &Pubkey,
+    creator_index: Option<u8>,
+    payout_split_index: Option<u8>,
+) -> Instruction {
+    Instruction {
+        program_id: listing.program_id,
+        accounts: vec![
+            AccountMeta::new(listing.accept_payment, false),
+            AccountMeta::new(destination, false),
+            AccountMeta::new(listing.auction_manager, false),
+            AccountMeta::new(
+                payout_ticket_pubkey(listing, recipient, creator_index, payout_split_index),
+                false,
+            ),
+            AccountMeta::new(listing.payer.pubkey(), true),
+            AccountMeta::new_readonly(listing.metadata, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(listing.safety_deposit, false),
+            AccountMeta::new_readonly(listing.store, false),
+            AccountMeta::new_readonly(listing.vault, false),
+            AccountMeta::new_readonly(listing.listing, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: MetaplexInstruction::EmptyPaymentAccount(EmptyPaymentAccountArgs {
+            winning_config_index: Some(0),
+            winning_config_item_index: Some(0),
+            creator_index,
+            payout_split_index,
+        })
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// An EmptyPaymentAccounts paying a batch of shares of the listing's only winning config, each
+/// given by its destination and recipient, and by its creator index or payout split index as in
+/// payout_instruction.
+fn batch_payout_instruction(
+    listing: &Listing,
+    payouts: &[(Pubkey, Pubkey, Option<u8>, Option<u8>)],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(listing.accept_payment, false),
+        AccountMeta::new_readonly(listing.auction_manager, false),
+        AccountMeta::new(listing.payer.pubkey(), true),
+        AccountMeta::new_readonly(listing.store, false),
+        AccountMeta::new_readonly(listing.vault, false),
+        AccountMeta::new_readonly(listing.listing, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    let mut args = vec![];
+    for (destination, recipient, creator_index, payout_split_index) in payouts.iter() {
+        accounts.extend(vec![
+            AccountMeta::new(*destination, false),
+            AccountMeta::new(
+                payout_ticket_pubkey(listing, recipient, *creator_index, *payout_split_index),
+                false,
+            ),
+            AccountMeta::new_readonly(listing.metadata, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(listing.safety_deposit, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ]);
+        args.push(BatchedPayoutArgs {
+            payout: EmptyPaymentAccountArgs {
+                winning_config_index: Some(0),
+                winning_config_item_index: Some(0),
+                creator_index: *creator_index,
+                payout_split_index: *payout_split_index,
+            },
+            swap_route: false,
+        });
+    }
+
+    Instruction {
+        program_id: listing.program_id,
+        accounts,
+        data: MetaplexInstruction::EmptyPaymentAccounts(EmptyPaymentAccountsArgs { payouts: args })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// An EmptyPaymentAccount paying the creator's share of the listing through a swap route.
+fn empty_payment_account_instruction(listing: &Listing, route: &SwapRoute) -> Instruction {
+    let mut instruction = payout_instruction(
+        listing,
+        listing.destination,
+        &listing.creator.pubkey(),
+        Some(0),
+        None,
+    );
+    instruction.accounts.extend(vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(route.swap_pool, false),
+        AccountMeta::new_readonly(route.conversion_rate, false),
+        AccountMeta::new(route.from_holding, false),
+        AccountMeta::new(route.to_holding, false),
+        AccountMeta::new_readonly(listing.creator.pubkey(), true),
+    ]);
+    instruction
+}
+
+fn assert_metaplex_error(err: TransportError, expected: MetaplexError) {
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(expected as u32))
+    );
+}
+
+/// Safety deposit box of a listing's vault, the token account it stores its tokens in, and the
+/// accounts of the buyer and of the authority of the auction manager to take them out into.
+struct ShopBox {
+    safety_deposit: Pubkey,
+    token_store: Pubkey,
+    buyer_destination: Pubkey,
+    authority_destination: Pubkey,
+}
+
+/// A running fixed price listing whose vault holds two safety deposit boxes: box 0 with plain
+/// tokens, and box 1 with the authorization tokens of a master edition, from which limited editions
+/// are minted. Each box holds SHOP_STOCK tokens. The buyer can pay SHOP_STOCK times the price, and
+/// has fresh mints of their own to mint editions onto.
+struct Shop {
+    program_id: Pubkey,
+    banks_client: BanksClient,
+    payer: Keypair,
+    recent_blockhash: Hash,
+    authority: Keypair,
+    buyer: Keypair,
+    listing: Pubkey,
+    auction_manager: Pubkey,
+    store: Pubkey,
+    vault: Pubkey,
+    fraction_mint: Pubkey,
+    transfer_authority: Pubkey,
+    accept_payment: Pubkey,
+    buyer_payment: Pubkey,
+    authority_payment: Pubkey,
+    boxes: Vec<ShopBox>,
+    master_edition: Pubkey,
+    master_metadata: Pubkey,
+    master_update_authority: Pubkey,
+    printing_mint: Pubkey,
+    edition_mints: Vec<Pubkey>,
+}
+
+/// A token account holding an amount of a mint, as written into the bank before the test starts.
+fn token_account_with(mint: &Pubkey, owner: &Pubkey, amount: u64) -> spl_token::state::Account {
+    spl_token::state::Account {
+        amount,
+        ..helpers::token_account(mint, owner)
+    }
+}
+
+/// Sets up a fixed price listing selling the given winning configs, letting the test change the
+/// auction manager it starts from. As with setup_listing, the state of the vault and of the token
+/// metadata program is written straight into the accounts, which the programs then work on. Each
+/// box the winning configs use has the validation ticket validating it would have left.
+async fn setup_shop(
+    winning_configs: Vec<WinningConfig>,
+    configure: impl FnOnce(&mut AuctionManager),
+) -> Shop {
+    setup_sale(Keypair::new(), None, winning_configs, configure).await
+}
+
+/// Same as setup_shop, but when given an auction the configs are sold through it rather than at a
+/// fixed price, with the buyer holding the bidder metadata of a bid on it. The auction manager is
+/// made the authority of the auction, as InitAuctionManager requires.
+async fn setup_sale(
+    buyer: Keypair,
+    auction: Option<AuctionData>,
+    winning_configs: Vec<WinningConfig>,
+    configure: impl FnOnce(&mut AuctionManager),
+) -> Shop {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("spl_metaplex", program_id, None);
+    program_test.add_program("spl_token_vault", spl_token_vault::id(), None);
+    program_test.add_program("spl_token_metadata", spl_token_metadata::id(), None);
+    program_test.add_program("spl_auction", spl_auction::id(), None);
+    let rent = Rent::default();
+
+    let authority = Keypair::new();
+    program_test.add_account(
+        buyer.pubkey(),
+        Account {
+            lamports: 1_000_000_000,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let store = Pubkey::new_unique();
+    program_test.add_account(
+        store,
+        helpers::program_account(
+            &Store {
+                key: Key::StoreV1,
+                public: true,
+                auction_program: spl_auction::id(),
+                token_vault_program: spl_token_vault::id(),
+                token_metadata_program: spl_token_metadata::id(),
+                token_program: spl_token::id(),
+            },
+            MAX_STORE_SIZE,
+            &program_id,
+        ),
+    );
+
+    // An auction is created with the vault as its resource.
+    let vault = Pubkey::new_unique();
+    let auction_program = spl_auction::id();
+    let listing = match auction {
+        Some(_) => {
+            Pubkey::find_program_address(
+                &[
+                    spl_auction::PREFIX.as_bytes(),
+                    auction_program.as_ref(),
+                    vault.as_ref(),
+                ],
+                &auction_program,
+            )
+            .0
+        }
+        None => Pubkey::new_unique(),
+    };
+    let (auction_manager, _) =
+        Pubkey::find_program_address(&[PREFIX.as_bytes(), listing.as_ref()], &program_id);
+
+    let fixed_price = match auction {
+        Some(mut auction) => {
+            auction.authority = auction_manager;
+            // Room for one more bid than the auction has winners covers the extra state the bid
+            // states keep, such as the prices of a Dutch auction.
+            let size = BASE_AUCTION_DATA_SIZE
+                + mem::size_of::<Bid>() * (auction.num_possible_winners() as usize + 1);
+            program_test.add_account(
+                listing,
+                helpers::program_account(&auction, size, &auction_program),
+            );
+
+            // The buyer's bid is the only one standing, and the auction can be cancelled with it.
+            let (auction_extended, _) = Pubkey::find_program_address(
+                &[
+                    spl_auction::PREFIX.as_bytes(),
+                    auction_program.as_ref(),
+                    vault.as_ref(),
+                    spl_auction::EXTENDED.as_bytes(),
+                ],
+                &auction_program,
+            );
+            program_test.add_account(
+                auction_extended,
+                helpers::program_account(
+                    &AuctionDataExtended {
+                        total_uncancelled_bids: 1,
+                        tick_size: None,
+                        gap_tick_size_percentage: None,
+                        uniform_price: false,
+                        extension_policy: None,
+                        total_extension: 0,
+                        instant_sale_price: None,
+                        open_bidder_pots: Some(1),
+                        bid_history_pages: None,
+                        start_auction_at: None,
+                        bidder_gate: None,
+                        max_wins_per_wallet: None,
+                        cancellable_with_bids: true,
+                        exchange_rates: vec![],
+                    },
+                    MAX_AUCTION_DATA_EXTENDED_SIZE,
+                    &auction_program,
+                ),
+            );
+
+            let (bidder_metadata, _) = Pubkey::find_program_address(
+                &[
+                    spl_auction::PREFIX.as_bytes(),
+                    auction_program.as_ref(),
+                    listing.as_ref(),
+                    buyer.pubkey().as_ref(),
+                    "metadata".as_bytes(),
+                ],
+                &auction_program,
+            );
+            program_test.add_account(
+                bidder_metadata,
+                helpers::program_account(
+                    &BidderMetadata {
+                        bidder_pubkey: buyer.pubkey(),
+                        auction_pubkey: listing,
+                        last_bid: PRICE,
+                        last_bid_timestamp: 0,
+                        cancelled: false,
+                        payer: buyer.pubkey(),
+                    },
+                    BIDDER_METADATA_LEN,
+                    &auction_program,
+                ),
+            );
+            None
+        }
+        None => Some(PRICE),
+    };
+
+    let mint_authority = Pubkey::new_unique();
+    let add_mint = |program_test: &mut ProgramTest, mint: Pubkey, authority: &Pubkey, supply| {
+        program_test.add_packable_account(
+            mint,
+            rent.minimum_balance(spl_token::state::Mint::LEN),
+            &spl_token::state::Mint {
+                supply,
+                ..helpers::mint(authority)
+            },
+            &spl_token::id(),
+        );
+    };
+    let add_token_account =
+        |program_test: &mut ProgramTest, mint: Pubkey, owner: Pubkey, amount| {
+            let account = Pubkey::new_unique();
+            program_test.add_packable_account(
+                account,
+                rent.minimum_balance(spl_token::state::Account::LEN),
+                &token_account_with(&mint, &owner, amount),
+                &spl_token::id(),
+            );
+            account
+        };
+
+    // The payment mint.
+    let accept_mint = Pubkey::new_unique();
+    add_mint(&mut program_test, accept_mint, &mint_authority, 0);
+    let accept_payment = add_token_account(&mut program_test, accept_mint, auction_manager, 0);
+    let buyer_payment = add_token_account(
+        &mut program_test,
+        accept_mint,
+        buyer.pubkey(),
+        SHOP_STOCK * PRICE,
+    );
+    let authority_payment =
+        add_token_account(&mut program_test, accept_mint, authority.pubkey(), 0);
+
+    // The master edition that box 1 holds the authorization tokens of.
+    let master_mint = Pubkey::new_unique();
+    let printing_mint = Pubkey::new_unique();
+    let metadata_program = spl_token_metadata::id();
+    let (master_metadata, _) = Pubkey::find_program_address(
+        &[
+            spl_token_metadata::state::PREFIX.as_bytes(),
+            metadata_program.as_ref(),
+            master_mint.as_ref(),
+        ],
+        &metadata_program,
+    );
+    let (master_edition, _) = Pubkey::find_program_address(
+        &[
+            spl_token_metadata::state::PREFIX.as_bytes(),
+            metadata_program.as_ref(),
+            master_mint.as_ref(),
+            EDITION.as_bytes(),
+        ],
+        &metadata_program,
+    );
+    let master_update_authority = Pubkey::new_unique();
+    add_mint(&mut program_test, master_mint, &master_edition, 1);
+    add_mint(
+        &mut program_test,
+        printing_mint,
+        &master_edition,
+        SHOP_STOCK,
+    );
+    program_test.add_account(
+        master_metadata,
+        helpers::program_account(
+            &Metadata {
+                key: spl_token_metadata::state::Key::MetadataV1,
+                update_authority: master_update_authority,
+                mint: master_mint,
+                data: Data {
+                    name: "Master".to_owned(),
+                    symbol: "MSTR".to_owned(),
+                    uri: "https://example.com".to_owned(),
+                    seller_fee_basis_points: 500,
+                    creators: Some(vec![Creator {
+                        address: master_update_authority,
+                        verified: true,
+                        share: 100,
+                    }]),
+                },
+                primary_sale_happened: false,
+                is_mutable: true,
+            },
+            MAX_METADATA_LEN,
+            &metadata_program,
+        ),
+    );
+    program_test.add_account(
+        master_edition,
+        helpers::program_account(
+            &MasterEdition {
+                key: spl_token_metadata::state::Key::MasterEditionV1,
+                supply: 0,
+                max_supply: Some(10),
+                printing_mint,
+                one_time_printing_authorization_mint: Pubkey::new_unique(),
+            },
+            MAX_MASTER_EDITION_LEN,
+            &metadata_program,
+        ),
+    );
+
+    // Mints the buyer has editions minted onto, holding one token each. The token metadata program
+    // takes over both of their authorities.
+    let mut edition_mints = vec![];
+    for _ in 0..SHOP_STOCK {
+        let edition_mint = Pubkey::new_unique();
+        program_test.add_packable_account(
+            edition_mint,
+            rent.minimum_balance(spl_token::state::Mint::LEN),
+            &spl_token::state::Mint {
+                supply: 1,
+                freeze_authority: COption::Some(buyer.pubkey()),
+                ..helpers::mint(&buyer.pubkey())
+            },
+            &spl_token::id(),
+        );
+        edition_mints.push(edition_mint);
+    }
+
+    // A combined vault, which the auction manager has authority over.
+    let fraction_mint = Pubkey::new_unique();
+    let vault_program = spl_token_vault::id();
+    let (transfer_authority, _) = Pubkey::find_program_address(
+        &[
+            spl_token_vault::state::PREFIX.as_bytes(),
+            vault_program.as_ref(),
+            vault.as_ref(),
+        ],
+        &vault_program,
+    );
+    add_mint(&mut program_test, fraction_mint, &transfer_authority, 0);
+    program_test.add_account(
+        vault,
+        helpers::program_account(
+            &Vault {
+                key: spl_token_vault::state::Key::VaultV1,
+                token_program: spl_token::id(),
+                fraction_mint,
+                authority: auction_manager,
+                fraction_treasury: Pubkey::new_unique(),
+                redeem_treasury: Pubkey::new_unique(),
+                allow_further_share_creation: false,
+                pricing_lookup_address: Pubkey::new_unique(),
+                token_type_count: 2,
+                state: VaultState::Combined,
+                locked_price_per_share: 0,
+            },
+            MAX_VAULT_SIZE,
+            &vault_program,
+        ),
+    );
+
+    let item_mint = Pubkey::new_unique();
+    add_mint(&mut program_test, item_mint, &mint_authority, SHOP_STOCK);
+    let mut boxes = vec![];
+    for (order, mint) in [item_mint, printing_mint].iter().enumerate() {
+        let (safety_deposit, _) = Pubkey::find_program_address(
+            &[
+                spl_token_vault::state::PREFIX.as_bytes(),
+                vault.as_ref(),
+                mint.as_ref(),
+            ],
+            &vault_program,
+        );
+        let token_store =
+            add_token_account(&mut program_test, *mint, transfer_authority, SHOP_STOCK);
+        program_test.add_account(
+            safety_deposit,
+            helpers::program_account(
+                &SafetyDepositBox {
+                    key: spl_token_vault::state::Key::SafetyDepositBoxV1,
+                    vault,
+                    token_mint: *mint,
+                    store: token_store,
+                    order: order as u8,
+                },
+                MAX_SAFETY_DEPOSIT_SIZE,
+                &vault_program,
+            ),
+        );
+        boxes.push(ShopBox {
+            safety_deposit,
+            token_store,
+            buyer_destination: add_token_account(&mut program_test, *mint, buyer.pubkey(), 0),
+            authority_destination: add_token_account(
+                &mut program_test,
+                *mint,
+                authority.pubkey(),
+                0,
+            ),
+        });
+    }
+
+    let winning_config_states = winning_configs
+        .iter()
+        .map(|config| WinningConfigState {
+            items: config
+                .items
+                .iter()
+                .map(|_| WinningConfigStateItem {
+                    primary_sale_happened: false,
+                    claimed: false,
+                })
+                .collect(),
+            money_pushed_to_accept_payment: false,
+        })
+        .collect();
+    let mut auction_manager_data = AuctionManager {
+        key: Key::AuctionManagerV1,
+        store,
+        authority: authority.pubkey(),
+        auction: listing,
+        vault,
+        accept_payment,
+        state: AuctionManagerState {
+            status: AuctionManagerStatus::Running,
+            winning_config_items_validated: winning_configs
+                .iter()
+                .map(|config| config.items.len() as u8)
+                .sum(),
+            winning_config_states,
+            participation_state: None,
+        },
+        settings: AuctionManagerSettings {
+            winning_configs,
+            participation_config: None,
+            payout_split: None,
+            fixed_price,
+        },
+    };
+    configure(&mut auction_manager_data);
+    for (order, shop_box) in boxes.iter().enumerate() {
+        let used = auction_manager_data
+            .settings
+            .winning_configs
+            .iter()
+            .any(|config| {
+                config
+                    .items
+                    .iter()
+                    .any(|item| item.safety_deposit_box_index as usize == order)
+            });
+        if used {
+            program_test.add_account(
+                validation_ticket_pubkey(&program_id, &auction_manager, &shop_box.safety_deposit),
+                helpers::program_account(
+                    &SafetyDepositValidationTicket {
+                        key: Key::SafetyDepositValidationTicketV1,
+                        address: shop_box.safety_deposit,
+                    },
+                    MAX_VALIDATION_TICKET_SIZE,
+                    &program_id,
+                ),
+            );
+        }
+    }
+    program_test.add_account(
+        auction_manager,
+        helpers::program_account(&auction_manager_data, MAX_AUCTION_MANAGER_SIZE, &program_id),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    Shop {
+        program_id,
+        banks_client,
+        payer,
+        recent_blockhash,
+        authority,
+        buyer,
+        listing,
+        auction_manager,
+        store,
+        vault,
+        fraction_mint,
+        transfer_authority,
+        accept_payment,
+        buyer_payment,
+        authority_payment,
+        boxes,
+        master_edition,
+        master_metadata,
+        master_update_authority,
+        printing_mint,
+        edition_mints,
+    }
+}
+
+/// The validation ticket of a safety deposit box of an auction manager.
+fn validation_ticket_pubkey(
+    program_id: &Pubkey,
+    auction_manager: &Pubkey,
+    safety_deposit: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            auction_manager.as_ref(),
+            safety_deposit.as_ref(),
+        ],
+        program_id,
+    )
+    .0
+}
+
+/// A winning config of a single item, taking an amount out of a box of the shop.
+fn winning_config(
+    safety_deposit_box_index: u8,
+    amount: u8,
+    winning_config_type: WinningConfigType,
+) -> WinningConfig {
+    WinningConfig {
+        items: vec![WinningConfigItem {
+            safety_deposit_box_index,
+            amount,
+            winning_config_type,
+        }],
+    }
+}
+
+/// Buys the next config of a shop of single item configs out of the given box, as the buyer or as
+/// the authority taking it back. The buyer has an edition minted onto the given mint when the item
+/// is a limited edition.
+async fn buy(
+    shop: &mut Shop,
+    box_index: usize,
+    taking_back: bool,
+    edition_mint: Option<Pubkey>,
+) -> Result<(), TransportError> {
+    let metadata_program = spl_token_metadata::id();
+    let limited_edition = edition_mint.map(|new_mint| {
+        let seeds = &[
+            spl_token_metadata::state::PREFIX.as_bytes(),
+            metadata_program.as_ref(),
+            new_mint.as_ref(),
+        ];
+        let (new_metadata, _) = Pubkey::find_program_address(seeds, &metadata_program);
+        let (new_edition, _) = Pubkey::find_program_address(
+            &[seeds[0], seeds[1], seeds[2], EDITION.as_bytes()],
+            &metadata_program,
+        );
+        LimitedEditionAccounts {
+            new_metadata,
+            new_edition,
+            master_edition: shop.master_edition,
+            new_mint,
+            printing_mint: shop.printing_mint,
+            master_metadata: shop.master_metadata,
+            master_update_authority: shop.master_update_authority,
+        }
+    });
+    let shop_box = &shop.boxes[box_index];
+    let (buyer, payment, destination) = if taking_back {
+        (
+            &shop.authority,
+            shop.authority_payment,
+            shop_box.authority_destination,
+        )
+    } else {
+        (&shop.buyer, shop.buyer_payment, shop_box.buyer_destination)
+    };
+
+    helpers::process(
+        &mut shop.banks_client,
+        &shop.recent_blockhash,
+        &shop.payer,
+        &[create_buy_fixed_price_instruction(
+            shop.program_id,
+            shop.auction_manager,
+            shop.store,
+            shop.vault,
+            shop.fraction_mint,
+            shop.accept_payment,
+            payment,
+            buyer.pubkey(),
+            shop.transfer_authority,
+            &[(
+                shop_box.safety_deposit,
+                shop_box.token_store,
+                destination,
+                limited_edition,
+            )],
+        )],
+        &[&shop.payer, buyer],
+    )
+    .await
+}
+
+/// Redeems the buyer's winning bid on the auction of a sale, taking its item out of the given box.
+async fn redeem(shop: &mut Shop, box_index: usize) -> Result<(), TransportError> {
+    let auction_program = spl_auction::id();
+    let (bidder_metadata, _) = Pubkey::find_program_address(
+        &[
+            spl_auction::PREFIX.as_bytes(),
+            auction_program.as_ref(),
+            shop.listing.as_ref(),
+            shop.buyer.pubkey().as_ref(),
+            "metadata".as_bytes(),
+        ],
+        &auction_program,
+    );
+    let (bid_redemption, _) = Pubkey::find_program_address(
+        &[
+            PREFIX.as_bytes(),
+            shop.listing.as_ref(),
+            bidder_metadata.as_ref(),
+        ],
+        &shop.program_id,
+    );
+    let shop_box = &shop.boxes[box_index];
+
+    helpers::process(
+        &mut shop.banks_client,
+        &shop.recent_blockhash,
+        &shop.payer,
+        &[create_redeem_bid_instruction(
+            shop.program_id,
+            shop.auction_manager,
+            shop_box.token_store,
+            shop_box.buyer_destination,
+            bid_redemption,
+            shop_box.safety_deposit,
+            shop.vault,
+            shop.fraction_mint,
+            shop.listing,
+            bidder_metadata,
+            shop.buyer.pubkey(),
+            shop.payer.pubkey(),
+            shop.store,
+            shop.transfer_authority,
+        )],
+        &[&shop.payer, &shop.buyer],
+    )
+    .await
+}
+
+async fn get_auction_manager(shop: &mut Shop) -> AuctionManager {
+    let account = shop
+        .banks_client
+        .get_account(shop.auction_manager)
+        .await
+        .unwrap()
+        .unwrap();
+    try_from_slice_unchecked(&account.data).unwrap()
+}
+
+/// A creator paid in another mint than the listing took has their share converted through a swap
+/// pool, which keeps the payout and pays its converted value out of its stock.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_payout_through_swap_pool() {
+    let mut listing = setup_listing().await;
+    let authority = Keypair::new();
+    let route = create_holdings(&mut listing, &authority).await;
+
+    helpers::process(
+        &mut listing.banks_client,
+        &listing.recent_blockhash,
+        &listing.payer,
+        &[create_init_swap_pool_instruction(
+            listing.program_id,
+            route.swap_pool,
+            authority.pubkey(),
+            listing.payer.pubkey(),
+            route.conversion_rate,
+            route.from_holding,
+            route.to_holding,
+        )],
+        &[&listing.payer, &authority],
+    )
+    .await
+    .expect("init_swap_pool");
+
+    helpers::mint_tokens(
+        &mut listing.banks_client,
+        &listing.payer,
+        &listing.recent_blockhash,
+        &listing.price_mint,
+        &route.to_holding,
+        &listing.mint_authority,
+        STOCK,
+    )
+    .await
+    .unwrap();
+
+    let instruction = empty_payment_account_instruction(&listing, &route);
+    helpers::process(
+        &mut listing.banks_client,
+        &listing.recent_blockhash,
+        &listing.payer,
+        &[instruction],
+        &[&listing.payer, &listing.creator],
+    )
+    .await
+    .expect("empty_payment_account");
+
+    let banks_client = &mut listing.banks_client;
+    assert_eq!(
+        helpers::get_token_balance(banks_client, &listing.accept_payment).await,
+        0
+    );
+    assert_eq!(
+        helpers::get_token_balance(banks_client, &route.from_holding).await,
+        PRICE
+    );
+    assert_eq!(
+        helpers::get_token_balance(banks_client, &route.to_holding).await,
+        STOCK - 2 * PRICE
+    );
+    assert_eq!(
+        helpers::get_token_balance(banks_client, &listing.destination).await,
+        2 * PRICE
+    );
+}
+
+/// On a secondary sale the auctioneer's share is split further, each payout split recipient taking
+/// its basis points of it into their associated token account on a payout ticket of their own, and
+/// the auctioneer keeping the rest. Paying a recipient again pays them nothing more.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_payout_split() {
+    let auctioneer = Pubkey::new_unique();
+    let recipients = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let mut listing = setup_listing_with(|auction_manager| {
+        auction_manager.authority = auctioneer;
+        auction_manager.state.winning_config_states[0].items[0].primary_sale_happened = true;
+        auction_manager.settings.payout_split = Some(PayoutSplit {
+            recipients: vec![
+                PayoutSplitRecipient {
+                    address: recipients[0],
+                    basis_points: 2_000,
+                },
+                PayoutSplitRecipient {
+                    address: recipients[1],
+                    basis_points: 1_000,
+                },
+            ],
+        });
+    })
+    .await;
+    let auctioneer_destination = Keypair::new();
+    helpers::create_token_account(
+        &mut listing.banks_client,
+        &listing.payer,
+        &listing.recent_blockhash,
+        &auctioneer_destination,
+        &listing.accept_mint,
+        &auctioneer,
+    )
+    .await
+    .unwrap();
+    let destinations = [
+        associated_token_address(&recipients[0], &listing.accept_mint),
+        associated_token_address(&recipients[1], &listing.accept_mint),
+    ];
+
+    // A recipient the split doesn't have, one recipient's share into the other's account, and a
+    // recipient's share taken out of a creator's.
+    for (destination, recipient, creator_index, payout_split_index, error) in [
+        (
+            destinations[0],
+            recipients[0],
+            None,
+            Some(2),
+            MetaplexError::InvalidPayoutSplitIndex,
+        ),
+        (
+            destinations[1],
+            recipients[1],
+            None,
+            Some(0),
+            MetaplexError::IncorrectOwner,
+        ),
+        (
+            destinations[0],
+            recipients[0],
+            Some(0),
+            Some(0),
+            MetaplexError::InvalidPayoutSplitIndex,
+        ),
+    ]
+    .iter()
+    {
+        let instruction = payout_instruction(
+            &listing,
+            *destination,
+            recipient,
+            *creator_index,
+            *payout_split_index,
+        );
+        let err = helpers::process(
+            &mut listing.banks_client,
+            &listing.recent_blockhash,
+            &listing.payer,
+            &[instruction],
+            &[&listing.payer],
+        )
+        .await
+        .unwrap_err();
+        assert_metaplex_error(err, error.clone());
+    }
+
+    // The first recipient is paid on their own, and then again along with everyone else.
+    let first = payout_instruction(&listing, destinations[0], &recipients[0], None, Some(0));
+    let all = vec![
+        first.clone(),
+        payout_instruction(&listing, destinations[1], &recipients[1], None, Some(1)),
+        payout_instruction(
+            &listing,
+            auctioneer_destination.pubkey(),
+            &auctioneer,
+            None,
+            None,
+        ),
+    ];
+    for instructions in [vec![first], all].iter() {
+        helpers::process(
+            &mut listing.banks_client,
+            &listing.recent_blockhash,
+            &listing.payer,
+            instructions,
+            &[&listing.payer],
+        )
+        .await
+        .expect("empty_payment_account");
+    }
+
+    // The creator's royalty of 5% is all that is left.
+    let ticket = payout_ticket_pubkey(&listing, &recipients[0], None, Some(0));
+    let banks_client = &mut listing.banks_client;
+    for (account, amount) in [
+        (destinations[0], 190),
+        (destinations[1], 95),
+        (auctioneer_destination.pubkey(), 665),
+        (listing.accept_payment, 50),
+    ]
+    .iter()
+    {
+        assert_eq!(
+            helpers::get_token_balance(banks_client, account).await,
+            *amount
+        );
+    }
+    let account = banks_client.get_account(ticket).await.unwrap().unwrap();
+    let ticket: PayoutTicket = try_from_slice_unchecked(&account.data).unwrap();
+    assert_eq!(ticket.recipient, recipients[0]);
+    assert_eq!(ticket.amount_paid, 190);
+}
+
+/// A batch of payouts pays each of them on its own payout ticket, so resending it, or paying one of
+/// them again on its own, pays nobody twice. A batch with a payout that can't be made pays nothing.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_batched_payouts_are_idempotent() {
+    let auctioneer = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let mut listing = setup_listing_with(|auction_manager| {
+        auction_manager.authority = auctioneer;
+        auction_manager.state.winning_config_states[0].items[0].primary_sale_happened = true;
+        auction_manager.settings.payout_split = Some(PayoutSplit {
+            recipients: vec![PayoutSplitRecipient {
+                address: recipient,
+                basis_points: 2_000,
+            }],
+        });
+    })
+    .await;
+    let auctioneer_destination = Keypair::new();
+    helpers::create_token_account(
+        &mut listing.banks_client,
+        &listing.payer,
+        &listing.recent_blockhash,
+        &auctioneer_destination,
+        &listing.accept_mint,
+        &auctioneer,
+    )
+    .await
+    .unwrap();
+    let split = (
+        associated_token_address(&recipient, &listing.accept_mint),
+        recipient,
+        None,
+        Some(0),
+    );
+    let auctioneer_share = (auctioneer_destination.pubkey(), auctioneer, None, None);
+
+    let failing = batch_payout_instruction(&listing, &[split, (split.0, split.1, None, Some(1))]);
+    let empty = batch_payout_instruction(&listing, &[]);
+    let err = helpers::process(
+        &mut listing.banks_client,
+        &listing.recent_blockhash,
+        &listing.payer,
+        &[failing],
+        &[&listing.payer],
+    )
+    .await
+    .unwrap_err();
+    assert_metaplex_error(err, MetaplexError::InvalidPayoutSplitIndex);
+    let err = helpers::process(
+        &mut listing.banks_client,
+        &listing.recent_blockhash,
+        &listing.payer,
+        &[empty],
+        &[&listing.payer],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+    assert_eq!(
+        helpers::get_token_balance(&mut listing.banks_client, &split.0).await,
+        0
+    );
+
+    let instructions = [
+        batch_payout_instruction(&listing, &[split]),
+        batch_payout_instruction(&listing, &[split, auctioneer_share]),
+        payout_instruction(&listing, split.0, &recipient, None, Some(0)),
+    ];
+    for instruction in instructions.iter() {
+        helpers::process(
+            &mut listing.banks_client,
+            &listing.recent_blockhash,
+            &listing.payer,
+            &[instruction.clone()],
+            &[&listing.payer],
+        )
+        .await
+        .expect("empty_payment_accounts");
+    }
+
+    let banks_client = &mut listing.banks_client;
+    for (account, amount) in [
+        (split.0, 190),
+        (auctioneer_destination.pubkey(), 760),
+        (listing.accept_payment, 50),
+    ]
+    .iter()
+    {
+        assert_eq!(
+            helpers::get_token_balance(banks_client, account).await,
+            *amount
+        );
+    }
+}
+
+/// A swap pool only takes holdings in the mints of its conversion rate, and only converts through
+/// the conversion rate and holdings it was created with.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_swap_pool_rejects_mismatches() {
+    let mut listing = setup_listing().await;
+    let authority = Keypair::new();
+    let route = create_holdings(&mut listing, &authority).await;
+
+    // Holdings given the wrong way around are each in the other mint.
+    let err = helpers::process(
+        &mut listing.banks_client,
+        &listing.recent_blockhash,
+        &listing.payer,
+        &[create_init_swap_pool_instruction(
+            listing.program_id,
+            route.swap_pool,
+            authority.pubkey(),
+            listing.payer.pubkey(),
+            route.conversion_rate,
+            route.to_holding,
+            route.from_holding,
+        )],
+        &[&listing.payer, &authority],
+    )
+    .await
+    .unwrap_err();
+    assert_metaplex_error(err, MetaplexError::SwapPoolMismatch);
+
+    helpers::process(
+        &mut listing.banks_client,
+        &listing.recent_blockhash,
+        &listing.payer,
+        &[create_init_swap_pool_instruction(
+            listing.program_id,
+            route.swap_pool,
+            authority.pubkey(),
+            listing.payer.pubkey(),
+            route.conversion_rate,
+            route.from_holding,
+            route.to_holding,
+        )],
+        &[&listing.payer, &authority],
+    )
+    .await
+    .expect("init_swap_pool");
+    helpers::mint_tokens(
+        &mut listing.banks_client,
+        &listing.payer,
+        &listing.recent_blockhash,
+        &listing.price_mint,
+        &route.to_holding,
+        &listing.mint_authority,
+        STOCK,
+    )
+    .await
+    .unwrap();
+
+    // A better rate between the same mints, that the pool was not created with.
+    let other_rate = Keypair::new();
+    create_conversion_rate(
+        &mut listing.banks_client,
+        &listing.program_id,
+        &listing.payer,
+        &listing.recent_blockhash,
+        &other_rate,
+        4 * CONVERSION_RATE_SCALE as u64,
+        &listing.accept_mint,
+        &listing.price_mint,
+    )
+    .await;
+
+    // A holding of the right mint that the pool does not hold.
+    let other_holding = Keypair::new();
+    helpers::create_token_account(
+        &mut listing.banks_client,
+        &listing.payer,
+        &listing.recent_blockhash,
+        &other_holding,
+        &listing.price_mint,
+        &authority.pubkey(),
+    )
+    .await
+    .unwrap();
+
+    let mismatches = [
+        SwapRoute {
+            conversion_rate: other_rate.pubkey(),
+            ..route
+        },
+        SwapRoute {
+            to_holding: other_holding.pubkey(),
+            ..route
+        },
+    ];
+    for mismatch in mismatches.iter() {
+        let instruction = empty_payment_account_instruction(&listing, mismatch);
+        let err = helpers::process(
+            &mut listing.banks_client,
+            &listing.recent_blockhash,
+            &listing.payer,
+            &[instruction],
+            &[&listing.payer, &listing.creator],
+        )
+        .await
+        .unwrap_err();
+        assert_metaplex_error(err, MetaplexError::SwapPoolMismatch);
+    }
+
+    assert_eq!(
+        helpers::get_token_balance(&mut listing.banks_client, &listing.accept_payment).await,
+        PRICE
+    );
+    assert_eq!(
+        helpers::get_token_balance(&mut listing.banks_client, &route.to_holding).await,
+        STOCK
+    );
+}
+
+/// Each buy pays the fixed price into the accept payment account and hands the buyer the items of
+/// the next config, in order, until the listing is finished and nothing more can be taken out.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_buy_fixed_price() {
+    let configs = vec![
+        winning_config(0, 1, WinningConfigType::TokenOnlyTransfer),
+        winning_config(1, 2, WinningConfigType::TokenOnlyTransfer),
+    ];
+    let mut shop = setup_shop(configs, |_| {}).await;
+
+    buy(&mut shop, 0, false, None)
+        .await
+        .expect("buy_fixed_price");
+    let auction_manager = get_auction_manager(&mut shop).await;
+    let states = &auction_manager.state.winning_config_states;
+    assert!(states[0].money_pushed_to_accept_payment && states[0].items[0].claimed);
+    assert!(!states[1].money_pushed_to_accept_payment && !states[1].items[0].claimed);
+    assert_eq!(auction_manager.state.status, AuctionManagerStatus::Running);
+    assert_eq!(
+        helpers::get_token_balance(&mut shop.banks_client, &shop.accept_payment).await,
+        PRICE
+    );
+    assert_eq!(
+        helpers::get_token_balance(&mut shop.banks_client, &shop.boxes[0].buyer_destination).await,
+        1
+    );
+
+    buy(&mut shop, 1, false, None)
+        .await
+        .expect("buy_fixed_price");
+    let auction_manager = get_auction_manager(&mut shop).await;
+    assert!(auction_manager.state.winning_config_states[1].money_pushed_to_accept_payment);
+    assert_eq!(auction_manager.state.status, AuctionManagerStatus::Finished);
+    assert_eq!(
+        helpers::get_token_balance(&mut shop.banks_client, &shop.accept_payment).await,
+        2 * PRICE
+    );
+    assert_eq!(
+        helpers::get_token_balance(&mut shop.banks_client, &shop.buyer_payment).await,
+        (SHOP_STOCK - 2) * PRICE
+    );
+    assert_eq!(
+        helpers::get_token_balance(&mut shop.banks_client, &shop.boxes[1].buyer_destination).await,
+        2
+    );
+
+    let err = buy(&mut shop, 0, true, None).await.unwrap_err();
+    assert_metaplex_error(err, MetaplexError::InvalidStatus);
+}
+
+/// A config that was paid for, or had any of its items taken out, is never sold again, so a
+/// listing with nothing else left turns buyers away even while it is still running.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_buy_fixed_price_sold_out() {
+    let configs = vec![
+        winning_config(0, 1, WinningConfigType::TokenOnlyTransfer),
+        WinningConfig {
+            items: vec![
+                winning_config(0, 1, WinningConfigType::TokenOnlyTransfer).items[0],
+                winning_config(1, 1, WinningConfigType::TokenOnlyTransfer).items[0],
+            ],
+        },
+    ];
+    let mut shop = setup_shop(configs, |auction_manager| {
+        let states = &mut auction_manager.state.winning_config_states;
+        states[0].money_pushed_to_accept_payment = true;
+        states[0].items[0].claimed = true;
+        states[1].items[0].claimed = true;
+    })
+    .await;
+
+    let err = buy(&mut shop, 0, false, None).await.unwrap_err();
+    assert_metaplex_error(err, MetaplexError::FixedPriceListingSoldOut);
+    assert_eq!(
+        helpers::get_token_balance(&mut shop.banks_client, &shop.buyer_payment).await,
+        SHOP_STOCK * PRICE
+    );
+}
+
+/// The authority takes the next config back without paying, leaving nothing owed on it, and the
+/// buyer after them is sold the config after it.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_buy_fixed_price_take_back() {
+    let configs = vec![
+        winning_config(0, 1, WinningConfigType::TokenOnlyTransfer),
+        winning_config(0, 1, WinningConfigType::TokenOnlyTransfer),
+    ];
+    let mut shop = setup_shop(configs, |_| {}).await;
+
+    buy(&mut shop, 0, true, None)
+        .await
+        .expect("buy_fixed_price");
+    let auction_manager = get_auction_manager(&mut shop).await;
+    let states = &auction_manager.state.winning_config_states;
+    assert!(!states[0].money_pushed_to_accept_payment && states[0].items[0].claimed);
+    assert_eq!(
+        helpers::get_token_balance(&mut shop.banks_client, &shop.accept_payment).await,
+        0
+    );
+    assert_eq!(
+        helpers::get_token_balance(&mut shop.banks_client, &shop.boxes[0].authority_destination)
+            .await,
+        1
+    );
+
+    buy(&mut shop, 0, false, None)
+        .await
+        .expect("buy_fixed_price");
+    let auction_manager = get_auction_manager(&mut shop).await;
+    assert!(auction_manager.state.winning_config_states[1].money_pushed_to_accept_payment);
+    assert_eq!(
+        helpers::get_token_balance(&mut shop.banks_client, &shop.accept_payment).await,
+        PRICE
+    );
+}
+
+/// A buyer of a limited edition has the authorization token burned for an edition on their own
+/// mint right away, with the metadata given to the master's update authority, who doesn't sign.
+/// The authority takes the next authorization token back as it is.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_buy_fixed_price_limited_edition() {
+    let configs = vec![
+        winning_config(1, 1, WinningConfigType::LimitedEdition),
+        winning_config(1, 1, WinningConfigType::LimitedEdition),
+    ];
+    let mut shop = setup_shop(configs, |_| {}).await;
+
+    let new_mint = shop.edition_mints[0];
+    buy(&mut shop, 1, false, Some(new_mint))
+        .await
+        .expect("buy_fixed_price");
+    assert_eq!(
+        helpers::get_token_balance(&mut shop.banks_client, &shop.boxes[1].buyer_destination).await,
+        0
+    );
+
+    let metadata_program = spl_token_metadata::id();
+    let seeds = &[
+        spl_token_metadata::state::PREFIX.as_bytes(),
+        metadata_program.as_ref(),
+        new_mint.as_ref(),
+    ];
+    let (new_metadata, _) = Pubkey::find_program_address(seeds, &metadata_program);
+    let (new_edition, _) = Pubkey::find_program_address(
+        &[seeds[0], seeds[1], seeds[2], EDITION.as_bytes()],
+        &metadata_program,
+    );
+    let account = shop.banks_client.get_account(new_metadata).await.unwrap();
+    let metadata: Metadata = try_from_slice_unchecked(&account.unwrap().data).unwrap();
+    assert_eq!(metadata.mint, new_mint);
+    assert_eq!(metadata.update_authority, shop.master_update_authority);
+    let account = shop.banks_client.get_account(new_edition).await.unwrap();
+    let edition: Edition = try_from_slice_unchecked(&account.unwrap().data).unwrap();
+    assert_eq!(edition.parent, shop.master_edition);
+    assert_eq!(edition.edition, 1);
+
+    buy(&mut shop, 1, true, None)
+        .await
+        .expect("buy_fixed_price");
+    assert_eq!(
+        helpers::get_token_balance(&mut shop.banks_client, &shop.boxes[1].authority_destination)
+            .await,
+        1
+    );
+    let account = shop.banks_client.get_account(shop.master_edition).await;
+    let master_edition: MasterEdition =
+        try_from_slice_unchecked(&account.unwrap().unwrap().data).unwrap();
+    assert_eq!(master_edition.supply, 1);
+}
+
+/// Only one authorization token is burned for a limited edition, so a config taking more of them
+/// is refused rather than leaving the rest with the buyer.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_buy_fixed_price_rejects_limited_edition_amount() {
+    let configs = vec![winning_config(1, 2, WinningConfigType::LimitedEdition)];
+    let mut shop = setup_shop(configs, |_| {}).await;
+
+    let new_mint = shop.edition_mints[0];
+    let err = buy(&mut shop, 1, false, Some(new_mint)).await.unwrap_err();
+    assert_metaplex_error(err, MetaplexError::InvalidFixedPriceListing);
+}
+
+/// A Dutch auction is over as soon as its last place is sold, even while its state says it is still
+/// running, so its winners can redeem their bids right away. Until then nobody can.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_redeem_bid_sold_out_dutch_auction() {
+    for sold_out in [true, false].iter() {
+        let buyer = Keypair::new();
+        // Bids are kept oldest last, so the buyer took the first place.
+        let mut bids = vec![Bid(buyer.pubkey(), PRICE)];
+        if *sold_out {
+            bids.insert(0, Bid(Pubkey::new_unique(), PRICE));
+        }
+        let auction = AuctionData {
+            authority: Pubkey::new_unique(),
+            token_mint: Pubkey::new_unique(),
+            last_bid: None,
+            ended_at: Some(i64::MAX / 2),
+            end_auction_at: Some(3_600),
+            end_auction_gap: None,
+            price_floor: PriceFloor::None([0; 32]),
+            state: AuctionState::Started,
+            bid_state: BidState::DutchAuction {
+                bids,
+                max: 2,
+                start_price: PRICE,
+                end_price: 100,
+                curve: PriceCurve::Linear,
+                step: 0,
+            },
+        };
+        let mut shop = setup_sale(
+            buyer,
+            Some(auction),
+            vec![
+                winning_config(0, 1, WinningConfigType::TokenOnlyTransfer),
+                winning_config(0, 1, WinningConfigType::TokenOnlyTransfer),
+            ],
+            |_| {},
+        )
+        .await;
+
+        let result = redeem(&mut shop, 0).await;
+        if !*sold_out {
+            assert_metaplex_error(result.unwrap_err(), MetaplexError::AuctionHasNotEnded);
+            continue;
+        }
+        result.expect("redeem_bid");
+
+        let account = shop
+            .banks_client
+            .get_account(shop.boxes[0].buyer_destination)
+            .await
+            .unwrap()
+            .unwrap();
+        let destination = spl_token::state::Account::unpack(&account.data).unwrap();
+        assert_eq!(destination.amount, 1);
+
+        let auction_manager = get_auction_manager(&mut shop).await;
+        assert_eq!(
+            auction_manager.state.status,
+            AuctionManagerStatus::Disbursing
+        );
+        assert!(auction_manager.state.winning_config_states[0].items[0].claimed);
+        assert!(!auction_manager.state.winning_config_states[1].items[0].claimed);
+    }
+}
+
+/// Cancelling the auction of a sale leaves its bidders nothing to redeem, and lets the authority
+/// take the items of every config back out of the vault, including those the buyer was winning.
+/// Nobody else can cancel it.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_cancel_auction_returns_items() {
+    let buyer = Keypair::new();
+    let auction = AuctionData {
+        authority: Pubkey::new_unique(),
+        token_mint: Pubkey::new_unique(),
+        last_bid: None,
+        ended_at: Some(i64::MAX / 2),
+        end_auction_at: Some(3_600),
+        end_auction_gap: None,
+        price_floor: PriceFloor::None([0; 32]),
+        state: AuctionState::Started,
+        bid_state: BidState::EnglishAuction {
+            bids: vec![Bid(buyer.pubkey(), PRICE)],
+            max: 1,
+        },
+    };
+    let mut shop = setup_sale(
+        buyer,
+        Some(auction),
+        vec![winning_config(0, 1, WinningConfigType::TokenOnlyTransfer)],
+        |_| {},
+    )
+    .await;
+    let auction_program = spl_auction::id();
+    let (auction_extended, _) = Pubkey::find_program_address(
+        &[
+            spl_auction::PREFIX.as_bytes(),
+            auction_program.as_ref(),
+            shop.vault.as_ref(),
+            spl_auction::EXTENDED.as_bytes(),
+        ],
+        &auction_program,
+    );
+
+    for canceller in [&shop.buyer, &shop.authority].iter() {
+        let result = helpers::process(
+            &mut shop.banks_client,
+            &shop.recent_blockhash,
+            &shop.payer,
+            &[create_cancel_auction_instruction(
+                shop.program_id,
+                shop.auction_manager,
+                shop.listing,
+                canceller.pubkey(),
+                shop.store,
+                auction_extended,
+            )],
+            &[&shop.payer, *canceller],
+        )
+        .await;
+        if canceller.pubkey() == shop.buyer.pubkey() {
+            assert_metaplex_error(
+                result.unwrap_err(),
+                MetaplexError::AuctionManagerAuthorityMismatch,
+            );
+        } else {
+            result.expect("cancel_auction");
+        }
+    }
+    let account = shop.banks_client.get_account(shop.listing).await.unwrap();
+    let auction: AuctionData = try_from_slice_unchecked(&account.unwrap().data).unwrap();
+    assert_eq!(auction.state, AuctionState::Cancelled);
+    assert_eq!(
+        get_auction_manager(&mut shop).await.state.status,
+        AuctionManagerStatus::Disbursing
+    );
+
+    let err = redeem(&mut shop, 0).await.unwrap_err();
+    assert_metaplex_error(err, MetaplexError::AuctionCancelled);
+
+    // The authority redeems the config through the auctioneer's proxy to RedeemBid, which has no
+    // use for the bidder metadata or bid redemption accounts.
+    let shop_box = &shop.boxes[0];
+    let mut instruction = create_redeem_bid_instruction(
+        shop.program_id,
+        shop.auction_manager,
+        shop_box.token_store,
+        shop_box.authority_destination,
+        Pubkey::new_unique(),
+        shop_box.safety_deposit,
+        shop.vault,
+        shop.fraction_mint,
+        shop.listing,
+        Pubkey::new_unique(),
+        shop.authority.pubkey(),
+        shop.payer.pubkey(),
+        shop.store,
+        shop.transfer_authority,
+    );
+    instruction.data = MetaplexInstruction::RedeemUnusedWinningConfigItemsAsAuctioneer(
+        RedeemUnusedWinningConfigItemsAsAuctioneerArgs {
+            winning_config_item_index: 0,
+            proxy_call: ProxyCallAddress::RedeemBid,
+        },
+    )
+    .try_to_vec()
+    .unwrap();
+    helpers::process(
+        &mut shop.banks_client,
+        &shop.recent_blockhash,
+        &shop.payer,
+        &[instruction],
+        &[&shop.payer, &shop.authority],
+    )
+    .await
+    .expect("redeem_unused_winning_config_items_as_auctioneer");
+    assert_eq!(
+        helpers::get_token_balance(&mut shop.banks_client, &shop.boxes[0].authority_destination)
+            .await,
+        1
+    );
+    let auction_manager = get_auction_manager(&mut shop).await;
+    assert!(auction_manager.state.winning_config_states[0].items[0].claimed);
+    assert_eq!(auction_manager.state.status, AuctionManagerStatus::Finished);
+}
+
+/// The settings of an auction manager still being set up can change how a box that hasn't been
+/// validated is used, when it is passed along with its missing validation ticket. A box that has
+/// been validated keeps its use, passed or not.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_update_settings_refused_for_validated_boxes() {
+    // Box 0 has been validated for the one config, box 1 has yet to be used.
+    let mut shop = setup_shop(
+        vec![winning_config(0, 1, WinningConfigType::TokenOnlyTransfer)],
+        |auction_manager| auction_manager.state.status = AuctionManagerStatus::Initialized,
+    )
+    .await;
+    let settings = |configs: Vec<WinningConfig>| AuctionManagerSettings {
+        winning_configs: configs,
+        participation_config: None,
+        payout_split: None,
+        fixed_price: Some(PRICE),
+    };
+    let shown = |shop: &Shop, box_index: usize| {
+        let safety_deposit = shop.boxes[box_index].safety_deposit;
+        (
+            safety_deposit,
+            validation_ticket_pubkey(&shop.program_id, &shop.auction_manager, &safety_deposit),
+        )
+    };
+
+    for (boxes, error) in [
+        (vec![], MetaplexError::SettingsChangeNotShownUnvalidated),
+        (vec![shown(&shop, 0)], MetaplexError::AlreadyValidated),
+    ]
+    .iter()
+    {
+        let instruction = create_update_auction_manager_settings_instruction(
+            shop.program_id,
+            shop.auction_manager,
+            shop.authority.pubkey(),
+            shop.vault,
+            shop.listing,
+            shop.store,
+            boxes,
+            settings(vec![winning_config(
+                0,
+                2,
+                WinningConfigType::TokenOnlyTransfer,
+            )]),
+        );
+        let err = helpers::process(
+            &mut shop.banks_client,
+            &shop.recent_blockhash,
+            &shop.payer,
+            &[instruction],
+            &[&shop.payer, &shop.authority],
+        )
+        .await
+        .unwrap_err();
+        assert_metaplex_error(err, error.clone());
+    }
+
+    let configs = vec![
+        winning_config(0, 1, WinningConfigType::TokenOnlyTransfer),
+        winning_config(1, 2, WinningConfigType::TokenOnlyTransfer),
+    ];
+    let instruction = create_update_auction_manager_settings_instruction(
+        shop.program_id,
+        shop.auction_manager,
+        shop.authority.pubkey(),
+        shop.vault,
+        shop.listing,
+        shop.store,
+        &[shown(&shop, 1)],
+        settings(configs),
+    );
+    helpers::process(
+        &mut shop.banks_client,
+        &shop.recent_blockhash,
+        &shop.payer,
+        &[instruction],
+        &[&shop.payer, &shop.authority],
+    )
+    .await
+    .expect("update_auction_manager_settings");
+
+    let auction_manager = get_auction_manager(&mut shop).await;
+    let item = auction_manager.settings.winning_configs[1].items[0];
+    assert_eq!((item.safety_deposit_box_index, item.amount), (1, 2));
+    assert_eq!(auction_manager.state.winning_config_states.len(), 2);
+    assert_eq!(auction_manager.state.winning_config_items_validated, 1);
+    assert_eq!(
+        auction_manager.state.status,
+        AuctionManagerStatus::Initialized
+    );
+}
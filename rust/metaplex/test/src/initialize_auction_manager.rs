@@ -204,6 +204,20 @@ fn find_or_initialize_auction(
                 },
                 token_mint: *payer_mint_key,
                 price_floor: PriceFloor::None([0; 32]),
+                tick_size: None,
+                gap_tick_size_percentage: None,
+                dutch_auction: None,
+                reveal_period: None,
+                uniform_price: false,
+                extension_policy: None,
+                instant_sale_price: None,
+                paged_bids: false,
+                bid_history: false,
+                start_auction_at: None,
+                bidder_gate: None,
+                max_wins_per_wallet: None,
+                cancellable_with_bids: false,
+                exchange_rates: vec![],
             },
         )];
 
@@ -160,6 +160,9 @@ pub fn make_bid(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) {
         PlaceBidArgs {
             amount,
             resource: manager.vault,
+            commitment: None,
+            top_up: false,
+            allowlist_proof: vec![],
         },
     ));
 
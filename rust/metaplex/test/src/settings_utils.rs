@@ -2,10 +2,11 @@ use {
     serde::{Deserialize, Serialize},
     solana_program::pubkey::Pubkey,
     spl_metaplex::state::{
-        AuctionManagerSettings, NonWinningConstraint, ParticipationConfig, WinningConfig,
-        WinningConfigItem, WinningConfigType, WinningConstraint,
+        AuctionManagerSettings, NonWinningConstraint, ParticipationConfig, PayoutSplit,
+        PayoutSplitRecipient, WinningConfig, WinningConfigItem, WinningConfigType,
+        WinningConstraint,
     },
-    std::fs::File,
+    std::{fs::File, str::FromStr},
 };
 #[derive(Serialize, Deserialize, Clone)]
 pub struct JsonWinningConfig {
@@ -31,11 +32,21 @@ pub struct JsonParticipationConfig {
     pub fixed_price: Option<u64>,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct JsonPayoutSplitRecipient {
+    pub address: String,
+    pub basis_points: u16,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct JsonAuctionManagerSettings {
     pub winning_configs: Vec<JsonWinningConfig>,
 
     pub participation_config: Option<JsonParticipationConfig>,
+
+    pub payout_split: Option<Vec<JsonPayoutSplitRecipient>>,
+
+    pub fixed_price: Option<u64>,
 }
 
 pub fn parse_metadata_keys(settings_file: &str) -> Vec<Pubkey> {
@@ -60,6 +71,7 @@ pub fn parse_settings(settings_file: &str) -> (AuctionManagerSettings, JsonAucti
                     0 => WinningConfigType::TokenOnlyTransfer,
                     1 => WinningConfigType::FullRightsTransfer,
                     2 => WinningConfigType::Printing,
+                    3 => WinningConfigType::LimitedEdition,
                     _ => WinningConfigType::TokenOnlyTransfer,
                 },
             })
@@ -87,6 +99,19 @@ pub fn parse_settings(settings_file: &str) -> (AuctionManagerSettings, JsonAucti
             }),
             None => None,
         },
+        payout_split: json_settings
+            .payout_split
+            .as_ref()
+            .map(|recipients| PayoutSplit {
+                recipients: recipients
+                    .iter()
+                    .map(|recipient| PayoutSplitRecipient {
+                        address: Pubkey::from_str(&recipient.address).unwrap(),
+                        basis_points: recipient.basis_points,
+                    })
+                    .collect(),
+            }),
+        fixed_price: json_settings.fixed_price,
     };
 
     (settings, json_settings)